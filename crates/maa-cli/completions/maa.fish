@@ -4,7 +4,9 @@
 complete -c maa -s v -l verbose -d 'Output more information, repeat to increase verbosity'
 complete -c maa -s q -l quiet -d 'Output less information, repeat to increase quietness'
 complete -c maa -l batch -d 'Enable touch mode'
+complete -c maa -l answers -d 'Answers of prompts in batch mode' -r -F
 complete -c maa -l log-file -d 'Log to file instead of stderr'
+complete -c maa -l log-format -d 'Format of log messages' -r -f -a 'text json'
 
 # Subcommands
 set -g __maa_subcommands
@@ -29,9 +31,23 @@ __maa_add_subcommand closedown 'Close game'
 __maa_add_subcommand fight 'Run fight task'
 __maa_add_subcommand copilot 'Run copilot task'
 __maa_add_subcommand roguelike 'Run rogue-like task'
+__maa_add_subcommand infrast 'Run infrast task'
+__maa_add_subcommand depot 'Recognize and export depot'
+__maa_add_subcommand operbox 'Recognize and export operator box'
+__maa_add_subcommand convert-video 'Generate copilot file from video of a clear'
+__maa_add_subcommand mall 'Collect credits and shop in credit store'
+__maa_add_subcommand award 'Collect mission rewards and mails'
+__maa_add_subcommand recruit 'Run recruit task or recognize recruitment tags'
+__maa_add_subcommand daily 'Run common daily tasks with the defaults of each task'
 __maa_add_subcommand activity 'Show stage activity of given client'
 __maa_add_subcommand remainder 'Get remainder of given divisor and current date'
 __maa_add_subcommand list 'List all available tasks'
+complete -c maa -n "__fish_seen_subcommand_from list" -f -l names -d 'Only list names of task files'
+__maa_add_subcommand daemon 'Run jobs in a long-lived assistant'
+__maa_add_subcommand stop 'Stop a running instance'
+__maa_add_subcommand pause 'Pause a running instance'
+__maa_add_subcommand resume 'Resume a paused instance'
+__maa_add_subcommand schedule 'Run custom tasks periodically by schedules'
 __maa_add_subcommand complete 'Generate completion script for given shell'
 __maa_add_subcommand mangen 'Generate man page for maa-cli at given path'
 __maa_add_subcommand convert 'Convert config file to another format'
@@ -69,21 +85,37 @@ complete -c maa -n "__fish_seen_subcommand_from dir" -f -a "$maa_dirs"
 # Maa run related options
 set -l clients Official Bilibili Txwy YoStarEN YoStarJP YoStarKR
 ## Common options
-set -l run_commands run startup closedown fight copilot sscopilot roguelike reclamation
+set -l run_commands run startup closedown fight copilot sscopilot roguelike reclamation infrast mall award recruit daily depot operbox convert-video
 complete -c maa -n "__fish_seen_subcommand_from $run_commands" -s a -l addr -d 'ADB serial number of device or MaaTools address set in PlayCover' -r
 complete -c maa -n "__fish_seen_subcommand_from $run_commands" -s p -l profile -d 'Profile to use' -r
+complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l device -d 'Run tasks on multiple devices in parallel' -r
 complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l user-resource -d 'Load resources from the config directory'
 complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l dry-run -d 'Parse the your config but do not connect to the game'
 complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l no-summary -d 'Do not print summary when finnish'
+complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l drops-output -d 'Append drops of each stage run to given file' -r
+complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l timeout -d 'Maximum time to run all tasks' -r
+complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l loop -d 'Run tasks repeatedly'
+complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l interval -d 'Time between the starts of two runs of the loop' -r -f
+complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l sanity-loop -d 'Run tasks again after sanity regenerates' -f
+complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l until -d 'Do not start a new run of the loop after given time' -r -f
+complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l count -d 'Maximum number of runs of the loop' -r -f
+complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l max-failures -d 'Stop the loop after given number of failed runs' -r -f
+complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l post-action -d 'Action after all tasks finished' -r -f -a 'exit-game kill-emulator suspend shutdown'
+complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l closedown-after -d 'Close the game after it is left idle for given time' -r -f
+complete -c maa -n "__fish_seen_subcommand_from $run_commands" -l fix-resolution -d 'Change the resolution of the device to a supported one while running tasks'
 complete -c maa -n "__fish_seen_subcommand_from $run_commands" -f # prevent fish complete from path
 ## command specific options
-complete -c maa -n "__fish_seen_subcommand_from run" -f -a "$(maa list)"
+complete -c maa -n "__fish_seen_subcommand_from run" -f -a "$(maa list --names)"
+complete -c maa -n "__fish_seen_subcommand_from run" -l set -d 'Override a parameter of tasks with given type' -r
+complete -c maa -n "__fish_seen_subcommand_from run" -l task-json -d 'Append a task given in JSON to the run' -r
+complete -c maa -n "__fish_seen_subcommand_from run" -l resume -d 'Resume the last run of this task file after interruption'
+complete -c maa -n "__fish_seen_subcommand_from run" -l today -d 'Print tasks to run today and exit without running them'
 complete -c maa -n "__fish_seen_subcommand_from startup" -f -a "$clients"
 complete -c maa -n "__fish_seen_subcommand_from startup" -f -l account -d 'Account to login' -r
 complete -c maa -n "__fish_seen_subcommand_from closedown" -f -a "$clients"
 
 complete -c maa -n "__fish_seen_subcommand_from fight" -f -s m -l medicine -d 'Medicine to use' -r
-complete -c maa -n "__fish_seen_subcommand_from fight" -f -l expiring-medicine -d 'Expiring medicine to use' -r
+complete -c maa -n "__fish_seen_subcommand_from fight" -f -l expiring-medicine -d 'Expiring medicine to use, all if no number is given'
 complete -c maa -n "__fish_seen_subcommand_from fight" -f -l stone -d 'Stone to use' -r
 complete -c maa -n "__fish_seen_subcommand_from fight" -f -l times -d 'Times to fight' -r
 complete -c maa -n "__fish_seen_subcommand_from fight" -f -s D -l drops -d 'Exit after collecting given number of drops' -r
@@ -94,17 +126,70 @@ complete -c maa -n "__fish_seen_subcommand_from fight" -f -l report-to-yituliu -
 complete -c maa -n "__fish_seen_subcommand_from fight" -f -l yituliu-id -d 'Yituliu ID to report drops' -r
 complete -c maa -n "__fish_seen_subcommand_from fight" -f -l client-type -d 'Client type to restart' -r
 complete -c maa -n "__fish_seen_subcommand_from fight" -f -l dr-grandet -d 'Use Dr. Grandet'
+complete -c maa -n "__fish_seen_subcommand_from fight" -f -l item -d 'Item to farm at the most efficient stage' -r
+complete -c maa -n "__fish_seen_subcommand_from fight" -f -s y -l yes -d 'Fight the stage chosen for the item without confirmation'
+complete -c maa -n "__fish_seen_subcommand_from fight" -f -l target -d 'Stop after given quantity of the item is farmed' -r
+
+complete -c maa -n "__fish_seen_subcommand_from infrast" -f -s f -l facility -d 'Facilities to shift' -ra 'Mfg Trade Power Control Reception Office Dorm'
+complete -c maa -n "__fish_seen_subcommand_from infrast" -f -s d -l drones -d 'Usage of drones' -ra 'not-use money synthetic-jade combat-record pure-gold origin-stone chip'
+complete -c maa -n "__fish_seen_subcommand_from infrast" -f -l threshold -d 'Morale threshold' -r
+complete -c maa -n "__fish_seen_subcommand_from infrast" -f -l dorm-notstationed -d 'Fill dormitories with not stationed operators'
+complete -c maa -n "__fish_seen_subcommand_from infrast" -f -l dorm-trust -d 'Fill dormitories with operators to gain trust'
+complete -c maa -n "__fish_seen_subcommand_from infrast" -f -l replenish -d 'Replenish originium shards'
+complete -c maa -n "__fish_seen_subcommand_from infrast" -F -l plan -d 'Custom infrast plan file' -r
+complete -c maa -n "__fish_seen_subcommand_from infrast" -f -l plan-index -d 'Index of plan' -r
+
+complete -c maa -n "__fish_seen_subcommand_from depot" -f -s f -l format -d 'Format of exported depot' -ra 'arkplanner lolicon'
+complete -c maa -n "__fish_seen_subcommand_from depot" -F -s o -l output -d 'Path of output file' -r
+complete -c maa -n "__fish_seen_subcommand_from depot" -f -l diff -d 'Print items gained and spent since the last scan'
+
+complete -c maa -n "__fish_seen_subcommand_from operbox" -f -s f -l format -d 'Format of exported operators' -ra 'json csv'
+complete -c maa -n "__fish_seen_subcommand_from operbox" -F -s o -l output -d 'Path of output file' -r
+complete -c maa -n "__fish_seen_subcommand_from convert-video" -F
+complete -c maa -n "__fish_seen_subcommand_from convert-video" -F -s o -l output -d 'Path of generated copilot file' -r
+
+complete -c maa -n "__fish_seen_subcommand_from mall" -f -l no-shopping -d 'Only collect credits'
+complete -c maa -n "__fish_seen_subcommand_from mall" -f -s b -l buy-first -d 'Items to buy first' -r
+complete -c maa -n "__fish_seen_subcommand_from mall" -f -s B -l blacklist -d 'Items not to buy' -r
+complete -c maa -n "__fish_seen_subcommand_from mall" -f -l force-if-full -d 'Buy items in blacklist if credits are full'
+complete -c maa -n "__fish_seen_subcommand_from mall" -f -l only-discount -d 'Only buy discounted items'
+complete -c maa -n "__fish_seen_subcommand_from mall" -f -l reserve-max-credit -d 'Stop shopping when credits are less than 300'
+complete -c maa -n "__fish_seen_subcommand_from mall" -f -l credit-fight -d 'Fight a stage to gain extra credits'
+
+complete -c maa -n "__fish_seen_subcommand_from award" -f -l no-award -d "Don't collect mission rewards"
+complete -c maa -n "__fish_seen_subcommand_from award" -f -l mail -d 'Collect mails'
+complete -c maa -n "__fish_seen_subcommand_from award" -f -l recruit -d 'Free pull of limited headhunting'
+complete -c maa -n "__fish_seen_subcommand_from award" -f -l orundum -d 'Collect Orundum of limited events'
+complete -c maa -n "__fish_seen_subcommand_from award" -f -l mining -d 'Collect Orundum of limited mining events'
+complete -c maa -n "__fish_seen_subcommand_from award" -f -l special-access -d 'Collect monthly card gift'
+
+complete -c maa -n "__fish_seen_subcommand_from daily" -f -l stage -d 'Stage to fight' -r
+complete -c maa -n "__fish_seen_subcommand_from daily" -f -l skip -d 'Steps to skip' -ra "startup fight infrast recruit mall award closedown"
+
+complete -c maa -n "__fish_seen_subcommand_from recruit" -f -l recognize -d 'Only recognize tags and show possible operators'
+complete -c maa -n "__fish_seen_subcommand_from recruit" -f -l times -d 'Number of recruitment' -r
+complete -c maa -n "__fish_seen_subcommand_from recruit" -f -l select -d 'Levels of tags to select' -r
+complete -c maa -n "__fish_seen_subcommand_from recruit" -f -l confirm -d 'Levels of tags to confirm' -r
+complete -c maa -n "__fish_seen_subcommand_from recruit" -f -l refresh -d 'Refresh 3-star tags'
+complete -c maa -n "__fish_seen_subcommand_from recruit" -f -l expedite -d 'Use expedited plans'
+complete -c maa -n "__fish_seen_subcommand_from recruit" -f -l expedite-times -d 'Maximum number of expedited plans to use' -r
+complete -c maa -n "__fish_seen_subcommand_from recruit" -f -l first-tags -d 'Tags preferred when only 3-star tags are available' -r
+complete -c maa -n "__fish_seen_subcommand_from recruit" -f -l extra-tags-mode -d 'How to select more tags' -ra "0 1 2"
+complete -c maa -n "__fish_seen_subcommand_from recruit" -f -l recruit-robot -d 'Recruit 1-star robots instead of skipping them'
+complete -c maa -n "__fish_seen_subcommand_from recruit" -f -l three-star-time -d 'Recruitment time of 3-star tags' -r
 
 complete -c maa -n "__fish_seen_subcommand_from copilot" -f -s f -l formation -d 'Use formation'
 complete -c maa -n "__fish_seen_subcommand_from copilot" -f -l use-sanity-potion -d 'Use sanity potion'
 complete -c maa -n "__fish_seen_subcommand_from copilot" -f -l need-navigate -d 'Need navigate'
 complete -c maa -n "__fish_seen_subcommand_from copilot" -f -l add-trust -d 'Add trust'
-complete -c maa -n "__fish_seen_subcommand_from copilot" -f -l select-formation -d 'Select formation' -r
+complete -c maa -n "__fish_seen_subcommand_from copilot" -f -l select-formation -d 'Select formation by number or name of squad' -r
 complete -c maa -n "__fish_seen_subcommand_from copilot" -f -l support-unit-name -d 'Support unit name' -r
+complete -c maa -n "__fish_seen_subcommand_from copilot" -l oper-box -d 'Owned operators to check requirements' -r -F
+complete -c maa -n "__fish_seen_subcommand_from copilot" -f -l ignore-requirements -d 'Do not check requirements of operators'
 complete -c maa -n "__fish_seen_subcommand_from sscopilot" -f -s l -l loop-times -d 'Loop times' -r
 
 complete -c maa -n "__fish_seen_subcommand_from roguelike" -a "Phantom Mizuki Sami Sarkaz"
-complete -c maa -n "__fish_seen_subcommand_from roguelike" -l mode -d 'Mode of roguelike' -a "0 1 2 3 4"
+complete -c maa -n "__fish_seen_subcommand_from roguelike" -l mode -d 'Mode of roguelike' -a "score invest pass collectible collapse"
 complete -c maa -n "__fish_seen_subcommand_from roguelike" -l squad -d 'Squad to use' -r
 complete -c maa -n "__fish_seen_subcommand_from roguelike" -l roles -d 'Roles to use' -r
 complete -c maa -n "__fish_seen_subcommand_from roguelike" -l core-char -d 'Core character to use' -r
@@ -118,12 +203,15 @@ complete -c maa -n "__fish_seen_subcommand_from roguelike" -l use-nonfriend-supp
 complete -c maa -n "__fish_seen_subcommand_from roguelike" -l start-with-elite-two -d 'Start with elite two'
 complete -c maa -n "__fish_seen_subcommand_from roguelike" -l only-start-with-elite-two -d 'Only start with elite two'
 complete -c maa -n "__fish_seen_subcommand_from roguelike" -l stop-at-final-boss -d 'Stop exploration before final boss'
+complete -c maa -n "__fish_seen_subcommand_from roguelike" -l stop-at-max-level -d 'Stop exploration when level reaches the maximum'
+complete -c maa -n "__fish_seen_subcommand_from roguelike" -l collectible-mode-squad -d 'Squad to start with in collectible mode' -r
+complete -c maa -n "__fish_seen_subcommand_from roguelike" -l collectible-mode-shopping -d 'Buy items in collectible mode'
 complete -c maa -n "__fish_seen_subcommand_from roguelike" -l refresh-trader-with-dice -d 'Refresh trader with dice'
 complete -c maa -n "__fish_seen_subcommand_from roguelike" -l use-foldartal -d 'Use foldartal'
 complete -c maa -n "__fish_seen_subcommand_from roguelike" -l start-foldartals -d 'Start foldartals' -r
 complete -c maa -n "__fish_seen_subcommand_from roguelike" -l expected-collapsal-paradigms -d 'Expected collapsal paradigms' -r
 
-complete -c maa -n "__fish_seen_subcommand_from reclamation" -a "Tales"
+complete -c maa -n "__fish_seen_subcommand_from reclamation" -a "Fire Tales"
 complete -c maa -n "__fish_seen_subcommand_from reclamation" -f -s m -l mode -d 'Mode of reclamation' -r -a "0 1"
 complete -c maa -n "__fish_seen_subcommand_from reclamation" -f -s C -l tool-to-craft -d 'Tool to craft' -r
 complete -c maa -n "__fish_seen_subcommand_from reclamation" -f -s m -l increase-mode -d 'Method to increase the number of tools' -r
@@ -144,4 +232,9 @@ complete -c maa -n "__fish_seen_subcommand_from init" -s n -l name -d 'Name of p
 complete -c maa -n "__fish_seen_subcommand_from init" -l force -d 'Force to initialize even if the config already exists'
 complete -c maac -n "__fish_seen_subcommand_from init" -s f -l format -a "j json y yaml t toml" -r
 
+complete -c maa -n "__fish_seen_subcommand_from daemon" -f -a "start submit"
+complete -c maa -n "__fish_seen_subcommand_from submit" -f -a "$(maa list --names)"
+complete -c maa -n "__fish_seen_subcommand_from submit" -f -l account -d 'Account to switch to before running tasks' -r
+complete -c maa -n "__fish_seen_subcommand_from stop pause resume" -f -l pid -d 'Process id of the instance to control' -r
+complete -c maa -n "__fish_seen_subcommand_from schedule" -f -l list -d 'List schedules and their next run time'
 complete -c maa -n "__fish_seen_subcommand_from hot-update list" -f # prevent fish complete from path
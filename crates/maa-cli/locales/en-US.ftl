@@ -0,0 +1,183 @@
+## Messages of MaaCore
+
+initialization-error = Failed to initialize MaaCore
+all-tasks-completed = All tasks completed
+none = none
+unknown = unknown
+
+## Connection
+
+connect-failed = Failed to connect to android device, { $why }, Please check your connect configuration: { $details }
+unsupported-resolution = The resolution of the device is not supported
+resolution-error = Failed to get the resolution of the device
+connected = Connected
+disconnected = Disconnected
+reconnecting = Reconnecting, attempt { $times }
+reconnected = Reconnected
+screencap-failed = Failed to take a screenshot
+fastest-way-to-screencap = Fastest way to take screenshots: { $method } ({ $cost } ms)
+touch-mode-not-available = The touch mode is not available
+
+## Tasks
+
+taskchain-start = { $taskchain } started
+taskchain-completed = { $taskchain } completed
+taskchain-stopped = { $taskchain } stopped
+taskchain-error = { $taskchain } failed
+taskchain-completed-in = { $taskchain } completed in { $duration }
+taskchain-stopped-after = { $taskchain } stopped after { $duration }
+taskchain-error-after = { $taskchain } failed after { $duration }
+failed-to-start-game = Failed to start the game
+recruit-returned = { $why }, returned
+failed-to-recognize-drops = Failed to recognize drops
+failed-to-report-to-penguin-stats = Failed to report drops to Penguin Stats, { $why }
+stage-not-supported = EX stages are not supported, stopped
+failed-to-switch-account = Failed to switch account
+failed-to-switch-account-to = Failed to switch to account { $account }, make sure the account has logged in on this device, the account name can be a part of the account like the phone number or the username
+account-switched = Switched account from { $from } to { $to }
+
+## Fight
+
+mission-start-times = Start mission, { $times } times
+use-stones = Use { $count } stones
+prts-error = PRTS error, the action is abandoned
+drops = Drops: { $drops }
+farmed = Farmed { $item }: { $farmed }/{ $quantity }
+current-sanity = Current sanity: { $current }/{ $max }
+use-medicine = Use { $count } medicine
+use-expiring-medicine = Use { $count } expiring medicine
+stage-info = Start combat: { $stage }
+stage-info-error = Failed to recognize the stage
+
+## Recruit
+
+refresh-tags = Refresh tags
+recruit = Recruit
+recruiting-tips = Recruiting tips: { $tag }
+recruit-result = Recruit result: { $stars }
+recruit-result-tags = Tags: { $tags }
+recruit-result-best = Best: { $tags } → { $stars }
+recruit-tags-selected = Tags selected: { $tags }
+recruit-tags-refreshed = Tags refreshed: { $count } times
+
+## Infrast
+
+infrast-dorm-double-confirmed = Operators conflict, confirmed to move them to the dorm
+enter-facility = Enter { $facility } #{ $index }
+product-incorrect = The product is incorrect
+product-unknown = The product is unknown
+product-changed = The product is changed
+not-enough-staff = Not enough staff
+product-of-facility = Product: { $product }
+custom-infrast-room-operators = Operators: { $operators }
+
+## Roguelike
+
+start-exploration = Start exploration { $times } times
+exploration-abandoned = Exploration abandoned
+exploration-confirmed = Exploration settled
+mission-completed = Mission completed
+mission-failed = Mission failed
+stage-trader-enter = Enter the shop
+stage-safe-house-enter = Enter the safe house
+stage-combat-dps-enter = Enter a combat stage
+stage-emergency-dps-enter = Enter an emergency stage
+stage-dreadful-foe-enter = Enter a dreadful foe stage
+trader-invest-system-full = The investment system is full
+roguelike-game-pass = Game passed!
+roguelike-special-item-bought = Special item bought after refreshing
+roguelike-investment = Deposit { $count } / { $total } / { $deposit } originium ingots
+roguelike-settlement = Gain { $exp } exp during this exploration
+game-offline = The game is offline, reconnecting
+mission-start = Mission started
+
+## Copilot
+
+battle-formation = Formation: { $operators }
+battle-formation-selected = Operator selected: { $operator }
+copilot-action = Current step: { $action } { $target }
+current-stage = Current stage: { $stage }
+sss-settlement = Settled: { $why }
+sss-game-pass = All stages passed
+unsupported-level = The level is not supported
+
+## Status line
+
+progress-navigating = navigating to { $stage }
+progress-times = times { $times }
+progress-times-total = times { $times }/{ $total }
+progress-refreshing-tags = refreshing tags
+progress-recruiting = recruiting
+progress-exploration = exploration { $times }
+progress-entering-facility = entering { $facility } #{ $index }
+
+## Summary
+
+summary = Summary
+result-completed = Completed
+result-stopped = Stopped
+result-error = Error
+result-timed-out = Timed out
+result-unfinished = Unfinished
+result-unstarted = Unstarted
+result-skipped = Skipped
+copilot-completed = Copilot: { $completed }/{ $total } completed
+not-completed = Not completed: { $tasks }
+overview = Overview
+overview-duration = Duration: { $duration }
+overview-tasks = Tasks: { $results }
+overview-completed = { $count } completed
+overview-stopped = { $count } stopped
+overview-error = { $count } error
+overview-timed-out = { $count } timed out
+overview-unfinished = { $count } unfinished
+overview-unstarted = { $count } unstarted
+overview-skipped = { $count } skipped
+overview-stages = Stages: { $stages }
+overview-sanity = Sanity spent: { $sanity }
+overview-recruit = Recruit: { $total } recruited, { $refreshed } refreshed
+overview-recruit-levels = Recruit: { $total } recruited ({ $levels }), { $refreshed } refreshed
+overview-infrast = Infrast: { $rooms } rooms shifted
+infrast-operators = with operators: { $operators }
+fight-stage = Fight { $stage }
+fight-times = { $times } times
+fight-medicine = used { $count } medicine ({ $expiring } expiring)
+fight-stone = used { $count } stone
+fight-drops = drops:
+fight-total-drops = total drops:
+recruit-detected-tags = Detected tags:
+recruit-refreshed = Refreshed
+recruit-recruited = Recruited
+recruit-recruited-times = Recruited { $times } times
+recruit-refreshed-times = Refreshed { $times } times
+explorations = Explorations:
+explorations-summary = Summary:
+exploration-state-passed = Passed
+exploration-state-failed = Failed
+exploration-state-abandoned = Abandoned
+exploration-state-unknown = Unknown
+exploration-invest = invest { $count } originium ingotes
+exploration-exp = gained { $exp } exp
+explorations-invest = Total invest { $count } originium ingotes
+explorations-exp = Total gained { $exp } exp
+sss-stages = Stages:
+sss-passed = Passed all stages
+sss-settled = Settled: { $why }
+
+## Errors
+
+error-load-profile = Failed to load profile
+error-apply-options = Failed to apply options to profile
+error-load-tasks = Failed to load tasks
+error-resolve-tasks = Failed to resolve tasks
+error-load-core = Failed to load MaaCore!
+error-setup-core = Failed to set up MaaCore!
+error-launch-emulator = Failed to launch emulator
+error-open-app = Failed to open external app
+error-check-resolution = Failed to check the resolution
+error-connect = Failed to connect to the device
+error-interrupted = Interrupted by user!
+error-task-aborted = Task { $task } failed, the remaining tasks are aborted
+error-task-failed = Some error occurred during running task!
+error-task-timed-out = Task { $task } timed out, the remaining tasks are aborted
+error-timed-out = Tasks timed out, the remaining tasks are aborted
@@ -0,0 +1,183 @@
+## Messages of MaaCore
+
+initialization-error = MaaCore の初期化に失敗しました
+all-tasks-completed = すべてのタスクが完了しました
+none = なし
+unknown = 不明
+
+## Connection
+
+connect-failed = Android デバイスへの接続に失敗しました。{ $why }。接続設定を確認してください：{ $details }
+unsupported-resolution = デバイスの解像度はサポートされていません
+resolution-error = デバイスの解像度の取得に失敗しました
+connected = 接続しました
+disconnected = 接続が切断されました
+reconnecting = 再接続中（{ $times } 回目）
+reconnected = 再接続しました
+screencap-failed = スクリーンショットの取得に失敗しました
+fastest-way-to-screencap = 最速のスクリーンショット方式：{ $method }（{ $cost } ms）
+touch-mode-not-available = タッチモードは利用できません
+
+## Tasks
+
+taskchain-start = タスク開始：{ $taskchain }
+taskchain-completed = タスク完了：{ $taskchain }
+taskchain-stopped = タスク停止：{ $taskchain }
+taskchain-error = タスク失敗：{ $taskchain }
+taskchain-completed-in = タスク完了：{ $taskchain }（所要時間 { $duration }）
+taskchain-stopped-after = タスク停止：{ $taskchain }（所要時間 { $duration }）
+taskchain-error-after = タスク失敗：{ $taskchain }（所要時間 { $duration }）
+failed-to-start-game = ゲームの起動に失敗しました
+recruit-returned = { $why }、戻りました
+failed-to-recognize-drops = ドロップの認識に失敗しました
+failed-to-report-to-penguin-stats = ペンギン急便へのドロップ報告に失敗しました。{ $why }
+stage-not-supported = EX ステージはサポートされていません。停止しました
+failed-to-switch-account = アカウントの切り替えに失敗しました
+failed-to-switch-account-to = アカウント { $account } への切り替えに失敗しました。このデバイスでログイン済みであることを確認してください。アカウント名は電話番号やユーザー名などアカウントの一部でも構いません
+account-switched = アカウントを { $from } から { $to } に切り替えました
+
+## Fight
+
+mission-start-times = 作戦開始（{ $times } 回目）
+use-stones = 純正源石を { $count } 個使用
+prts-error = PRTS エラー、作戦を放棄しました
+drops = ドロップ：{ $drops }
+farmed = { $item } を周回：{ $farmed }/{ $quantity }
+current-sanity = 現在の理性：{ $current }/{ $max }
+use-medicine = 理性回復剤を { $count } 個使用
+use-expiring-medicine = 期限切れ間近の理性回復剤を { $count } 個使用
+stage-info = 戦闘開始：{ $stage }
+stage-info-error = ステージの認識に失敗しました
+
+## Recruit
+
+refresh-tags = タグを更新
+recruit = 公開求人
+recruiting-tips = 公開求人のヒント：{ $tag }
+recruit-result = 公開求人の結果：{ $stars }
+recruit-result-tags = タグ：{ $tags }
+recruit-result-best = 最適：{ $tags } → { $stars }
+recruit-tags-selected = 選択したタグ：{ $tags }
+recruit-tags-refreshed = タグを更新：{ $count } 回
+
+## Infrast
+
+infrast-dorm-double-confirmed = オペレーターが重複しているため、宿舎への移動を確認しました
+enter-facility = { $facility } #{ $index } に入る
+product-incorrect = 製造物が正しくありません
+product-unknown = 製造物が不明です
+product-changed = 製造物を変更しました
+not-enough-staff = オペレーターが足りません
+product-of-facility = 製造物：{ $product }
+custom-infrast-room-operators = オペレーター：{ $operators }
+
+## Roguelike
+
+start-exploration = { $times } 回目の探索を開始
+exploration-abandoned = 探索を放棄しました
+exploration-confirmed = 探索を精算しました
+mission-completed = 戦闘完了
+mission-failed = 戦闘失敗
+stage-trader-enter = ショップに入る
+stage-safe-house-enter = 安全な片隅に入る
+stage-combat-dps-enter = 作戦に入る
+stage-emergency-dps-enter = 緊急作戦に入る
+stage-dreadful-foe-enter = 悪路凶敵に入る
+trader-invest-system-full = 投資システムが満杯です
+roguelike-game-pass = クリア！
+roguelike-special-item-bought = 更新後に特別な商品を購入しました
+roguelike-investment = 源石錐を { $count } / { $total } / { $deposit } 投資
+roguelike-settlement = この探索で { $exp } 経験値を獲得
+game-offline = ゲームがオフラインになりました。再接続中
+mission-start = 作戦開始
+
+## Copilot
+
+battle-formation = 編成：{ $operators }
+battle-formation-selected = 選択したオペレーター：{ $operator }
+copilot-action = 現在のステップ：{ $action } { $target }
+current-stage = 現在のステージ：{ $stage }
+sss-settlement = 精算：{ $why }
+sss-game-pass = すべてのステージをクリアしました
+unsupported-level = このステージはサポートされていません
+
+## Status line
+
+progress-navigating = { $stage } へ移動中
+progress-times = { $times } 回目
+progress-times-total = { $times }/{ $total } 回目
+progress-refreshing-tags = タグを更新中
+progress-recruiting = 求人中
+progress-exploration = { $times } 回目の探索
+progress-entering-facility = { $facility } #{ $index } に入っています
+
+## Summary
+
+summary = まとめ
+result-completed = 完了
+result-stopped = 停止
+result-error = エラー
+result-timed-out = タイムアウト
+result-unfinished = 未完了
+result-unstarted = 未開始
+result-skipped = スキップ
+copilot-completed = 自動指揮：{ $completed }/{ $total } 完了
+not-completed = 未完了：{ $tasks }
+overview = 概要
+overview-duration = 所要時間：{ $duration }
+overview-tasks = タスク：{ $results }
+overview-completed = 完了 { $count }
+overview-stopped = 停止 { $count }
+overview-error = エラー { $count }
+overview-timed-out = タイムアウト { $count }
+overview-unfinished = 未完了 { $count }
+overview-unstarted = 未開始 { $count }
+overview-skipped = スキップ { $count }
+overview-stages = ステージ：{ $stages }
+overview-sanity = 消費した理性：{ $sanity }
+overview-recruit = 公開求人：求人 { $total } 回、更新 { $refreshed } 回
+overview-recruit-levels = 公開求人：求人 { $total } 回（{ $levels }）、更新 { $refreshed } 回
+overview-infrast = 基地：{ $rooms } 部屋を交代
+infrast-operators = オペレーター：{ $operators }
+fight-stage = { $stage } を周回
+fight-times = { $times } 回
+fight-medicine = 理性回復剤 { $count } 個使用（期限切れ間近 { $expiring } 個）
+fight-stone = 純正源石 { $count } 個使用
+fight-drops = ドロップ：
+fight-total-drops = 合計ドロップ：
+recruit-detected-tags = 認識したタグ：
+recruit-refreshed = 更新済み
+recruit-recruited = 求人済み
+recruit-recruited-times = 求人 { $times } 回
+recruit-refreshed-times = 更新 { $times } 回
+explorations = 探索：
+explorations-summary = まとめ：
+exploration-state-passed = クリア
+exploration-state-failed = 失敗
+exploration-state-abandoned = 放棄
+exploration-state-unknown = 不明
+exploration-invest = 源石錐を { $count } 投資
+exploration-exp = 経験値 { $exp } を獲得
+explorations-invest = 合計で源石錐を { $count } 投資
+explorations-exp = 合計で経験値 { $exp } を獲得
+sss-stages = ステージ：
+sss-passed = すべてのステージをクリアしました
+sss-settled = 精算：{ $why }
+
+## Errors
+
+error-load-profile = プロファイルの読み込みに失敗しました
+error-apply-options = オプションをプロファイルに適用できませんでした
+error-load-tasks = タスクの読み込みに失敗しました
+error-resolve-tasks = タスクの解決に失敗しました
+error-load-core = MaaCore の読み込みに失敗しました！
+error-setup-core = MaaCore の設定に失敗しました！
+error-launch-emulator = エミュレーターの起動に失敗しました
+error-open-app = 外部アプリを開けませんでした
+error-check-resolution = 解像度の確認に失敗しました
+error-connect = デバイスへの接続に失敗しました
+error-interrupted = ユーザーによって中断されました！
+error-task-aborted = タスク { $task } が失敗したため、残りのタスクを中止しました
+error-task-failed = タスクの実行中にエラーが発生しました！
+error-task-timed-out = タスク { $task } がタイムアウトしたため、残りのタスクを中止しました
+error-timed-out = タスクがタイムアウトしたため、残りのタスクを中止しました
@@ -0,0 +1,183 @@
+## Messages of MaaCore
+
+initialization-error = MaaCore 초기화에 실패했습니다
+all-tasks-completed = 모든 작업을 완료했습니다
+none = 없음
+unknown = 알 수 없음
+
+## Connection
+
+connect-failed = 안드로이드 기기에 연결하지 못했습니다. { $why }. 연결 설정을 확인하세요: { $details }
+unsupported-resolution = 기기의 해상도를 지원하지 않습니다
+resolution-error = 기기의 해상도를 가져오지 못했습니다
+connected = 연결되었습니다
+disconnected = 연결이 끊어졌습니다
+reconnecting = 다시 연결하는 중, { $times }번째 시도
+reconnected = 다시 연결되었습니다
+screencap-failed = 스크린샷을 찍지 못했습니다
+fastest-way-to-screencap = 가장 빠른 스크린샷 방식: { $method } ({ $cost } ms)
+touch-mode-not-available = 터치 모드를 사용할 수 없습니다
+
+## Tasks
+
+taskchain-start = 작업 시작: { $taskchain }
+taskchain-completed = 작업 완료: { $taskchain }
+taskchain-stopped = 작업 중지: { $taskchain }
+taskchain-error = 작업 실패: { $taskchain }
+taskchain-completed-in = 작업 완료: { $taskchain } (소요 시간 { $duration })
+taskchain-stopped-after = 작업 중지: { $taskchain } (소요 시간 { $duration })
+taskchain-error-after = 작업 실패: { $taskchain } (소요 시간 { $duration })
+failed-to-start-game = 게임을 시작하지 못했습니다
+recruit-returned = { $why }, 돌아갔습니다
+failed-to-recognize-drops = 드롭을 인식하지 못했습니다
+failed-to-report-to-penguin-stats = 펭귄 물류에 드롭을 보고하지 못했습니다. { $why }
+stage-not-supported = EX 스테이지는 지원하지 않으므로 중지했습니다
+failed-to-switch-account = 계정을 전환하지 못했습니다
+failed-to-switch-account-to = 계정 { $account }(으)로 전환하지 못했습니다. 이 기기에서 로그인한 적이 있는 계정인지 확인하세요. 계정 이름은 전화번호나 사용자 이름처럼 계정의 일부여도 됩니다
+account-switched = 계정을 { $from }에서 { $to }(으)로 전환했습니다
+
+## Fight
+
+mission-start-times = 작전 시작, { $times }회
+use-stones = 오리지늄 { $count }개 사용
+prts-error = PRTS 오류, 작전을 포기했습니다
+drops = 드롭: { $drops }
+farmed = { $item } 파밍: { $farmed }/{ $quantity }
+current-sanity = 현재 이성: { $current }/{ $max }
+use-medicine = 이성 회복제 { $count }개 사용
+use-expiring-medicine = 곧 만료되는 이성 회복제 { $count }개 사용
+stage-info = 전투 시작: { $stage }
+stage-info-error = 스테이지를 인식하지 못했습니다
+
+## Recruit
+
+refresh-tags = 태그 갱신
+recruit = 공개모집
+recruiting-tips = 공개모집 팁: { $tag }
+recruit-result = 공개모집 결과: { $stars }
+recruit-result-tags = 태그: { $tags }
+recruit-result-best = 최적: { $tags } → { $stars }
+recruit-tags-selected = 선택한 태그: { $tags }
+recruit-tags-refreshed = 태그 갱신: { $count }회
+
+## Infrast
+
+infrast-dorm-double-confirmed = 오퍼레이터가 겹쳐서 숙소로 옮기는 것을 확인했습니다
+enter-facility = { $facility } #{ $index } 입장
+product-incorrect = 생산품이 올바르지 않습니다
+product-unknown = 생산품을 알 수 없습니다
+product-changed = 생산품을 변경했습니다
+not-enough-staff = 오퍼레이터가 부족합니다
+product-of-facility = 생산품: { $product }
+custom-infrast-room-operators = 오퍼레이터: { $operators }
+
+## Roguelike
+
+start-exploration = { $times }번째 탐색 시작
+exploration-abandoned = 탐색을 포기했습니다
+exploration-confirmed = 탐색을 정산했습니다
+mission-completed = 전투 완료
+mission-failed = 전투 실패
+stage-trader-enter = 상점 입장
+stage-safe-house-enter = 안전가옥 입장
+stage-combat-dps-enter = 작전 입장
+stage-emergency-dps-enter = 긴급 작전 입장
+stage-dreadful-foe-enter = 험로악적 입장
+trader-invest-system-full = 투자 시스템이 가득 찼습니다
+roguelike-game-pass = 클리어!
+roguelike-special-item-bought = 갱신 후 특별 상품을 구매했습니다
+roguelike-investment = 오리지늄 각뿔 { $count } / { $total } / { $deposit } 투자
+roguelike-settlement = 이번 탐색에서 경험치 { $exp } 획득
+game-offline = 게임이 오프라인입니다. 다시 연결하는 중
+mission-start = 작전 시작
+
+## Copilot
+
+battle-formation = 편성: { $operators }
+battle-formation-selected = 선택한 오퍼레이터: { $operator }
+copilot-action = 현재 단계: { $action } { $target }
+current-stage = 현재 스테이지: { $stage }
+sss-settlement = 정산: { $why }
+sss-game-pass = 모든 스테이지를 클리어했습니다
+unsupported-level = 지원하지 않는 스테이지입니다
+
+## Status line
+
+progress-navigating = { $stage }(으)로 이동 중
+progress-times = { $times }회
+progress-times-total = { $times }/{ $total }회
+progress-refreshing-tags = 태그 갱신 중
+progress-recruiting = 모집 중
+progress-exploration = { $times }번째 탐색
+progress-entering-facility = { $facility } #{ $index } 입장 중
+
+## Summary
+
+summary = 요약
+result-completed = 완료
+result-stopped = 중지
+result-error = 오류
+result-timed-out = 시간 초과
+result-unfinished = 미완료
+result-unstarted = 시작 안 함
+result-skipped = 건너뜀
+copilot-completed = 자동 지휘: { $completed }/{ $total } 완료
+not-completed = 미완료: { $tasks }
+overview = 개요
+overview-duration = 소요 시간: { $duration }
+overview-tasks = 작업: { $results }
+overview-completed = 완료 { $count }
+overview-stopped = 중지 { $count }
+overview-error = 오류 { $count }
+overview-timed-out = 시간 초과 { $count }
+overview-unfinished = 미완료 { $count }
+overview-unstarted = 시작 안 함 { $count }
+overview-skipped = 건너뜀 { $count }
+overview-stages = 스테이지: { $stages }
+overview-sanity = 소모한 이성: { $sanity }
+overview-recruit = 공개모집: 모집 { $total }회, 갱신 { $refreshed }회
+overview-recruit-levels = 공개모집: 모집 { $total }회 ({ $levels }), 갱신 { $refreshed }회
+overview-infrast = 기반시설: { $rooms }개 방 교대
+infrast-operators = 오퍼레이터: { $operators }
+fight-stage = { $stage } 파밍
+fight-times = { $times }회
+fight-medicine = 이성 회복제 { $count }개 사용 (곧 만료 { $expiring }개)
+fight-stone = 오리지늄 { $count }개 사용
+fight-drops = 드롭:
+fight-total-drops = 전체 드롭:
+recruit-detected-tags = 인식한 태그:
+recruit-refreshed = 갱신함
+recruit-recruited = 모집함
+recruit-recruited-times = 모집 { $times }회
+recruit-refreshed-times = 갱신 { $times }회
+explorations = 탐색:
+explorations-summary = 요약:
+exploration-state-passed = 클리어
+exploration-state-failed = 실패
+exploration-state-abandoned = 포기
+exploration-state-unknown = 알 수 없음
+exploration-invest = 오리지늄 각뿔 { $count } 투자
+exploration-exp = 경험치 { $exp } 획득
+explorations-invest = 총 오리지늄 각뿔 { $count } 투자
+explorations-exp = 총 경험치 { $exp } 획득
+sss-stages = 스테이지:
+sss-passed = 모든 스테이지를 클리어했습니다
+sss-settled = 정산: { $why }
+
+## Errors
+
+error-load-profile = 프로필을 불러오지 못했습니다
+error-apply-options = 옵션을 프로필에 적용하지 못했습니다
+error-load-tasks = 작업을 불러오지 못했습니다
+error-resolve-tasks = 작업을 해석하지 못했습니다
+error-load-core = MaaCore를 불러오지 못했습니다!
+error-setup-core = MaaCore를 설정하지 못했습니다!
+error-launch-emulator = 에뮬레이터를 실행하지 못했습니다
+error-open-app = 외부 앱을 열지 못했습니다
+error-check-resolution = 해상도를 확인하지 못했습니다
+error-connect = 기기에 연결하지 못했습니다
+error-interrupted = 사용자가 중단했습니다!
+error-task-aborted = 작업 { $task }이(가) 실패하여 남은 작업을 중단했습니다
+error-task-failed = 작업을 실행하는 중 오류가 발생했습니다!
+error-task-timed-out = 작업 { $task }의 시간이 초과되어 남은 작업을 중단했습니다
+error-timed-out = 작업 시간이 초과되어 남은 작업을 중단했습니다
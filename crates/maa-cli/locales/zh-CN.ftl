@@ -0,0 +1,183 @@
+## Messages of MaaCore
+
+initialization-error = MaaCore 初始化失败
+all-tasks-completed = 任务已全部完成
+none = 无
+unknown = 未知
+
+## Connection
+
+connect-failed = 连接安卓设备失败，{ $why }，请检查连接配置：{ $details }
+unsupported-resolution = 不支持该设备的分辨率
+resolution-error = 获取设备分辨率失败
+connected = 已连接
+disconnected = 连接已断开
+reconnecting = 正在重新连接，第 { $times } 次尝试
+reconnected = 重新连接成功
+screencap-failed = 截图失败
+fastest-way-to-screencap = 最快的截图方式：{ $method }（{ $cost } ms）
+touch-mode-not-available = 触控模式不可用
+
+## Tasks
+
+taskchain-start = 开始任务：{ $taskchain }
+taskchain-completed = 完成任务：{ $taskchain }
+taskchain-stopped = 停止任务：{ $taskchain }
+taskchain-error = 任务出错：{ $taskchain }
+taskchain-completed-in = 完成任务：{ $taskchain }，耗时 { $duration }
+taskchain-stopped-after = 停止任务：{ $taskchain }，耗时 { $duration }
+taskchain-error-after = 任务出错：{ $taskchain }，耗时 { $duration }
+failed-to-start-game = 启动游戏失败
+recruit-returned = { $why }，已返回
+failed-to-recognize-drops = 掉落识别失败
+failed-to-report-to-penguin-stats = 上报企鹅物流失败，{ $why }
+stage-not-supported = 不支持 EX 关卡，已停止
+failed-to-switch-account = 切换账号失败
+failed-to-switch-account-to = 切换到账号 { $account } 失败，请确认该账号已在此设备上登录过，账号名可以是账号的一部分，例如手机号或用户名
+account-switched = 已从账号 { $from } 切换到 { $to }
+
+## Fight
+
+mission-start-times = 开始行动，第 { $times } 次
+use-stones = 使用 { $count } 颗源石
+prts-error = 代理指挥出错，已放弃行动
+drops = 掉落：{ $drops }
+farmed = 已刷取 { $item }：{ $farmed }/{ $quantity }
+current-sanity = 当前理智：{ $current }/{ $max }
+use-medicine = 使用 { $count } 瓶理智药
+use-expiring-medicine = 使用 { $count } 瓶即将过期的理智药
+stage-info = 开始战斗：{ $stage }
+stage-info-error = 关卡识别失败
+
+## Recruit
+
+refresh-tags = 刷新标签
+recruit = 招募
+recruiting-tips = 公招提示：{ $tag }
+recruit-result = 公招结果：{ $stars }
+recruit-result-tags = 标签：{ $tags }
+recruit-result-best = 最佳：{ $tags } → { $stars }
+recruit-tags-selected = 已选择标签：{ $tags }
+recruit-tags-refreshed = 已刷新标签：{ $count } 次
+
+## Infrast
+
+infrast-dorm-double-confirmed = 干员冲突，已确认将其移入宿舍
+enter-facility = 进入 { $facility } #{ $index }
+product-incorrect = 产物不正确
+product-unknown = 产物未知
+product-changed = 产物已更换
+not-enough-staff = 可用干员不足
+product-of-facility = 产物：{ $product }
+custom-infrast-room-operators = 干员：{ $operators }
+
+## Roguelike
+
+start-exploration = 开始第 { $times } 次探索
+exploration-abandoned = 已放弃本次探索
+exploration-confirmed = 探索已结算
+mission-completed = 战斗完成
+mission-failed = 战斗失败
+stage-trader-enter = 进入商店
+stage-safe-house-enter = 进入安全屋
+stage-combat-dps-enter = 进入普通作战
+stage-emergency-dps-enter = 进入紧急作战
+stage-dreadful-foe-enter = 进入险路恶敌
+trader-invest-system-full = 投资系统已满
+roguelike-game-pass = 通关！
+roguelike-special-item-bought = 刷新后购买了特殊商品
+roguelike-investment = 投资 { $count } / { $total } / { $deposit } 源石锭
+roguelike-settlement = 本次探索获得 { $exp } 经验
+game-offline = 游戏掉线，正在重新连接
+mission-start = 开始行动
+
+## Copilot
+
+battle-formation = 编队：{ $operators }
+battle-formation-selected = 已选择干员：{ $operator }
+copilot-action = 当前步骤：{ $action } { $target }
+current-stage = 当前关卡：{ $stage }
+sss-settlement = 已结算：{ $why }
+sss-game-pass = 已通过所有关卡
+unsupported-level = 不支持该关卡
+
+## Status line
+
+progress-navigating = 正在前往 { $stage }
+progress-times = 第 { $times } 次
+progress-times-total = 第 { $times }/{ $total } 次
+progress-refreshing-tags = 正在刷新标签
+progress-recruiting = 正在招募
+progress-exploration = 第 { $times } 次探索
+progress-entering-facility = 正在进入 { $facility } #{ $index }
+
+## Summary
+
+summary = 总结
+result-completed = 完成
+result-stopped = 停止
+result-error = 出错
+result-timed-out = 超时
+result-unfinished = 未完成
+result-unstarted = 未开始
+result-skipped = 已跳过
+copilot-completed = 自动战斗：完成 { $completed }/{ $total }
+not-completed = 未完成：{ $tasks }
+overview = 概览
+overview-duration = 耗时：{ $duration }
+overview-tasks = 任务：{ $results }
+overview-completed = { $count } 个完成
+overview-stopped = { $count } 个停止
+overview-error = { $count } 个出错
+overview-timed-out = { $count } 个超时
+overview-unfinished = { $count } 个未完成
+overview-unstarted = { $count } 个未开始
+overview-skipped = { $count } 个已跳过
+overview-stages = 关卡：{ $stages }
+overview-sanity = 消耗理智：{ $sanity }
+overview-recruit = 公招：招募 { $total } 次，刷新 { $refreshed } 次
+overview-recruit-levels = 公招：招募 { $total } 次（{ $levels }），刷新 { $refreshed } 次
+overview-infrast = 基建：换班 { $rooms } 个房间
+infrast-operators = 干员：{ $operators }
+fight-stage = 刷取 { $stage }
+fight-times = { $times } 次
+fight-medicine = 使用 { $count } 瓶理智药（{ $expiring } 瓶即将过期）
+fight-stone = 使用 { $count } 颗源石
+fight-drops = 掉落：
+fight-total-drops = 总掉落：
+recruit-detected-tags = 识别到的标签：
+recruit-refreshed = 已刷新
+recruit-recruited = 已招募
+recruit-recruited-times = 招募 { $times } 次
+recruit-refreshed-times = 刷新 { $times } 次
+explorations = 探索：
+explorations-summary = 总结：
+exploration-state-passed = 通关
+exploration-state-failed = 失败
+exploration-state-abandoned = 放弃
+exploration-state-unknown = 未知
+exploration-invest = 投资 { $count } 源石锭
+exploration-exp = 获得 { $exp } 经验
+explorations-invest = 共投资 { $count } 源石锭
+explorations-exp = 共获得 { $exp } 经验
+sss-stages = 关卡：
+sss-passed = 已通过所有关卡
+sss-settled = 已结算：{ $why }
+
+## Errors
+
+error-load-profile = 加载配置文件失败
+error-apply-options = 应用选项到配置文件失败
+error-load-tasks = 加载任务失败
+error-resolve-tasks = 解析任务失败
+error-load-core = 加载 MaaCore 失败！
+error-setup-core = 设置 MaaCore 失败！
+error-launch-emulator = 启动模拟器失败
+error-open-app = 打开外部应用失败
+error-check-resolution = 检查分辨率失败
+error-connect = 连接设备失败
+error-interrupted = 已被用户中断！
+error-task-aborted = 任务 { $task } 失败，已中止剩余任务
+error-task-failed = 运行任务时出现错误！
+error-task-timed-out = 任务 { $task } 超时，已中止剩余任务
+error-timed-out = 任务超时，已中止剩余任务
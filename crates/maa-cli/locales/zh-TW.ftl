@@ -0,0 +1,183 @@
+## Messages of MaaCore
+
+initialization-error = MaaCore 初始化失敗
+all-tasks-completed = 任務已全部完成
+none = 無
+unknown = 未知
+
+## Connection
+
+connect-failed = 連線 Android 裝置失敗，{ $why }，請檢查連線設定：{ $details }
+unsupported-resolution = 不支援該裝置的解析度
+resolution-error = 取得裝置解析度失敗
+connected = 已連線
+disconnected = 連線已中斷
+reconnecting = 正在重新連線，第 { $times } 次嘗試
+reconnected = 重新連線成功
+screencap-failed = 截圖失敗
+fastest-way-to-screencap = 最快的截圖方式：{ $method }（{ $cost } ms）
+touch-mode-not-available = 觸控模式無法使用
+
+## Tasks
+
+taskchain-start = 開始任務：{ $taskchain }
+taskchain-completed = 完成任務：{ $taskchain }
+taskchain-stopped = 停止任務：{ $taskchain }
+taskchain-error = 任務出錯：{ $taskchain }
+taskchain-completed-in = 完成任務：{ $taskchain }，耗時 { $duration }
+taskchain-stopped-after = 停止任務：{ $taskchain }，耗時 { $duration }
+taskchain-error-after = 任務出錯：{ $taskchain }，耗時 { $duration }
+failed-to-start-game = 啟動遊戲失敗
+recruit-returned = { $why }，已返回
+failed-to-recognize-drops = 掉落辨識失敗
+failed-to-report-to-penguin-stats = 回報企鵝物流失敗，{ $why }
+stage-not-supported = 不支援 EX 關卡，已停止
+failed-to-switch-account = 切換帳號失敗
+failed-to-switch-account-to = 切換到帳號 { $account } 失敗，請確認該帳號已在此裝置上登入過，帳號名稱可以是帳號的一部分，例如手機號碼或使用者名稱
+account-switched = 已從帳號 { $from } 切換到 { $to }
+
+## Fight
+
+mission-start-times = 開始行動，第 { $times } 次
+use-stones = 使用 { $count } 顆源石
+prts-error = 代理指揮出錯，已放棄行動
+drops = 掉落：{ $drops }
+farmed = 已刷取 { $item }：{ $farmed }/{ $quantity }
+current-sanity = 目前理智：{ $current }/{ $max }
+use-medicine = 使用 { $count } 瓶理智藥
+use-expiring-medicine = 使用 { $count } 瓶即將過期的理智藥
+stage-info = 開始戰鬥：{ $stage }
+stage-info-error = 關卡辨識失敗
+
+## Recruit
+
+refresh-tags = 重新整理標籤
+recruit = 招募
+recruiting-tips = 公招提示：{ $tag }
+recruit-result = 公招結果：{ $stars }
+recruit-result-tags = 標籤：{ $tags }
+recruit-result-best = 最佳：{ $tags } → { $stars }
+recruit-tags-selected = 已選擇標籤：{ $tags }
+recruit-tags-refreshed = 已重新整理標籤：{ $count } 次
+
+## Infrast
+
+infrast-dorm-double-confirmed = 幹員衝突，已確認將其移入宿舍
+enter-facility = 進入 { $facility } #{ $index }
+product-incorrect = 產物不正確
+product-unknown = 產物未知
+product-changed = 產物已更換
+not-enough-staff = 可用幹員不足
+product-of-facility = 產物：{ $product }
+custom-infrast-room-operators = 幹員：{ $operators }
+
+## Roguelike
+
+start-exploration = 開始第 { $times } 次探索
+exploration-abandoned = 已放棄本次探索
+exploration-confirmed = 探索已結算
+mission-completed = 戰鬥完成
+mission-failed = 戰鬥失敗
+stage-trader-enter = 進入商店
+stage-safe-house-enter = 進入安全屋
+stage-combat-dps-enter = 進入普通作戰
+stage-emergency-dps-enter = 進入緊急作戰
+stage-dreadful-foe-enter = 進入險路惡敵
+trader-invest-system-full = 投資系統已滿
+roguelike-game-pass = 通關！
+roguelike-special-item-bought = 重新整理後購買了特殊商品
+roguelike-investment = 投資 { $count } / { $total } / { $deposit } 源石錠
+roguelike-settlement = 本次探索獲得 { $exp } 經驗
+game-offline = 遊戲斷線，正在重新連線
+mission-start = 開始行動
+
+## Copilot
+
+battle-formation = 編隊：{ $operators }
+battle-formation-selected = 已選擇幹員：{ $operator }
+copilot-action = 目前步驟：{ $action } { $target }
+current-stage = 目前關卡：{ $stage }
+sss-settlement = 已結算：{ $why }
+sss-game-pass = 已通過所有關卡
+unsupported-level = 不支援該關卡
+
+## Status line
+
+progress-navigating = 正在前往 { $stage }
+progress-times = 第 { $times } 次
+progress-times-total = 第 { $times }/{ $total } 次
+progress-refreshing-tags = 正在重新整理標籤
+progress-recruiting = 正在招募
+progress-exploration = 第 { $times } 次探索
+progress-entering-facility = 正在進入 { $facility } #{ $index }
+
+## Summary
+
+summary = 總結
+result-completed = 完成
+result-stopped = 停止
+result-error = 出錯
+result-timed-out = 逾時
+result-unfinished = 未完成
+result-unstarted = 未開始
+result-skipped = 已跳過
+copilot-completed = 自動戰鬥：完成 { $completed }/{ $total }
+not-completed = 未完成：{ $tasks }
+overview = 概覽
+overview-duration = 耗時：{ $duration }
+overview-tasks = 任務：{ $results }
+overview-completed = { $count } 個完成
+overview-stopped = { $count } 個停止
+overview-error = { $count } 個出錯
+overview-timed-out = { $count } 個逾時
+overview-unfinished = { $count } 個未完成
+overview-unstarted = { $count } 個未開始
+overview-skipped = { $count } 個已跳過
+overview-stages = 關卡：{ $stages }
+overview-sanity = 消耗理智：{ $sanity }
+overview-recruit = 公招：招募 { $total } 次，重新整理 { $refreshed } 次
+overview-recruit-levels = 公招：招募 { $total } 次（{ $levels }），重新整理 { $refreshed } 次
+overview-infrast = 基建：換班 { $rooms } 個房間
+infrast-operators = 幹員：{ $operators }
+fight-stage = 刷取 { $stage }
+fight-times = { $times } 次
+fight-medicine = 使用 { $count } 瓶理智藥（{ $expiring } 瓶即將過期）
+fight-stone = 使用 { $count } 顆源石
+fight-drops = 掉落：
+fight-total-drops = 總掉落：
+recruit-detected-tags = 辨識到的標籤：
+recruit-refreshed = 已重新整理
+recruit-recruited = 已招募
+recruit-recruited-times = 招募 { $times } 次
+recruit-refreshed-times = 重新整理 { $times } 次
+explorations = 探索：
+explorations-summary = 總結：
+exploration-state-passed = 通關
+exploration-state-failed = 失敗
+exploration-state-abandoned = 放棄
+exploration-state-unknown = 未知
+exploration-invest = 投資 { $count } 源石錠
+exploration-exp = 獲得 { $exp } 經驗
+explorations-invest = 共投資 { $count } 源石錠
+explorations-exp = 共獲得 { $exp } 經驗
+sss-stages = 關卡：
+sss-passed = 已通過所有關卡
+sss-settled = 已結算：{ $why }
+
+## Errors
+
+error-load-profile = 載入設定檔失敗
+error-apply-options = 套用選項到設定檔失敗
+error-load-tasks = 載入任務失敗
+error-resolve-tasks = 解析任務失敗
+error-load-core = 載入 MaaCore 失敗！
+error-setup-core = 設定 MaaCore 失敗！
+error-launch-emulator = 啟動模擬器失敗
+error-open-app = 開啟外部應用程式失敗
+error-check-resolution = 檢查解析度失敗
+error-connect = 連線裝置失敗
+error-interrupted = 已被使用者中斷！
+error-task-aborted = 任務 { $task } 失敗，已中止剩餘任務
+error-task-failed = 執行任務時發生錯誤！
+error-task-timed-out = 任務 { $task } 逾時，已中止剩餘任務
+error-timed-out = 任務逾時，已中止剩餘任務
@@ -1,26 +1,196 @@
-use std::{io::Write, path::Path, sync::LazyLock};
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, LazyLock, PoisonError, RwLock},
+    time::Duration,
+};
 
 use anyhow::{bail, Context, Result};
 use chrono::{DateTime, FixedOffset, NaiveDateTime};
-use log::warn;
+use log::{info, warn};
 use serde::Deserialize;
 use serde_json::Value as JsonValue;
 
 use crate::{config::task::ClientType, dirs};
 
-static STAGE_ACTIVITY: LazyLock<Option<StageActivityJson>> = LazyLock::new(|| {
-    load_stage_activity(
-        dirs::hot_update()
-            .join("cache")
-            .join("gui")
-            .join("StageActivity.json"),
+/// Base URL of OTA data of MAA, which is updated earlier than the resource repository
+const OTA_URL: &str = "https://ota.maa.plus/MaaAssistantArknights/api";
+
+/// Stage activity loaded on first use, which is replaced once it's fetched from OTA
+static STAGE_ACTIVITY: LazyLock<RwLock<Option<Arc<StageActivityJson>>>> = LazyLock::new(|| {
+    RwLock::new(
+        load_stage_activity(stage_activity_path())
+            .warn_err()
+            .map(Arc::new),
     )
-    .warn_err()
 });
 
-pub fn has_side_story_open(client: ClientType) -> bool {
+fn stage_activity() -> Option<Arc<StageActivityJson>> {
     STAGE_ACTIVITY
-        .as_ref()
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Directory of data fetched from OTA, which has the same layout as the cache of hot update
+fn ota_dir() -> PathBuf {
+    dirs::cache().join("ota")
+}
+
+/// Time the resource in the directory is updated, by `last_updated` of its `version.json`
+fn last_updated(resource_dir: &Path) -> Option<NaiveDateTime> {
+    let version: JsonValue =
+        serde_json::from_reader(std::fs::File::open(resource_dir.join("version.json")).ok()?)
+            .ok()?;
+    NaiveDateTime::parse_from_str(
+        version.get("last_updated")?.as_str()?,
+        "%Y-%m-%d %H:%M:%S%.f",
+    )
+    .ok()
+}
+
+/// Whether the resource fetched from OTA is updated later than the hot update resource
+///
+/// Resource without version is older than any resource with a version.
+fn is_ota_newer() -> bool {
+    let hot_update = dirs::hot_update();
+    let hot_update = last_updated(&hot_update.join("resource"))
+        .max(last_updated(&join!(hot_update, "cache", "resource")));
+    match (last_updated(&ota_dir().join("resource")), hot_update) {
+        (Some(ota), Some(hot_update)) => ota > hot_update,
+        (Some(_), None) => true,
+        (None, _) => false,
+    }
+}
+
+/// Path of StageActivity.json, the one fetched from OTA is used if it's newer
+fn stage_activity_path() -> PathBuf {
+    if is_ota_newer() {
+        join!(ota_dir(), "gui", "StageActivity.json")
+    } else {
+        join!(dirs::hot_update(), "cache", "gui", "StageActivity.json")
+    }
+}
+
+/// Resource directory fetched from OTA, if it's newer than the hot update resource
+pub fn ota_resource_dir() -> Option<PathBuf> {
+    is_ota_newer().then(|| ota_dir().join("resource"))
+}
+
+/// Whether the stage looks like a stage of an event (side story), e.g. `XX-8`
+///
+/// Main story, resource and annihilation stages are not event stages,
+/// and they can always be navigated by MaaCore.
+fn is_event_stage(stage: &str) -> bool {
+    let Some((prefix, number)) = stage.rsplit_once('-') else {
+        return false;
+    };
+    let prefix = prefix.to_ascii_uppercase();
+    prefix.len() >= 2
+        && prefix.chars().all(|c| c.is_ascii_alphabetic())
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+        && !matches!(
+            prefix.as_str(),
+            "CE" | "CA" | "AP" | "SK" | "LS" | "PR" | "TR"
+        )
+}
+
+/// Fetch the latest stage activity and tasks from OTA if any event stage is unknown
+///
+/// Event stages are navigated by MaaCore with the stage activity and tasks of the resource,
+/// which are only updated some time after a new event is opened. To fight a new event stage
+/// on the first day, the data is fetched from OTA on demand.
+///
+/// Return the resource directory fetched from OTA, which should be loaded after the hot update
+/// resource, or `None` if there is no newer OTA resource.
+pub fn update_event_stages<'a>(
+    stages: impl IntoIterator<Item = &'a str>,
+    client: ClientType,
+) -> Option<PathBuf> {
+    let is_unknown = |stage_activity: Option<&StageActivityJson>, stage: &str| {
+        !stage_activity.is_some_and(|s| s.get_stage_activity(client).has_stage(stage))
+    };
+
+    let stage_activity = stage_activity();
+    let unknown: Vec<&str> = stages
+        .into_iter()
+        .filter(|stage| is_event_stage(stage))
+        .filter(|stage| is_unknown(stage_activity.as_deref(), stage))
+        .collect();
+    if unknown.is_empty() {
+        return ota_resource_dir();
+    }
+
+    info!(
+        "Stage {} not found in local stage activity, fetching from OTA",
+        unknown.join(", ")
+    );
+    match fetch_ota(client) {
+        Ok(()) => {
+            // Following lookups, like resource stages and event drops, use the fetched one
+            let stage_activity = load_stage_activity(stage_activity_path())
+                .warn_err()
+                .map(Arc::new);
+            *STAGE_ACTIVITY
+                .write()
+                .unwrap_or_else(PoisonError::into_inner) = stage_activity.clone();
+            for stage in unknown {
+                if is_unknown(stage_activity.as_deref(), stage) {
+                    warn!("Stage {stage} is not an opening event stage, MaaCore may fail to navigate to it");
+                }
+            }
+        }
+        Err(err) => warn!("Failed to fetch stage activity from OTA: {err:#}"),
+    }
+
+    ota_resource_dir()
+}
+
+fn fetch_ota(client: ClientType) -> Result<()> {
+    let mut files = vec![
+        PathBuf::from("gui/StageActivity.json"),
+        PathBuf::from("resource/tasks.json"),
+    ];
+    if let Some(global_resource) = client.resource() {
+        files.push(join!(
+            "resource",
+            "global",
+            global_resource,
+            "resource",
+            "tasks.json"
+        ));
+    }
+    // The version is written last, so that partially fetched data is never used
+    files.push(PathBuf::from("resource/version.json"));
+
+    let http = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+    let ota_dir = ota_dir();
+    for file in files {
+        // URL always uses `/` as separator, even on Windows
+        let path = file.to_string_lossy().replace('\\', "/");
+        let content = http
+            .get(format!("{OTA_URL}/{path}"))
+            .send()
+            .and_then(|resp| resp.error_for_status())
+            .and_then(|resp| resp.bytes())
+            .with_context(|| format!("Failed to download {path}"))?;
+
+        let dest = ota_dir.join(&file);
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&dest, content)
+            .with_context(|| format!("Failed to write {}", dest.display()))?;
+    }
+
+    Ok(())
+}
+
+pub fn has_side_story_open(client: ClientType) -> bool {
+    stage_activity()
         .map(|stage_activity| {
             stage_activity
                 .get_stage_activity(client)
@@ -29,8 +199,18 @@ pub fn has_side_story_open(client: ClientType) -> bool {
         .unwrap_or(false)
 }
 
+/// Whether all resource stages are open by the resource collection event of the client
+pub fn is_resource_collection_open(client: ClientType) -> bool {
+    stage_activity().is_some_and(|stage_activity| {
+        stage_activity
+            .get_stage_activity(client)
+            .resource_collection
+            .is_active()
+    })
+}
+
 pub fn display_stage_activity(client: ClientType) -> std::io::Result<()> {
-    if let Some(stage_activity) = STAGE_ACTIVITY.as_ref() {
+    if let Some(stage_activity) = stage_activity() {
         stage_activity.display(std::io::stdout(), client)?;
         std::io::stdout().flush()?;
     }
@@ -115,6 +295,54 @@ impl StageActivityContent {
         }
         false
     }
+
+    fn has_stage(&self, stage: &str) -> bool {
+        self.side_story_stage
+            .iter()
+            .any(|s| s.value.eq_ignore_ascii_case(stage) && s.activity.is_active())
+    }
+}
+
+/// IDs of items with given name in the item data, in the language of the client
+pub fn item_ids_named(name: &str, client: ClientType) -> Vec<String> {
+    let Ok(JsonValue::Object(item_index)) = load_item_index(client) else {
+        return Vec::new();
+    };
+
+    item_index
+        .iter()
+        .filter(|(_, item)| {
+            item.get("name")
+                .and_then(JsonValue::as_str)
+                .is_some_and(|item| item.eq_ignore_ascii_case(name))
+        })
+        .map(|(id, _)| id.clone())
+        .collect()
+}
+
+/// Opening event stages dropping the item with given name, in the language of the client
+pub fn event_stages_dropping(item: &str, client: ClientType) -> Vec<String> {
+    let Some(stage_activity) = stage_activity() else {
+        return Vec::new();
+    };
+    let Ok(item_index) = load_item_index(client) else {
+        return Vec::new();
+    };
+    let item_name = |id: &str| {
+        item_index
+            .get(id)
+            .and_then(|item| item.get("name"))
+            .and_then(JsonValue::as_str)
+    };
+
+    stage_activity
+        .get_stage_activity(client)
+        .side_story_stage
+        .iter()
+        .filter(|stage| stage.activity.is_active())
+        .filter(|stage| item_name(&stage.drop).is_some_and(|name| name.eq_ignore_ascii_case(item)))
+        .map(|stage| stage.value.clone())
+        .collect()
 }
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
@@ -436,13 +664,84 @@ mod tests {
         .has_side_story_open());
     }
 
+    #[test]
+    fn event_stage() {
+        assert!(is_event_stage("XX-8"));
+        assert!(is_event_stage("fc-7"));
+        assert!(is_event_stage("IS-10"));
+        assert!(!is_event_stage("1-7"));
+        assert!(!is_event_stage("H9-4"));
+        assert!(!is_event_stage("S3-7"));
+        assert!(!is_event_stage("CE-6"));
+        assert!(!is_event_stage("PR-A-1"));
+        assert!(!is_event_stage("LS-6"));
+        assert!(!is_event_stage("Annihilation"));
+        assert!(!is_event_stage("SSReopen-FC"));
+        assert!(!is_event_stage(""));
+    }
+
+    #[test]
+    fn has_stage() {
+        let activity = |expire: &str| ActivityInfo {
+            tip: "Test".to_string(),
+            utc_start_time: "1970/01/01 00:00:00".to_string(),
+            utc_expire_time: expire.to_string(),
+            time_zone: 8,
+        };
+        let content = StageActivityContent {
+            side_story_stage: vec![
+                StageInfo {
+                    value: "FC-7".to_string(),
+                    drop: "31043".to_string(),
+                    activity: activity("3000/01/01 00:00:00"),
+                },
+                StageInfo {
+                    value: "IS-10".to_string(),
+                    drop: "30073".to_string(),
+                    activity: activity("1970/01/01 00:00:00"),
+                },
+            ],
+            resource_collection: activity("1970/01/01 00:00:00"),
+        };
+
+        assert!(content.has_stage("FC-7"));
+        assert!(content.has_stage("fc-7"));
+        assert!(!content.has_stage("FC-8"));
+        // closed event stage
+        assert!(!content.has_stage("IS-10"));
+    }
+
+    #[test]
+    fn resource_version() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(last_updated(dir.path()), None);
+
+        std::fs::write(
+            dir.path().join("version.json"),
+            r#"{"last_updated": "2024-05-14 08:08:19.123"}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            last_updated(dir.path()),
+            NaiveDateTime::parse_from_str("2024-05-14 08:08:19.123", "%Y-%m-%d %H:%M:%S%.f").ok()
+        );
+        // Versions are compared by time instead of strings
+        assert!(
+            last_updated(dir.path())
+                > NaiveDateTime::parse_from_str("2024-05-14 8:08:19", "%Y-%m-%d %H:%M:%S").ok()
+        );
+
+        std::fs::write(dir.path().join("version.json"), "{}").unwrap();
+        assert_eq!(last_updated(dir.path()), None);
+    }
+
     #[test]
     #[ignore = "need installed resource"]
     fn test_load_stage_activity() {
         if var_os("SKIP_CORE_TEST").is_some() {
             return;
         }
-        let _ = STAGE_ACTIVITY.as_ref();
+        let _ = stage_activity();
     }
 
     #[test]
@@ -1,6 +1,6 @@
 use std::path::PathBuf;
 
-use clap::{Parser, Subcommand, ValueEnum};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use clap_complete::Shell;
 
 use crate::{cleanup, config, log, run};
@@ -18,6 +18,14 @@ pub(crate) struct Cli {
     /// and parameters will be set to default values.
     #[arg(long, global = true)]
     pub(crate) batch: bool,
+    /// Answers of prompts in batch mode
+    ///
+    /// A file that supplies values of inputs in task files by their parameter keys,
+    /// answers in the `tasks.<name>` table are only used for the task with that name.
+    /// This option implies `--batch`, and an input without answer is an error
+    /// instead of using its default value.
+    #[arg(long, global = true, value_name = "FILE", verbatim_doc_comment)]
+    pub(crate) answers: Option<PathBuf>,
     #[command(flatten)]
     pub(crate) log: log::Args,
 }
@@ -99,12 +107,66 @@ pub(crate) enum Command {
     /// to define a task. More information can be found in the README.
     /// You can also use `maa-cli list` to list all available tasks.
     Run {
-        /// Name of the task to run
+        /// Names of the tasks to run
         ///
         /// The task name is the name of the task file without the extension.
         /// The task file must be in the `tasks` directory of the config directory.
         /// The task file must be in the TOML, YAML or JSON format.
-        task: String,
+        /// It can be omitted if tasks are given by `--task-json`.
+        /// Multiple task files are merged and run in the given order, in which
+        /// StartUp tasks after the first one are dropped unless they switch the account
+        /// or have a name, and only the last CloseDown task is kept and moved to the end.
+        /// Names of tasks must be unique across task files.
+        #[arg(
+            value_name = "TASK",
+            required_unless_present = "task_json",
+            verbatim_doc_comment
+        )]
+        tasks: Vec<String>,
+        /// Append a task given in JSON to the run, can be specified multiple times
+        ///
+        /// The task is given in the same format as the one in task files,
+        /// e.g. `{"type": "Custom", "params": {"task_names": ["..."]}}`,
+        /// or as a task of MaaCore, e.g. `{"type": "Fight", "stage": "1-7"}`,
+        /// whose type can be one not supported by maa-cli yet.
+        /// Use `-` to read a task or an array of tasks from stdin.
+        #[arg(long = "task-json", value_name = "JSON", verbatim_doc_comment)]
+        task_json: Vec<String>,
+        /// Account to switch to before running tasks
+        ///
+        /// The account name can be a part of the account, such as the phone number
+        /// or the username. This option overrides the `account` field in the task file
+        /// and the account of StartUp tasks in it.
+        /// If there is no StartUp task in the task file, one will be prepended
+        /// to switch the account.
+        #[arg(long, verbatim_doc_comment)]
+        account: Option<String>,
+        #[command(flatten)]
+        sanity: run::SanityArgs,
+        /// Override a parameter of tasks with given type, can be specified multiple times
+        ///
+        /// The override is given by `<type>.<key>=<value>`, e.g. `fight.stage=CE-6`,
+        /// which overrides the parameter `stage` of all fight tasks.
+        /// The key can be a dotted path to a nested parameter,
+        /// and the value is parsed as a TOML value or a string if it's not valid TOML.
+        /// Overrides have higher priority than the sanity options.
+        #[arg(long = "set", value_name = "TYPE.KEY=VALUE", verbatim_doc_comment)]
+        overrides: Vec<run::ParamOverride>,
+        /// Resume the last run of this task file after interruption
+        ///
+        /// The progress of each run is recorded in the state directory,
+        /// and tasks finished in the last run are skipped with this option.
+        /// A task is only skipped if its position and parameters are not changed.
+        /// StartUp and CloseDown tasks are always run.
+        #[arg(long, verbatim_doc_comment)]
+        resume: bool,
+        /// Print tasks to run today and exit without running them
+        ///
+        /// Tasks are selected by the weekly plan in the `week` table of task files
+        /// and conditions of tasks, where the day is decided by the server in the profile.
+        /// Other options like `--task-json`, `--account` and `--set` are applied as running.
+        #[arg(long, conflicts_with = "resume", verbatim_doc_comment)]
+        today: bool,
         #[command(flatten)]
         common: run::CommonArgs,
     },
@@ -153,6 +215,67 @@ pub(crate) enum Command {
         #[command(flatten)]
         common: run::CommonArgs,
     },
+    /// Run infrast task to shift operators in the base
+    Infrast {
+        #[command(flatten)]
+        params: run::preset::InfrastParams,
+        #[command(flatten)]
+        common: run::CommonArgs,
+    },
+    /// Recognize items in depot and export them
+    ///
+    /// The exported JSON can be imported by ArkPlanner (the planner of penguin-statistics)
+    /// or Arknights Toolbox (yituliu).
+    #[command(verbatim_doc_comment)]
+    Depot {
+        #[command(flatten)]
+        args: run::export::DepotArgs,
+        #[command(flatten)]
+        common: run::CommonArgs,
+    },
+    /// Recognize operators in operator box and export owned ones
+    ///
+    /// The exported JSON or CSV contains the id, name, rarity, elite, level and potential
+    /// of each owned operator, which can be used by rosters like Krooster.
+    #[command(name = "operbox", verbatim_doc_comment)]
+    OperBox {
+        #[command(flatten)]
+        args: run::export::OperBoxArgs,
+        #[command(flatten)]
+        common: run::CommonArgs,
+    },
+    /// Generate a copilot file from a video of a clear
+    ///
+    /// The video is recognized by MaaCore, and the generated copilot file can be
+    /// shared and run by `maa copilot`.
+    #[command(name = "convert-video", verbatim_doc_comment)]
+    ConvertVideo {
+        #[command(flatten)]
+        args: run::export::ConvertVideoArgs,
+        #[command(flatten)]
+        common: run::CommonArgs,
+    },
+    /// Collect credits and shop in credit store
+    Mall {
+        #[command(flatten)]
+        params: run::preset::MallParams,
+        #[command(flatten)]
+        common: run::CommonArgs,
+    },
+    /// Collect rewards of daily and weekly missions, mails and other free gifts
+    Award {
+        #[command(flatten)]
+        params: run::preset::AwardParams,
+        #[command(flatten)]
+        common: run::CommonArgs,
+    },
+    /// Run recruit task or recognize recruitment tags
+    Recruit {
+        #[command(flatten)]
+        params: run::preset::RecruitParams,
+        #[command(flatten)]
+        common: run::CommonArgs,
+    },
     /// Run Reclamation Algorithm task
     Reclamation {
         #[command(flatten)]
@@ -160,6 +283,18 @@ pub(crate) enum Command {
         #[command(flatten)]
         common: run::CommonArgs,
     },
+    /// Run common daily tasks with the defaults of each task
+    ///
+    /// Start the game, fight, shift operators in the base, recruit, visit friends and buy items
+    /// in the credit store, collect rewards and close the game, in this order. All tasks run with
+    /// the same parameters as the corresponding commands without arguments, like `maa infrast`,
+    /// so they can be customized by the `defaults` of profile.
+    Daily {
+        #[command(flatten)]
+        params: run::preset::DailyParams,
+        #[command(flatten)]
+        common: run::CommonArgs,
+    },
     /// Convert file format between TOML, YAML and JSON
     ///
     /// This command will convert a file from TOML, YAML or JSON format to another format.
@@ -202,7 +337,66 @@ pub(crate) enum Command {
         targets: Vec<cleanup::CleanupTarget>,
     },
     /// List all available tasks
-    List,
+    ///
+    /// Each task file is listed with its description, or the error if it fails to parse,
+    /// the time and result of its last run, and its tasks with variants active now.
+    List {
+        /// Only list names of task files
+        #[arg(long)]
+        names: bool,
+    },
+    /// Show past runs with their tasks, results and drops
+    ///
+    /// Every run of tasks is recorded in `history.db` in the state directory,
+    /// with the result and duration of each task, drops of fight tasks
+    /// and the error if the run failed.
+    History {
+        /// Number of the latest runs to show
+        #[arg(long, default_value_t = 10)]
+        last: usize,
+        /// Only show tasks of given type or name, e.g. `fight`, and runs with them
+        #[arg(long)]
+        task: Option<String>,
+    },
+    /// Run jobs in a long-lived assistant
+    ///
+    /// The daemon loads MaaCore and connects to the device only once,
+    /// and runs custom tasks submitted to its queue one by one,
+    /// which saves the startup and connection time of frequent small tasks.
+    #[command(subcommand)]
+    Daemon(DaemonCommand),
+    /// Stop a running instance of `maa run` or `maa daemon start`
+    ///
+    /// The current task is stopped immediately and the connection is closed cleanly,
+    /// which is the same as interrupting it twice by Ctrl-C.
+    Stop {
+        #[command(flatten)]
+        target: ControlArgs,
+    },
+    /// Pause a running instance of `maa run` or `maa daemon start`
+    ///
+    /// MaaCore can not pause a task, so the current task is stopped,
+    /// and run again from the beginning after resumed by `maa resume`.
+    Pause {
+        #[command(flatten)]
+        target: ControlArgs,
+    },
+    /// Resume a paused instance
+    Resume {
+        #[command(flatten)]
+        target: ControlArgs,
+    },
+    /// Run custom tasks periodically by schedules
+    ///
+    /// Schedules are defined in `schedule.toml` in the config directory,
+    /// each of which runs a custom task by a cron expression like `0 4 * * *`.
+    /// This command keeps running and runs each scheduled task by `maa run` in batch mode,
+    /// which is useful on systems without cron, like Windows.
+    Schedule {
+        /// List schedules and their next run time instead of running them
+        #[arg(long)]
+        list: bool,
+    },
     /// Import configuration files
     Import {
         /// Path of the configuration file
@@ -245,6 +439,9 @@ pub(crate) enum Command {
         #[arg(long)]
         force: bool,
     },
+    /// Manage configuration files
+    #[command(subcommand)]
+    Config(ConfigCommand),
     /// Generate completion script for given shell
     Complete { shell: Shell },
     /// Generate man page
@@ -269,6 +466,119 @@ pub(crate) enum SelfCommand {
     },
 }
 
+#[derive(Args)]
+pub(crate) struct ControlArgs {
+    /// Process id of the instance to control
+    ///
+    /// Required only if there are multiple running instances.
+    #[arg(long)]
+    pub pid: Option<u32>,
+}
+
+#[derive(Subcommand)]
+pub(crate) enum DaemonCommand {
+    /// Start the daemon and run jobs until it's terminated
+    ///
+    /// Options like `--startup`, `--closedown` and `--timeout` are applied to every job.
+    Start {
+        #[command(flatten)]
+        common: run::CommonArgs,
+    },
+    /// Submit a custom task to the queue of the daemon
+    ///
+    /// Jobs are run in the order of submission,
+    /// and can be submitted even if the daemon is not running.
+    Submit {
+        /// Name of the task to run
+        task: String,
+        /// Account to switch to before running tasks
+        #[arg(long)]
+        account: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub(crate) enum ConfigCommand {
+    /// Migrate configuration files to the current format
+    ///
+    /// Some configuration keys are renamed or deprecated between versions of maa-cli.
+    /// This command will detect configuration files in old format and rewrite them
+    /// to the current format. The original files will be backed up with a `.bak` suffix.
+    Migrate,
+    /// Edit a configuration file with editor
+    ///
+    /// This command will open the configuration file with the editor set by `VISUAL` or
+    /// `EDITOR` environment variable. After the editor exits, the configuration file will be
+    /// validated, and only be saved if it is valid. If the file does not exist, a new TOML file
+    /// will be created.
+    Edit {
+        /// Type of the configuration file
+        config_type: config::edit::ConfigType,
+        /// Name of the profile or task
+        ///
+        /// Required for task, default to `default` for profile, and ignored for cli.
+        name: Option<String>,
+    },
+    /// Show the effective configuration compared with built-in defaults
+    ///
+    /// Every known key is printed with its effective value, and the values differing from
+    /// built-in defaults are highlighted together with the file they come from.
+    /// Only cli and profile configurations are supported.
+    Diff {
+        /// Type of the configuration file
+        config_type: config::edit::ConfigType,
+        /// Name of the profile, default to `default`, ignored for cli
+        name: Option<String>,
+    },
+    /// Create a new task file from a template
+    ///
+    /// The task file is created in the `tasks` directory of the config directory in TOML format,
+    /// with commented fields, so you can start from a working task file and adjust it.
+    /// With `--interactive`, the task file is built by choosing tasks and their parameters
+    /// from menus instead.
+    /// After creating, you can edit it by `maa config edit task <name>`
+    /// and run it by `maa run <name>`.
+    #[command(verbatim_doc_comment)]
+    NewTask {
+        /// Name of the task file, without extension
+        name: String,
+        /// Template of the task file
+        #[arg(short, long, default_value = "daily")]
+        template: config::template::Template,
+        /// Build the task file by choosing tasks and parameters from menus
+        #[arg(short, long, conflicts_with = "template")]
+        interactive: bool,
+        /// Overwrite the existing task file with the same name
+        #[arg(long)]
+        force: bool,
+    },
+    /// Encrypt a configuration file with a passphrase
+    ///
+    /// The file is encrypted in place and decrypted transparently when it is loaded,
+    /// which is useful if you sync your configuration files to a public repository.
+    /// The passphrase is read from the `MAA_CONFIG_PASSPHRASE` environment variable,
+    /// or the key file specified by the `MAA_CONFIG_KEY_FILE` environment variable.
+    /// If neither is set, you will be prompted for the passphrase.
+    #[command(verbatim_doc_comment)]
+    Encrypt {
+        /// Type of the configuration file
+        config_type: config::edit::ConfigType,
+        /// Name of the profile or task
+        ///
+        /// Required for task, default to `default` for profile, and ignored for cli.
+        name: Option<String>,
+    },
+    /// Decrypt a configuration file encrypted by `maa config encrypt`
+    Decrypt {
+        /// Type of the configuration file
+        config_type: config::edit::ConfigType,
+        /// Name of the profile or task
+        ///
+        /// Required for task, default to `default` for profile, and ignored for cli.
+        name: Option<String>,
+    },
+}
+
 #[derive(ValueEnum, Clone, Default)]
 pub(crate) enum Component {
     #[default]
@@ -323,6 +633,10 @@ mod test {
     fn batch() {
         assert!(!parse_from(["maa", "list"]).batch);
         assert!(parse_from(["maa", "list", "--batch"]).batch);
+        assert_eq!(
+            parse_from(["maa", "run", "daily", "--answers", "answers.toml"]).answers,
+            Some(PathBuf::from("answers.toml"))
+        );
     }
 
     #[cfg(feature = "core_installer")]
@@ -508,45 +822,227 @@ mod test {
         assert_matches!(
             parse_from(["maa", "run", "task"]).command,
             Command::Run {
-                task,
+                tasks,
                 common: run::CommonArgs { .. },
-            } if task == "task"
+                ..
+            } if tasks == ["task"]
+        );
+
+        assert_matches!(
+            parse_from(["maa", "run", "--task-json", "{}", "--task-json", "-"]).command,
+            Command::Run { tasks, task_json, .. } if tasks.is_empty() && task_json == ["{}", "-"]
+        );
+        assert!(Cli::try_parse_from(["maa", "run"]).is_err());
+        assert_matches!(
+            parse_from(["maa", "run", "daily", "weekly-extras"]).command,
+            Command::Run { tasks, .. } if tasks == ["daily", "weekly-extras"]
+        );
+        assert_matches!(
+            parse_from(["maa", "run", "week", "--today"]).command,
+            Command::Run { today: true, .. }
+        );
+        assert!(Cli::try_parse_from(["maa", "run", "week", "--today", "--resume"]).is_err());
+
+        assert_matches!(
+            parse_from(["maa", "run", "task"]).command,
+            Command::Run { common, .. } if common.looping.sanity_loop.is_none()
+        );
+        assert_matches!(
+            parse_from(["maa", "run", "task", "--sanity-loop"]).command,
+            Command::Run { common, .. } if common.looping.sanity_loop == Some(None)
+        );
+        assert_matches!(
+            parse_from(["maa", "run", "task", "--sanity-loop", "100"]).command,
+            Command::Run { common, .. } if common.looping.sanity_loop == Some(Some(100))
+        );
+        assert_matches!(
+            parse_from([
+                "maa", "run", "farm", "--loop", "--interval", "4h", "--until", "2024-08-01T04:00",
+                "--count", "3", "--max-failures", "2",
+            ])
+            .command,
+            Command::Run { common, .. } if common.looping.repeat
+                && common.looping.interval == Some(std::time::Duration::from_secs(4 * 3600))
+                && common.looping.until.is_some()
+                && common.looping.count == Some(3)
+                && common.looping.max_failures == Some(2)
+        );
+        assert!(Cli::try_parse_from(["maa", "run", "farm", "--interval", "4h"]).is_err());
+        assert!(Cli::try_parse_from(["maa", "run", "farm", "--count", "3"]).is_err());
+        assert!(Cli::try_parse_from(["maa", "run", "farm", "--loop", "--sanity-loop"]).is_err());
+        assert!(
+            Cli::try_parse_from(["maa", "run", "farm", "--sanity-loop", "--count", "3"]).is_ok()
+        );
+
+        assert_matches!(
+            parse_from(["maa", "run", "task", "--device", "a", "--device", "b"]).command,
+            Command::Run { common, .. } if common.devices == ["a", "b"]
+        );
+        assert!(
+            Cli::try_parse_from(["maa", "run", "task", "--device", "a", "--instance", "b"])
+                .is_err()
         );
 
         assert!(matches!(
             parse_from(["maa", "run", "task", "-a", "addr"]).command,
             Command::Run {
-                task,
+                tasks,
                 common: run::CommonArgs {
                     addr: Some(addr),
                     ..
                 },
                 ..
-            } if task == "task" && addr == "addr"
+            } if tasks == ["task"] && addr == "addr"
         ));
         assert!(matches!(
             parse_from(["maa", "run", "task", "--addr", "addr"]).command,
             Command::Run {
-                task,
+                tasks,
                 common: run::CommonArgs {
                     addr: Some(addr),
                     ..
                 },
                 ..
-            } if task == "task" && addr == "addr"
+            } if tasks == ["task"] && addr == "addr"
         ));
 
+        assert_matches!(
+            parse_from(["maa", "run", "task", "--timeout", "1h30m"]).command,
+            Command::Run {
+                common: run::CommonArgs {
+                    timeout: Some(timeout),
+                    ..
+                },
+                ..
+            } if timeout == std::time::Duration::from_secs(5400)
+        );
+        assert!(Cli::try_parse_from(["maa", "run", "task", "--timeout", "soon"]).is_err());
+
+        assert_matches!(
+            parse_from(["maa", "run", "task", "--instance", "mumu"]).command,
+            Command::Run {
+                common: run::CommonArgs {
+                    instance: Some(instance),
+                    ..
+                },
+                ..
+            } if instance == "mumu"
+        );
+
         assert!(matches!(
             parse_from(["maa", "run", "task", "--user-resource"]).command,
             Command::Run {
-                task,
+                tasks,
                 common: run::CommonArgs {
                     user_resource: true,
                     ..
                 },
                 ..
-            } if task == "task"
+            } if tasks == ["task"]
         ));
+
+        assert_matches!(
+            parse_from([
+                "maa",
+                "run",
+                "task",
+                "--resource-layer",
+                "hotfix/resource",
+                "--resource-layer",
+                "tweaks/resource",
+            ])
+            .command,
+            Command::Run {
+                common: run::CommonArgs {
+                    resource_layers,
+                    ..
+                },
+                ..
+            } if resource_layers == [
+                PathBuf::from("hotfix/resource"),
+                PathBuf::from("tweaks/resource"),
+            ]
+        );
+
+        assert_matches!(
+            parse_from(["maa", "fight", "1-7", "--startup", "--closedown"]).command,
+            Command::Fight {
+                common: run::CommonArgs {
+                    startup: true,
+                    closedown: true,
+                    ..
+                },
+                ..
+            }
+        );
+        assert!(Cli::try_parse_from(["maa", "fight", "1-7", "--series", "7"]).is_err());
+
+        assert_matches!(
+            parse_from(["maa", "fight", "1-7", "--closedown-after", "30m"]).command,
+            Command::Fight {
+                common: run::CommonArgs {
+                    closedown_after: Some(idle),
+                    ..
+                },
+                ..
+            } if idle == std::time::Duration::from_secs(1800)
+        );
+        assert!(Cli::try_parse_from([
+            "maa",
+            "fight",
+            "1-7",
+            "--closedown",
+            "--closedown-after",
+            "30m"
+        ])
+        .is_err());
+
+        assert_matches!(
+            parse_from(["maa", "run", "task", "--account", "alt"]).command,
+            Command::Run {
+                account: Some(account),
+                ..
+            } if account == "alt"
+        );
+
+        assert_matches!(
+            parse_from(["maa", "run", "task", "-m", "2", "--stone", "1"]).command,
+            Command::Run {
+                sanity: run::SanityArgs {
+                    medicine: Some(2),
+                    expiring_medicine: None,
+                    stone: Some(1),
+                },
+                ..
+            }
+        );
+
+        assert_matches!(
+            parse_from(["maa", "run", "task", "--expiring-medicine"]).command,
+            Command::Run {
+                sanity: run::SanityArgs {
+                    expiring_medicine: Some(999),
+                    ..
+                },
+                ..
+            }
+        );
+
+        assert_matches!(
+            parse_from(["maa", "run", "task", "--set", "fight.stage=CE-6", "--set", "fight.times=3"])
+                .command,
+            Command::Run { overrides, .. } if overrides.len() == 2
+        );
+        assert!(Cli::try_parse_from(["maa", "run", "task", "--set", "stage=CE-6"]).is_err());
+
+        assert_matches!(parse_from(["maa", "run", "task"]).command, Command::Run {
+            resume: false,
+            ..
+        });
+        assert_matches!(
+            parse_from(["maa", "run", "task", "--resume"]).command,
+            Command::Run { resume: true, .. }
+        );
     }
 
     #[test]
@@ -640,7 +1136,65 @@ mod test {
 
     #[test]
     fn list() {
-        assert_matches!(parse_from(["maa", "list"]).command, Command::List);
+        assert_matches!(parse_from(["maa", "list"]).command, Command::List {
+            names: false
+        });
+        assert_matches!(
+            parse_from(["maa", "list", "--names"]).command,
+            Command::List { names: true }
+        );
+    }
+
+    #[test]
+    fn history() {
+        assert_matches!(parse_from(["maa", "history"]).command, Command::History {
+            last: 10,
+            task: None
+        });
+        assert_matches!(
+            parse_from(["maa", "history", "--last", "3", "--task", "fight"]).command,
+            Command::History { last: 3, task: Some(task) } if task == "fight"
+        );
+    }
+
+    #[test]
+    fn daemon() {
+        assert_matches!(
+            parse_from(["maa", "daemon", "start", "-p", "bilibili"]).command,
+            Command::Daemon(DaemonCommand::Start { common }) if common.profile.as_deref() == Some("bilibili")
+        );
+        assert_matches!(
+            parse_from(["maa", "daemon", "submit", "daily", "--account", "alt"]).command,
+            Command::Daemon(DaemonCommand::Submit { task, account })
+                if task == "daily" && account.as_deref() == Some("alt")
+        );
+    }
+
+    #[test]
+    fn control() {
+        assert_matches!(
+            parse_from(["maa", "stop"]).command,
+            Command::Stop { target } if target.pid.is_none()
+        );
+        assert_matches!(
+            parse_from(["maa", "pause", "--pid", "42"]).command,
+            Command::Pause { target } if target.pid == Some(42)
+        );
+        assert_matches!(
+            parse_from(["maa", "resume"]).command,
+            Command::Resume { target } if target.pid.is_none()
+        );
+    }
+
+    #[test]
+    fn schedule() {
+        assert_matches!(parse_from(["maa", "schedule"]).command, Command::Schedule {
+            list: false
+        });
+        assert_matches!(
+            parse_from(["maa", "schedule", "--list"]).command,
+            Command::Schedule { list: true }
+        );
     }
 
     #[test]
@@ -703,6 +1257,94 @@ mod test {
         );
     }
 
+    #[test]
+    fn config() {
+        assert_matches!(
+            parse_from(["maa", "config", "migrate"]).command,
+            Command::Config(ConfigCommand::Migrate)
+        );
+
+        assert_matches!(
+            parse_from(["maa", "config", "edit", "task", "daily"]).command,
+            Command::Config(ConfigCommand::Edit {
+                config_type: config::edit::ConfigType::Task,
+                name: Some(name),
+            }) if name == "daily"
+        );
+
+        assert_matches!(
+            parse_from(["maa", "config", "edit", "asst"]).command,
+            Command::Config(ConfigCommand::Edit {
+                config_type: config::edit::ConfigType::Profile,
+                name: None,
+            })
+        );
+
+        assert_matches!(
+            parse_from(["maa", "config", "diff", "cli"]).command,
+            Command::Config(ConfigCommand::Diff {
+                config_type: config::edit::ConfigType::Cli,
+                name: None,
+            })
+        );
+
+        assert_matches!(
+            parse_from(["maa", "config", "new-task", "daily"]).command,
+            Command::Config(ConfigCommand::NewTask {
+                name,
+                template: config::template::Template::Daily,
+                interactive: false,
+                force: false,
+            }) if name == "daily"
+        );
+
+        assert_matches!(
+            parse_from(["maa", "config", "new-task", "daily", "-i"]).command,
+            Command::Config(ConfigCommand::NewTask {
+                interactive: true,
+                ..
+            })
+        );
+        assert!(
+            Cli::try_parse_from(["maa", "config", "new-task", "daily", "-i", "-t", "weekly"])
+                .is_err()
+        );
+
+        assert_matches!(
+            parse_from([
+                "maa",
+                "config",
+                "new-task",
+                "is",
+                "-t",
+                "roguelike",
+                "--force"
+            ])
+            .command,
+            Command::Config(ConfigCommand::NewTask {
+                template: config::template::Template::Roguelike,
+                force: true,
+                ..
+            })
+        );
+
+        assert_matches!(
+            parse_from(["maa", "config", "encrypt", "profile"]).command,
+            Command::Config(ConfigCommand::Encrypt {
+                config_type: config::edit::ConfigType::Profile,
+                name: None,
+            })
+        );
+
+        assert_matches!(
+            parse_from(["maa", "config", "decrypt", "task", "daily"]).command,
+            Command::Config(ConfigCommand::Decrypt {
+                config_type: config::edit::ConfigType::Task,
+                name: Some(name),
+            }) if name == "daily"
+        );
+    }
+
     #[test]
     fn complete() {
         assert_matches!(
@@ -1,24 +1,54 @@
-use std::{borrow::Cow, path::PathBuf};
-
-use anyhow::{Context, Result};
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
 use log::{debug, info, warn};
-use maa_sys::Assistant;
+use maa_sys::{Assistant, TaskType};
 use maa_types::{InstanceOptionKey, StaticOptionKey, TouchMode};
 use serde::Deserialize;
 
-use crate::dirs;
+use crate::{
+    config::task::{ClientType, Server, TaskDefaults},
+    dirs,
+    value::MAAValue,
+};
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default, Clone)]
 pub struct AsstConfig {
+    /// Client type of the game, used when it's not specified in tasks
+    pub client_type: Option<ClientType>,
+    /// Server of the game, used to decide the time zone of task conditions
+    pub server: Option<Server>,
+    /// Default parameters of tasks, keyed by task type
+    pub task_defaults: TaskDefaults,
+    /// Named emulator instances, which can be selected by `--instance` option
+    pub instances: BTreeMap<String, Instance>,
+    /// Named squads in the game, which can be selected by name in copilot tasks
+    pub squads: BTreeMap<String, i32>,
     pub connection: ConnectionConfig,
     pub resource: ResourceConfig,
     pub static_options: StaticOptions,
     pub instance_options: InstanceOptions,
+    /// Shell commands run on events of tasks
+    pub hooks: Hooks,
+    /// Notifications sent when a run finishes
+    pub notify: Notify,
+    /// Reporting drops to Penguin Statistics
+    pub penguin: Penguin,
+    /// Reporting recruitments and drops to yituliu
+    pub yituliu: Yituliu,
 }
 
 impl AsstConfig {
     pub fn new(
+        client_type: Option<ClientType>,
+        task_defaults: TaskDefaults,
+        instances: BTreeMap<String, Instance>,
         connection: ConnectionConfig,
         mut resource: ResourceConfig,
         static_options: StaticOptions,
@@ -31,11 +61,49 @@ impl AsstConfig {
         }
 
         Self {
+            client_type,
+            server: None,
+            task_defaults,
+            instances,
+            squads: BTreeMap::new(),
             connection,
             resource,
             static_options,
             instance_options,
+            hooks: Hooks::default(),
+            notify: Notify::default(),
+            penguin: Penguin::default(),
+            yituliu: Yituliu::default(),
+        }
+    }
+}
+
+impl AsstConfig {
+    /// Use the named instance to connect, which overrides the connection config
+    pub fn use_instance(&mut self, name: &str) -> Result<&mut Self> {
+        let instance = self
+            .instances
+            .get(name)
+            .with_context(|| format!("Instance `{name}` not found in profile"))?
+            .clone();
+        info!("Using instance: {}", name);
+        self.connection.apply_instance(instance);
+        Ok(self)
+    }
+
+    /// Number of the squad with given name, or the number itself if a number is given
+    pub fn squad(&self, name: &str) -> Result<i32> {
+        let index = match name.parse() {
+            Ok(index) => index,
+            Err(_) => *self
+                .squads
+                .get(name)
+                .with_context(|| format!("Squad `{name}` not found in profile"))?,
+        };
+        if !(1..=4).contains(&index) {
+            bail!("Invalid squad number {index} of `{name}`, should be 1 to 4");
         }
+        Ok(index)
     }
 }
 
@@ -46,6 +114,16 @@ impl<'de> Deserialize<'de> for AsstConfig {
     {
         #[derive(Deserialize)]
         struct AsstConfigHelper {
+            #[serde(default)]
+            client_type: Option<ClientType>,
+            #[serde(default)]
+            server: Option<Server>,
+            #[serde(default, rename = "defaults")]
+            task_defaults: TaskDefaults,
+            #[serde(default)]
+            instances: BTreeMap<String, Instance>,
+            #[serde(default)]
+            squads: BTreeMap<String, i32>,
             #[serde(default)]
             connection: ConnectionConfig,
             #[serde(default)]
@@ -54,16 +132,35 @@ impl<'de> Deserialize<'de> for AsstConfig {
             static_options: StaticOptions,
             #[serde(default)]
             instance_options: InstanceOptions,
+            #[serde(default)]
+            hooks: Hooks,
+            #[serde(default)]
+            notify: Notify,
+            #[serde(default)]
+            penguin: Penguin,
+            #[serde(default)]
+            yituliu: Yituliu,
         }
 
         let config = AsstConfigHelper::deserialize(deserializer)?;
 
-        Ok(AsstConfig::new(
+        let mut asst_config = AsstConfig::new(
+            config.client_type,
+            config.task_defaults,
+            config.instances,
             config.connection,
             config.resource,
             config.static_options,
             config.instance_options,
-        ))
+        );
+        asst_config.server = config.server;
+        asst_config.squads = config.squads;
+        asst_config.hooks = config.hooks;
+        asst_config.notify = config.notify;
+        asst_config.penguin = config.penguin;
+        asst_config.yituliu = config.yituliu;
+
+        Ok(asst_config)
     }
 }
 
@@ -78,6 +175,18 @@ pub struct ConnectionConfig {
     pub(super) address: Option<String>,
     #[serde(default)]
     pub(super) config: Option<String>,
+    /// Bundle identifier of the game app, only used by PlayCover
+    #[serde(default, alias = "bundle_id")]
+    pub(super) app: Option<String>,
+    /// Command to launch the emulator if the device is not online
+    #[serde(default)]
+    pub(super) launch: Option<Vec<String>>,
+    /// Seconds to wait for the emulator to boot after launching
+    #[serde(default)]
+    pub(super) boot_timeout: Option<u64>,
+    /// Command to kill the emulator
+    #[serde(default)]
+    pub(super) kill: Option<Vec<String>>,
 }
 
 impl ConnectionConfig {
@@ -85,6 +194,39 @@ impl ConnectionConfig {
         self.preset
     }
 
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+    pub fn app(&self) -> Option<&str> {
+        self.app.as_deref()
+    }
+
+    pub fn emulator(&self) -> Option<Emulator> {
+        self.launch.clone().map(|launch| Emulator {
+            launch,
+            boot_timeout: Duration::from_secs(self.boot_timeout.unwrap_or(DEFAULT_BOOT_TIMEOUT)),
+        })
+    }
+
+    pub fn kill_command(&self) -> Option<&[String]> {
+        self.kill.as_deref()
+    }
+
+    fn apply_instance(&mut self, instance: Instance) -> &mut Self {
+        if instance.adb_path.is_some() {
+            self.adb_path = instance.adb_path;
+        }
+        if instance.address.is_some() {
+            self.address = instance.address;
+        }
+        if instance.config.is_some() {
+            self.config = instance.config;
+        }
+        // The emulator of an instance is never mixed with the one in connection config
+        self.launch = instance.launch;
+        self.boot_timeout = instance.boot_timeout;
+        self.kill = instance.kill;
+        self
+    }
+
     pub fn set_address(&mut self, address: impl Into<String>) -> &mut Self {
         self.address = Some(address.into());
         self
@@ -117,6 +259,390 @@ impl ConnectionConfig {
     }
 }
 
+/// Shell commands run on events of tasks
+///
+/// Commands are run by `sh -c` or `cmd /C`, with information of the task in environment variables.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Hooks {
+    /// Command run before a task is started
+    #[serde(default)]
+    pub on_task_start: Option<String>,
+    /// Command run after a task succeeded
+    #[serde(default)]
+    pub on_task_success: Option<String>,
+    /// Command run after a task failed, timed out or interrupted
+    #[serde(default)]
+    pub on_task_failure: Option<String>,
+    /// Command run if owned operators changed since the last `maa operbox`
+    #[serde(default)]
+    pub on_operbox_change: Option<String>,
+    /// Seconds to wait for a command to exit before killing it
+    #[serde(default)]
+    timeout: Option<u64>,
+}
+
+impl Hooks {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout.unwrap_or(DEFAULT_HOOK_TIMEOUT))
+    }
+}
+
+const DEFAULT_HOOK_TIMEOUT: u64 = 60;
+
+/// Notifications sent when a run finishes or fails
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Notify {
+    #[serde(default)]
+    pub webhook: Option<Webhook>,
+    #[serde(default)]
+    pub telegram: Option<Telegram>,
+    #[serde(default)]
+    pub discord: Option<Discord>,
+    #[serde(default)]
+    pub ntfy: Option<Ntfy>,
+    #[serde(default)]
+    pub gotify: Option<Gotify>,
+    #[serde(default)]
+    pub bark: Option<Bark>,
+    #[serde(default)]
+    pub email: Option<Email>,
+    /// Whether to show a notification on the desktop by the notification service of the system
+    #[serde(default)]
+    pub desktop: bool,
+}
+
+/// A webhook to POST a JSON summary of the run to
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Webhook {
+    pub url: String,
+    /// Extra HTTP headers, like `Authorization`
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+    /// Payload to send instead of the default one, with placeholders in strings
+    #[serde(default)]
+    pub template: Option<serde_json::Value>,
+}
+
+/// A Telegram bot to send the summary of the run by
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Telegram {
+    /// Token of the bot, given by @BotFather
+    pub token: String,
+    /// Chat to send messages to, an id or `@username` of a channel
+    pub chat_id: ChatId,
+    /// Whether to attach a screenshot of the game if the run failed
+    #[serde(default)]
+    pub screenshot: bool,
+    /// URL of the Bot API server, only needed by a self-hosted one
+    #[serde(default = "default_telegram_api_url")]
+    pub api_url: String,
+}
+
+/// A Discord webhook to send the summary of the run to as an embed
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Discord {
+    /// URL of the webhook, given by the integrations settings of the channel
+    pub url: String,
+    /// Name shown as the sender instead of the default one of the webhook
+    #[serde(default)]
+    pub username: Option<String>,
+}
+
+fn default_telegram_api_url() -> String {
+    "https://api.telegram.org".to_owned()
+}
+
+/// A topic of ntfy to push notifications to
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Ntfy {
+    #[serde(default = "default_ntfy_server")]
+    pub server: String,
+    pub topic: String,
+    /// Access token of a protected topic
+    #[serde(default)]
+    pub token: Option<String>,
+    /// Priority from 1 (min) to 5 (max) of a succeeded run
+    #[serde(default = "default_ntfy_priority")]
+    pub priority: u8,
+    /// Priority of a failed run
+    #[serde(default = "default_ntfy_failure_priority")]
+    pub failure_priority: u8,
+}
+
+fn default_ntfy_server() -> String {
+    "https://ntfy.sh".to_owned()
+}
+
+fn default_ntfy_priority() -> u8 {
+    3
+}
+
+fn default_ntfy_failure_priority() -> u8 {
+    5
+}
+
+/// A Gotify server to push notifications to
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Gotify {
+    pub server: String,
+    /// Token of the application
+    pub token: String,
+    /// Priority of a succeeded run, from 0 to 10
+    #[serde(default = "default_gotify_priority")]
+    pub priority: u8,
+    /// Priority of a failed run
+    #[serde(default = "default_gotify_failure_priority")]
+    pub failure_priority: u8,
+}
+
+fn default_gotify_priority() -> u8 {
+    4
+}
+
+fn default_gotify_failure_priority() -> u8 {
+    8
+}
+
+/// An iOS device to push notifications to by Bark
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Bark {
+    #[serde(default = "default_bark_server")]
+    pub server: String,
+    pub device_key: String,
+    /// Interruption level of a succeeded run
+    #[serde(default)]
+    pub level: BarkLevel,
+    /// Interruption level of a failed run
+    #[serde(default = "default_bark_failure_level")]
+    pub failure_level: BarkLevel,
+}
+
+fn default_bark_server() -> String {
+    "https://api.day.app".to_owned()
+}
+
+fn default_bark_failure_level() -> BarkLevel {
+    BarkLevel::TimeSensitive
+}
+
+/// Interruption level of Bark notifications, from the lowest to the highest
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum BarkLevel {
+    /// Added to the notification list without lighting up the screen
+    Passive,
+    #[default]
+    Active,
+    /// Shown even in focus mode
+    TimeSensitive,
+    /// Played with sound even in silent mode
+    Critical,
+}
+
+impl BarkLevel {
+    pub fn to_str(self) -> &'static str {
+        match self {
+            BarkLevel::Passive => "passive",
+            BarkLevel::Active => "active",
+            BarkLevel::TimeSensitive => "timeSensitive",
+            BarkLevel::Critical => "critical",
+        }
+    }
+}
+
+/// Reporting drops of fight tasks to Penguin Statistics at the end of a run
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Penguin {
+    /// Whether to report drops
+    #[serde(default)]
+    pub report: bool,
+    /// Penguin ID to report as, a new one is assigned and saved in state directory if not set
+    #[serde(default)]
+    pub id: Option<String>,
+    /// Server of the game, inferred from the client type if not set
+    #[serde(default)]
+    pub server: Option<PenguinServer>,
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone, Copy)]
+pub enum PenguinServer {
+    CN,
+    US,
+    JP,
+    KR,
+}
+
+impl PenguinServer {
+    /// Server of given client type, `None` if the client is not supported by Penguin Statistics
+    pub fn from_client_type(client_type: ClientType) -> Option<Self> {
+        match client_type {
+            ClientType::Official | ClientType::Bilibili => Some(PenguinServer::CN),
+            ClientType::YoStarEN => Some(PenguinServer::US),
+            ClientType::YoStarJP => Some(PenguinServer::JP),
+            ClientType::YoStarKR => Some(PenguinServer::KR),
+            ClientType::Txwy => None,
+        }
+    }
+
+    pub fn to_str(self) -> &'static str {
+        match self {
+            PenguinServer::CN => "CN",
+            PenguinServer::US => "US",
+            PenguinServer::JP => "JP",
+            PenguinServer::KR => "KR",
+        }
+    }
+}
+
+/// Reporting results of recruit and fight tasks to yituliu (Arknights Toolbox)
+///
+/// Reports are sent by MaaCore like the GUI, by setting `report_to_yituliu` of the tasks.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone, Default)]
+#[serde(deny_unknown_fields)]
+pub struct Yituliu {
+    /// Whether to report
+    #[serde(default)]
+    pub report: bool,
+    /// ID of the user on yituliu, leave empty to report anonymously
+    #[serde(default)]
+    pub id: Option<String>,
+}
+
+impl Yituliu {
+    /// Enable reporting in parameters of a task, unless it's set in the task
+    pub fn apply_to(&self, task_type: TaskType, params: &mut MAAValue) {
+        if !self.report
+            || !matches!(task_type, TaskType::Fight | TaskType::Recruit)
+            || params.get("report_to_yituliu").is_some()
+        {
+            return;
+        }
+        params.insert("report_to_yituliu", true);
+        params.maybe_insert("yituliu_id", self.id.clone());
+    }
+}
+
+/// An SMTP server to send the summary of the run by email
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Email {
+    /// Host name of the SMTP server
+    pub server: String,
+    /// Port of the server, 465 for `tls`, 587 for `starttls` and 25 for `none` by default
+    #[serde(default)]
+    pub port: Option<u16>,
+    #[serde(default)]
+    pub security: SmtpSecurity,
+    #[serde(default)]
+    pub username: Option<String>,
+    #[serde(default)]
+    pub password: Option<String>,
+    /// Sender of the email, an address or `Name <address>`
+    pub from: String,
+    /// Recipients of the email
+    pub to: Vec<String>,
+    /// Whether to attach a screenshot of the game if the run failed
+    #[serde(default)]
+    pub screenshot: bool,
+}
+
+impl Email {
+    pub fn port(&self) -> u16 {
+        self.port.unwrap_or(match self.security {
+            SmtpSecurity::Tls => 465,
+            SmtpSecurity::Starttls => 587,
+            SmtpSecurity::None => 25,
+        })
+    }
+}
+
+/// How the connection to an SMTP server is secured
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum SmtpSecurity {
+    /// Connect by TLS directly
+    #[default]
+    Tls,
+    /// Connect in plain text, and upgrade to TLS by `STARTTLS`
+    Starttls,
+    /// Never use TLS, only for trusted local servers
+    None,
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone)]
+#[serde(untagged)]
+pub enum ChatId {
+    Id(i64),
+    Username(String),
+}
+
+impl std::fmt::Display for ChatId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ChatId::Id(id) => write!(f, "{id}"),
+            ChatId::Username(username) => write!(f, "{username}"),
+        }
+    }
+}
+
+/// A named emulator instance
+///
+/// The connection related fields override the ones in connection config when the instance is
+/// selected, and the emulator will be launched by the `launch` command if it's not running.
+/// The emulator related fields replace the ones in connection config, even if they are not set.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone, Default)]
+pub struct Instance {
+    #[serde(default)]
+    adb_path: Option<String>,
+    #[serde(default)]
+    address: Option<String>,
+    #[serde(default)]
+    config: Option<String>,
+    /// Command to launch the emulator, the first element is the executable
+    #[serde(default)]
+    launch: Option<Vec<String>>,
+    /// Seconds to wait for the emulator to be ready after launching
+    #[serde(default)]
+    boot_timeout: Option<u64>,
+    /// Command to kill the emulator, used by the `kill-emulator` post action
+    #[serde(default)]
+    kill: Option<Vec<String>>,
+}
+
+const DEFAULT_BOOT_TIMEOUT: u64 = 120;
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone)]
+pub struct Emulator {
+    pub launch: Vec<String>,
+    pub boot_timeout: Duration,
+}
+
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Default, Clone, Copy)]
 pub enum Preset {
@@ -232,6 +758,8 @@ pub struct ResourceConfig {
     /// Resource base directories, a list of directories containing resource directories
     /// Not deserialized from config file
     pub(crate) resource_base_dirs: Vec<PathBuf>,
+    /// Resource overlay layers, loaded in order after all other resources
+    layers: Vec<PathBuf>,
 }
 
 impl<'de> Deserialize<'de> for ResourceConfig {
@@ -247,6 +775,8 @@ impl<'de> Deserialize<'de> for ResourceConfig {
             platform_diff_resource: Option<PathBuf>,
             #[serde(default)]
             user_resource: bool,
+            #[serde(default)]
+            user_dirs: Vec<PathBuf>,
         }
 
         let helper = ResourceConfigHelper::deserialize(deserializer)?;
@@ -257,12 +787,19 @@ impl<'de> Deserialize<'de> for ResourceConfig {
             push_user_resource(&mut resource_base_dirs);
         }
 
-        Ok(Self {
+        let mut config = Self {
             resource_base_dirs,
             global_resource: helper.global_resource,
             platform_diff_resource: helper.platform_diff_resource,
             user_resource: helper.user_resource,
-        })
+            layers: Vec::new(),
+        };
+
+        for dir in helper.user_dirs {
+            config.add_layer(user_dir(&dir));
+        }
+
+        Ok(config)
     }
 }
 
@@ -273,6 +810,7 @@ impl Default for ResourceConfig {
             global_resource: None,
             platform_diff_resource: None,
             user_resource: false,
+            layers: Vec::new(),
         }
     }
 }
@@ -348,6 +886,42 @@ impl ResourceConfig {
         self
     }
 
+    /// Use resource fetched from OTA as a base resource directory
+    ///
+    /// The OTA resource is loaded right after the hot update resource, and before the user
+    /// resource and layers. Its global resource is loaded after the global resource of the hot
+    /// update resource, as global resources are loaded in the order of base directories.
+    pub fn use_ota_resource(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        let dir = dir.into();
+        if !self.resource_base_dirs.contains(&dir) {
+            info!("Using resource fetched from OTA: {}", dir.display());
+            let user_resource = dirs::config().join("resource");
+            let index = self
+                .resource_base_dirs
+                .iter()
+                .position(|dir| *dir == user_resource)
+                .unwrap_or(self.resource_base_dirs.len());
+            self.resource_base_dirs.insert(index, dir);
+        }
+        self
+    }
+
+    /// Add a resource overlay layer, which will be loaded after all previous resources
+    ///
+    /// Layers are loaded in the order they are added, so a later layer overrides the earlier ones.
+    /// A non-existent directory will be ignored with a warning.
+    pub fn add_layer(&mut self, dir: impl Into<PathBuf>) -> &mut Self {
+        let dir = dir.into();
+        if dir.file_name().is_none_or(|name| name != "resource") {
+            warn!(
+                "Resource directory {} is not named `resource`, MaaCore may fail to load it",
+                dir.display()
+            );
+        }
+        push_resource(&mut self.layers, dir);
+        self
+    }
+
     /// Get base resource directories
     pub fn base_dirs(&self) -> &Vec<PathBuf> {
         &self.resource_base_dirs
@@ -379,6 +953,7 @@ impl ResourceConfig {
                 resource_dirs.extend(full_paths);
             }
         }
+        resource_dirs.extend(self.layers.iter().cloned());
 
         resource_dirs
     }
@@ -398,6 +973,11 @@ fn push_user_resource(resource_dirs: &mut Vec<PathBuf>) -> &mut Vec<PathBuf> {
     push_resource(resource_dirs, dirs::config().join("resource"))
 }
 
+/// Resolve a user resource directory, relative paths are relative to the config directory
+fn user_dir(dir: &Path) -> PathBuf {
+    dirs::config().join(dirs::expand_tilde(dir))
+}
+
 fn push_resource(resource_dirs: &mut Vec<PathBuf>, dir: impl Into<PathBuf>) -> &mut Vec<PathBuf> {
     let dir = dir.into();
     if dir.exists() {
@@ -527,11 +1107,20 @@ mod tests {
             .unwrap();
 
             assert_eq!(config, AsstConfig {
+                client_type: None,
+                server: None,
+                task_defaults: TaskDefaults::default(),
+                instances: BTreeMap::new(),
+                squads: BTreeMap::new(),
                 connection: ConnectionConfig {
                     preset: Preset::Adb,
                     adb_path: Some(String::from("adb")),
                     address: Some(String::from("emulator-5554")),
                     config: Some(String::from("CompatMac")),
+                    app: None,
+                    launch: None,
+                    boot_timeout: None,
+                    kill: None,
                 },
                 resource: ResourceConfig {
                     resource_base_dirs: {
@@ -542,6 +1131,7 @@ mod tests {
                     global_resource: Some(PathBuf::from("YoStarEN")),
                     platform_diff_resource: Some(PathBuf::from("iOS")),
                     user_resource: true,
+                    layers: Vec::new(),
                 },
                 static_options: StaticOptions {
                     cpu_ocr: Some(false),
@@ -553,6 +1143,10 @@ mod tests {
                     adb_lite_enabled: Some(false),
                     kill_adb_on_exit: Some(false),
                 },
+                hooks: Hooks::default(),
+                notify: Notify::default(),
+                penguin: Penguin::default(),
+                yituliu: Yituliu::default(),
             });
         }
 
@@ -608,6 +1202,10 @@ mod tests {
                     adb_path: Some(String::from("/path/to/adb")),
                     address: Some(String::from("127.0.0.1:5555")),
                     config: Some(String::from("SomeConfig")),
+                    app: None,
+                    launch: None,
+                    boot_timeout: None,
+                    kill: None,
                 },
                 &[
                     Token::Map { len: Some(4) },
@@ -625,6 +1223,27 @@ mod tests {
                     Token::MapEnd,
                 ],
             );
+
+            assert_de_tokens(
+                &ConnectionConfig {
+                    preset: Preset::PlayCover,
+                    address: Some(String::from("localhost:1717")),
+                    app: Some(String::from("com.YoStarEN.Arknights")),
+                    ..Default::default()
+                },
+                &[
+                    Token::Map { len: Some(3) },
+                    Token::Str("preset"),
+                    Token::Str("PlayCover"),
+                    Token::Str("address"),
+                    Token::Some,
+                    Token::Str("localhost:1717"),
+                    Token::Str("bundle_id"),
+                    Token::Some,
+                    Token::Str("com.YoStarEN.Arknights"),
+                    Token::MapEnd,
+                ],
+            );
         }
 
         #[test]
@@ -636,6 +1255,7 @@ mod tests {
                     global_resource: None,
                     platform_diff_resource: None,
                     user_resource: false,
+                    layers: Vec::new(),
                 },
                 &[Token::Map { len: Some(0) }, Token::MapEnd],
             );
@@ -652,6 +1272,7 @@ mod tests {
                     global_resource: Some(PathBuf::from("YoStarEN")),
                     platform_diff_resource: Some(PathBuf::from("iOS")),
                     user_resource: true,
+                    layers: Vec::new(),
                 },
                 &[
                     Token::Map { len: Some(4) },
@@ -668,6 +1289,28 @@ mod tests {
             );
         }
 
+        #[test]
+        fn resource_config_user_dirs() {
+            let tmp_dir = tempfile::tempdir().unwrap();
+            let user_dir = tmp_dir.path().join("resource");
+            std::fs::create_dir_all(&user_dir).unwrap();
+
+            // Non-existent directories are ignored
+            let config: ResourceConfig = toml::from_str(&format!(
+                "user_dirs = [{:?}, \"/not/exist/resource\"]",
+                user_dir.to_str().unwrap()
+            ))
+            .unwrap();
+
+            assert_eq!(config, ResourceConfig {
+                resource_base_dirs: default_resource_base_dirs(),
+                global_resource: None,
+                platform_diff_resource: None,
+                user_resource: false,
+                layers: vec![user_dir],
+            });
+        }
+
         #[test]
         fn static_options() {
             assert_de_tokens(
@@ -737,6 +1380,38 @@ mod tests {
             );
         }
 
+        #[test]
+        fn instance() {
+            assert_de_tokens(&Instance::default(), &[
+                Token::Map { len: Some(0) },
+                Token::MapEnd,
+            ]);
+
+            assert_de_tokens(
+                &Instance {
+                    address: Some(String::from("127.0.0.1:16384")),
+                    launch: Some(vec![String::from("MuMuPlayer")]),
+                    boot_timeout: Some(60),
+                    ..Default::default()
+                },
+                &[
+                    Token::Map { len: Some(3) },
+                    Token::Str("address"),
+                    Token::Some,
+                    Token::Str("127.0.0.1:16384"),
+                    Token::Str("launch"),
+                    Token::Some,
+                    Token::Seq { len: Some(1) },
+                    Token::Str("MuMuPlayer"),
+                    Token::SeqEnd,
+                    Token::Str("boot_timeout"),
+                    Token::Some,
+                    Token::U64(60),
+                    Token::MapEnd,
+                ],
+            );
+        }
+
         #[test]
         fn preset() {
             assert_de_tokens(&Preset::Adb, &[Token::Str("ADB")]);
@@ -750,12 +1425,18 @@ mod tests {
         fn asst_config() {
             assert_de_tokens(
                 &AsstConfig {
+                    client_type: None,
+                    server: None,
+                    task_defaults: TaskDefaults::default(),
+                    instances: BTreeMap::new(),
+                    squads: BTreeMap::new(),
                     connection: ConnectionConfig::default(),
                     resource: ResourceConfig {
                         resource_base_dirs: default_resource_base_dirs(),
                         global_resource: None,
                         platform_diff_resource: None,
                         user_resource: false,
+                        layers: Vec::new(),
                     },
                     static_options: StaticOptions {
                         cpu_ocr: None,
@@ -767,13 +1448,50 @@ mod tests {
                         adb_lite_enabled: None,
                         kill_adb_on_exit: None,
                     },
+                    hooks: Hooks::default(),
+                    notify: Notify::default(),
+                    penguin: Penguin::default(),
+                    yituliu: Yituliu::default(),
                 },
                 &[Token::Map { len: Some(0) }, Token::MapEnd],
             );
 
+            assert_de_tokens(
+                &AsstConfig {
+                    client_type: Some(ClientType::YoStarEN),
+                    ..Default::default()
+                },
+                &[
+                    Token::Map { len: Some(1) },
+                    Token::Str("client_type"),
+                    Token::Some,
+                    Token::Str("YoStarEN"),
+                    Token::MapEnd,
+                ],
+            );
+
+            assert_de_tokens(
+                &AsstConfig {
+                    server: Some(Server::Offset(9)),
+                    ..Default::default()
+                },
+                &[
+                    Token::Map { len: Some(1) },
+                    Token::Str("server"),
+                    Token::Some,
+                    Token::I8(9),
+                    Token::MapEnd,
+                ],
+            );
+
             // Auto load iOS resource and set touch mode to MacPlayTools
             assert_de_tokens(
                 &AsstConfig {
+                    client_type: None,
+                    server: None,
+                    task_defaults: TaskDefaults::default(),
+                    instances: BTreeMap::new(),
+                    squads: BTreeMap::new(),
                     connection: ConnectionConfig {
                         preset: Preset::PlayCover,
                         ..Default::default()
@@ -787,6 +1505,10 @@ mod tests {
                         touch_mode: Some(TouchMode::MacPlayTools),
                         ..Default::default()
                     },
+                    hooks: Hooks::default(),
+                    notify: Notify::default(),
+                    penguin: Penguin::default(),
+                    yituliu: Yituliu::default(),
                 },
                 &[
                     Token::Map { len: Some(1) },
@@ -801,6 +1523,324 @@ mod tests {
         }
     }
 
+    mod asst_config {
+        use super::*;
+
+        #[test]
+        fn hooks() {
+            let config: AsstConfig = toml::from_str(
+                r#"
+                [hooks]
+                on_task_start = "echo $MAA_TASK_NAME"
+                on_task_failure = "notify-send failed"
+                on_operbox_change = "notify-send \"$MAA_OPERBOX_CHANGES\""
+                "#,
+            )
+            .unwrap();
+            assert_eq!(config.hooks, Hooks {
+                on_task_start: Some("echo $MAA_TASK_NAME".to_owned()),
+                on_task_success: None,
+                on_task_failure: Some("notify-send failed".to_owned()),
+                on_operbox_change: Some("notify-send \"$MAA_OPERBOX_CHANGES\"".to_owned()),
+                timeout: None,
+            });
+            assert_eq!(config.hooks.timeout(), Duration::from_secs(60));
+
+            let config: AsstConfig = toml::from_str("[hooks]\ntimeout = 10").unwrap();
+            assert_eq!(config.hooks.timeout(), Duration::from_secs(10));
+
+            assert!(toml::from_str::<AsstConfig>("[hooks]\non_task_end = \"echo\"").is_err());
+        }
+
+        #[test]
+        fn notify() {
+            let config: AsstConfig = toml::from_str(
+                r#"
+                [notify.webhook]
+                url = "https://example.com/webhook"
+                headers = { Authorization = "Bearer token" }
+                template = { text = "maa {result} in {duration}" }
+                "#,
+            )
+            .unwrap();
+            assert_eq!(
+                config.notify.webhook,
+                Some(Webhook {
+                    url: "https://example.com/webhook".to_owned(),
+                    headers: BTreeMap::from([(
+                        "Authorization".to_owned(),
+                        "Bearer token".to_owned()
+                    )]),
+                    template: Some(serde_json::json!({ "text": "maa {result} in {duration}" })),
+                })
+            );
+
+            assert_eq!(
+                toml::from_str::<AsstConfig>("").unwrap().notify,
+                Notify::default()
+            );
+            assert!(toml::from_str::<AsstConfig>("[notify.webhook]\nheaders = {}").is_err());
+
+            let config: AsstConfig = toml::from_str(
+                r#"
+                [notify.telegram]
+                token = "123:abc"
+                chat_id = 42
+                screenshot = true
+                "#,
+            )
+            .unwrap();
+            assert_eq!(
+                config.notify.telegram,
+                Some(Telegram {
+                    token: "123:abc".to_owned(),
+                    chat_id: ChatId::Id(42),
+                    screenshot: true,
+                    api_url: "https://api.telegram.org".to_owned(),
+                })
+            );
+            let telegram = toml::from_str::<AsstConfig>(
+                "[notify.telegram]\ntoken = \"123:abc\"\nchat_id = \"@channel\"",
+            )
+            .unwrap()
+            .notify
+            .telegram
+            .unwrap();
+            assert_eq!(telegram.chat_id.to_string(), "@channel");
+            assert!(!telegram.screenshot);
+
+            let config: AsstConfig = toml::from_str(
+                r#"
+                [notify.discord]
+                url = "https://discord.com/api/webhooks/1/abc"
+                username = "maa"
+                "#,
+            )
+            .unwrap();
+            assert_eq!(
+                config.notify.discord,
+                Some(Discord {
+                    url: "https://discord.com/api/webhooks/1/abc".to_owned(),
+                    username: Some("maa".to_owned()),
+                })
+            );
+
+            let config: AsstConfig = toml::from_str(
+                r#"
+                [notify.ntfy]
+                topic = "maa"
+
+                [notify.gotify]
+                server = "https://gotify.example.com"
+                token = "abc"
+                failure_priority = 10
+
+                [notify.bark]
+                device_key = "key"
+                level = "passive"
+                "#,
+            )
+            .unwrap();
+            assert_eq!(
+                config.notify.ntfy,
+                Some(Ntfy {
+                    server: "https://ntfy.sh".to_owned(),
+                    topic: "maa".to_owned(),
+                    token: None,
+                    priority: 3,
+                    failure_priority: 5,
+                })
+            );
+            assert_eq!(
+                config.notify.gotify,
+                Some(Gotify {
+                    server: "https://gotify.example.com".to_owned(),
+                    token: "abc".to_owned(),
+                    priority: 4,
+                    failure_priority: 10,
+                })
+            );
+            assert_eq!(
+                config.notify.bark,
+                Some(Bark {
+                    server: "https://api.day.app".to_owned(),
+                    device_key: "key".to_owned(),
+                    level: BarkLevel::Passive,
+                    failure_level: BarkLevel::TimeSensitive,
+                })
+            );
+            assert!(toml::from_str::<AsstConfig>(
+                "[notify.bark]\ndevice_key = \"key\"\nlevel = \"loud\""
+            )
+            .is_err());
+
+            let config: AsstConfig = toml::from_str(
+                r#"
+                [notify.email]
+                server = "smtp.example.com"
+                security = "starttls"
+                username = "maa@example.com"
+                password = "secret"
+                from = "maa-cli <maa@example.com>"
+                to = ["doctor@example.com"]
+                "#,
+            )
+            .unwrap();
+            let email = config.notify.email.unwrap();
+            assert_eq!(email.security, SmtpSecurity::Starttls);
+            assert_eq!(email.port(), 587);
+            assert_eq!(email.to, ["doctor@example.com"]);
+            assert!(!email.screenshot);
+
+            assert!(
+                toml::from_str::<AsstConfig>("[notify]\ndesktop = true")
+                    .unwrap()
+                    .notify
+                    .desktop
+            );
+        }
+
+        #[test]
+        fn penguin() {
+            assert_eq!(
+                toml::from_str::<AsstConfig>("").unwrap().penguin,
+                Penguin::default()
+            );
+            assert_eq!(
+                toml::from_str::<AsstConfig>(
+                    r#"
+                    [penguin]
+                    report = true
+                    id = "12345678"
+                    server = "JP"
+                    "#,
+                )
+                .unwrap()
+                .penguin,
+                Penguin {
+                    report: true,
+                    id: Some("12345678".to_owned()),
+                    server: Some(PenguinServer::JP),
+                }
+            );
+            assert!(toml::from_str::<AsstConfig>("[penguin]\nserver = \"TW\"").is_err());
+            assert_eq!(
+                PenguinServer::from_client_type(ClientType::YoStarEN),
+                Some(PenguinServer::US)
+            );
+            assert_eq!(PenguinServer::from_client_type(ClientType::Txwy), None);
+        }
+
+        #[test]
+        fn yituliu() {
+            let config: AsstConfig =
+                toml::from_str("[yituliu]\nreport = true\nid = \"abc\"").unwrap();
+            let yituliu = config.yituliu;
+
+            let mut params = MAAValue::new();
+            yituliu.apply_to(TaskType::Recruit, &mut params);
+            assert_eq!(
+                params.get("report_to_yituliu").unwrap().as_bool(),
+                Some(true)
+            );
+            assert_eq!(params.get("yituliu_id").unwrap().as_str(), Some("abc"));
+
+            // Tasks setting it explicitly and other tasks are not changed
+            let mut params = MAAValue::new();
+            params.insert("report_to_yituliu", false);
+            yituliu.apply_to(TaskType::Fight, &mut params);
+            assert_eq!(
+                params.get("report_to_yituliu").unwrap().as_bool(),
+                Some(false)
+            );
+            assert!(params.get("yituliu_id").is_none());
+
+            let mut params = MAAValue::new();
+            yituliu.apply_to(TaskType::Infrast, &mut params);
+            assert!(params.get("report_to_yituliu").is_none());
+
+            let mut params = MAAValue::new();
+            Yituliu::default().apply_to(TaskType::Fight, &mut params);
+            assert!(params.get("report_to_yituliu").is_none());
+        }
+
+        #[test]
+        fn use_instance() {
+            let mut config: AsstConfig = toml::from_str(
+                r#"
+                [connection]
+                adb_path = "/path/to/adb"
+                address = "emulator-5554"
+                launch = ["emulator", "-avd", "Pixel"]
+                boot_timeout = 60
+
+                [instances.mumu]
+                address = "127.0.0.1:16384"
+                launch = ["open", "-a", "MuMuPlayer"]
+                kill = ["pkill", "MuMuPlayer"]
+
+                [instances.bluestacks]
+                address = "127.0.0.1:5555"
+                "#,
+            )
+            .unwrap();
+
+            assert_eq!(
+                config.connection.emulator(),
+                Some(Emulator {
+                    launch: vec!["emulator".into(), "-avd".into(), "Pixel".into()],
+                    boot_timeout: Duration::from_secs(60),
+                })
+            );
+
+            config.use_instance("mumu").unwrap();
+            assert_eq!(config.connection.adb_path.as_deref(), Some("/path/to/adb"));
+            assert_eq!(
+                config.connection.address.as_deref(),
+                Some("127.0.0.1:16384")
+            );
+            assert_eq!(
+                config.connection.emulator(),
+                Some(Emulator {
+                    launch: vec!["open".into(), "-a".into(), "MuMuPlayer".into()],
+                    boot_timeout: Duration::from_secs(DEFAULT_BOOT_TIMEOUT),
+                })
+            );
+
+            assert_eq!(
+                config.connection.kill_command(),
+                Some(&["pkill".to_owned(), "MuMuPlayer".to_owned()][..])
+            );
+
+            config.use_instance("bluestacks").unwrap();
+            assert_eq!(config.connection.address.as_deref(), Some("127.0.0.1:5555"));
+            assert_eq!(config.connection.emulator(), None);
+            assert_eq!(config.connection.kill_command(), None);
+
+            assert!(config.use_instance("unknown").is_err());
+        }
+
+        #[test]
+        fn squad() {
+            let config: AsstConfig = toml::from_str(
+                r#"
+                [squads]
+                main = 1
+                "低配" = 2
+                invalid = 5
+                "#,
+            )
+            .unwrap();
+
+            assert_eq!(config.squad("main").unwrap(), 1);
+            assert_eq!(config.squad("低配").unwrap(), 2);
+            assert_eq!(config.squad("3").unwrap(), 3);
+            assert!(config.squad("0").is_err());
+            assert!(config.squad("invalid").is_err());
+            assert!(config.squad("unknown").is_err());
+        }
+    }
+
     mod connection_config {
         use super::*;
 
@@ -811,6 +1851,10 @@ mod tests {
                 adb_path: None,
                 address: None,
                 config: None,
+                app: None,
+                launch: None,
+                boot_timeout: None,
+                kill: None,
             });
         }
 
@@ -870,6 +1914,10 @@ mod tests {
                     adb_path: None,
                     address: None,
                     config: None,
+                    app: None,
+                    launch: None,
+                    boot_timeout: None,
+                    kill: None,
                 }
                 .connect_args(),
                 (
@@ -885,6 +1933,10 @@ mod tests {
                     adb_path: None,
                     address: None,
                     config: None,
+                    app: None,
+                    launch: None,
+                    boot_timeout: None,
+                    kill: None,
                 }
                 .connect_args(),
                 ("", "127.0.0.1:1717", config_based_on_os()),
@@ -896,6 +1948,10 @@ mod tests {
                     adb_path: Some("/path/to/adb".to_owned()),
                     address: Some("127.0.0.1:11111".to_owned()),
                     config: Some("SomeConfig".to_owned()),
+                    app: None,
+                    launch: None,
+                    boot_timeout: None,
+                    kill: None,
                 }
                 .connect_args(),
                 ("/path/to/adb", "127.0.0.1:11111", "SomeConfig"),
@@ -943,6 +1999,7 @@ mod tests {
                 global_resource: None,
                 platform_diff_resource: None,
                 user_resource: false,
+                layers: Vec::new(),
             });
         }
 
@@ -962,6 +2019,7 @@ mod tests {
                     global_resource: None,
                     platform_diff_resource: None,
                     user_resource: true,
+                    layers: Vec::new(),
                 }
             );
         }
@@ -975,6 +2033,7 @@ mod tests {
                     global_resource: Some(PathBuf::from("YoStarEN")),
                     platform_diff_resource: None,
                     user_resource: false,
+                    layers: Vec::new(),
                 }
             );
 
@@ -987,6 +2046,7 @@ mod tests {
                     global_resource: Some(PathBuf::from("YoStarEN")),
                     platform_diff_resource: None,
                     user_resource: false,
+                    layers: Vec::new(),
                 }
             );
         }
@@ -1013,6 +2073,33 @@ mod tests {
             );
         }
 
+        #[test]
+        fn use_ota_resource() {
+            let user_resource = dirs::config().join("resource");
+            let ota = PathBuf::from("ota/resource");
+
+            // The user resource flag is set even if the directory is not found
+            let mut config = ResourceConfig {
+                resource_base_dirs: Vec::new(),
+                user_resource: true,
+                ..Default::default()
+            };
+            config.use_ota_resource(&ota);
+            assert_eq!(config.base_dirs(), &[ota.clone()]);
+
+            let mut config = ResourceConfig {
+                resource_base_dirs: vec![PathBuf::from("hot_update"), user_resource.clone()],
+                user_resource: true,
+                ..Default::default()
+            };
+            config.use_ota_resource(&ota).use_ota_resource(&ota);
+            assert_eq!(config.base_dirs(), &[
+                PathBuf::from("hot_update"),
+                ota,
+                user_resource
+            ]);
+        }
+
         #[test]
         fn base_dirs() {
             assert_eq!(
@@ -1111,6 +2198,29 @@ mod tests {
                 [resource_dir.clone()]
             );
 
+            // Layers are loaded after global and platform diff resources in order
+            let layer1 = test_root.join("layer1").join("resource");
+            let layer2 = test_root.join("layer2").join("resource");
+            layer1.ensure().unwrap();
+            layer2.ensure().unwrap();
+            assert_eq!(
+                ResourceConfig {
+                    resource_base_dirs: vec![resource_dir.clone()],
+                    global_resource: Some(PathBuf::from("YoStarEN")),
+                    ..Default::default()
+                }
+                .add_layer(&layer2)
+                .add_layer(&layer1)
+                .add_layer(test_root.join("not_exists"))
+                .resource_dirs(),
+                [
+                    resource_dir.clone(),
+                    yostar_en_dir.clone(),
+                    layer2.clone(),
+                    layer1.clone()
+                ]
+            );
+
             fs::remove_dir_all(test_root).unwrap();
         }
     }
@@ -0,0 +1,268 @@
+//! Build a task file interactively by choosing task types and parameters from menus
+
+use anyhow::{Context, Result};
+
+use crate::{
+    object,
+    value::{
+        userinput::{BoolInput, Input, SelectD, UserInput, ValueWithDesc},
+        MAAValue,
+    },
+};
+
+/// Facilities in the base, in the order of shifting
+const FACILITIES: [&str; 7] = [
+    "Mfg",
+    "Trade",
+    "Control",
+    "Power",
+    "Reception",
+    "Office",
+    "Dorm",
+];
+
+fn client_type_select() -> SelectD<String> {
+    SelectD::<String>::new(
+        [
+            ValueWithDesc::new("Official", Some("Chinese client by Hypergryph")),
+            ValueWithDesc::new("Bilibili", Some("Chinese client by Bilibili")),
+            ValueWithDesc::new("txwy", Some("Traditional Chinese client")),
+            ValueWithDesc::new("YoStarEN", Some("English client")),
+            ValueWithDesc::new("YoStarJP", Some("Japanese client")),
+            ValueWithDesc::new("YoStarKR", Some("Korean client")),
+        ],
+        Some(1),
+        Some("client type of the game"),
+        false,
+    )
+    .unwrap()
+}
+
+fn task_type_select() -> SelectD<String> {
+    SelectD::<String>::new(
+        [
+            ValueWithDesc::new("StartUp", Some("start the game and enter the main screen")),
+            ValueWithDesc::new("Fight", Some("fight a stage with sanity")),
+            ValueWithDesc::new("Recruit", Some("recruit operators")),
+            ValueWithDesc::new("Infrast", Some("shift operators in the base")),
+            ValueWithDesc::new(
+                "Mall",
+                Some("visit friends and buy items in the credit store"),
+            ),
+            ValueWithDesc::new("Award", Some("collect rewards of missions")),
+            ValueWithDesc::new("Roguelike", Some("Integrated Strategies")),
+            ValueWithDesc::new("CloseDown", Some("close the game")),
+        ],
+        Some(1),
+        Some("a task to add"),
+        false,
+    )
+    .unwrap()
+}
+
+/// Questions about parameters of given task type
+fn params_template(task_type: &str) -> MAAValue {
+    match task_type {
+        "StartUp" => object!(
+            "start_game_enabled" => BoolInput::new(Some(true), Some("start the game")),
+        ),
+        "Fight" => object!(
+            "stage" => SelectD::<String>::new(
+                [
+                    ValueWithDesc::new("1-7", Some("the most efficient stage for sanity")),
+                    ValueWithDesc::new("CE-6", Some("LMD")),
+                    ValueWithDesc::new("LS-6", Some("battle records")),
+                    ValueWithDesc::new("CA-5", Some("skill summaries")),
+                    ValueWithDesc::new("AP-5", Some("purchase certificates")),
+                    ValueWithDesc::new("Annihilation", Some("orundum")),
+                ],
+                Some(1),
+                Some("a stage to fight"),
+                true,
+            ).unwrap(),
+            "medicine" => Input::<i32>::new(Some(0), Some("number of sanity potions to use")),
+            "expiring_medicine" => Input::<i32>::new(
+                Some(0),
+                Some("number of expiring sanity potions to use"),
+            ),
+            "stone" => Input::<i32>::new(Some(0), Some("number of originite primes to use")),
+        ),
+        "Recruit" => object!(
+            "refresh" => BoolInput::new(Some(true), Some("refresh tags of 3-star recruitment")),
+            "times" => Input::<i32>::new(Some(4), Some("number of recruitments")),
+            "expedite" => BoolInput::new(Some(false), Some("use expedited plans")),
+            "select" => [4],
+            "confirm" => [3, 4],
+        ),
+        "Infrast" => {
+            let mut facility = MAAValue::new();
+            for name in FACILITIES {
+                facility.insert(
+                    name,
+                    BoolInput::new(Some(true), Some(&format!("shift operators in {name}"))),
+                );
+            }
+            object!(
+                "mode" => 0,
+                "facility" => facility,
+                "drones" => SelectD::<String>::new(
+                    [
+                        ValueWithDesc::new("_NotUse", Some("don't use drones")),
+                        ValueWithDesc::new("Money", Some("LMD")),
+                        ValueWithDesc::new("SyntheticJade", Some("orundum")),
+                        ValueWithDesc::new("CombatRecord", Some("battle records")),
+                        ValueWithDesc::new("PureGold", Some("pure gold")),
+                        ValueWithDesc::new("OriginStone", Some("originium shards")),
+                        ValueWithDesc::new("Chip", Some("chips")),
+                    ],
+                    Some(2),
+                    Some("usage of drones"),
+                    false,
+                ).unwrap(),
+            )
+        }
+        "Mall" => object!(
+            "shopping" => BoolInput::new(Some(true), Some("buy items in the credit store")),
+            "credit_fight" => BoolInput::new(
+                Some(false),
+                Some("fight a stage with a friend's support unit for more credits"),
+            ),
+        ),
+        "Award" => object!(
+            "award" => BoolInput::new(Some(true), Some("collect rewards of daily and weekly missions")),
+            "mail" => BoolInput::new(Some(false), Some("collect mails")),
+        ),
+        "Roguelike" => object!(
+            "theme" => SelectD::<String>::new(
+                ["Phantom", "Mizuki", "Sami", "Sarkaz"],
+                Some(4),
+                Some("theme of Integrated Strategies"),
+                false,
+            ).unwrap(),
+            "mode" => SelectD::<i32>::new(
+                [
+                    ValueWithDesc::new(0, Some("for score")),
+                    ValueWithDesc::new(1, Some("for ingots")),
+                    ValueWithDesc::new(4, Some("exit after 3rd floor")),
+                ],
+                Some(1),
+                Some("mode of exploration"),
+                false,
+            ).unwrap(),
+            "start_count" => Input::<i32>::new(Some(999), Some("number of explorations")),
+            "investment_enabled" => BoolInput::new(Some(true), Some("invest ingots")),
+            "investments_count" => Input::<i32>::new(
+                Some(999),
+                Some("number of ingots to invest before stopping exploration"),
+            ),
+            "stop_at_final_boss" => BoolInput::new(
+                Some(false),
+                Some("stop exploration before the final boss"),
+            ),
+        ),
+        _ => object!(),
+    }
+}
+
+/// Ask parameters of given task type, and convert answers to parameters of the task
+fn ask_params(task_type: &str) -> Result<MAAValue> {
+    let mut params = params_template(task_type).init()?;
+
+    // Facilities are asked one by one, but passed as a list of enabled ones
+    if let Some(facility) = params.get("facility") {
+        let enabled: Vec<&str> = FACILITIES
+            .into_iter()
+            .filter(|name| facility.get(name).and_then(MAAValue::as_bool) == Some(true))
+            .collect();
+        params.insert("facility", enabled);
+    }
+
+    Ok(params)
+}
+
+fn task_of(task_type: &str, params: MAAValue) -> MAAValue {
+    let mut task = object!("type" => task_type);
+    // An empty table would be parsed as a user input instead of empty parameters
+    if params.as_object().is_some_and(|params| !params.is_empty()) {
+        task.insert("params", params);
+    }
+    task
+}
+
+/// Build a task file by asking the user, and return its content in TOML
+pub fn build(name: &str) -> Result<String> {
+    let client_type = client_type_select().value()?;
+
+    let mut tasks = Vec::new();
+    loop {
+        let task_type = task_type_select().value()?;
+        let params = ask_params(&task_type)?;
+        tasks.push(task_of(&task_type, params));
+
+        if !BoolInput::new(Some(false), Some("add another task")).value()? {
+            break;
+        }
+    }
+
+    let task_config = object!(
+        "client_type" => client_type,
+        "tasks" => tasks,
+    );
+
+    let content = toml::to_string(&task_config).context("Failed to serialize task file")?;
+    Ok(format!(
+        "# Created by `maa config new-task --interactive`, run it by `maa run {name}`\n\
+         #\n\
+         # Parameters of each task type can be found in the MAA integration document:\n\
+         # https://maa.plus/docs/en-us/protocol/integration.html#list-of-task-types\n\n\
+         {content}"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::task::TaskConfig;
+
+    #[test]
+    fn build_in_batch_mode() {
+        let content = build("test").unwrap();
+        assert!(content.contains("maa run test"));
+
+        let task_config: TaskConfig = toml::from_str(&content).unwrap();
+        let task_config = task_config.init().unwrap();
+        assert_eq!(task_config.tasks.len(), 1);
+    }
+
+    #[test]
+    fn params_of_all_task_types() {
+        let task_types = [
+            "StartUp",
+            "Fight",
+            "Recruit",
+            "Infrast",
+            "Mall",
+            "Award",
+            "Roguelike",
+            "CloseDown",
+        ];
+
+        for task_type in task_types {
+            let params = ask_params(task_type).unwrap();
+            let content = toml::to_string(&object!(
+                "client_type" => "Official",
+                "tasks" => [task_of(task_type, params)],
+            ))
+            .unwrap();
+            let task_config: TaskConfig = toml::from_str(&content).unwrap();
+            task_config.init().unwrap();
+        }
+
+        let params = ask_params("Infrast").unwrap();
+        assert_eq!(params.get("facility"), Some(&FACILITIES.into()));
+        assert_eq!(
+            params.get("drones").and_then(MAAValue::as_str),
+            Some("Money")
+        );
+    }
+}
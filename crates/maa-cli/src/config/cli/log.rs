@@ -0,0 +1,259 @@
+use serde::Deserialize;
+
+pub const DEFAULT_TIMESTAMP: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Configuration of the log file in `$(maa dir state)/log`, and messages printed to console
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone)]
+#[serde(default)]
+pub struct Config {
+    /// Whether to write logs to the log file
+    enabled: bool,
+    /// Level of logs written to the log file, independent of the verbosity of console
+    #[serde(deserialize_with = "deserialize_level")]
+    level: log::LevelFilter,
+    /// When to rotate the log file
+    rotation: Rotation,
+    /// Maximum size of the log file in MiB, only used by size based rotation
+    max_size: u64,
+    /// Number of rotated log files to keep
+    keep: usize,
+    /// Number of screenshots of failed tasks to keep, 0 to disable them
+    screenshots: usize,
+    /// Format of timestamps prefixed to logs in console, empty to omit timestamps
+    #[serde(deserialize_with = "deserialize_timestamp")]
+    timestamp: String,
+    /// Categories of messages from MaaCore to show or hide in console
+    messages: Messages,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            level: log::LevelFilter::Info,
+            rotation: Rotation::default(),
+            max_size: 10,
+            keep: 7,
+            screenshots: 20,
+            timestamp: DEFAULT_TIMESTAMP.to_owned(),
+            messages: Messages::default(),
+        }
+    }
+}
+
+impl Config {
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn level(&self) -> log::LevelFilter {
+        self.level
+    }
+
+    pub fn rotation(&self) -> Rotation {
+        self.rotation
+    }
+
+    /// Maximum size of the log file in bytes
+    pub fn max_size(&self) -> u64 {
+        self.max_size.saturating_mul(1024 * 1024)
+    }
+
+    pub fn keep(&self) -> usize {
+        self.keep
+    }
+
+    pub fn screenshots(&self) -> usize {
+        self.screenshots
+    }
+
+    /// Format of timestamps in console like `%Y-%m-%d %H:%M:%S`, see `chrono::format::strftime`
+    pub fn timestamp(&self) -> &str {
+        &self.timestamp
+    }
+
+    pub fn messages(&self) -> &Messages {
+        &self.messages
+    }
+}
+
+/// Category of messages from MaaCore, by what they are about
+#[cfg_attr(test, derive(Debug))]
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum MessageCategory {
+    /// Connection to the device, like resolution and screencap methods
+    Connection,
+    /// Start and end of tasks
+    Task,
+    /// Drops of stages
+    Drops,
+    /// Fight tasks, except drops
+    Fight,
+    /// Recruit tasks
+    Recruit,
+    /// Infrast tasks
+    Infrast,
+    /// Roguelike tasks
+    Roguelike,
+    /// Copilot tasks, including SSS and paradox ones
+    Copilot,
+    /// Messages of other tasks
+    Other,
+}
+
+impl MessageCategory {
+    /// Minimum verbosity to show messages of this category by default
+    ///
+    /// Messages of connection are only shown by `-vv`, as they are noisy and rarely useful.
+    fn default_verbosity(self) -> log::LevelFilter {
+        match self {
+            MessageCategory::Connection => log::LevelFilter::Debug,
+            _ => log::LevelFilter::Info,
+        }
+    }
+}
+
+/// Categories of messages from MaaCore to show or hide in console
+///
+/// Only info and lower level messages are hidden, warnings and errors are always shown.
+/// Hidden messages are still written to the log file.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone, Default)]
+#[serde(default, deny_unknown_fields)]
+pub struct Messages {
+    /// Show only messages of these categories if not empty, regardless of the defaults
+    show: Vec<MessageCategory>,
+    /// Hide messages of these categories
+    hide: Vec<MessageCategory>,
+}
+
+impl Messages {
+    /// Whether messages of given category are shown in console with given verbosity
+    pub fn shows(&self, category: MessageCategory, verbosity: log::LevelFilter) -> bool {
+        if self.hide.contains(&category) {
+            false
+        } else if !self.show.is_empty() {
+            self.show.contains(&category)
+        } else {
+            verbosity >= category.default_verbosity()
+        }
+    }
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Default, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum Rotation {
+    /// Rotate the log file when the date changes
+    #[default]
+    Daily,
+    /// Rotate the log file when its size exceeds `max_size`
+    Size,
+}
+
+fn deserialize_level<'de, D>(deserializer: D) -> Result<log::LevelFilter, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(serde::de::Error::custom)
+}
+
+fn deserialize_timestamp<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use chrono::format::{Item, StrftimeItems};
+
+    let format = String::deserialize(deserializer)?;
+    if StrftimeItems::new(&format).any(|item| item == Item::Error) {
+        return Err(serde::de::Error::custom(format!(
+            "invalid format of timestamps `{format}`"
+        )));
+    }
+    Ok(format)
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    pub fn example_config() -> Config {
+        Config {
+            enabled: true,
+            level: log::LevelFilter::Debug,
+            rotation: Rotation::Size,
+            max_size: 5,
+            keep: 3,
+            screenshots: 10,
+            timestamp: "%H:%M:%S".to_owned(),
+            messages: Messages {
+                show: Vec::new(),
+                hide: vec![MessageCategory::Connection, MessageCategory::Infrast],
+            },
+        }
+    }
+
+    #[test]
+    fn deserialize() {
+        assert_eq!(toml::from_str::<Config>("").unwrap(), Config::default());
+        assert_eq!(
+            toml::from_str::<Config>(
+                r#"
+                enabled = true
+                level = "debug"
+                rotation = "size"
+                max_size = 5
+                keep = 3
+                screenshots = 10
+                timestamp = "%H:%M:%S"
+
+                [messages]
+                hide = ["connection", "infrast"]
+                "#
+            )
+            .unwrap(),
+            example_config()
+        );
+        assert!(toml::from_str::<Config>("level = \"loud\"").is_err());
+        assert!(toml::from_str::<Config>("rotation = \"weekly\"").is_err());
+        assert!(toml::from_str::<Config>("timestamp = \"%H:%Q\"").is_err());
+        assert_eq!(
+            toml::from_str::<Config>("timestamp = \"\"")
+                .unwrap()
+                .timestamp(),
+            ""
+        );
+        assert!(toml::from_str::<Config>("[messages]\nhide = [\"heartbeat\"]").is_err());
+    }
+
+    #[test]
+    fn messages() {
+        use log::LevelFilter::*;
+        use MessageCategory::*;
+
+        let default = Messages::default();
+        assert!(!default.shows(Drops, Warn));
+        assert!(default.shows(Drops, Info));
+        assert!(!default.shows(Connection, Info));
+        assert!(default.shows(Connection, Debug));
+
+        let messages = Messages {
+            show: vec![Drops, Connection],
+            hide: vec![Connection],
+        };
+        assert!(messages.shows(Drops, Info));
+        assert!(!messages.shows(Fight, Trace));
+        assert!(!messages.shows(Connection, Trace));
+
+        let messages = Messages {
+            show: Vec::new(),
+            hide: vec![Infrast],
+        };
+        assert!(!messages.shows(Infrast, Trace));
+        assert!(messages.shows(Recruit, Info));
+    }
+}
@@ -3,6 +3,7 @@ pub mod maa_cli;
 #[cfg(feature = "core_installer")]
 pub mod maa_core;
 
+pub mod log;
 pub mod resource;
 
 use std::sync::LazyLock;
@@ -10,13 +11,19 @@ use std::sync::LazyLock;
 use clap::ValueEnum;
 use serde::Deserialize;
 
-use super::FindFileOrDefault;
-use crate::dirs;
+use super::{
+    schema::{warn_unknown_keys, Schema},
+    FindFileOrDefault,
+};
+use crate::{dirs, i18n::Language};
 
 /// Configuration for the CLI (cli.toml)
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Deserialize, Default)]
 pub struct CLIConfig {
+    /// Language of messages, detected from the environment if not given
+    #[serde(default)]
+    language: Option<Language>,
     /// MaaCore configuration
     #[cfg(feature = "core_installer")]
     #[serde(default)]
@@ -26,9 +33,15 @@ pub struct CLIConfig {
     cli: maa_cli::Config,
     #[serde(default)]
     resource: resource::Config,
+    #[serde(default)]
+    log: log::Config,
 }
 
 impl CLIConfig {
+    pub fn language(&self) -> Option<Language> {
+        self.language
+    }
+
     #[cfg(feature = "core_installer")]
     pub fn core_config(&self) -> maa_core::Config {
         self.core.clone()
@@ -42,11 +55,16 @@ impl CLIConfig {
     pub fn resource_config(&self) -> resource::Config {
         self.resource.clone()
     }
+
+    pub fn log_config(&self) -> log::Config {
+        self.log.clone()
+    }
 }
 
 pub(crate) static CLI_CONFIG: LazyLock<CLIConfig> = LazyLock::new(|| {
-    CLIConfig::find_file_or_default(dirs::config().join("cli"))
-        .expect("Failed to load installer config")
+    let path = dirs::config().join("cli");
+    warn_unknown_keys(&path, Schema::Cli);
+    CLIConfig::find_file_or_default(path).expect("Failed to load installer config")
 });
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
@@ -220,11 +238,13 @@ mod tests {
                 .unwrap();
 
         let expect = CLIConfig {
+            language: Some(Language::SimplifiedChinese),
             #[cfg(feature = "core_installer")]
             core: maa_core::tests::example_config(),
             #[cfg(feature = "cli_installer")]
             cli: maa_cli::tests::example_config(),
             resource: resource::tests::example_config(),
+            log: log::tests::example_config(),
         };
 
         assert_eq!(config, expect);
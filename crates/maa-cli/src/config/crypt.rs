@@ -0,0 +1,210 @@
+//! Encryption at rest of configuration files
+//!
+//! An encrypted file keeps its original name and extension, but its content is replaced by
+//! an armored block, which is decrypted transparently when the file is loaded. The key is
+//! derived from a passphrase by Argon2id, and the content is encrypted by ChaCha20-Poly1305.
+
+use std::{fs, sync::Mutex};
+
+use anyhow::{bail, Context};
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305,
+};
+
+use super::{edit::ConfigType, Error, Result};
+
+const BEGIN: &str = "-----BEGIN MAA ENCRYPTED CONFIG-----";
+const END: &str = "-----END MAA ENCRYPTED CONFIG-----";
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// Environment variable of the passphrase
+const PASSPHRASE_ENV: &str = "MAA_CONFIG_PASSPHRASE";
+/// Environment variable of the path to a key file, whose content is used as the passphrase
+const KEY_FILE_ENV: &str = "MAA_CONFIG_KEY_FILE";
+
+/// Passphrase used in this session, so that the user is only asked once
+static PASSPHRASE: Mutex<Option<Vec<u8>>> = Mutex::new(None);
+
+fn error(msg: impl std::fmt::Display) -> Error {
+    Error::Encryption(msg.to_string())
+}
+
+pub fn is_encrypted(content: &[u8]) -> bool {
+    content.trim_ascii_start().starts_with(BEGIN.as_bytes())
+}
+
+fn derive_key(passphrase: &[u8], salt: &[u8]) -> Result<chacha20poly1305::Key> {
+    let mut key = chacha20poly1305::Key::default();
+    Argon2::default()
+        .hash_password_into(passphrase, salt, &mut key)
+        .map_err(error)?;
+    Ok(key)
+}
+
+pub fn encrypt(content: &[u8], passphrase: &[u8]) -> Result<String> {
+    let mut salt = [0u8; SALT_LEN];
+    getrandom(&mut salt)?;
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, &salt)?);
+    let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+    let ciphertext = cipher.encrypt(&nonce, content).map_err(error)?;
+
+    let mut payload = Vec::with_capacity(SALT_LEN + NONCE_LEN + ciphertext.len());
+    payload.extend_from_slice(&salt);
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+
+    let encoded = STANDARD.encode(payload);
+    let mut armored = String::with_capacity(encoded.len() + BEGIN.len() + END.len() + 64);
+    armored.push_str(BEGIN);
+    armored.push('\n');
+    for line in encoded.as_bytes().chunks(64) {
+        // base64 is always valid ASCII
+        armored.push_str(std::str::from_utf8(line).unwrap());
+        armored.push('\n');
+    }
+    armored.push_str(END);
+    armored.push('\n');
+
+    Ok(armored)
+}
+
+pub fn decrypt(content: &[u8], passphrase: &[u8]) -> Result<Vec<u8>> {
+    let content = std::str::from_utf8(content).map_err(error)?.trim();
+    let encoded: String = content
+        .strip_prefix(BEGIN)
+        .and_then(|s| s.strip_suffix(END))
+        .ok_or_else(|| error("malformed encrypted file"))?
+        .split_whitespace()
+        .collect();
+    let payload = STANDARD.decode(encoded).map_err(error)?;
+    if payload.len() < SALT_LEN + NONCE_LEN {
+        return Err(error("malformed encrypted file"));
+    }
+
+    let (salt, rest) = payload.split_at(SALT_LEN);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let cipher = ChaCha20Poly1305::new(&derive_key(passphrase, salt)?);
+    cipher
+        .decrypt(nonce.into(), ciphertext)
+        .map_err(|_| error("wrong passphrase or corrupted file"))
+}
+
+fn getrandom(buf: &mut [u8]) -> Result<()> {
+    use chacha20poly1305::aead::rand_core::RngCore;
+    OsRng.try_fill_bytes(buf).map_err(error)
+}
+
+/// Get the passphrase from environment variables, or ask the user for it
+///
+/// The passphrase is read from `MAA_CONFIG_PASSPHRASE`, or the key file specified by
+/// `MAA_CONFIG_KEY_FILE`. If neither is set, the user will be prompted for it,
+/// and asked to type it twice if `confirm` is true.
+pub fn passphrase(confirm: bool) -> Result<Vec<u8>> {
+    let mut cached = PASSPHRASE.lock().unwrap();
+    if let Some(passphrase) = cached.as_ref() {
+        return Ok(passphrase.clone());
+    }
+
+    let passphrase = if let Some(passphrase) = std::env::var_os(PASSPHRASE_ENV) {
+        passphrase.into_encoded_bytes()
+    } else if let Some(key_file) = std::env::var_os(KEY_FILE_ENV) {
+        let mut key = fs::read(key_file)?;
+        key.truncate(key.trim_ascii_end().len());
+        key
+    } else {
+        let passphrase = rpassword::prompt_password("Passphrase of config files: ")?;
+        if confirm && rpassword::prompt_password("Confirm passphrase: ")? != passphrase {
+            return Err(error("passphrases do not match"));
+        }
+        passphrase.into_bytes()
+    };
+
+    if passphrase.is_empty() {
+        return Err(error("empty passphrase"));
+    }
+
+    *cached = Some(passphrase.clone());
+    Ok(passphrase)
+}
+
+/// Use given passphrase in this session, instead of reading or asking for it
+#[cfg(test)]
+pub(super) fn set_passphrase(passphrase: &[u8]) {
+    *PASSPHRASE.lock().unwrap() = Some(passphrase.to_vec());
+}
+
+/// Decrypt the content if it's encrypted, otherwise return it as is
+pub(super) fn maybe_decrypt(content: Vec<u8>) -> Result<Vec<u8>> {
+    if is_encrypted(&content) {
+        decrypt(&content, &passphrase(false)?)
+    } else {
+        Ok(content)
+    }
+}
+
+/// Encrypt a configuration file in place
+///
+/// The file is validated before encryption, so a broken file can't be hidden by encryption.
+pub fn encrypt_file(config_type: ConfigType, name: Option<&str>) -> anyhow::Result<()> {
+    let path = config_type.path(name)?;
+    let content = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if is_encrypted(&content) {
+        bail!("{} is already encrypted", path.display());
+    }
+    config_type.validate(&path)?;
+
+    let encrypted = encrypt(&content, &passphrase(true)?)?;
+    fs::write(&path, encrypted)?;
+    println!("Encrypted {}", path.display());
+
+    Ok(())
+}
+
+/// Decrypt a configuration file in place
+pub fn decrypt_file(config_type: ConfigType, name: Option<&str>) -> anyhow::Result<()> {
+    let path = config_type.path(name)?;
+    let content = fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+    if !is_encrypted(&content) {
+        bail!("{} is not encrypted", path.display());
+    }
+
+    let decrypted = decrypt(&content, &passphrase(false)?)?;
+    fs::write(&path, decrypted)?;
+    println!("Decrypted {}", path.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn roundtrip() {
+        let content = b"[connection]\naddress = \"127.0.0.1:5555\"\n";
+
+        let encrypted = encrypt(content, b"secret").unwrap();
+        assert!(is_encrypted(encrypted.as_bytes()));
+        assert!(!is_encrypted(content));
+        assert!(encrypted
+            .lines()
+            .all(|line| line.len() <= 64 || line == BEGIN));
+        assert_eq!(decrypt(encrypted.as_bytes(), b"secret").unwrap(), content);
+
+        // Salt and nonce are random
+        assert_ne!(encrypt(content, b"secret").unwrap(), encrypted);
+
+        assert!(decrypt(encrypted.as_bytes(), b"wrong").is_err());
+        // Flip a character in the last line of payload
+        let mut tampered = encrypted.clone().into_bytes();
+        let index = encrypted.len() - END.len() - 3;
+        tampered[index] = if tampered[index] == b'A' { b'B' } else { b'A' };
+        assert!(decrypt(&tampered, b"secret").is_err());
+        assert!(decrypt(format!("{BEGIN}\nAAAA\n{END}").as_bytes(), b"secret").is_err());
+    }
+}
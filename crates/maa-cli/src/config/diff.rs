@@ -0,0 +1,157 @@
+use std::{collections::BTreeMap, io::IsTerminal};
+
+use anyhow::{bail, Result};
+use color_print::cformat;
+use serde_json::Value as JsonValue;
+
+use super::{edit::ConfigType, schema::Schema, FromFile};
+
+/// A configuration key with the value set in the file and the built-in default value
+#[cfg_attr(test, derive(Debug, PartialEq))]
+struct Entry<'a> {
+    key: String,
+    value: Option<&'a JsonValue>,
+    default: Option<&'a JsonValue>,
+}
+
+impl Entry<'_> {
+    fn is_default(&self) -> bool {
+        self.value.is_none() || self.value == self.default
+    }
+}
+
+/// Print the effective configuration, with values differing from built-in defaults highlighted
+///
+/// Built-in defaults are read from the JSON schema, so keys without a default value in the
+/// schema are only printed when they are set in the configuration file.
+pub fn diff(config_type: ConfigType, name: Option<&str>) -> Result<()> {
+    let schema = match config_type {
+        ConfigType::Cli => Schema::Cli,
+        ConfigType::Profile => Schema::Asst,
+        ConfigType::Task => bail!("Task configuration has no built-in defaults"),
+    };
+
+    let path = config_type.path(name)?;
+    let value = if path.exists() {
+        JsonValue::from_file(&path)?
+    } else {
+        JsonValue::Object(Default::default())
+    };
+    let source = path.display().to_string();
+    let color = std::io::stdout().is_terminal();
+
+    for entry in entries(&value, schema.defaults()) {
+        let value = entry.value.or(entry.default).unwrap();
+        let line = if entry.is_default() {
+            format!("{} = {value} # default", entry.key)
+        } else if let Some(default) = entry.default {
+            format!("{} = {value} # {source}, default: {default}", entry.key)
+        } else {
+            format!("{} = {value} # {source}", entry.key)
+        };
+
+        if color && !entry.is_default() {
+            println!("{}", cformat!("<y,bold>{}</>", line));
+        } else {
+            println!("{line}");
+        }
+    }
+
+    Ok(())
+}
+
+/// Merge the flattened configuration and default values, sorted by key
+fn entries<'a>(value: &'a JsonValue, defaults: BTreeMap<String, &'a JsonValue>) -> Vec<Entry<'a>> {
+    let mut merged: BTreeMap<String, (Option<&JsonValue>, Option<&JsonValue>)> = defaults
+        .into_iter()
+        .map(|(key, default)| (key, (None, Some(default))))
+        .collect();
+
+    let mut leaves = Vec::new();
+    flatten(value, String::new(), &mut leaves);
+    for (key, value) in leaves {
+        merged.entry(key).or_default().0 = Some(value);
+    }
+
+    merged
+        .into_iter()
+        .map(|(key, (value, default))| Entry {
+            key,
+            value,
+            default,
+        })
+        .collect()
+}
+
+/// Flatten nested objects into dotted keys, other values are treated as leaves
+fn flatten<'a>(value: &'a JsonValue, prefix: String, leaves: &mut Vec<(String, &'a JsonValue)>) {
+    match value {
+        JsonValue::Object(map) => {
+            for (key, value) in map {
+                // Keys like `$schema` are used by editors
+                if key.starts_with('$') {
+                    continue;
+                }
+                let key = if prefix.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{prefix}.{key}")
+                };
+                flatten(value, key, leaves);
+            }
+        }
+        _ => leaves.push((prefix, value)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn merge_entries() {
+        let value = json!({
+            "$schema": "cli.schema.json",
+            "core": { "channel": "Beta", "test_time": 3 },
+            "resource": { "remote": { "ssh_key": "~/.ssh/id_ed25519" } },
+        });
+        let stable = json!("Stable");
+        let test_time = json!(3);
+        let defaults = BTreeMap::from([
+            ("core.channel".to_owned(), &stable),
+            ("core.test_time".to_owned(), &test_time),
+            ("cli.channel".to_owned(), &stable),
+        ]);
+
+        let entries = entries(&value, defaults);
+        assert_eq!(entries, vec![
+            Entry {
+                key: "cli.channel".to_owned(),
+                value: None,
+                default: Some(&stable),
+            },
+            Entry {
+                key: "core.channel".to_owned(),
+                value: Some(&value["core"]["channel"]),
+                default: Some(&stable),
+            },
+            Entry {
+                key: "core.test_time".to_owned(),
+                value: Some(&value["core"]["test_time"]),
+                default: Some(&test_time),
+            },
+            Entry {
+                key: "resource.remote.ssh_key".to_owned(),
+                value: Some(&value["resource"]["remote"]["ssh_key"]),
+                default: None,
+            },
+        ]);
+
+        assert!(entries[0].is_default());
+        assert!(!entries[1].is_default());
+        assert!(entries[2].is_default());
+        assert!(!entries[3].is_default());
+    }
+}
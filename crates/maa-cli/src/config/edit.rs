@@ -0,0 +1,178 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context, Result};
+
+use super::{
+    asst::AsstConfig, cli::CLIConfig, crypt, find_config, task::TaskConfig, Filetype, FromFile,
+};
+use crate::{
+    dirs::{self, Ensure},
+    value::userinput::{BoolInput, UserInput},
+};
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum ConfigType {
+    /// CLI configuration file, `cli.toml`
+    Cli,
+    /// MaaCore configuration file, `profiles/<name>.toml`
+    #[value(alias("asst"))]
+    Profile,
+    /// Task configuration file, `tasks/<name>.toml`
+    Task,
+}
+
+impl ConfigType {
+    /// Path of the configuration file to edit
+    ///
+    /// If the name has a supported extension, it will be used directly.
+    /// Otherwise, the first existing file with supported extension will be used,
+    /// or a new TOML file will be created if no such file exists.
+    pub(super) fn path(self, name: Option<&str>) -> Result<PathBuf> {
+        let config_dir = dirs::config();
+        let path = match self {
+            ConfigType::Cli => config_dir.join("cli"),
+            ConfigType::Profile => join!(config_dir, "profiles", name.unwrap_or("default")),
+            ConfigType::Task => join!(
+                config_dir,
+                "tasks",
+                name.context("Name of the task is required")?
+            ),
+        };
+
+        if Filetype::is_valid_file(&path) {
+            return Ok(path);
+        }
+
+        Ok(find_config(&path).unwrap_or_else(|| path.with_extension("toml")))
+    }
+
+    /// Try to parse the configuration file to check if it is valid
+    pub(super) fn validate(self, path: &Path) -> Result<()> {
+        match self {
+            ConfigType::Cli => {
+                CLIConfig::from_file(path)?;
+            }
+            ConfigType::Profile => {
+                AsstConfig::from_file(path)?;
+            }
+            ConfigType::Task => {
+                TaskConfig::from_file(path)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Get the editor command from `VISUAL` or `EDITOR` environment variable
+fn editor() -> Vec<String> {
+    std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .ok()
+        .map(|editor| editor.split_whitespace().map(String::from).collect())
+        .filter(|editor: &Vec<String>| !editor.is_empty())
+        .unwrap_or_else(|| vec![if cfg!(windows) { "notepad" } else { "vi" }.to_owned()])
+}
+
+/// Edit a configuration file with editor and validate it after editing
+///
+/// The file is edited in a temporary copy, and only saved back if it is valid.
+/// If the file is invalid, the user will be asked whether to edit it again,
+/// otherwise the changes will be discarded.
+pub fn edit(config_type: ConfigType, name: Option<&str>) -> Result<()> {
+    let path = config_type.path(name)?;
+    let editor = editor();
+
+    let tmp_dir = tempfile::tempdir()?;
+    let tmp_file = tmp_dir.path().join(path.file_name().unwrap());
+    // Encrypted file is edited in plain text and encrypted again when saving
+    let mut encrypted = false;
+    if path.exists() {
+        let content = fs::read(&path)?;
+        encrypted = crypt::is_encrypted(&content);
+        fs::write(&tmp_file, crypt::maybe_decrypt(content)?)?;
+    }
+
+    loop {
+        let status = Command::new(&editor[0])
+            .args(&editor[1..])
+            .arg(&tmp_file)
+            .status()
+            .with_context(|| format!("Failed to launch editor `{}`", editor.join(" ")))?;
+
+        if !status.success() {
+            bail!("Editor exited with {status}, changes discarded");
+        }
+
+        if !tmp_file.exists() {
+            println!("File not saved, nothing to do.");
+            return Ok(());
+        }
+
+        match config_type.validate(&tmp_file) {
+            Ok(()) => break,
+            Err(err) => {
+                eprintln!("Invalid configuration file: {err:#}");
+                if !BoolInput::new(Some(false), Some("edit again")).value()? {
+                    bail!("Invalid configuration file, changes discarded");
+                }
+            }
+        }
+    }
+
+    if let Some(dir) = path.parent() {
+        dir.ensure()?;
+    }
+    if encrypted {
+        let content = crypt::encrypt(&fs::read(&tmp_file)?, &crypt::passphrase(false)?)?;
+        fs::write(&path, content)?;
+    } else {
+        fs::copy(&tmp_file, &path)?;
+    }
+    println!("Saved to {}", path.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path() {
+        let config_dir = dirs::config();
+
+        assert_eq!(
+            ConfigType::Task.path(Some("daily.json")).unwrap(),
+            join!(config_dir, "tasks", "daily.json")
+        );
+        assert!(ConfigType::Task.path(None).is_err());
+
+        assert_eq!(
+            ConfigType::Profile.path(Some("not_exist")).unwrap(),
+            join!(config_dir, "profiles", "not_exist.toml")
+        );
+    }
+
+    #[test]
+    fn validate() {
+        let tmp_dir = tempfile::tempdir().unwrap();
+
+        let valid = tmp_dir.path().join("valid.toml");
+        fs::write(&valid, "[[tasks]]\ntype = \"StartUp\"\n").unwrap();
+        assert!(ConfigType::Task.validate(&valid).is_ok());
+
+        let invalid = tmp_dir.path().join("invalid.toml");
+        fs::write(&invalid, "[[tasks]]\ntype = \"StartUp\"\nunknown = 1\n").unwrap();
+        assert!(ConfigType::Task.validate(&invalid).is_err());
+
+        let invalid = tmp_dir.path().join("invalid.json");
+        fs::write(&invalid, "{").unwrap();
+        assert!(ConfigType::Cli.validate(&invalid).is_err());
+    }
+}
@@ -0,0 +1,423 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use serde_json::{Map, Value as JsonValue};
+
+use super::{crypt, find_config, Filetype};
+use crate::dirs;
+
+/// A migration rule, which modifies the value in place and returns whether it is changed
+type Rule = fn(&mut JsonValue) -> bool;
+
+/// Migrate all configuration files in the config directory to the current format
+///
+/// The original files will be backed up with a `.bak` suffix before being rewritten.
+/// Encrypted files are encrypted again with the same passphrase after migration.
+pub fn migrate() -> Result<()> {
+    let migrated = migrate_dir(dirs::config())?;
+
+    if migrated.is_empty() {
+        println!("All configuration files are up to date.");
+    } else {
+        for (path, backup) in migrated {
+            println!(
+                "Migrated {} (original file backed up to {})",
+                path.display(),
+                backup.display()
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Migrate all configuration files in given directory,
+/// returns a list of migrated files and their backups.
+fn migrate_dir(root: impl AsRef<Path>) -> Result<Vec<(PathBuf, PathBuf)>> {
+    let root = root.as_ref();
+    let mut migrated = Vec::new();
+
+    // `asst.toml` is deprecated in favor of `profiles/default.toml`
+    if let Some(asst) = find_config(root.join("asst")) {
+        if find_config(join!(root, "profiles", "default")).is_some() {
+            warn!(
+                "Both {} and default profile exist, the former is ignored",
+                asst.display()
+            );
+        } else {
+            let filetype = Filetype::parse_filetype(&asst).context("Unsupported filetype")?;
+            let encrypted = crypt::is_encrypted(&fs::read(&asst)?);
+            let mut value: JsonValue = filetype
+                .read(&asst)
+                .with_context(|| format!("Failed to read {}", asst.display()))?;
+            migrate_profile(&mut value);
+
+            let profile = join!(root, "profiles", "default").with_extension(filetype.to_str());
+            fs::create_dir_all(profile.parent().unwrap())?;
+            write_file(filetype, &profile, &value, encrypted)
+                .with_context(|| format!("Failed to write {}", profile.display()))?;
+
+            let backup = backup_path(&asst);
+            fs::rename(&asst, &backup)?;
+            migrated.push((profile, backup));
+        }
+    }
+
+    if let Some(cli) = find_config(root.join("cli")) {
+        if let Some(backup) = migrate_file(&cli, migrate_cli)? {
+            migrated.push((cli, backup));
+        }
+    }
+
+    for (dir, rule) in [
+        ("profiles", migrate_profile as Rule),
+        ("tasks", migrate_task as Rule),
+    ] {
+        let dir = root.join(dir);
+        if !dir.exists() {
+            continue;
+        }
+        for entry in dir.read_dir()? {
+            let path = entry?.path();
+            if path.is_file() && Filetype::is_valid_file(&path) {
+                if let Some(backup) = migrate_file(&path, rule)? {
+                    migrated.push((path, backup));
+                }
+            }
+        }
+    }
+
+    Ok(migrated)
+}
+
+fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    backup.into()
+}
+
+/// Migrate given file with given rule,
+/// returns the path of backup file if the file is changed.
+fn migrate_file(path: &Path, rule: Rule) -> Result<Option<PathBuf>> {
+    let filetype = Filetype::parse_filetype(path).context("Unsupported filetype")?;
+    let encrypted = crypt::is_encrypted(&fs::read(path)?);
+    let mut value: JsonValue = filetype
+        .read(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    if !rule(&mut value) {
+        debug!("{} is up to date", path.display());
+        return Ok(None);
+    }
+
+    let backup = backup_path(path);
+    fs::copy(path, &backup)?;
+    write_file(filetype, path, &value, encrypted)
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+
+    Ok(Some(backup))
+}
+
+/// Write the value to given file, which is encrypted if `encrypted` is true
+///
+/// Encrypted files are decrypted transparently when read, so they must be encrypted again,
+/// or their secrets would be written back in plain text.
+fn write_file(filetype: Filetype, path: &Path, value: &JsonValue, encrypted: bool) -> Result<()> {
+    let mut content = Vec::new();
+    filetype.write(&mut content, value)?;
+    if encrypted {
+        content = crypt::encrypt(&content, &crypt::passphrase(false)?)?.into_bytes();
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+/// Rename a key in given object, returns whether the object is changed
+fn rename_key(obj: &mut Map<String, JsonValue>, old: &str, new: &str) -> bool {
+    if !obj.contains_key(old) {
+        return false;
+    }
+
+    if obj.contains_key(new) {
+        warn!("Both `{old}` and `{new}` are set, please remove `{old}` manually");
+        return false;
+    }
+
+    let value = obj.remove(old).unwrap();
+    obj.insert(new.to_owned(), value);
+    true
+}
+
+/// Replace a string value, returns whether the value is changed
+fn replace_str(value: &mut JsonValue, old: &str, new: &str) -> bool {
+    if value.as_str() == Some(old) {
+        *value = new.into();
+        true
+    } else {
+        false
+    }
+}
+
+fn migrate_profile(value: &mut JsonValue) -> bool {
+    let Some(connection) = value
+        .get_mut("connection")
+        .and_then(JsonValue::as_object_mut)
+    else {
+        return false;
+    };
+
+    let mut changed = rename_key(connection, "type", "preset");
+    changed |= rename_key(connection, "device", "address");
+    if let Some(preset) = connection.get_mut("preset") {
+        changed |= replace_str(preset, "PlayTools", "PlayCover");
+    }
+
+    changed
+}
+
+fn migrate_cli(value: &mut JsonValue) -> bool {
+    let mut changed = false;
+
+    for section in ["core", "cli"] {
+        if let Some(channel) = value
+            .get_mut(section)
+            .and_then(|section| section.get_mut("channel"))
+        {
+            for (old, new) in [("stable", "Stable"), ("beta", "Beta"), ("alpha", "Alpha")] {
+                changed |= replace_str(channel, old, new);
+            }
+        }
+    }
+
+    changed
+}
+
+fn migrate_task(value: &mut JsonValue) -> bool {
+    let mut changed = false;
+
+    let Some(tasks) = value.get_mut("tasks").and_then(JsonValue::as_array_mut) else {
+        return false;
+    };
+
+    for task in tasks {
+        let Some(variants) = task.get_mut("variants").and_then(JsonValue::as_array_mut) else {
+            continue;
+        };
+        for variant in variants {
+            if let Some(condition) = variant.get_mut("condition") {
+                changed |= migrate_condition(condition);
+            }
+        }
+    }
+
+    changed
+}
+
+fn migrate_condition(condition: &mut JsonValue) -> bool {
+    let Some(obj) = condition.as_object_mut() else {
+        return false;
+    };
+
+    let mut changed = false;
+
+    if let Some(condition_type) = obj.get_mut("type") {
+        changed |= replace_str(condition_type, "Combined", "And");
+    }
+
+    if obj.get("type").is_some_and(|t| t == "Weekday") {
+        changed |= rename_key(obj, "client", "timezone");
+    }
+
+    if let Some(conditions) = obj.get_mut("conditions").and_then(JsonValue::as_array_mut) {
+        for condition in conditions {
+            changed |= migrate_condition(condition);
+        }
+    }
+
+    if let Some(condition) = obj.get_mut("condition") {
+        changed |= migrate_condition(condition);
+    }
+
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn profile() {
+        let mut value = json!({
+            "connection": {
+                "type": "PlayTools",
+                "device": "localhost:1717",
+            },
+        });
+        assert!(migrate_profile(&mut value));
+        assert_eq!(
+            value,
+            json!({
+                "connection": {
+                    "preset": "PlayCover",
+                    "address": "localhost:1717",
+                },
+            })
+        );
+        assert!(!migrate_profile(&mut value));
+
+        // Do not overwrite the new key
+        let mut value = json!({
+            "connection": {
+                "type": "ADB",
+                "preset": "MuMuPro",
+            },
+        });
+        assert!(!migrate_profile(&mut value));
+    }
+
+    #[test]
+    fn cli() {
+        let mut value = json!({
+            "core": { "channel": "beta" },
+            "cli": { "channel": "Alpha" },
+        });
+        assert!(migrate_cli(&mut value));
+        assert_eq!(
+            value,
+            json!({
+                "core": { "channel": "Beta" },
+                "cli": { "channel": "Alpha" },
+            })
+        );
+        assert!(!migrate_cli(&mut value));
+    }
+
+    #[test]
+    fn task() {
+        let mut value = json!({
+            "tasks": [
+                {
+                    "type": "Fight",
+                    "variants": [
+                        {
+                            "condition": {
+                                "type": "Combined",
+                                "conditions": [
+                                    { "type": "Weekday", "weekdays": ["Mon"], "client": "Official" },
+                                    {
+                                        "type": "Not",
+                                        "condition": { "type": "Combined", "conditions": [] },
+                                    },
+                                ],
+                            },
+                        },
+                        { "params": {} },
+                    ],
+                },
+                { "type": "Mall" },
+            ],
+        });
+        assert!(migrate_task(&mut value));
+        assert_eq!(
+            value,
+            json!({
+                "tasks": [
+                    {
+                        "type": "Fight",
+                        "variants": [
+                            {
+                                "condition": {
+                                    "type": "And",
+                                    "conditions": [
+                                        { "type": "Weekday", "weekdays": ["Mon"], "timezone": "Official" },
+                                        {
+                                            "type": "Not",
+                                            "condition": { "type": "And", "conditions": [] },
+                                        },
+                                    ],
+                                },
+                            },
+                            { "params": {} },
+                        ],
+                    },
+                    { "type": "Mall" },
+                ],
+            })
+        );
+        assert!(!migrate_task(&mut value));
+    }
+
+    #[test]
+    fn dir() {
+        let root = std::env::temp_dir().join("maa-test-migrate");
+        if root.exists() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+        fs::create_dir_all(root.join("tasks")).unwrap();
+
+        fs::write(
+            root.join("asst.toml"),
+            "[connection]\ntype = \"ADB\"\ndevice = \"emulator-5554\"\n",
+        )
+        .unwrap();
+        fs::write(root.join("cli.toml"), "[core]\nchannel = \"Stable\"\n").unwrap();
+        fs::write(
+            join!(&root, "tasks", "daily.json"),
+            r#"{"tasks": [{"type": "Mall"}]}"#,
+        )
+        .unwrap();
+
+        let migrated = migrate_dir(&root).unwrap();
+        let profile = join!(&root, "profiles", "default.toml");
+        assert_eq!(migrated, vec![(
+            profile.clone(),
+            root.join("asst.toml.bak")
+        )]);
+        assert!(!root.join("asst.toml").exists());
+        assert_eq!(
+            Filetype::Toml.read::<JsonValue>(&profile).unwrap(),
+            json!({ "connection": { "preset": "ADB", "address": "emulator-5554" } })
+        );
+
+        // Nothing to migrate
+        assert!(migrate_dir(&root).unwrap().is_empty());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn encrypted() {
+        let root = std::env::temp_dir().join("maa-test-migrate-encrypted");
+        if root.exists() {
+            fs::remove_dir_all(&root).unwrap();
+        }
+        fs::create_dir_all(root.join("profiles")).unwrap();
+
+        crypt::set_passphrase(b"secret");
+        let profile = join!(&root, "profiles", "default.toml");
+        let content = b"[connection]\ntype = \"ADB\"\naddress = \"emulator-5554\"\n";
+        fs::write(&profile, crypt::encrypt(content, b"secret").unwrap()).unwrap();
+
+        let migrated = migrate_dir(&root).unwrap();
+        let backup = join!(&root, "profiles", "default.toml.bak");
+        assert_eq!(migrated, vec![(profile.clone(), backup.clone())]);
+
+        // Both the migrated file and the backup are still encrypted
+        let migrated = fs::read(&profile).unwrap();
+        assert!(crypt::is_encrypted(&migrated));
+        assert!(crypt::is_encrypted(&fs::read(&backup).unwrap()));
+        let decrypted = crypt::decrypt(&migrated, b"secret").unwrap();
+        assert_eq!(
+            toml::from_str::<JsonValue>(std::str::from_utf8(&decrypted).unwrap()).unwrap(),
+            json!({ "connection": { "preset": "ADB", "address": "emulator-5554" } })
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
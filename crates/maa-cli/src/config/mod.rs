@@ -1,6 +1,6 @@
 use std::{
     fs::{self, File},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use serde_json::Value as JsonValue;
@@ -16,6 +16,7 @@ pub enum Error {
     TomlDe(toml::de::Error),
     TomlSer(toml::ser::Error),
     Yaml(serde_yaml::Error),
+    Encryption(String),
 }
 
 type Result<T, E = Error> = std::result::Result<T, E>;
@@ -30,6 +31,7 @@ impl std::fmt::Display for Error {
             Error::TomlSer(e) => write!(f, "TOML serialize error, {}", e),
             Error::TomlDe(e) => write!(f, "TOML deserialize error, {}", e),
             Error::Yaml(e) => write!(f, "YAML parse error, {}", e),
+            Error::Encryption(e) => write!(f, "Encryption error, {}", e),
         }
     }
 }
@@ -78,7 +80,7 @@ fn file_not_found(path: impl AsRef<Path>) -> Error {
     .into()
 }
 
-const SUPPORTED_EXTENSION: [&str; 4] = ["json", "yaml", "yml", "toml"];
+pub(crate) const SUPPORTED_EXTENSION: [&str; 4] = ["json", "yaml", "yml", "toml"];
 
 #[derive(Clone, Copy, clap::ValueEnum)]
 pub enum Filetype {
@@ -116,10 +118,14 @@ impl Filetype {
         T: serde::de::DeserializeOwned,
     {
         use Filetype::*;
+        let content = crypt::maybe_decrypt(fs::read(path)?)?;
         Ok(match self {
-            Json => serde_json::from_reader(File::open(path)?)?,
-            Yaml => serde_yaml::from_reader(File::open(path)?)?,
-            Toml => toml::from_str(&fs::read_to_string(path)?)?,
+            Json => serde_json::from_slice(&content)?,
+            Yaml => serde_yaml::from_slice(&content)?,
+            Toml => toml::from_str(
+                std::str::from_utf8(&content)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?,
+            )?,
         })
     }
 
@@ -146,6 +152,17 @@ impl Filetype {
     }
 }
 
+/// Find an existing file with supported extension.
+///
+/// The file should not have extension. If it has extension, it will be ignored.
+fn find_config(path: impl AsRef<Path>) -> Option<PathBuf> {
+    let path = path.as_ref();
+    SUPPORTED_EXTENSION
+        .iter()
+        .map(|ext| path.with_extension(ext))
+        .find(|path| path.exists())
+}
+
 pub trait FromFile: Sized + serde::de::DeserializeOwned {
     fn from_file(path: impl AsRef<Path>) -> Result<Self> {
         let path = path.as_ref();
@@ -349,8 +366,24 @@ pub mod cli;
 
 pub mod task;
 
+pub mod watch;
+
 pub mod init;
 
+pub mod migrate;
+
+pub mod edit;
+
+pub mod diff;
+
+pub mod crypt;
+
+pub mod template;
+
+mod builder;
+
+pub mod schema;
+
 #[cfg(test)]
 mod tests {
     use std::env::temp_dir;
@@ -0,0 +1,351 @@
+//! Check configuration files against JSON schemas to find unknown or deprecated keys.
+//!
+//! Most configuration structs are deserialized with default values, so a typo in key name will
+//! be silently ignored. To help users find such mistakes, the keys in configuration files are
+//! checked against the JSON schemas in `schemas` directory, and warnings will be emitted for
+//! unknown or deprecated keys.
+
+use std::{collections::BTreeMap, path::Path, sync::LazyLock};
+
+use log::warn;
+use serde_json::Value as JsonValue;
+
+use super::{find_config, FromFile};
+
+#[derive(Clone, Copy)]
+pub enum Schema {
+    Asst,
+    Cli,
+}
+
+static ASST_SCHEMA: LazyLock<JsonValue> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("../../schemas/asst.schema.json"))
+        .expect("Invalid schema of asst config")
+});
+
+static CLI_SCHEMA: LazyLock<JsonValue> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("../../schemas/cli.schema.json"))
+        .expect("Invalid schema of cli config")
+});
+
+impl Schema {
+    fn schema(self) -> &'static JsonValue {
+        match self {
+            Schema::Asst => &ASST_SCHEMA,
+            Schema::Cli => &CLI_SCHEMA,
+        }
+    }
+
+    /// Default values declared in the schema, keyed by dotted path like `core.channel`
+    ///
+    /// Only keys under `properties` are collected, keys of maps like `instances` are unknown.
+    pub fn defaults(self) -> BTreeMap<String, &'static JsonValue> {
+        let root = self.schema();
+        let mut defaults = BTreeMap::new();
+        collect_defaults(root, root, "", &mut defaults);
+        defaults
+    }
+
+    /// Deprecated keys and their replacements
+    fn deprecated_keys(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            Schema::Asst => &[
+                ("connection.type", "connection.preset"),
+                ("connection.device", "connection.address"),
+                ("connection.bundle_id", "connection.app"),
+            ],
+            Schema::Cli => &[],
+        }
+    }
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum KeyWarning {
+    /// An unknown key with the closest valid key if any
+    Unknown(String, Option<String>),
+    /// A deprecated key with its replacement
+    Deprecated(String, &'static str),
+}
+
+impl std::fmt::Display for KeyWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            KeyWarning::Unknown(key, Some(suggestion)) => {
+                write!(f, "unknown key `{key}`, did you mean `{suggestion}`?")
+            }
+            KeyWarning::Unknown(key, None) => write!(f, "unknown key `{key}`"),
+            KeyWarning::Deprecated(key, replacement) => write!(
+                f,
+                "key `{key}` is deprecated, use `{replacement}` instead \
+                 or run `maa config migrate` to migrate it"
+            ),
+        }
+    }
+}
+
+/// Find the configuration file and warn about unknown or deprecated keys in it
+///
+/// The path should not have extension, like [`super::FindFile::find_file`].
+/// Errors are ignored here, because they will be reported when deserializing the file.
+pub fn warn_unknown_keys(path: impl AsRef<Path>, schema: Schema) {
+    let Some(path) = find_config(path) else {
+        return;
+    };
+    let Ok(value) = JsonValue::from_file(&path) else {
+        return;
+    };
+
+    for warning in check_keys(&value, schema) {
+        warn!("{}: {warning}", path.display());
+    }
+}
+
+fn check_keys(value: &JsonValue, schema: Schema) -> Vec<KeyWarning> {
+    let mut warnings = Vec::new();
+    let root = schema.schema();
+    walk(
+        value,
+        root,
+        root,
+        "",
+        schema.deprecated_keys(),
+        &mut warnings,
+    );
+    warnings
+}
+
+fn walk(
+    value: &JsonValue,
+    node: &JsonValue,
+    root: &JsonValue,
+    prefix: &str,
+    deprecated: &[(&str, &'static str)],
+    warnings: &mut Vec<KeyWarning>,
+) {
+    let Some(node) = resolve(node, root) else {
+        return;
+    };
+
+    match value {
+        JsonValue::Object(map) => {
+            let Some(properties) = node.get("properties").and_then(JsonValue::as_object) else {
+                return;
+            };
+            let additional = node.get("additionalProperties");
+            let parent = if prefix.is_empty() {
+                String::new()
+            } else {
+                format!("{prefix}.")
+            };
+
+            for (key, value) in map {
+                // Keys like `$schema` are used by editors
+                if key.starts_with('$') {
+                    continue;
+                }
+
+                let full_key = format!("{parent}{key}");
+
+                if let Some(property) = properties.get(key) {
+                    walk(value, property, root, &full_key, deprecated, warnings);
+                } else if let Some(&(_, replacement)) =
+                    deprecated.iter().find(|(old, _)| *old == full_key)
+                {
+                    warnings.push(KeyWarning::Deprecated(full_key, replacement));
+                } else if let Some(additional) = additional.filter(|v| v.is_object()) {
+                    walk(value, additional, root, &full_key, deprecated, warnings);
+                } else if additional.is_none_or(|v| v.as_bool() == Some(false)) {
+                    let suggestion = closest(key, properties.keys().map(String::as_str))
+                        .map(|s| format!("{parent}{s}"));
+                    warnings.push(KeyWarning::Unknown(full_key, suggestion));
+                }
+            }
+        }
+        JsonValue::Array(array) => {
+            if let Some(items) = node.get("items") {
+                for (i, value) in array.iter().enumerate() {
+                    walk(
+                        value,
+                        items,
+                        root,
+                        &format!("{prefix}[{i}]"),
+                        deprecated,
+                        warnings,
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+fn collect_defaults<'a>(
+    node: &'a JsonValue,
+    root: &'a JsonValue,
+    prefix: &str,
+    defaults: &mut BTreeMap<String, &'a JsonValue>,
+) {
+    let Some(resolved) = resolve(node, root) else {
+        return;
+    };
+
+    // The default value beside `$ref` has higher priority than the one in the definition
+    if let Some(default) = node.get("default").or_else(|| resolved.get("default")) {
+        defaults.insert(prefix.to_owned(), default);
+        return;
+    }
+
+    if let Some(properties) = resolved.get("properties").and_then(JsonValue::as_object) {
+        for (key, property) in properties {
+            let key = if prefix.is_empty() {
+                key.clone()
+            } else {
+                format!("{prefix}.{key}")
+            };
+            collect_defaults(property, root, &key, defaults);
+        }
+    }
+}
+
+/// Resolve `$ref` in schema, only local references are supported
+fn resolve<'a>(node: &'a JsonValue, root: &'a JsonValue) -> Option<&'a JsonValue> {
+    match node.get("$ref").and_then(JsonValue::as_str) {
+        Some(reference) => reference
+            .strip_prefix('#')
+            .and_then(|pointer| root.pointer(pointer)),
+        None => Some(node),
+    }
+}
+
+/// Find the closest candidate of given key by edit distance
+fn closest<'a>(key: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    let threshold = key.chars().count().max(3) / 3;
+    candidates
+        .map(|candidate| (edit_distance(key, candidate), candidate))
+        .filter(|(distance, _)| *distance <= threshold)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cur = row[j + 1];
+            row[j + 1] = if ca == cb {
+                prev
+            } else {
+                1 + prev.min(cur).min(row[j])
+            };
+            prev = cur;
+        }
+    }
+
+    row[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn distance() {
+        assert_eq!(edit_distance("channel", "channel"), 0);
+        assert_eq!(edit_distance("chanel", "channel"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+
+        assert_eq!(
+            closest("chanel", ["channel", "api_url"].into_iter()),
+            Some("channel")
+        );
+        assert_eq!(closest("foo", ["channel", "api_url"].into_iter()), None);
+    }
+
+    #[test]
+    fn cli() {
+        assert!(check_keys(
+            &json!({
+                "core": { "channel": "Beta", "components": { "library": true } },
+                "resource": { "remote": { "passphrase": { "env": "PASS" } } },
+            }),
+            Schema::Cli,
+        )
+        .is_empty());
+
+        assert_eq!(
+            check_keys(
+                &json!({
+                    "core": { "chanel": "Beta", "components": { "libary": true } },
+                    "unknown": {},
+                }),
+                Schema::Cli,
+            ),
+            vec![
+                KeyWarning::Unknown("core.chanel".into(), Some("core.channel".into())),
+                KeyWarning::Unknown(
+                    "core.components.libary".into(),
+                    Some("core.components.library".into())
+                ),
+                KeyWarning::Unknown("unknown".into(), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn asst() {
+        assert_eq!(
+            check_keys(
+                &json!({
+                    "connection": {
+                        "type": "ADB",
+                        "device": "emulator-5554",
+                        "bundle_id": "com.YoStarEN.Arknights",
+                    },
+                    "defaults": { "fight": { "medicine": 0 } },
+                    "instance_options": { "touch_mod": "ADB" },
+                }),
+                Schema::Asst,
+            ),
+            vec![
+                KeyWarning::Deprecated("connection.bundle_id".into(), "connection.app"),
+                KeyWarning::Deprecated("connection.device".into(), "connection.address"),
+                KeyWarning::Deprecated("connection.type".into(), "connection.preset"),
+                KeyWarning::Unknown(
+                    "instance_options.touch_mod".into(),
+                    Some("instance_options.touch_mode".into())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn defaults() {
+        let defaults = Schema::Cli.defaults();
+        assert_eq!(defaults["core.channel"], "Stable");
+        assert_eq!(defaults["core.test_time"], 3);
+        assert_eq!(defaults["resource.remote.use_ssh_agent"], false);
+        assert!(!defaults.contains_key("core"));
+        assert!(!defaults.contains_key("resource.remote.ssh_key"));
+
+        let defaults = Schema::Asst.defaults();
+        assert_eq!(defaults["connection.preset"], "ADB");
+        assert!(!defaults.keys().any(|key| key.starts_with("instances")));
+    }
+
+    #[test]
+    fn example() {
+        let value = JsonValue::from_file("./config_examples/profiles/default.toml").unwrap();
+        assert!(check_keys(&value, Schema::Asst).is_empty());
+
+        let value = JsonValue::from_file("./config_examples/cli.toml").unwrap();
+        assert!(check_keys(&value, Schema::Cli).is_empty());
+    }
+}
@@ -0,0 +1,253 @@
+//! Weekly cap of orundum rewarded by annihilation
+//!
+//! Annihilation rewards at most 1800 orundum per week, and the cap is reset at 04:00 on Monday
+//! in server time. Orundum got from annihilation is recorded in the state directory for each
+//! client type and account, so that annihilation is skipped once the cap is reached this week,
+//! and a running annihilation task is stopped as soon as the cap is reached, instead of wasting
+//! sanity.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use anyhow::{Context, Result};
+use chrono::{Datelike, NaiveDate, NaiveDateTime, TimeDelta};
+use log::{info, warn};
+use maa_dirs::Ensure;
+use serde::{Deserialize, Serialize};
+
+use super::{ClientType, TimeOffset};
+use crate::{config::FromFile, dirs, value::MAAValue};
+
+/// Maximum orundum rewarded by annihilation per week
+const WEEKLY_CAP: i64 = 1800;
+
+/// Item id of orundum
+const ORUNDUM: &str = "4003";
+
+/// Client type and account of the running annihilation task, whose orundum is recorded
+static TRACKED: Mutex<Option<(ClientType, Option<String>)>> = Mutex::new(None);
+
+/// Whether the cap is reached by the running annihilation task
+static REACHED: AtomicBool = AtomicBool::new(false);
+
+/// Whether the stage is an annihilation stage, like `Annihilation` or `Chernobog@Annihilation`
+pub fn is_annihilation(stage: &str) -> bool {
+    stage.to_ascii_lowercase().contains("annihilation")
+}
+
+/// First day of the week of given server time, a week starts at 04:00 on Monday
+fn week_of(time: NaiveDateTime) -> NaiveDate {
+    let date = (time - TimeDelta::hours(4)).date();
+    date - TimeDelta::days(date.weekday().num_days_from_monday().into())
+}
+
+fn state_path() -> PathBuf {
+    dirs::state().join("annihilation.json")
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Serialize, Deserialize, Clone, Copy)]
+struct Record {
+    /// First day of the week when the orundum is got
+    week: NaiveDate,
+    orundum: i64,
+}
+
+/// Orundum got this week of each client type and account
+///
+/// Records are keyed by the client type, followed by the account if the task switches to one,
+/// like `Official` or `Official/123****4567`.
+#[derive(Serialize, Deserialize, Default)]
+struct State(BTreeMap<String, Record>);
+
+fn key(client_type: ClientType, account: Option<&str>) -> String {
+    match account {
+        Some(account) => format!("{}/{account}", client_type.to_str()),
+        None => client_type.to_str().to_owned(),
+    }
+}
+
+impl State {
+    fn load(path: &Path) -> Self {
+        if !path.exists() {
+            return Self::default();
+        }
+        Self::from_file(path).unwrap_or_else(|err| {
+            warn!("Failed to load annihilation state, ignored: {err:#}");
+            Self::default()
+        })
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            dir.ensure()?;
+        }
+        std::fs::write(path, serde_json::to_string_pretty(self)?)
+            .context("Failed to save annihilation state")
+    }
+
+    fn orundum(&self, key: &str, week: NaiveDate) -> i64 {
+        self.0
+            .get(key)
+            .filter(|record| record.week == week)
+            .map_or(0, |record| record.orundum)
+    }
+
+    /// Add orundum got in the given week, and return the total orundum of the week
+    fn add(&mut self, key: String, week: NaiveDate, orundum: i64) -> i64 {
+        let total = self.orundum(&key, week) + orundum;
+        self.0.insert(key, Record {
+            week,
+            orundum: total,
+        });
+        total
+    }
+}
+
+/// Skip the annihilation task if the weekly cap of the account is reached
+pub fn prepare(params: &mut MAAValue, client_type: ClientType, account: Option<&str>) {
+    let state = State::load(&state_path());
+    let week = week_of(TimeOffset::Client(client_type).naive_now());
+    prepare_with(params, &key(client_type, account), &state, week);
+}
+
+fn prepare_with(params: &mut MAAValue, key: &str, state: &State, week: NaiveDate) {
+    let Some(stage) = params.get("stage").and_then(MAAValue::as_str) else {
+        return;
+    };
+    if !is_annihilation(stage) {
+        return;
+    }
+
+    let orundum = state.orundum(key, week);
+    if orundum >= WEEKLY_CAP {
+        warn!("Weekly cap of annihilation ({orundum}/{WEEKLY_CAP}) is reached, skip {stage}");
+        params.insert("enable", false);
+        return;
+    }
+
+    info!("Orundum from annihilation this week: {orundum}/{WEEKLY_CAP}");
+}
+
+/// Track orundum of the annihilation task to run with given parameters and account
+///
+/// All tasks are prepared before any of them is run, so a task is tracked when it's started.
+pub fn track(params: &MAAValue, account: Option<&str>) {
+    let client_type = params
+        .get("client_type")
+        .and_then(MAAValue::as_str)
+        .and_then(|client_type| client_type.parse().ok())
+        .unwrap_or_default();
+    *TRACKED.lock().unwrap() = Some((client_type, account.map(str::to_owned)));
+    REACHED.store(false, Ordering::Relaxed);
+}
+
+/// Record orundum dropped by an annihilation stage
+///
+/// Drops are pairs of item id and quantity, and drops of other stages are ignored.
+pub fn record_drops<'a>(stage_id: &str, drops: impl IntoIterator<Item = (&'a str, i64)>) {
+    // Annihilation stages are named like `camp_01` and `camp_r_07`
+    if !stage_id.starts_with("camp_") {
+        return;
+    }
+    let Some((client_type, account)) = TRACKED.lock().unwrap().clone() else {
+        return;
+    };
+    let orundum: i64 = drops
+        .into_iter()
+        .filter(|(id, _)| *id == ORUNDUM)
+        .map(|(_, quantity)| quantity)
+        .sum();
+
+    let path = state_path();
+    let mut state = State::load(&path);
+    let week = week_of(TimeOffset::Client(client_type).naive_now());
+    let total = state.add(key(client_type, account.as_deref()), week, orundum);
+    if let Err(err) = state.save(&path) {
+        warn!("{err:#}");
+    }
+
+    if total >= WEEKLY_CAP {
+        info!("Weekly cap of annihilation ({total}/{WEEKLY_CAP}) is reached");
+        REACHED.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Whether the weekly cap is reached by the running annihilation task
+pub fn reached() -> bool {
+    REACHED.load(Ordering::Relaxed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object;
+
+    fn date(s: &str) -> NaiveDate {
+        s.parse().unwrap()
+    }
+
+    fn time(s: &str) -> NaiveDateTime {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn week() {
+        // 2024-01-01 is Monday
+        assert_eq!(week_of(time("2024-01-01T04:00:00")), date("2024-01-01"));
+        assert_eq!(week_of(time("2024-01-07T23:59:59")), date("2024-01-01"));
+        assert_eq!(week_of(time("2024-01-08T03:59:59")), date("2024-01-01"));
+        assert_eq!(week_of(time("2024-01-08T04:00:00")), date("2024-01-08"));
+        assert_eq!(week_of(time("2024-01-01T03:00:00")), date("2023-12-25"));
+    }
+
+    #[test]
+    fn annihilation_stage() {
+        assert!(is_annihilation("Annihilation"));
+        assert!(is_annihilation("Chernobog@Annihilation"));
+        assert!(!is_annihilation("1-7"));
+    }
+
+    #[test]
+    fn state() {
+        use ClientType::*;
+
+        let official = key(Official, None);
+        let mut state = State::default();
+        let week = date("2024-01-01");
+        assert_eq!(state.add(official.clone(), week, 400), 400);
+        assert_eq!(state.add(official.clone(), week, 1400), 1800);
+        assert_eq!(state.orundum(&official, week), 1800);
+        assert_eq!(state.orundum(&key(YoStarEN, None), week), 0);
+        // Reset next week
+        assert_eq!(state.orundum(&official, date("2024-01-08")), 0);
+        assert_eq!(state.add(official.clone(), date("2024-01-08"), 400), 400);
+
+        let mut params = object!("stage" => "Annihilation");
+        prepare_with(&mut params, &official, &state, week);
+        assert_eq!(params.get("enable"), None);
+
+        state.add(official.clone(), week, 1800);
+        let mut params = object!("stage" => "Annihilation");
+        prepare_with(&mut params, &official, &state, week);
+        assert_eq!(params.get("enable"), Some(&false.into()));
+
+        let mut params = object!("stage" => "1-7");
+        prepare_with(&mut params, &official, &state, week);
+        assert_eq!(params.get("enable"), None);
+
+        // Each account has its own cap
+        let alt = key(Official, Some("alt"));
+        assert_eq!(alt, "Official/alt");
+        assert_eq!(state.orundum(&alt, week), 0);
+        let mut params = object!("stage" => "Annihilation");
+        prepare_with(&mut params, &alt, &state, week);
+        assert_eq!(params.get("enable"), None);
+    }
+}
@@ -0,0 +1,135 @@
+//! Answers of prompts used in batch mode
+//!
+//! Inputs in task files are named by their parameter keys, which can be answered in advance,
+//! so that task files with inputs can be run unattended.
+
+use std::{path::Path, sync::OnceLock};
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+
+use crate::{
+    config::FromFile,
+    value::{MAAPrimate, MAAValue, Map},
+};
+
+static ANSWERS: OnceLock<Answers> = OnceLock::new();
+
+/// Load answers from given file and use them for all following tasks
+pub fn load(path: impl AsRef<Path>) -> Result<()> {
+    let path = path.as_ref();
+    let answers = Answers::from_file(path)
+        .with_context(|| format!("Failed to load answers from {}", path.display()))?;
+    // Answers are only loaded once in the main function, so it's never set before
+    let _ = ANSWERS.set(answers);
+    Ok(())
+}
+
+pub fn get() -> Option<&'static Answers> {
+    ANSWERS.get()
+}
+
+/// Answers of inputs by parameter key
+///
+/// Answers in the `tasks` table are only used for the task with the same name,
+/// and override the answers at the top level, which are used for all tasks.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Default)]
+pub struct Answers {
+    #[serde(default)]
+    tasks: Map<String, Map<String, MAAPrimate>>,
+    #[serde(flatten)]
+    params: Map<String, MAAPrimate>,
+}
+
+impl Answers {
+    fn get(&self, task: Option<&str>, key: &str) -> Option<&MAAPrimate> {
+        task.and_then(|task| self.tasks.get(task))
+            .and_then(|answers| answers.get(key))
+            .or_else(|| self.params.get(key))
+    }
+
+    /// Answer inputs of top-level parameters of a task
+    pub fn apply(&self, task: Option<&str>, params: &mut MAAValue) -> Result<()> {
+        let Some(params) = params.as_object_mut() else {
+            return Ok(());
+        };
+        for (key, value) in params.iter_mut() {
+            if let Some(answer) = self.get(task, key) {
+                if !value.answer(answer) {
+                    bail!(
+                        "Answer of `{key}` for task {} doesn't match the type of input",
+                        task.unwrap_or("without name"),
+                    );
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        object,
+        value::userinput::{BoolInput, Input, SelectD},
+    };
+
+    fn answers() -> Answers {
+        toml::from_str(
+            r#"
+            stage = "1-7"
+            medicine = 2
+
+            [tasks.Event]
+            stage = "XX-8"
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn deserialize() {
+        assert_eq!(answers(), Answers {
+            tasks: Map::from([(
+                "Event".to_owned(),
+                Map::from([("stage".to_owned(), MAAPrimate::String("XX-8".to_owned()))])
+            )]),
+            params: Map::from([
+                ("stage".to_owned(), MAAPrimate::String("1-7".to_owned())),
+                ("medicine".to_owned(), MAAPrimate::Int(2)),
+            ]),
+        });
+    }
+
+    #[test]
+    fn apply() {
+        let answers = answers();
+        let params = object!(
+            "stage" => SelectD::<String>::new(["1-7", "CE-6"], Some(1), None, true).unwrap(),
+            "medicine" => Input::<i32>::new(Some(0), None),
+            "stone" => Input::<i32>::new(Some(0), None),
+            "times" => 1,
+        );
+
+        let mut daily = params.clone();
+        answers.apply(Some("Daily"), &mut daily).unwrap();
+        assert_eq!(
+            daily,
+            object!(
+                "stage" => "1-7",
+                "medicine" => 2,
+                "stone" => Input::<i32>::new(Some(0), None),
+                "times" => 1,
+            )
+        );
+
+        let mut event = params.clone();
+        answers.apply(Some("Event"), &mut event).unwrap();
+        assert_eq!(event.get("stage").and_then(MAAValue::as_str), Some("XX-8"));
+
+        let mut mismatch = object!("medicine" => BoolInput::new(None, None));
+        assert!(answers.apply(None, &mut mismatch).is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use chrono::{DateTime, Datelike, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
 use serde::Deserialize;
 
 use super::client_type::ClientType;
@@ -51,10 +51,14 @@ pub enum Condition {
     ///
     /// If `start` is `None`, the task is active before `end`.
     /// If `end` is `None`, the task is active after `start`.
+    ///
+    /// The datetime can be a string like `"2023-08-01T16:00:00"` or a TOML datetime like
+    /// `2023-08-01T16:00:00`, and a date without time like `2023-08-21` means the start of the
+    /// day.
     DateTime {
-        #[serde(default)]
+        #[serde(default, deserialize_with = "deserialize_datetime")]
         start: Option<NaiveDateTime>,
-        #[serde(default)]
+        #[serde(default, deserialize_with = "deserialize_datetime")]
         end: Option<NaiveDateTime>,
         #[serde(default)]
         timezone: TimeOffset,
@@ -64,11 +68,13 @@ pub enum Condition {
         client: ClientType,
     },
     /// The task is active if all the sub-conditions are met
-    #[serde(alias = "Combined")]
+    #[serde(alias = "and", alias = "Combined")]
     And { conditions: Vec<Condition> },
     /// The task is active if any of the sub-conditions is met
+    #[serde(alias = "or")]
     Or { conditions: Vec<Condition> },
     /// The task is active if the inner condition is not met
+    #[serde(alias = "not")]
     Not { condition: Box<Condition> },
 }
 
@@ -84,7 +90,7 @@ pub enum TimeOffset {
 
 impl TimeOffset {
     /// Get the current date time in given time zone
-    fn naive_now(self) -> NaiveDateTime {
+    pub(super) fn naive_now(self) -> NaiveDateTime {
         self.date_time(Utc::now())
     }
 
@@ -101,6 +107,101 @@ impl TimeOffset {
     }
 }
 
+/// Server of the game, used to decide the time zone of conditions without explicit time zone
+///
+/// A server can be specified by its region, e.g. `"CN"`, or the UTC offset of the server
+/// in hours, e.g. `8`. The daily reset of the game is at 04:00 in server time, so the day
+/// starts at 04:00 instead of 00:00 in conditions when the server is specified.
+#[cfg_attr(test, derive(PartialEq, Debug))]
+#[derive(Clone, Copy, Deserialize)]
+#[serde(untagged)]
+pub enum Server {
+    Region(Region),
+    Offset(i8),
+}
+
+#[cfg_attr(test, derive(PartialEq, Debug))]
+#[derive(Clone, Copy, Deserialize)]
+pub enum Region {
+    CN,
+    US,
+    JP,
+    KR,
+    TW,
+}
+
+impl Region {
+    const fn client_type(self) -> ClientType {
+        match self {
+            Region::CN => ClientType::Official,
+            Region::US => ClientType::YoStarEN,
+            Region::JP => ClientType::YoStarJP,
+            Region::KR => ClientType::YoStarKR,
+            Region::TW => ClientType::Txwy,
+        }
+    }
+}
+
+impl Server {
+    pub fn time_offset(self) -> TimeOffset {
+        match self {
+            Server::Region(region) => TimeOffset::Client(region.client_type()),
+            // Shift the start of the day to 04:00 like the server time zone of clients
+            Server::Offset(offset) => TimeOffset::TimeZone(offset - 4),
+        }
+    }
+
+    /// Current wall clock time of the server, without shifting the start of the day
+    pub fn now(self) -> NaiveDateTime {
+        let offset = match self {
+            // Time zones of clients are shifted by 4 hours for the daily reset at 04:00
+            Server::Region(region) => region.client_type().server_time_zone() + 4,
+            Server::Offset(offset) => offset,
+        };
+        Utc::now()
+            .with_timezone(&tz_to_offset(offset))
+            .naive_local()
+    }
+}
+
+/// Deserialize a datetime from a string or a TOML datetime
+///
+/// The time zone of the datetime is decided by the condition, so the offset is not allowed.
+fn deserialize_datetime<'de, D>(deserializer: D) -> Result<Option<NaiveDateTime>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum DateTimeRepr {
+        String(String),
+        Toml(toml::value::Datetime),
+    }
+
+    let Some(repr) = Option::<DateTimeRepr>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+    let s = match repr {
+        DateTimeRepr::String(s) => s,
+        DateTimeRepr::Toml(datetime) => datetime.to_string(),
+    };
+
+    s.parse::<NaiveDateTime>()
+        .or_else(|_| {
+            s.parse::<NaiveDate>()
+                .map(|date| date.and_time(NaiveTime::MIN))
+        })
+        .map(Some)
+        .map_err(|_| {
+            D::Error::custom(format!(
+                "invalid datetime `{s}`, expected a datetime without offset like \
+                 `2023-08-01T16:00:00` or a date like `2023-08-01`"
+            ))
+        })
+}
+
 fn tz_to_offset(tz: i8) -> chrono::FixedOffset {
     chrono::FixedOffset::east_opt(tz as i32 * 3600).unwrap()
 }
@@ -168,6 +269,35 @@ impl Condition {
     }
 }
 
+impl Condition {
+    /// Use the given time zone for conditions without explicit time zone
+    ///
+    /// The `Local` time zone is treated as not specified, because it's the default value.
+    pub fn set_default_timezone(&mut self, default: TimeOffset) -> &mut Self {
+        use Condition::*;
+        match self {
+            Weekday { timezone, .. }
+            | DayMod { timezone, .. }
+            | Time { timezone, .. }
+            | DateTime { timezone, .. } => {
+                if matches!(timezone, TimeOffset::Local) {
+                    *timezone = default;
+                }
+            }
+            And { conditions } | Or { conditions } => {
+                for condition in conditions {
+                    condition.set_default_timezone(default);
+                }
+            }
+            Not { condition } => {
+                condition.set_default_timezone(default);
+            }
+            Always | OnSideStory { .. } => {}
+        }
+        self
+    }
+}
+
 fn time_in_range(time: NaiveTime, start: NaiveTime, end: NaiveTime) -> bool {
     if start <= end {
         start <= time && time < end
@@ -732,6 +862,25 @@ mod tests {
                     Token::MapEnd,
                 ],
             );
+
+            // TOML datetime and date
+            assert_eq!(
+                toml::from_str::<Condition>(
+                    "type = \"DateTime\"\nstart = 2021-08-01T16:00:00\nend = 2021-08-21\n"
+                )
+                .unwrap(),
+                Condition::DateTime {
+                    start: Some(naive_local_datetime(2021, 8, 1, 16, 0, 0)),
+                    end: Some(naive_local_datetime(2021, 8, 21, 0, 0, 0)),
+                    timezone: TimeOffset::Local,
+                }
+            );
+
+            // Offset is not allowed
+            assert!(toml::from_str::<Condition>(
+                "type = \"DateTime\"\nstart = 2021-08-01T16:00:00+08:00\n"
+            )
+            .is_err());
         }
 
         #[test]
@@ -852,4 +1001,117 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn server() {
+        assert_eq!(
+            serde_json::from_str::<Server>("\"CN\"")
+                .unwrap()
+                .time_offset(),
+            TimeOffset::Client(ClientType::Official)
+        );
+        assert_eq!(
+            serde_json::from_str::<Server>("\"US\"")
+                .unwrap()
+                .time_offset(),
+            TimeOffset::Client(ClientType::YoStarEN)
+        );
+        assert_eq!(
+            serde_json::from_str::<Server>("9").unwrap().time_offset(),
+            TimeOffset::TimeZone(5)
+        );
+        assert!(serde_json::from_str::<Server>("\"EU\"").is_err());
+
+        // Wall clock time of the server is not shifted by the daily reset
+        let offset_of = |server: Server| {
+            let offset = server.now() - Utc::now().naive_utc();
+            (offset.num_seconds() as f64 / 3600.0).round() as i64
+        };
+        assert_eq!(offset_of(Server::Region(Region::CN)), 8);
+        assert_eq!(offset_of(Server::Region(Region::US)), -7);
+        assert_eq!(offset_of(Server::Offset(9)), 9);
+    }
+
+    #[test]
+    fn default_timezone() {
+        let server = TimeOffset::Client(ClientType::YoStarJP);
+
+        let mut condition = Condition::And {
+            conditions: vec![
+                Condition::Weekday {
+                    weekdays: vec![Weekday::Mon],
+                    timezone: TimeOffset::Local,
+                },
+                Condition::Not {
+                    condition: Box::new(Condition::Time {
+                        start: None,
+                        end: None,
+                        timezone: TimeOffset::TimeZone(8),
+                    }),
+                },
+                Condition::Always,
+            ],
+        };
+        condition.set_default_timezone(server);
+
+        assert_eq!(condition, Condition::And {
+            conditions: vec![
+                Condition::Weekday {
+                    weekdays: vec![Weekday::Mon],
+                    timezone: server,
+                },
+                Condition::Not {
+                    condition: Box::new(Condition::Time {
+                        start: None,
+                        end: None,
+                        // Explicit time zone is not overridden
+                        timezone: TimeOffset::TimeZone(8),
+                    }),
+                },
+                Condition::Always,
+            ],
+        });
+    }
+
+    #[test]
+    fn logical_combination() {
+        // weekday is Tue or Fri, and time after 12:00
+        let condition: Condition = toml::from_str(
+            r#"
+            type = "and"
+            conditions = [
+                { type = "or", conditions = [
+                    { type = "Weekday", weekdays = ["Tue"] },
+                    { type = "Weekday", weekdays = ["Fri"] },
+                ] },
+                { type = "not", condition = { type = "Time", end = "12:00:00" } },
+            ]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(condition, Condition::And {
+            conditions: vec![
+                Condition::Or {
+                    conditions: vec![
+                        Condition::Weekday {
+                            weekdays: vec![Weekday::Tue],
+                            timezone: TimeOffset::Local,
+                        },
+                        Condition::Weekday {
+                            weekdays: vec![Weekday::Fri],
+                            timezone: TimeOffset::Local,
+                        },
+                    ],
+                },
+                Condition::Not {
+                    condition: Box::new(Condition::Time {
+                        start: None,
+                        end: Some(NaiveTime::from_hms_opt(12, 0, 0).unwrap()),
+                        timezone: TimeOffset::Local,
+                    }),
+                },
+            ],
+        });
+    }
 }
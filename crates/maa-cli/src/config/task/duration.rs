@@ -0,0 +1,126 @@
+//! Durations in task config, given by a number of seconds or a string like `5m` or `1h30m`
+
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// Parse a duration like `30s`, `5m` or `1h30m`, a number without unit is in seconds
+pub fn parse_duration(s: &str) -> Option<Duration> {
+    let s = s.trim();
+    if s.is_empty() {
+        return None;
+    }
+
+    let mut secs = 0;
+    let mut rest = s;
+    while !rest.is_empty() {
+        let end = rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return None;
+        }
+        let value: u64 = rest[..end].parse().ok()?;
+        rest = &rest[end..];
+
+        let unit = match rest.chars().next() {
+            Some('s') => 1,
+            Some('m') => 60,
+            Some('h') => 60 * 60,
+            Some('d') => 24 * 60 * 60,
+            // Seconds if no unit, which is only allowed at the end
+            None => 1,
+            Some(_) => return None,
+        };
+        rest = rest.get(1..).unwrap_or_default();
+        secs += value.checked_mul(unit)?;
+    }
+
+    Some(Duration::from_secs(secs))
+}
+
+/// Format a duration in the same format as parsed, e.g. `1h30m`
+pub fn format(duration: Duration) -> String {
+    let mut secs = duration.as_secs();
+    if secs == 0 {
+        return "0s".to_owned();
+    }
+
+    let mut s = String::new();
+    for (unit, unit_secs) in [('d', 24 * 60 * 60), ('h', 60 * 60), ('m', 60), ('s', 1)] {
+        if secs >= unit_secs {
+            s.push_str(&format!("{}{unit}", secs / unit_secs));
+            secs %= unit_secs;
+        }
+    }
+    s
+}
+
+/// Parse a duration from command line arguments
+pub fn parse_arg(s: &str) -> Result<Duration, String> {
+    parse_duration(s).ok_or_else(|| {
+        format!("invalid duration `{s}`, expected a duration like `30s`, `5m` or `1h30m`")
+    })
+}
+
+pub fn deserialize<'de, D>(deserializer: D) -> Result<Duration, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum DurationHelper {
+        Seconds(u64),
+        String(String),
+    }
+
+    match DurationHelper::deserialize(deserializer)? {
+        DurationHelper::Seconds(secs) => Ok(Duration::from_secs(secs)),
+        DurationHelper::String(s) => parse_arg(&s).map_err(D::Error::custom),
+    }
+}
+
+pub fn deserialize_option<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    deserialize(deserializer).map(Some)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(parse_duration("30"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("30s"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_duration("5m"), Some(Duration::from_secs(300)));
+        assert_eq!(parse_duration("1h30m"), Some(Duration::from_secs(5400)));
+        assert_eq!(parse_duration("1d"), Some(Duration::from_secs(86400)));
+        assert_eq!(parse_duration("1m30"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_duration(""), None);
+        assert_eq!(parse_duration("m"), None);
+        assert_eq!(parse_duration("5x"), None);
+        assert_eq!(parse_duration("-5m"), None);
+    }
+
+    #[test]
+    fn format_durations() {
+        assert_eq!(format(Duration::ZERO), "0s");
+        assert_eq!(format(Duration::from_secs(30)), "30s");
+        assert_eq!(format(Duration::from_secs(5400)), "1h30m");
+        assert_eq!(format(Duration::from_secs(90061)), "1d1h1m1s");
+        for s in ["5m", "1h30m", "2d3s"] {
+            assert_eq!(format(parse_duration(s).unwrap()), s);
+        }
+    }
+
+    #[test]
+    fn parse_args() {
+        assert_eq!(parse_arg("2h"), Ok(Duration::from_secs(7200)));
+        assert!(parse_arg("two hours").is_err());
+    }
+}
@@ -0,0 +1,213 @@
+//! Support of custom infrast plan files shared by MAA GUI
+//!
+//! A custom plan file contains a list of plans, and each plan can have some time periods
+//! in which it should be used. The file is read by MaaCore directly, but MaaCore doesn't
+//! select the plan by time periods, so maa-cli validates the file and selects the plan here.
+
+use std::path::PathBuf;
+
+use anyhow::{bail, Context, Result};
+use chrono::{NaiveTime, Timelike};
+use log::{info, warn};
+use serde::Deserialize;
+
+use crate::value::MAAValue;
+
+/// Mode of infrast task to use custom plan
+const CUSTOM_MODE: i32 = 10000;
+
+#[derive(Deserialize)]
+struct CustomPlan {
+    #[serde(default)]
+    title: Option<String>,
+    plans: Vec<Plan>,
+}
+
+#[derive(Deserialize)]
+struct Plan {
+    #[serde(default)]
+    name: Option<String>,
+    /// Time periods like `[["08:00", "15:59"]]`, both ends are included
+    #[serde(default)]
+    period: Vec<[String; 2]>,
+}
+
+impl Plan {
+    fn contains(&self, time: NaiveTime) -> Result<bool> {
+        // Periods are in minutes, so the end minute is included entirely
+        let time = NaiveTime::from_hms_opt(time.hour(), time.minute(), 0).unwrap();
+        for [start, end] in &self.period {
+            let start = parse_time(start)?;
+            let end = parse_time(end)?;
+            let matched = if start <= end {
+                start <= time && time <= end
+            } else {
+                start <= time || time <= end
+            };
+            if matched {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+fn parse_time(s: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(s, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(s, "%H:%M:%S"))
+        .with_context(|| format!("Invalid time `{s}` in period of custom infrast plan"))
+}
+
+impl CustomPlan {
+    /// Index of the first plan whose periods contain the given time
+    fn select(&self, time: NaiveTime) -> Result<Option<usize>> {
+        for (index, plan) in self.plans.iter().enumerate() {
+            if plan.contains(time)? {
+                return Ok(Some(index));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Validate the custom plan file of infrast task and complete the parameters
+///
+/// If the `filename` is given, the `mode` will be set to custom mode if not specified,
+/// and the `plan_index` will be selected by the periods containing `now` if not specified.
+pub fn prepare(params: &mut MAAValue, now: NaiveTime) -> Result<()> {
+    let Some(path) = params
+        .get("filename")
+        .and_then(MAAValue::as_str)
+        .map(PathBuf::from)
+    else {
+        return Ok(());
+    };
+    if !path.exists() {
+        warn!("Custom infrast plan file {} not found", path.display());
+        return Ok(());
+    }
+
+    let plan: CustomPlan = serde_json::from_reader(std::fs::File::open(&path)?)
+        .with_context(|| format!("Invalid custom infrast plan file {}", path.display()))?;
+    if plan.plans.is_empty() {
+        bail!(
+            "No plan found in custom infrast plan file {}",
+            path.display()
+        );
+    }
+    if let Some(title) = plan.title.as_deref() {
+        info!("Using custom infrast plan: {title}");
+    }
+
+    if params.get("mode").is_none() {
+        params.insert("mode", CUSTOM_MODE);
+    }
+
+    match params.get("plan_index").and_then(MAAValue::as_int) {
+        Some(index) if index < 0 || index as usize >= plan.plans.len() => bail!(
+            "Plan index {index} out of range, there are only {} plans in {}",
+            plan.plans.len(),
+            path.display()
+        ),
+        Some(_) => {}
+        None => match plan.select(now)? {
+            Some(index) => {
+                info!(
+                    "Selected infrast plan {index}{} by time period",
+                    plan.plans[index]
+                        .name
+                        .as_deref()
+                        .map(|name| format!(" ({name})"))
+                        .unwrap_or_default()
+                );
+                params.insert("plan_index", index as i32);
+            }
+            None => warn!("No infrast plan matches current time, the first plan will be used"),
+        },
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object;
+
+    fn time(h: u32, m: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(h, m, 0).unwrap()
+    }
+
+    #[test]
+    fn select() {
+        let plan: CustomPlan = serde_json::from_str(
+            r#"{
+                "title": "243",
+                "plans": [
+                    { "name": "day", "period": [["08:00", "15:59"]] },
+                    { "name": "night", "period": [["16:00", "23:59"], ["00:00", "07:59"]] },
+                    { "name": "no period" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        assert_eq!(plan.select(time(8, 0)).unwrap(), Some(0));
+        assert_eq!(plan.select(time(15, 59)).unwrap(), Some(0));
+        assert_eq!(
+            plan.select(NaiveTime::from_hms_opt(15, 59, 30).unwrap())
+                .unwrap(),
+            Some(0)
+        );
+        assert_eq!(plan.select(time(16, 0)).unwrap(), Some(1));
+        assert_eq!(plan.select(time(3, 0)).unwrap(), Some(1));
+
+        let plan: CustomPlan = serde_json::from_str(
+            r#"{ "plans": [{ "period": [["22:00", "06:00"]] }, { "period": [["06:00", "23:00"]] }] }"#,
+        )
+        .unwrap();
+        assert_eq!(plan.select(time(23, 30)).unwrap(), Some(0));
+        assert_eq!(plan.select(time(12, 0)).unwrap(), Some(1));
+
+        let plan: CustomPlan =
+            serde_json::from_str(r#"{ "plans": [{ "period": [["8 am", "16 pm"]] }] }"#).unwrap();
+        assert!(plan.select(time(12, 0)).is_err());
+    }
+
+    #[test]
+    fn prepare_params() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("plan.json");
+        std::fs::write(
+            &file,
+            r#"{ "plans": [{ "period": [["00:00", "23:59"]] }, { "period": [] }] }"#,
+        )
+        .unwrap();
+        let filename = file.to_str().unwrap();
+
+        let mut params = object!("filename" => filename);
+        prepare(&mut params, time(12, 0)).unwrap();
+        assert_eq!(
+            params,
+            object!("filename" => filename, "mode" => 10000, "plan_index" => 0)
+        );
+
+        let mut params = object!("filename" => filename, "mode" => 0, "plan_index" => 1);
+        prepare(&mut params, time(12, 0)).unwrap();
+        assert_eq!(
+            params,
+            object!("filename" => filename, "mode" => 0, "plan_index" => 1)
+        );
+
+        let mut params = object!("filename" => filename, "plan_index" => 2);
+        assert!(prepare(&mut params, time(12, 0)).is_err());
+
+        std::fs::write(&file, r#"{ "plans": [] }"#).unwrap();
+        assert!(prepare(&mut object!("filename" => filename), time(12, 0)).is_err());
+
+        // Missing file is left to MaaCore
+        let mut params = object!("filename" => "/not/exist/plan.json");
+        prepare(&mut params, time(12, 0)).unwrap();
+        assert_eq!(params, object!("filename" => "/not/exist/plan.json"));
+    }
+}
@@ -1,14 +1,36 @@
+pub mod annihilation;
+
 mod client_type;
 pub use client_type::ClientType;
 
 mod condition;
-use std::path::PathBuf;
+use std::{path::PathBuf, time::Duration};
+
+pub mod answers;
+
+pub mod duration;
+
+mod infrast;
+
+mod reclamation;
+
+mod retry;
+pub use retry::RetryPolicy;
+
+mod rotation;
 
+mod stage;
+
+mod week;
 use anyhow::Context;
+use chrono::{Datelike, Weekday};
 use condition::Condition;
-pub use condition::{remainder_of_day_mod, TimeOffset};
+pub use condition::{remainder_of_day_mod, Server, TimeOffset};
 use maa_sys::TaskType;
+use rotation::Rotation;
 use serde::Deserialize;
+pub use stage::{choose_stage, is_open_now};
+use week::Week;
 
 use crate::{dirs, object, value::MAAValue};
 
@@ -54,6 +76,21 @@ pub enum Strategy {
     Merge,
 }
 
+/// What to do if a task failed
+#[cfg_attr(test, derive(Debug))]
+#[derive(Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FailurePolicy {
+    /// Continue to run the following tasks
+    #[default]
+    Continue,
+    /// Stop running any following task
+    Abort,
+    /// Retry the task by its retry policy, or once if no retry policy is set,
+    /// and then continue to run the following tasks
+    Retry,
+}
+
 #[cfg_attr(test, derive(PartialEq, Debug))]
 #[derive(Deserialize)]
 #[serde(deny_unknown_fields)]
@@ -64,10 +101,47 @@ pub struct Task {
     task_type: TaskType,
     #[serde(default)]
     params: MAAValue,
+    /// Parameters rotated by weekday, merged before the parameters of variants
+    #[serde(default)]
+    rotation: Option<Rotation>,
     #[serde(default)]
     strategy: Strategy,
     #[serde(default)]
     variants: Vec<TaskVariant>,
+    /// Names of tasks run before this task, which must succeed to run this task
+    #[serde(default)]
+    depends_on: Vec<String>,
+    #[serde(default)]
+    on_failure: FailurePolicy,
+    /// How to retry the task if failed, override the default one of the task config
+    #[serde(default)]
+    retry: Option<RetryPolicy>,
+    /// Maximum time to run the task, including retries
+    ///
+    /// Only this task fails if exceeded, and the run continues by its `on_failure` policy.
+    #[serde(default, deserialize_with = "duration::deserialize_option")]
+    timeout: Option<Duration>,
+    /// Account to run the task with
+    ///
+    /// The account is switched automatically before the first task of each group of
+    /// consecutive tasks with the same account, and tasks without account are run with
+    /// the account switched to by previous tasks.
+    #[serde(default)]
+    account: Option<String>,
+    /// Order to run the task, tasks with smaller order run first
+    ///
+    /// Tasks with the same order run in the order they are defined, default to 0.
+    #[serde(default)]
+    order: Option<i32>,
+    /// Parameters merged after all others, which are passed to MaaCore as is
+    ///
+    /// They are not resolved or checked by maa-cli, so that new parameters of MaaCore
+    /// can be used before they are supported.
+    #[serde(default, rename = "override")]
+    overrides: Option<MAAValue>,
+    /// Type passed to MaaCore for tasks of types unknown to maa-cli
+    #[serde(skip)]
+    core_type: Option<String>,
 }
 
 // Constructor for Task
@@ -78,7 +152,24 @@ impl Task {
             task_type,
             strategy: Strategy::default(),
             params,
+            rotation: None,
             variants: Vec::new(),
+            depends_on: Vec::new(),
+            on_failure: FailurePolicy::default(),
+            retry: None,
+            timeout: None,
+            account: None,
+            order: None,
+            overrides: None,
+            core_type: None,
+        }
+    }
+
+    /// A task of given type unknown to maa-cli, whose parameters are passed to MaaCore as is
+    pub fn raw(core_type: String, params: MAAValue) -> Self {
+        Self {
+            core_type: Some(core_type),
+            ..Self::new(TaskType::Custom, params)
         }
     }
 
@@ -87,6 +178,12 @@ impl Task {
         self
     }
 
+    #[cfg(test)]
+    pub fn with_account(mut self, account: &str) -> Self {
+        self.account = Some(account.to_owned());
+        self
+    }
+
     #[cfg(test)]
     pub fn with_strategy(mut self, strategy: Strategy) -> Self {
         self.strategy = strategy;
@@ -109,6 +206,11 @@ impl Task {
         self.task_type
     }
 
+    /// Whether the task switches to an account, which is only done by StartUp tasks
+    fn switches_account(&self) -> bool {
+        self.account.is_some() || self.params.get("account_name").is_some()
+    }
+
     pub fn is_active(&self) -> bool {
         if self.variants.is_empty() {
             return true;
@@ -121,8 +223,28 @@ impl Task {
         false
     }
 
+    /// Indices of variants whose parameters are used, `None` if the task is inactive
+    fn active_variants(&self) -> Option<Vec<usize>> {
+        if !self.is_active() {
+            return None;
+        }
+        let active = self
+            .variants
+            .iter()
+            .enumerate()
+            .filter(|(_, variant)| variant.is_active())
+            .map(|(index, _)| index);
+        Some(match self.strategy {
+            Strategy::First => active.take(1).collect(),
+            Strategy::Merge => active.collect(),
+        })
+    }
+
     pub fn params(&self) -> MAAValue {
         let mut params = self.params.clone();
+        if let Some(rotated) = self.rotation.as_ref().and_then(Rotation::params) {
+            params.merge_mut(rotated);
+        }
         for variant in &self.variants {
             if variant.is_active() {
                 params.merge_mut(variant.params());
@@ -135,46 +257,403 @@ impl Task {
     }
 }
 
+/// Default parameters of tasks, which are usually defined in profile
+///
+/// The parameters are merged into every task with the same type,
+/// and the parameters defined in task will override the default ones.
+#[cfg_attr(test, derive(PartialEq, Debug))]
+#[derive(Default, Clone)]
+pub struct TaskDefaults(Vec<(TaskType, MAAValue)>);
+
+impl<'de> Deserialize<'de> for TaskDefaults {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use std::collections::BTreeMap;
+
+        let map = BTreeMap::<String, MAAValue>::deserialize(deserializer)?;
+        let mut defaults = Vec::with_capacity(map.len());
+        for (key, value) in map {
+            let task_type = key.parse().map_err(serde::de::Error::custom)?;
+            defaults.push((task_type, value));
+        }
+
+        Ok(Self(defaults))
+    }
+}
+
+impl TaskDefaults {
+    /// Get default parameters of given task type
+    pub fn get(&self, task_type: TaskType) -> Option<&MAAValue> {
+        self.0.iter().find(|(t, _)| *t == task_type).map(|(_, v)| v)
+    }
+
+    /// Insert parameters of given task type, merged into the existing ones if any
+    pub fn insert(&mut self, task_type: TaskType, params: MAAValue) -> &mut Self {
+        match self.0.iter_mut().find(|(t, _)| *t == task_type) {
+            Some((_, existing)) => existing.merge_mut(&params),
+            None => self.0.push((task_type, params)),
+        }
+        self
+    }
+}
+
 #[derive(Deserialize)]
 pub struct TaskConfig {
+    /// Description of the task file, shown by `maa list`
+    #[serde(default)]
+    description: Option<String>,
     client_type: Option<ClientType>,
+    /// Client type used when it's not set in the config or any task,
+    /// usually comes from the profile
+    #[serde(skip)]
+    default_client_type: Option<ClientType>,
+    /// Default parameters of tasks, usually comes from the profile
+    #[serde(skip)]
+    task_defaults: TaskDefaults,
+    /// Parameters override the ones defined in tasks, usually comes from command line
+    #[serde(skip)]
+    task_overrides: TaskDefaults,
+    /// Server whose time is used by conditions and plans, usually comes from the profile
+    #[serde(skip)]
+    server: Option<Server>,
+    /// Account to switch to before running tasks, used by the `StartUp` task
+    account: Option<String>,
+    /// Default retry policy of all tasks
+    #[serde(default)]
+    retry: Option<RetryPolicy>,
+    /// Maximum time to run all tasks
+    #[serde(default, deserialize_with = "duration::deserialize_option")]
+    timeout: Option<Duration>,
     startup: Option<bool>,
     closedown: Option<bool>,
+    /// Tasks to run on each day of a week, all tasks run every day if not set
+    #[serde(default)]
+    week: Option<Week>,
     tasks: Vec<Task>,
 }
 
 impl TaskConfig {
     pub fn new() -> Self {
         Self {
+            description: None,
             client_type: None,
+            default_client_type: None,
+            task_defaults: TaskDefaults::default(),
+            task_overrides: TaskDefaults::default(),
+            server: None,
+            account: None,
+            retry: None,
+            timeout: None,
             startup: None,
             closedown: None,
+            week: None,
             tasks: Vec::new(),
         }
     }
 
+    pub fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+
+    /// Names of tasks and indices of their variants used now
+    ///
+    /// The indices are `None` if the task is inactive, and empty if the task has no variant.
+    pub fn active_variants(&self) -> Vec<(&str, Option<Vec<usize>>)> {
+        let weekday = self.weekday();
+        self.tasks
+            .iter()
+            .map(|task| {
+                let name = task.name.as_deref().unwrap_or(task.task_type.to_str());
+                let variants = if self.is_planned(task, weekday) {
+                    task.active_variants()
+                } else {
+                    None
+                };
+                (name, variants)
+            })
+            .collect()
+    }
+
+    /// Day of the week used to select tasks of the weekly plan, `None` if there is no plan
+    ///
+    /// The day is decided by the time of the server like conditions, so a day starts at 04:00
+    /// if the server is set.
+    pub fn weekday(&self) -> Option<Weekday> {
+        self.week.as_ref()?;
+        let timezone = self.server.map_or(TimeOffset::Local, Server::time_offset);
+        Some(timezone.naive_now().weekday())
+    }
+
+    /// Whether the task is planned to run on given day by the weekly plan
+    fn is_planned(&self, task: &Task, weekday: Option<Weekday>) -> bool {
+        match (&self.week, weekday) {
+            (Some(week), Some(weekday)) => week.runs_on(task.name.as_deref(), weekday),
+            _ => true,
+        }
+    }
+
     pub fn push(&mut self, task: Task) {
         self.tasks.push(task);
     }
 
+    /// Append tasks of another task file, used to run multiple task files in one run
+    ///
+    /// Settings like `client_type` and `account` are taken from the first task file setting them.
+    /// The game should not be restarted or closed between task files, so a StartUp task is
+    /// dropped if there is one before, unless it switches the account or has a name, which may
+    /// be referred by other tasks, and only the last CloseDown task is kept and moved to the end.
+    ///
+    /// Tasks are referred by names across task files, so a name can't be used in both of them.
+    pub fn merge(&mut self, other: TaskConfig) -> anyhow::Result<&mut Self> {
+        if let Some(name) = other
+            .tasks
+            .iter()
+            .filter_map(|task| task.name.as_deref())
+            .find(|&name| self.tasks.iter().any(|t| t.name.as_deref() == Some(name)))
+        {
+            anyhow::bail!("Task `{name}` is defined in more than one task file");
+        }
+
+        self.description = self.description.take().or(other.description);
+        match (self.client_type, other.client_type) {
+            (Some(client_type), Some(other)) if client_type != other => log::warn!(
+                "Client type {other} of the merged task file is ignored, {client_type} is used"
+            ),
+            _ => {}
+        }
+        self.client_type = self.client_type.or(other.client_type);
+        self.account = self.account.take().or(other.account);
+        self.retry = self.retry.take().or(other.retry);
+        self.timeout = self.timeout.or(other.timeout);
+        self.startup = self.startup.or(other.startup);
+        self.closedown = self.closedown.or(other.closedown);
+        match (&mut self.week, other.week) {
+            (Some(week), Some(other)) => week.merge(other),
+            (week @ None, other) => *week = other,
+            (Some(_), None) => {}
+        }
+
+        let mut has_startup = false;
+        let mut closedown = None;
+        let mut tasks = Vec::with_capacity(self.tasks.len() + other.tasks.len());
+        for task in std::mem::take(&mut self.tasks)
+            .into_iter()
+            .chain(other.tasks)
+        {
+            match task.task_type {
+                TaskType::StartUp
+                    if has_startup && !task.switches_account() && task.name.is_none() =>
+                {
+                    log::debug!(
+                        "Skip duplicate StartUp task {}",
+                        task.name.as_deref().unwrap_or(task.task_type.to_str())
+                    );
+                }
+                TaskType::StartUp => {
+                    has_startup = true;
+                    tasks.push(task);
+                }
+                TaskType::CloseDown => closedown = Some(task),
+                _ => tasks.push(task),
+            }
+        }
+        tasks.extend(closedown);
+        self.tasks = tasks;
+
+        Ok(self)
+    }
+
+    pub fn set_default_client_type(&mut self, client_type: ClientType) -> &mut Self {
+        self.default_client_type = Some(client_type);
+        self
+    }
+
+    pub fn set_task_defaults(&mut self, task_defaults: TaskDefaults) -> &mut Self {
+        self.task_defaults = task_defaults;
+        self
+    }
+
+    pub fn set_task_overrides(&mut self, task_overrides: TaskDefaults) -> &mut Self {
+        self.task_overrides = task_overrides;
+        self
+    }
+
+    pub fn set_startup(&mut self, startup: bool) -> &mut Self {
+        self.startup = Some(startup);
+        self
+    }
+
+    pub fn set_closedown(&mut self, closedown: bool) -> &mut Self {
+        self.closedown = Some(closedown);
+        self
+    }
+
+    pub fn set_timeout(&mut self, timeout: Duration) -> &mut Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Switch to given account, which overrides the accounts of the task file and its StartUp
+    /// tasks, so it should be set after the overrides of parameters
+    pub fn set_account(&mut self, account: String) -> &mut Self {
+        self.task_overrides.insert(
+            TaskType::StartUp,
+            object!("account_name" => account.as_str()),
+        );
+        self.account = Some(account);
+        self
+    }
+
+    /// Use the time zone of given server for conditions without explicit time zone
+    pub fn set_server(&mut self, server: Server) -> &mut Self {
+        self.server = Some(server);
+        let timezone = server.time_offset();
+        for task in self.tasks.iter_mut() {
+            if let Some(rotation) = task.rotation.as_mut() {
+                rotation.set_default_timezone(timezone);
+            }
+            for variant in task.variants.iter_mut() {
+                variant.condition.set_default_timezone(timezone);
+            }
+        }
+        self
+    }
+
+    /// Indices of tasks in the order to run them, sorted by their orders stably
+    fn run_order(&self) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..self.tasks.len()).collect();
+        indices.sort_by_key(|&i| self.tasks[i].order.unwrap_or(0));
+        indices
+    }
+
+    /// Resolve dependencies of the task at `index` to indices of initialized tasks
+    ///
+    /// `before` are indices of tasks run before the task. Dependencies must run before
+    /// the task, and inactive ones are ignored, because they will never fail.
+    fn resolve_dependencies(
+        &self,
+        index: usize,
+        before: &[usize],
+        initialized: &[InitializedTask],
+    ) -> anyhow::Result<Vec<usize>> {
+        let task = &self.tasks[index];
+        let mut depends_on = Vec::new();
+        for name in &task.depends_on {
+            let run_before = before
+                .iter()
+                .any(|&i| self.tasks[i].name.as_deref() == Some(name.as_str()));
+            if !run_before {
+                anyhow::bail!(
+                    "Task {} depends on `{name}`, which is not a task run before it",
+                    task.name
+                        .as_deref()
+                        .unwrap_or_else(|| task.task_type.to_str()),
+                );
+            }
+            depends_on.extend(
+                initialized
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, t)| t.name.as_deref() == Some(name.as_str()))
+                    .map(|(i, _)| i),
+            );
+        }
+        Ok(depends_on)
+    }
+
     pub fn init(&self) -> anyhow::Result<InitializedTaskConfig> {
+        if let Some(week) = &self.week {
+            for name in week.names() {
+                if !self.tasks.iter().any(|t| t.name.as_deref() == Some(name)) {
+                    anyhow::bail!("Task `{name}` in the weekly plan is not defined");
+                }
+            }
+        }
+        let weekday = self.weekday();
+
         let mut startup = self.startup;
         let mut closedown = self.closedown;
         let mut client_type = self.client_type;
 
         let mut tasks: Vec<InitializedTask> = Vec::new();
-        let mut prepend_startup = startup.unwrap_or(false);
+        // Raw parameters of each task, merged after all parameters are resolved
+        let mut raw_params: Vec<Option<MAAValue>> = Vec::new();
+        // Account each task is run with, which is switched to by previous StartUp tasks
+        let mut accounts: Vec<Option<String>> = Vec::new();
         let mut append_closedown = closedown.unwrap_or(false);
+        // Account switched to by the last startup task and the index of it
+        let mut current_account = self.account.clone();
+        let mut switch_index = None;
 
         use TaskType::*;
 
-        for task in self.tasks.iter() {
-            if !task.is_active() {
+        let run_order = self.run_order();
+
+        // Account can only be switched by the startup task, so a startup task is prepended
+        // if the game should be started or the account should be switched but no one is planned
+        let prepend_startup = (startup.unwrap_or(false) || self.account.is_some())
+            && !run_order.iter().any(|&index| {
+                let task = &self.tasks[index];
+                task.core_type.is_none()
+                    && task.task_type() == StartUp
+                    && task.is_active()
+                    && self.is_planned(task, weekday)
+            });
+        // Whether the game is started by a previous startup task
+        let mut has_startup = prepend_startup;
+        if prepend_startup {
+            let mut params = object!("start_game_enabled" => startup.unwrap_or(false));
+            params.maybe_insert("account_name", self.account.as_deref());
+            let mut startup = InitializedTask::new(StartUp, params);
+            startup.retry = self.retry.unwrap_or_default();
+            tasks.push(startup);
+            raw_params.push(None);
+            accounts.push(current_account.clone());
+            switch_index = Some(0);
+        }
+
+        for (position, &index) in run_order.iter().enumerate() {
+            let task = &self.tasks[index];
+            if !task.is_active() || !self.is_planned(task, weekday) {
                 continue;
             }
 
+            let mut depends_on =
+                self.resolve_dependencies(index, &run_order[..position], &tasks)?;
+
             let task_type = task.task_type();
-            let mut params = task.params().init()?;
+            // Tasks unknown to maa-cli are not resolved, and only run after their dependencies
+            if let Some(core_type) = &task.core_type {
+                let mut inited_task = InitializedTask::new(task_type, task.params().init()?);
+                inited_task.core_type = Some(core_type.clone());
+                inited_task.depends_on = depends_on;
+                tasks.push(inited_task);
+                raw_params.push(None);
+                accounts.push(current_account.clone());
+                continue;
+            }
+            let mut params = match self.task_defaults.get(task_type) {
+                Some(default) => {
+                    let mut params = default.clone();
+                    params.merge_mut(&task.params());
+                    params
+                }
+                None => task.params(),
+            };
+            if let Some(overrides) = self.task_overrides.get(task_type) {
+                params.merge_mut(overrides);
+            }
+            if let Some(answers) = answers::get() {
+                answers.apply(task.name.as_deref(), &mut params)?;
+            }
+            let mut params = params.init().with_context(|| {
+                format!(
+                    "Failed to initialize parameters of task {}",
+                    task.name.as_deref().unwrap_or_else(|| task_type.to_str())
+                )
+            })?;
 
             // If startup task is not enabled, enable it automatically
             match task_type {
@@ -193,7 +672,17 @@ impl TaskConfig {
                         _ => {}
                     }
 
-                    prepend_startup = false;
+                    if let Some(account) = task.account.as_deref().or(self.account.as_deref()) {
+                        if params.get("account_name").is_none() {
+                            params.insert("account_name", account);
+                        }
+                    }
+                    if let Some(account) = params.get("account_name").and_then(MAAValue::as_str) {
+                        current_account = Some(account.to_owned());
+                        switch_index = Some(tasks.len());
+                    }
+
+                    has_startup = true;
                 }
                 CloseDown => {
                     match (params.get_or("enable", true), closedown) {
@@ -211,6 +700,37 @@ impl TaskConfig {
                     append_closedown = false;
                 }
                 _ => {
+                    // Switch account before a group of tasks with another account,
+                    // and skip the group if switching failed
+                    if let Some(account) = task.account.as_deref() {
+                        if current_account.as_deref() != Some(account) {
+                            current_account = Some(account.to_owned());
+                            if prepend_startup && tasks.len() == 1 {
+                                // The prepended startup task switches to the account of the first
+                                // group
+                                tasks[0].params.insert("account_name", account);
+                                accounts[0] = current_account.clone();
+                            } else {
+                                // The game is only started by the first startup task
+                                let start_game = startup.unwrap_or(false) && !has_startup;
+                                let mut switch = InitializedTask::new(
+                                    StartUp,
+                                    object!(
+                                        "start_game_enabled" => start_game,
+                                        "account_name" => account,
+                                    ),
+                                );
+                                switch.retry = self.retry.unwrap_or_default();
+                                switch_index = Some(tasks.len());
+                                tasks.push(switch);
+                                raw_params.push(None);
+                                accounts.push(current_account.clone());
+                                has_startup = true;
+                            }
+                        }
+                        depends_on.extend(switch_index);
+                    }
+
                     // For any task that has a filename parameter
                     // and the filename parameter is not an absolute path,
                     // it will be treated as a relative path to the config directory
@@ -222,6 +742,36 @@ impl TaskConfig {
                             *v = path.to_str().context("Invilid UTF-8")?.into();
                         }
                     }
+
+                    if matches!(task_type, Infrast) {
+                        // Plans are selected by the time of the server like conditions,
+                        // or the local time like MAA GUI if the server is not specified
+                        let now = self
+                            .server
+                            .map_or_else(|| chrono::Local::now().naive_local(), Server::now);
+                        infrast::prepare(&mut params, now.time())?;
+                    }
+
+                    if matches!(task_type, Reclamation) {
+                        reclamation::prepare(&mut params)?;
+                    }
+
+                    // Custom tasks run tasks of the pipeline in resource by their names
+                    if matches!(task_type, Custom) {
+                        let names = params
+                            .get_mut("task_names")
+                            .context("task_names of Custom task is required")?;
+                        if let Some(name) = names.as_str().map(str::to_owned) {
+                            *names = vec![name].into();
+                        }
+                        if !matches!(names, MAAValue::Array(names)
+                            if !names.is_empty() && names.iter().all(|n| n.as_str().is_some()))
+                        {
+                            anyhow::bail!(
+                                "task_names of Custom task must be a string or a list of strings"
+                            );
+                        }
+                    }
                 }
             }
 
@@ -253,13 +803,24 @@ impl TaskConfig {
                 inited_task = inited_task.with_name(name.to_owned());
             }
 
-            tasks.push(inited_task)
+            inited_task.depends_on = depends_on;
+            inited_task.on_failure = task.on_failure;
+            inited_task.retry = match (task.retry.or(self.retry), task.on_failure) {
+                (None, FailurePolicy::Retry) => RetryPolicy::times(1),
+                (retry, _) => retry.unwrap_or_default(),
+            };
+
+            inited_task.timeout = task.timeout;
+
+            tasks.push(inited_task);
+            raw_params.push(task.overrides.clone());
+            accounts.push(current_account.clone());
         }
 
-        let client_type = client_type.unwrap_or_default();
+        let client_type = client_type.or(self.default_client_type).unwrap_or_default();
 
         // If client type is set in any task, set client type in all tasks automatically
-        for task in tasks.iter_mut() {
+        for ((task, raw_params), account) in tasks.iter_mut().zip(raw_params).zip(accounts) {
             let task_type = task.task_type;
             let params = &mut task.params;
 
@@ -267,34 +828,57 @@ impl TaskConfig {
             if matches!(task_type, StartUp | Fight | CloseDown) {
                 params.insert("client_type", client_type.to_str());
             }
-        }
 
-        if prepend_startup {
-            tasks.insert(
-                0,
-                InitializedTask::new(
-                    TaskType::StartUp,
-                    object!(
-                        "start_game_enabled" => true,
-                        "client_type" => client_type.to_string(),
-                    ),
-                ),
-            );
+            // Stages given by nicknames are resolved to stage codes first,
+            // resource stages are only open on some days in the server time zone
+            // and annihilation is skipped once the weekly cap is reached
+            if matches!(task_type, Fight) {
+                let enabled = params.get_or("enable", true);
+                if enabled {
+                    stage::resolve(params, client_type)?;
+                }
+                // Fallback stages are removed from disabled tasks too
+                let timezone = self
+                    .server
+                    .map_or(TimeOffset::Client(client_type), Server::time_offset);
+                stage::prepare(params, timezone, client_type);
+                if enabled {
+                    if params.get_or("enable", true) {
+                        annihilation::prepare(params, client_type, account.as_deref());
+                    }
+                    stage::check_series(params).with_context(|| {
+                        format!("Invalid parameters of task {}", task.name_or_default())
+                    })?;
+                }
+            }
+
+            if let Some(raw_params) = raw_params {
+                let raw_params = raw_params.init().with_context(|| {
+                    format!(
+                        "Failed to initialize override of task {}",
+                        task.name_or_default()
+                    )
+                })?;
+                task.params.merge_mut(&raw_params);
+            }
         }
 
         if append_closedown {
-            tasks.push(InitializedTask::new(
+            let mut closedown = InitializedTask::new(
                 TaskType::CloseDown,
                 object!(
                     "client_type" => client_type.to_string(),
                 ),
-            ));
+            );
+            closedown.retry = self.retry.unwrap_or_default();
+            tasks.push(closedown);
         }
 
         Ok(InitializedTaskConfig {
             client_type,
             start_app: startup.unwrap_or(false),
             close_app: closedown.unwrap_or(false),
+            timeout: self.timeout,
             tasks,
         })
     }
@@ -305,14 +889,70 @@ pub struct InitializedTaskConfig {
     pub client_type: ClientType,
     pub start_app: bool,
     pub close_app: bool,
+    /// Maximum time to run all tasks
+    pub timeout: Option<Duration>,
     pub tasks: Vec<InitializedTask>,
 }
 
+impl InitializedTaskConfig {
+    /// Display resolved tasks and the exact parameters passed to MaaCore
+    pub fn display(&self, mut f: impl std::io::Write) -> std::io::Result<()> {
+        writeln!(f, "Client type: {}", self.client_type)?;
+        if let Some(timeout) = self.timeout {
+            writeln!(f, "Timeout: {}", duration::format(timeout))?;
+        }
+        for (index, task) in self.tasks.iter().enumerate() {
+            write!(f, "{}. ", index + 1)?;
+            match &task.name {
+                Some(name) => writeln!(f, "{name} ({})", task.type_name())?,
+                None => writeln!(f, "{}", task.type_name())?,
+            }
+            if !task.depends_on.is_empty() {
+                let depends_on: Vec<_> = task
+                    .depends_on
+                    .iter()
+                    .map(|&i| self.tasks[i].name_or_default())
+                    .collect();
+                writeln!(f, "   Depends on: {}", depends_on.join(", "))?;
+            }
+            match task.on_failure {
+                FailurePolicy::Continue | FailurePolicy::Retry => {}
+                FailurePolicy::Abort => writeln!(f, "   On failure: abort")?,
+            }
+            if task.retry.attempts > 0 {
+                write!(f, "   Retry: {} times", task.retry.attempts)?;
+                if !task.retry.backoff.is_zero() {
+                    write!(f, " after {}", duration::format(task.retry.backoff))?;
+                }
+                writeln!(f)?;
+            }
+            if let Some(timeout) = task.timeout {
+                writeln!(f, "   Timeout: {}", duration::format(timeout))?;
+            }
+            let params =
+                serde_json::to_string_pretty(&task.params).map_err(std::io::Error::other)?;
+            for line in params.lines() {
+                writeln!(f, "   {line}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
 #[cfg_attr(test, derive(PartialEq, Debug))]
 pub struct InitializedTask {
     pub name: Option<String>,
     pub task_type: TaskType,
     pub params: MAAValue,
+    /// Indices of tasks this task depends on, which are always before this task
+    pub depends_on: Vec<usize>,
+    /// What to do if the task still failed after retrying
+    pub on_failure: FailurePolicy,
+    pub retry: RetryPolicy,
+    /// Maximum time to run the task
+    pub timeout: Option<Duration>,
+    /// Type passed to MaaCore instead of `task_type`, see [`Task::raw`]
+    pub core_type: Option<String>,
 }
 
 impl InitializedTask {
@@ -321,6 +961,11 @@ impl InitializedTask {
             name: None,
             task_type,
             params,
+            depends_on: Vec::new(),
+            on_failure: FailurePolicy::Continue,
+            retry: RetryPolicy::times(0),
+            timeout: None,
+            core_type: None,
         }
     }
 
@@ -329,11 +974,16 @@ impl InitializedTask {
         self
     }
 
-    pub fn name_or_default(&self) -> &str {
-        self.name
+    /// Type of the task passed to MaaCore
+    pub fn type_name(&self) -> &str {
+        self.core_type
             .as_deref()
             .unwrap_or_else(|| self.task_type.to_str())
     }
+
+    pub fn name_or_default(&self) -> &str {
+        self.name.as_deref().unwrap_or_else(|| self.type_name())
+    }
 }
 
 #[cfg(test)]
@@ -378,6 +1028,52 @@ mod tests {
             test_with_veriants(vec![never_active(), never_active()], false);
         }
 
+        #[test]
+        fn active_variants() {
+            let variant = |active: bool| TaskVariant {
+                condition: if active {
+                    Condition::Always
+                } else {
+                    Condition::Not {
+                        condition: Box::new(Condition::Always),
+                    }
+                },
+                params: MAAValue::default(),
+            };
+            let task = |strategy, variants| {
+                Task::new(TaskType::Fight, object!())
+                    .with_strategy(strategy)
+                    .with_variants(variants)
+            };
+
+            assert_eq!(
+                task(Strategy::First, vec![]).active_variants(),
+                Some(vec![])
+            );
+            assert_eq!(
+                task(Strategy::First, vec![
+                    variant(false),
+                    variant(true),
+                    variant(true)
+                ])
+                .active_variants(),
+                Some(vec![1])
+            );
+            assert_eq!(
+                task(Strategy::Merge, vec![
+                    variant(true),
+                    variant(false),
+                    variant(true)
+                ])
+                .active_variants(),
+                Some(vec![0, 2])
+            );
+            assert_eq!(
+                task(Strategy::Merge, vec![variant(false)]).active_variants(),
+                None
+            );
+        }
+
         #[test]
         fn get_type() {
             assert_eq!(
@@ -649,6 +1345,23 @@ mod tests {
                 .unwrap();
                 assert_eq!(task_config.tasks, example_task_config().tasks)
             }
+
+            #[test]
+            fn task_defaults() {
+                let defaults: TaskDefaults = toml::from_str(
+                    r#"
+                    fight.medicine = 0
+                    [Recruit]
+                    times = 4
+                    "#,
+                )
+                .unwrap();
+                assert_eq!(defaults.get(Fight), Some(&object!("medicine" => 0)));
+                assert_eq!(defaults.get(Recruit), Some(&object!("times" => 4)));
+                assert_eq!(defaults.get(Mall), None);
+
+                assert!(toml::from_str::<TaskDefaults>("unknown.times = 4").is_err());
+            }
         }
 
         #[test]
@@ -662,6 +1375,7 @@ mod tests {
                     startup: None,
                     closedown: None,
                     tasks: vec![],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -669,10 +1383,139 @@ mod tests {
                     client_type: Official,
                     start_app: false,
                     close_app: false,
+                    timeout: None,
                     tasks: vec![],
                 }
             );
 
+            // Client type in profile is used if not set in config or tasks
+            assert_eq!(
+                TaskConfig {
+                    default_client_type: Some(YoStarEN),
+                    tasks: vec![Task::new(CloseDown, object!())],
+                    ..TaskConfig::new()
+                }
+                .init()
+                .unwrap(),
+                InitializedTaskConfig {
+                    client_type: YoStarEN,
+                    start_app: false,
+                    close_app: true,
+                    timeout: None,
+                    tasks: vec![InitializedTask::new(
+                        CloseDown,
+                        object!("client_type" => "YoStarEN")
+                    )],
+                }
+            );
+
+            // Client type in task has higher priority than client type in profile
+            assert_eq!(
+                TaskConfig {
+                    default_client_type: Some(YoStarEN),
+                    tasks: vec![Task::new(CloseDown, object!("client_type" => "YoStarJP"))],
+                    ..TaskConfig::new()
+                }
+                .init()
+                .unwrap(),
+                InitializedTaskConfig {
+                    client_type: YoStarJP,
+                    start_app: false,
+                    close_app: true,
+                    timeout: None,
+                    tasks: vec![InitializedTask::new(
+                        CloseDown,
+                        object!("client_type" => "YoStarJP")
+                    )],
+                }
+            );
+
+            // Default parameters in profile are overridden by parameters in task
+            assert_eq!(
+                TaskConfig {
+                    task_defaults: TaskDefaults(vec![(
+                        Fight,
+                        object!("medicine" => 0, "stone" => 0)
+                    )]),
+                    tasks: vec![
+                        Task::new(Fight, object!("stage" => "1-7", "medicine" => 1)),
+                        Task::new(Mall, object!()),
+                    ],
+                    ..TaskConfig::new()
+                }
+                .init()
+                .unwrap(),
+                InitializedTaskConfig {
+                    client_type: Official,
+                    start_app: false,
+                    close_app: false,
+                    timeout: None,
+                    tasks: vec![
+                        InitializedTask::new(
+                            Fight,
+                            object!(
+                                "stage" => "1-7",
+                                "medicine" => 1,
+                                "stone" => 0,
+                                "client_type" => "Official",
+                            )
+                        ),
+                        InitializedTask::new(Mall, object!()),
+                    ],
+                }
+            );
+
+            // Overrides from command line take precedence over both defaults and tasks
+            let mut task_overrides = TaskDefaults::default();
+            task_overrides.insert(Fight, object!("medicine" => 2));
+            assert_eq!(
+                TaskConfig {
+                    task_defaults: TaskDefaults(vec![(
+                        Fight,
+                        object!("medicine" => 0, "stone" => 0)
+                    )]),
+                    task_overrides,
+                    tasks: vec![
+                        Task::new(Fight, object!("stage" => "1-7", "medicine" => 1)),
+                        Task::new(Mall, object!()),
+                    ],
+                    ..TaskConfig::new()
+                }
+                .init()
+                .unwrap(),
+                InitializedTaskConfig {
+                    client_type: Official,
+                    start_app: false,
+                    close_app: false,
+                    timeout: None,
+                    tasks: vec![
+                        InitializedTask::new(
+                            Fight,
+                            object!(
+                                "stage" => "1-7",
+                                "medicine" => 2,
+                                "stone" => 0,
+                                "client_type" => "Official",
+                            )
+                        ),
+                        InitializedTask::new(Mall, object!()),
+                    ],
+                }
+            );
+
+            // Blacklist of the task overrides the one in defaults like other parameters
+            let mut task_config = TaskConfig::new();
+            task_config.set_task_defaults(TaskDefaults(vec![(
+                Mall,
+                object!("blacklist" => ["家具零件", "碳"]),
+            )]));
+            task_config.push(Task::new(Mall, object!("blacklist" => ["碳", "加急许可"])));
+            task_config.push(Task::new(Mall, object!()));
+            assert_eq!(task_config.init().unwrap().tasks, vec![
+                InitializedTask::new(Mall, object!("blacklist" => ["碳", "加急许可"])),
+                InitializedTask::new(Mall, object!("blacklist" => ["家具零件", "碳"])),
+            ]);
+
             // No active tasks will be skipped
             assert_eq!(
                 TaskConfig {
@@ -686,6 +1529,7 @@ mod tests {
                             },
                             params: object!(),
                         }]),],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -693,6 +1537,7 @@ mod tests {
                     client_type: Official,
                     start_app: false,
                     close_app: false,
+                    timeout: None,
                     tasks: vec![],
                 }
             );
@@ -710,6 +1555,7 @@ mod tests {
                         )
                     )
                     .with_name(String::from("StartUp"))],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -717,6 +1563,7 @@ mod tests {
                     client_type: YoStarEN,
                     start_app: true,
                     close_app: false,
+                    timeout: None,
                     tasks: vec![InitializedTask::new(
                         StartUp,
                         object!(
@@ -740,6 +1587,7 @@ mod tests {
                             "client_type" => "YoStarEN",
                         )
                     )],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -747,6 +1595,7 @@ mod tests {
                     client_type: YoStarEN,
                     start_app: false,
                     close_app: false,
+                    timeout: None,
                     tasks: vec![InitializedTask::new(
                         StartUp,
                         object!(
@@ -764,6 +1613,7 @@ mod tests {
                     startup: None,
                     closedown: None,
                     tasks: vec![Task::new(CloseDown, object!("client_type" => "YoStarEN"))],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -771,6 +1621,7 @@ mod tests {
                     client_type: YoStarEN,
                     start_app: false,
                     close_app: true,
+                    timeout: None,
                     tasks: vec![InitializedTask::new(
                         CloseDown,
                         object!("client_type" => "YoStarEN")
@@ -790,6 +1641,7 @@ mod tests {
                             "client_type" => "YoStarEN",
                         )
                     )],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -797,6 +1649,7 @@ mod tests {
                     client_type: YoStarEN,
                     start_app: false,
                     close_app: false,
+                    timeout: None,
                     tasks: vec![InitializedTask::new(
                         CloseDown,
                         object!(
@@ -813,6 +1666,7 @@ mod tests {
                     startup: None,
                     closedown: None,
                     tasks: vec![Task::new(CloseDown, object!())],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -820,6 +1674,7 @@ mod tests {
                     client_type: Official,
                     start_app: false,
                     close_app: true,
+                    timeout: None,
                     tasks: vec![InitializedTask::new(
                         CloseDown,
                         object!("client_type" => "Official")
@@ -833,6 +1688,7 @@ mod tests {
                     startup: None,
                     closedown: None,
                     tasks: vec![Task::new(Fight, object!("client_type" => "YoStarEN"))],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -840,6 +1696,7 @@ mod tests {
                     client_type: YoStarEN,
                     start_app: false,
                     close_app: false,
+                    timeout: None,
                     tasks: vec![InitializedTask::new(
                         Fight,
                         object!("client_type" => "YoStarEN")
@@ -863,6 +1720,7 @@ mod tests {
                         Task::new(Fight, object!("stage" => "1-7")),
                         Task::new(CloseDown, object!()),
                     ],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -870,6 +1728,7 @@ mod tests {
                     client_type: Official,
                     start_app: true,
                     close_app: true,
+                    timeout: None,
                     tasks: vec![
                         InitializedTask::new(
                             StartUp,
@@ -900,6 +1759,7 @@ mod tests {
                         Task::new(Fight, object!("stage" => "1-7")),
                         Task::new(CloseDown, object!("enable" => false)),
                     ],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -907,6 +1767,7 @@ mod tests {
                     client_type: Official,
                     start_app: true,
                     close_app: true,
+                    timeout: None,
                     tasks: vec![
                         InitializedTask::new(
                             StartUp,
@@ -940,6 +1801,7 @@ mod tests {
                     startup: Some(true),
                     closedown: Some(true),
                     tasks: vec![Task::new(Fight, object!("stage" => "1-7"))],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -947,6 +1809,7 @@ mod tests {
                     client_type: Official,
                     start_app: true,
                     close_app: true,
+                    timeout: None,
                     tasks: vec![
                         InitializedTask::new(
                             StartUp,
@@ -973,6 +1836,7 @@ mod tests {
                     startup: Some(true),
                     closedown: Some(true),
                     tasks: vec![Task::new(Fight, object!("stage" => "1-7"))],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -980,6 +1844,7 @@ mod tests {
                     client_type: YoStarEN,
                     start_app: true,
                     close_app: true,
+                    timeout: None,
                     tasks: vec![
                         InitializedTask::new(
                             StartUp,
@@ -1000,6 +1865,191 @@ mod tests {
                 }
             );
 
+            // Account is switched by startup task, which is prepended if absent
+            assert_eq!(
+                TaskConfig {
+                    account: Some("alt".to_owned()),
+                    tasks: vec![Task::new(Fight, object!("stage" => "1-7"))],
+                    ..TaskConfig::new()
+                }
+                .init()
+                .unwrap(),
+                InitializedTaskConfig {
+                    client_type: Official,
+                    start_app: false,
+                    close_app: false,
+                    timeout: None,
+                    tasks: vec![
+                        InitializedTask::new(
+                            StartUp,
+                            object!(
+                                "start_game_enabled" => false,
+                                "client_type" => "Official",
+                                "account_name" => "alt",
+                            )
+                        ),
+                        InitializedTask::new(
+                            Fight,
+                            object!(
+                                "stage" => "1-7",
+                                "client_type" => "Official",
+                            )
+                        ),
+                    ]
+                }
+            );
+
+            // Account in startup task takes precedence
+            assert_eq!(
+                TaskConfig {
+                    account: Some("alt".to_owned()),
+                    startup: Some(true),
+                    tasks: vec![
+                        Task::new(StartUp, object!("account_name" => "main")),
+                        Task::new(Mall, object!()),
+                    ],
+                    ..TaskConfig::new()
+                }
+                .init()
+                .unwrap(),
+                InitializedTaskConfig {
+                    client_type: Official,
+                    start_app: true,
+                    close_app: false,
+                    timeout: None,
+                    tasks: vec![
+                        InitializedTask::new(
+                            StartUp,
+                            object!(
+                                "enable" => true,
+                                "start_game_enabled" => true,
+                                "client_type" => "Official",
+                                "account_name" => "main",
+                            )
+                        ),
+                        InitializedTask::new(Mall, object!()),
+                    ]
+                }
+            );
+
+            // Account is switched before each group of tasks with another account
+            let mut task_config = TaskConfig::new();
+            task_config.push(Task::new(Fight, object!("stage" => "1-7")).with_account("main"));
+            task_config.push(Task::new(Mall, object!()).with_account("main"));
+            task_config.push(Task::new(Fight, object!("stage" => "CE-6")).with_account("alt"));
+            task_config.push(Task::new(Award, object!()));
+            let tasks = task_config.init().unwrap().tasks;
+            assert_eq!(
+                tasks
+                    .iter()
+                    .map(|task| (
+                        task.task_type,
+                        task.params.get("account_name").and_then(MAAValue::as_str),
+                        task.depends_on.as_slice(),
+                    ))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (StartUp, Some("main"), [].as_slice()),
+                    (Fight, None, &[0]),
+                    (Mall, None, &[0]),
+                    (StartUp, Some("alt"), &[]),
+                    (Fight, None, &[3]),
+                    (Award, None, &[]),
+                ]
+            );
+
+            // The game is started only once, by the startup task switching to the first account
+            let mut task_config = TaskConfig::new();
+            task_config.set_startup(true);
+            task_config.push(Task::new(Fight, object!("stage" => "1-7")).with_account("main"));
+            task_config.push(Task::new(Mall, object!()).with_account("main"));
+            task_config.push(Task::new(Fight, object!("stage" => "CE-6")).with_account("alt"));
+            task_config.push(Task::new(Award, object!()));
+            let tasks = task_config.init().unwrap().tasks;
+            assert_eq!(
+                tasks
+                    .iter()
+                    .map(|task| (
+                        task.task_type,
+                        task.params.get("account_name").and_then(MAAValue::as_str),
+                        task.params
+                            .get("start_game_enabled")
+                            .and_then(MAAValue::as_bool),
+                        task.depends_on.as_slice(),
+                    ))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (StartUp, Some("main"), Some(true), [].as_slice()),
+                    (Fight, None, None, &[0]),
+                    (Mall, None, None, &[0]),
+                    (StartUp, Some("alt"), Some(false), &[]),
+                    (Fight, None, None, &[3]),
+                    (Award, None, None, &[]),
+                ]
+            );
+
+            // Tasks with the account of the task file depend on the prepended startup task
+            let task_config: TaskConfig = toml::from_str(
+                r#"
+                account = "main"
+
+                [[tasks]]
+                type = "Fight"
+                account = "main"
+
+                [[tasks]]
+                type = "Mall"
+
+                [[tasks]]
+                type = "Fight"
+                account = "alt"
+                "#,
+            )
+            .unwrap();
+            let tasks = task_config.init().unwrap().tasks;
+            assert_eq!(
+                tasks
+                    .iter()
+                    .map(|task| (
+                        task.task_type,
+                        task.params.get("account_name").and_then(MAAValue::as_str),
+                        task.depends_on.as_slice(),
+                    ))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (StartUp, Some("main"), [].as_slice()),
+                    (Fight, None, &[0]),
+                    (Mall, None, &[]),
+                    (StartUp, Some("alt"), &[]),
+                    (Fight, None, &[3]),
+                ]
+            );
+
+            // Account given by command line takes precedence over the ones of the task file
+            let mut task_config: TaskConfig = toml::from_str(
+                r#"
+                account = "alt"
+
+                [[tasks]]
+                type = "StartUp"
+                params = { account_name = "main" }
+
+                [[tasks]]
+                type = "Mall"
+                "#,
+            )
+            .unwrap();
+            task_config.set_account("cli".to_owned());
+            let tasks = task_config.init().unwrap().tasks;
+            assert_eq!(tasks.len(), 2);
+            assert_eq!(
+                tasks[0]
+                    .params
+                    .get("account_name")
+                    .and_then(MAAValue::as_str),
+                Some("cli")
+            );
+
             // Conflicting client type
             assert_eq!(
                 TaskConfig {
@@ -1010,6 +2060,7 @@ mod tests {
                         Task::new(StartUp, object!("client_type" => "YoStarEN")),
                         Task::new(CloseDown, object!("client_type" => "YoStarJP")),
                     ],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -1017,6 +2068,7 @@ mod tests {
                     client_type: Official,
                     start_app: false,
                     close_app: true,
+                    timeout: None,
                     tasks: vec![
                         InitializedTask::new(StartUp, object!("client_type" => "Official")),
                         InitializedTask::new(CloseDown, object!("client_type" => "Official")),
@@ -1035,6 +2087,7 @@ mod tests {
                         Task::new(Infrast, object!("filename" => "daily.json")),
                         Task::new(Infrast, object!("filename" => "/tmp/daily.json")),
                     ],
+                    ..TaskConfig::new()
                 }
                 .init()
                 .unwrap(),
@@ -1042,6 +2095,7 @@ mod tests {
                     client_type: Official,
                     start_app: false,
                     close_app: false,
+                    timeout: None,
                     tasks: vec![
                         InitializedTask::new(
                             Infrast,
@@ -1057,6 +2111,529 @@ mod tests {
             );
         }
 
+        #[test]
+        fn dependencies() {
+            let task_config: TaskConfig = toml::from_str(
+                r#"
+                startup = true
+
+                [[tasks]]
+                name = "Start"
+                type = "StartUp"
+                on_failure = "abort"
+
+                [[tasks]]
+                name = "Never"
+                type = "Award"
+                [[tasks.variants]]
+                condition = { type = "Weekday", weekdays = [] }
+
+                [[tasks]]
+                name = "Fight"
+                type = "Fight"
+                depends_on = ["Start", "Never"]
+                on_failure = "retry"
+                retry = 2
+
+                [[tasks]]
+                type = "Mall"
+                depends_on = ["Start"]
+                on_failure = "retry"
+
+                [[tasks]]
+                type = "Infrast"
+                retry = 2
+                "#,
+            )
+            .unwrap();
+
+            let tasks = task_config.init().unwrap().tasks;
+            assert_eq!(
+                tasks
+                    .iter()
+                    .map(|t| (t.depends_on.clone(), t.on_failure, t.retry.attempts))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (vec![], FailurePolicy::Abort, 0),
+                    (vec![0], FailurePolicy::Retry, 2),
+                    (vec![0], FailurePolicy::Retry, 1),
+                    (vec![], FailurePolicy::Continue, 2),
+                ]
+            );
+
+            // Indices are shifted by the prepended startup task
+            let mut task_config: TaskConfig = toml::from_str(
+                r#"
+                [[tasks]]
+                name = "Fight"
+                type = "Fight"
+
+                [[tasks]]
+                type = "Mall"
+                depends_on = ["Fight"]
+                "#,
+            )
+            .unwrap();
+            assert_eq!(task_config.init().unwrap().tasks[1].depends_on, vec![0]);
+            task_config.set_account("account".to_owned());
+            let tasks = task_config.init().unwrap().tasks;
+            assert_eq!(tasks[0].task_type, StartUp);
+            assert_eq!(tasks[2].depends_on, vec![1]);
+
+            // Dependencies must run before
+            let task_config: TaskConfig = toml::from_str(
+                r#"
+                [[tasks]]
+                type = "Mall"
+                depends_on = ["Fight"]
+
+                [[tasks]]
+                name = "Fight"
+                type = "Fight"
+                "#,
+            )
+            .unwrap();
+            assert!(task_config.init().is_err());
+
+            let task_config: TaskConfig = toml::from_str(
+                r#"
+                [[tasks]]
+                type = "Mall"
+                depends_on = ["Unknown"]
+                "#,
+            )
+            .unwrap();
+            assert!(task_config.init().is_err());
+
+            assert!(toml::from_str::<TaskConfig>(
+                r#"
+                [[tasks]]
+                type = "Mall"
+                on_failure = "ignore"
+                "#,
+            )
+            .is_err());
+        }
+
+        #[test]
+        fn retry() {
+            use std::time::Duration;
+
+            let task_config: TaskConfig = toml::from_str(
+                r#"
+                startup = true
+                retry = { attempts = 2, backoff = "1m" }
+
+                [[tasks]]
+                type = "Fight"
+                retry = { attempts = 3, backoff = "5m" }
+
+                [[tasks]]
+                type = "Mall"
+                on_failure = "retry"
+                retry = 1
+
+                [[tasks]]
+                type = "Award"
+                on_failure = "retry"
+
+                [[tasks]]
+                type = "Infrast"
+                on_failure = "abort"
+                "#,
+            )
+            .unwrap();
+
+            let default = RetryPolicy {
+                attempts: 2,
+                backoff: Duration::from_secs(60),
+            };
+            let tasks = task_config.init().unwrap().tasks;
+            assert_eq!(
+                tasks
+                    .iter()
+                    .map(|t| (t.task_type, t.on_failure, t.retry))
+                    .collect::<Vec<_>>(),
+                vec![
+                    (StartUp, FailurePolicy::Continue, default),
+                    (Fight, FailurePolicy::Continue, RetryPolicy {
+                        attempts: 3,
+                        backoff: Duration::from_secs(300),
+                    }),
+                    (Mall, FailurePolicy::Retry, RetryPolicy::times(1)),
+                    (Award, FailurePolicy::Retry, default),
+                    (Infrast, FailurePolicy::Abort, default),
+                ]
+            );
+
+            // No retry by default
+            let task_config: TaskConfig = toml::from_str(
+                r#"
+                [[tasks]]
+                type = "Fight"
+                "#,
+            )
+            .unwrap();
+            assert_eq!(
+                task_config.init().unwrap().tasks[0].retry,
+                RetryPolicy::times(0)
+            );
+        }
+
+        #[test]
+        fn merge() {
+            let mut task_config: TaskConfig = toml::from_str(
+                r#"
+                client_type = "YoStarEN"
+
+                [[tasks]]
+                type = "StartUp"
+
+                [[tasks]]
+                type = "Fight"
+
+                [[tasks]]
+                type = "CloseDown"
+                "#,
+            )
+            .unwrap();
+            let other: TaskConfig = toml::from_str(
+                r#"
+                client_type = "Official"
+                account = "123****4567"
+
+                [[tasks]]
+                type = "StartUp"
+
+                [[tasks]]
+                type = "Mall"
+
+                [[tasks]]
+                type = "StartUp"
+                params = { account_name = "another" }
+
+                [[tasks]]
+                type = "Award"
+                "#,
+            )
+            .unwrap();
+
+            task_config.merge(other).unwrap();
+            assert_eq!(task_config.client_type, Some(ClientType::YoStarEN));
+            assert_eq!(task_config.account.as_deref(), Some("123****4567"));
+            assert_eq!(
+                task_config
+                    .tasks
+                    .iter()
+                    .map(|task| task.task_type)
+                    .collect::<Vec<_>>(),
+                [StartUp, Fight, Mall, StartUp, Award, CloseDown]
+            );
+        }
+
+        #[test]
+        fn merge_named() {
+            let mut task_config: TaskConfig = toml::from_str(
+                r#"
+                [[tasks]]
+                type = "StartUp"
+
+                [[tasks]]
+                name = "Fight"
+                type = "Fight"
+                "#,
+            )
+            .unwrap();
+            let other: TaskConfig = toml::from_str(
+                r#"
+                [[tasks]]
+                name = "Wake"
+                type = "StartUp"
+
+                [[tasks]]
+                type = "Mall"
+                depends_on = ["Wake"]
+                "#,
+            )
+            .unwrap();
+
+            // Named StartUp tasks are kept, so that they can be depended on
+            task_config.merge(other).unwrap();
+            assert_eq!(
+                task_config
+                    .tasks
+                    .iter()
+                    .map(|task| task.task_type)
+                    .collect::<Vec<_>>(),
+                [StartUp, Fight, StartUp, Mall]
+            );
+            assert!(task_config.init().is_ok());
+
+            let other: TaskConfig = toml::from_str(
+                r#"
+                [[tasks]]
+                name = "Fight"
+                type = "Fight"
+                "#,
+            )
+            .unwrap();
+            assert!(task_config.merge(other).is_err());
+        }
+
+        #[test]
+        fn order() {
+            let task_config: TaskConfig = toml::from_str(
+                r#"
+                [[tasks]]
+                name = "Mall"
+                type = "Mall"
+                order = 1
+                depends_on = ["Fight"]
+
+                [[tasks]]
+                type = "Award"
+                order = 2
+
+                [[tasks]]
+                type = "StartUp"
+                order = -1
+
+                [[tasks]]
+                name = "Fight"
+                type = "Fight"
+
+                [[tasks]]
+                type = "Recruit"
+                "#,
+            )
+            .unwrap();
+
+            let tasks = task_config.init().unwrap().tasks;
+            assert_eq!(
+                tasks.iter().map(|task| task.task_type).collect::<Vec<_>>(),
+                [StartUp, Fight, Recruit, Mall, Award]
+            );
+            // Dependencies are resolved to indices in the order to run
+            assert_eq!(tasks[3].depends_on, vec![1]);
+
+            // Dependencies must run before, even if they are defined before
+            let task_config: TaskConfig = toml::from_str(
+                r#"
+                [[tasks]]
+                name = "Fight"
+                type = "Fight"
+                order = 1
+
+                [[tasks]]
+                type = "Mall"
+                depends_on = ["Fight"]
+                "#,
+            )
+            .unwrap();
+            assert!(task_config.init().is_err());
+        }
+
+        #[test]
+        fn week() {
+            let today = chrono::Local::now().weekday();
+            let day = |weekday: Weekday| weekday.to_string().to_lowercase();
+            let task_config: TaskConfig = toml::from_str(&format!(
+                r#"
+                [week]
+                {} = ["Fight", "Mall"]
+                {} = ["Roguelike", "Mall"]
+
+                [[tasks]]
+                type = "StartUp"
+
+                [[tasks]]
+                name = "Fight"
+                type = "Fight"
+
+                [[tasks]]
+                name = "Roguelike"
+                type = "Roguelike"
+
+                [[tasks]]
+                name = "Mall"
+                type = "Mall"
+                "#,
+                day(today),
+                day(today.succ()),
+            ))
+            .unwrap();
+
+            assert_eq!(task_config.weekday(), Some(today));
+            assert_eq!(
+                task_config
+                    .init()
+                    .unwrap()
+                    .tasks
+                    .iter()
+                    .map(|task| task.task_type)
+                    .collect::<Vec<_>>(),
+                [StartUp, Fight, Mall]
+            );
+            assert_eq!(TaskConfig::new().weekday(), None);
+
+            // Tasks in the plan must be defined
+            let task_config: TaskConfig = toml::from_str(
+                r#"
+                week = { mon = ["Unknown"] }
+
+                [[tasks]]
+                type = "Fight"
+                "#,
+            )
+            .unwrap();
+            assert!(task_config.init().is_err());
+        }
+
+        #[test]
+        fn raw_params() {
+            let task_config: TaskConfig = toml::from_str(
+                r#"
+                [[tasks]]
+                type = "Fight"
+                params = { stage = "狗粮", times = 2 }
+                override = { stage = "狗粮", new_param = [1, 2] }
+
+                [[tasks]]
+                type = "Custom"
+                params = { task_names = "SomeTask" }
+                "#,
+            )
+            .unwrap();
+            let tasks = task_config.init().unwrap().tasks;
+            // Raw parameters are merged after stage names are resolved
+            assert_eq!(
+                tasks[0].params,
+                object!(
+                    "stage" => "狗粮",
+                    "times" => 2,
+                    "new_param" => [1, 2],
+                    "client_type" => "Official",
+                )
+            );
+            assert_eq!(tasks[1].params, object!("task_names" => ["SomeTask"]));
+
+            for params in ["{}", "{ task_names = [] }", "{ task_names = [1] }"] {
+                let task_config: TaskConfig = toml::from_str(&format!(
+                    r#"
+                    [[tasks]]
+                    type = "Custom"
+                    params = {params}
+                    "#
+                ))
+                .unwrap();
+                assert!(task_config.init().is_err());
+            }
+        }
+
+        #[test]
+        fn unknown_type() {
+            let mut task_config = TaskConfig::new();
+            task_config.push(Task::raw("NewTask".to_owned(), object!("foo" => 1)));
+            let tasks = task_config.init().unwrap().tasks;
+            // Parameters are passed as is, without checks of Custom tasks
+            assert_eq!(tasks[0].type_name(), "NewTask");
+            assert_eq!(tasks[0].name_or_default(), "NewTask");
+            assert_eq!(tasks[0].params, object!("foo" => 1));
+        }
+
+        #[test]
+        fn timeout() {
+            use std::time::Duration;
+
+            let mut task_config: TaskConfig = toml::from_str(
+                r#"
+                timeout = "2h"
+
+                [[tasks]]
+                type = "Fight"
+                timeout = "30m"
+
+                [[tasks]]
+                type = "Mall"
+                timeout = 600
+
+                [[tasks]]
+                type = "Award"
+                "#,
+            )
+            .unwrap();
+
+            let config = task_config.init().unwrap();
+            assert_eq!(config.timeout, Some(Duration::from_secs(7200)));
+            assert_eq!(
+                config.tasks.iter().map(|t| t.timeout).collect::<Vec<_>>(),
+                vec![
+                    Some(Duration::from_secs(1800)),
+                    Some(Duration::from_secs(600)),
+                    None,
+                ]
+            );
+
+            task_config.set_timeout(Duration::from_secs(60));
+            assert_eq!(
+                task_config.init().unwrap().timeout,
+                Some(Duration::from_secs(60))
+            );
+
+            assert!(toml::from_str::<TaskConfig>(
+                r#"
+                [[tasks]]
+                type = "Fight"
+                timeout = "forever"
+                "#,
+            )
+            .is_err());
+        }
+
+        #[test]
+        fn display() {
+            let task_config: TaskConfig = toml::from_str(
+                r#"
+                timeout = "2h"
+
+                [[tasks]]
+                name = "Daily"
+                type = "Fight"
+                params = { stage = "1-7" }
+                on_failure = "abort"
+                retry = { attempts = 2, backoff = "5m" }
+                timeout = "30m"
+
+                [[tasks]]
+                type = "Mall"
+                depends_on = ["Daily"]
+                params = { shopping = false }
+                "#,
+            )
+            .unwrap();
+
+            let mut output = Vec::new();
+            task_config.init().unwrap().display(&mut output).unwrap();
+            assert_eq!(
+                String::from_utf8(output).unwrap(),
+                "Client type: Official\n\
+                 Timeout: 2h\n\
+                 1. Daily (Fight)\n   \
+                 On failure: abort\n   \
+                 Retry: 2 times after 5m\n   \
+                 Timeout: 30m\n   \
+                 {\n   \
+                 \x20 \"client_type\": \"Official\",\n   \
+                 \x20 \"stage\": \"1-7\"\n   \
+                 }\n\
+                 2. Mall\n   \
+                 Depends on: Daily\n   \
+                 {\n   \
+                 \x20 \"shopping\": false\n   \
+                 }\n"
+            );
+        }
+
         #[test]
         fn initialized_task() {
             let task = InitializedTask::new(Fight, object!("stage" => "1-7"))
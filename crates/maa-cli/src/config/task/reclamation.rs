@@ -0,0 +1,123 @@
+//! Validation of parameters of Reclamation Algorithm tasks
+//!
+//! Parameters of Reclamation tasks are passed to MaaCore as is, and MaaCore fails silently or
+//! behaves unexpectedly with invalid ones, so they are checked here before running any task.
+
+use std::ops::RangeInclusive;
+
+use anyhow::{bail, Context, Result};
+use log::warn;
+
+use crate::value::MAAValue;
+
+/// Themes of Reclamation Algorithm supported by MaaCore
+const THEMES: [&str; 2] = ["Fire", "Tales"];
+
+/// Mode to farm prosperity points by crafting tools
+const CRAFT_MODE: i32 = 1;
+
+/// Parameters only used to craft tools
+const CRAFT_PARAMS: [&str; 3] = ["tools_to_craft", "increase_mode", "num_craft_batches"];
+
+fn get_int(params: &MAAValue, key: &str, range: RangeInclusive<i32>) -> Result<Option<i32>> {
+    let Some(value) = params.get(key) else {
+        return Ok(None);
+    };
+    let value = value
+        .as_int()
+        .with_context(|| format!("{key} of Reclamation task must be an integer"))?;
+    if !range.contains(&value) {
+        bail!(
+            "Invalid {key} {value} of Reclamation task, should be {} to {}",
+            range.start(),
+            range.end()
+        );
+    }
+    Ok(Some(value))
+}
+
+/// Validate parameters of a Reclamation task
+///
+/// A single tool to craft can be given by a string, which is converted to a list.
+pub fn prepare(params: &mut MAAValue) -> Result<()> {
+    let theme = params
+        .get("theme")
+        .context("theme of Reclamation task is required")?
+        .as_str()
+        .context("theme of Reclamation task must be a string")?;
+    if !THEMES.contains(&theme) {
+        bail!(
+            "Unknown theme `{theme}` of Reclamation task, should be one of {}",
+            THEMES.join(", ")
+        );
+    }
+
+    let mode = get_int(params, "mode", 0..=1)?;
+    get_int(params, "increase_mode", 0..=1)?;
+    get_int(params, "num_craft_batches", 1..=i32::MAX)?;
+
+    if let Some(tools) = params.get_mut("tools_to_craft") {
+        if let Some(tool) = tools.as_str() {
+            *tools = vec![tool.to_owned()].into();
+        }
+        let MAAValue::Array(tools) = tools else {
+            bail!("tools_to_craft of Reclamation task must be a string or a list of strings");
+        };
+        if tools.is_empty() || tools.iter().any(|tool| tool.as_str().is_none()) {
+            bail!("tools_to_craft of Reclamation task must be a string or a list of strings");
+        }
+    }
+
+    if mode != Some(CRAFT_MODE) {
+        for key in CRAFT_PARAMS {
+            if params.get(key).is_some() {
+                warn!("{key} of Reclamation task is ignored, because tools are only crafted in mode {CRAFT_MODE}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object;
+
+    #[test]
+    fn prepare_params() {
+        let mut params = object!(
+            "theme" => "Tales",
+            "mode" => 1,
+            "tools_to_craft" => "荧光棒",
+            "num_craft_batches" => 16,
+        );
+        prepare(&mut params).unwrap();
+        assert_eq!(
+            params,
+            object!(
+                "theme" => "Tales",
+                "mode" => 1,
+                "tools_to_craft" => ["荧光棒"],
+                "num_craft_batches" => 16,
+            )
+        );
+
+        let mut params = object!("theme" => "Fire", "mode" => 0);
+        prepare(&mut params).unwrap();
+        assert_eq!(params, object!("theme" => "Fire", "mode" => 0));
+
+        for params in [
+            object!(),
+            object!("theme" => "Unknown"),
+            object!("theme" => "Tales", "mode" => 2),
+            object!("theme" => "Tales", "mode" => "1"),
+            object!("theme" => "Tales", "increase_mode" => 2),
+            object!("theme" => "Tales", "num_craft_batches" => 0),
+            object!("theme" => "Tales", "tools_to_craft" => MAAValue::Array(vec![])),
+            object!("theme" => "Tales", "tools_to_craft" => [1]),
+        ] {
+            assert!(prepare(&mut params.clone()).is_err());
+        }
+    }
+}
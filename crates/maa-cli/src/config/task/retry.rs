@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+/// How to retry a failed task
+///
+/// A failed task is retried up to `attempts` times, waiting `backoff` before each retry.
+/// The backoff can be given by a number of seconds or a duration string like `30s`, `5m`
+/// or `1h30m`. A number like `retry = 2` is a shorthand of retrying given times without waiting.
+#[cfg_attr(test, derive(Debug))]
+#[derive(Deserialize, Default, Clone, Copy, PartialEq)]
+#[serde(from = "RetryHelper")]
+pub struct RetryPolicy {
+    pub attempts: u32,
+    pub backoff: Duration,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged, deny_unknown_fields)]
+enum RetryHelper {
+    Times(u32),
+    Policy {
+        attempts: u32,
+        #[serde(default, deserialize_with = "super::duration::deserialize")]
+        backoff: Duration,
+    },
+}
+
+impl From<RetryHelper> for RetryPolicy {
+    fn from(helper: RetryHelper) -> Self {
+        match helper {
+            RetryHelper::Times(attempts) => Self::times(attempts),
+            RetryHelper::Policy { attempts, backoff } => Self { attempts, backoff },
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Retry given times without waiting
+    pub const fn times(attempts: u32) -> Self {
+        Self {
+            attempts,
+            backoff: Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_retry() {
+        let retry: RetryPolicy = toml::from_str("attempts = 3\nbackoff = \"5m\"").unwrap();
+        assert_eq!(retry, RetryPolicy {
+            attempts: 3,
+            backoff: Duration::from_secs(300),
+        });
+
+        let retry: RetryPolicy = toml::from_str("attempts = 2\nbackoff = 10").unwrap();
+        assert_eq!(retry, RetryPolicy {
+            attempts: 2,
+            backoff: Duration::from_secs(10),
+        });
+
+        assert_eq!(
+            toml::from_str::<RetryPolicy>("attempts = 1").unwrap(),
+            RetryPolicy::times(1)
+        );
+        assert!(toml::from_str::<RetryPolicy>("attempts = 1\nbackoff = \"soon\"").is_err());
+        assert!(toml::from_str::<RetryPolicy>("backoff = \"5m\"").is_err());
+
+        #[derive(Deserialize)]
+        struct Task {
+            retry: RetryPolicy,
+        }
+        assert_eq!(
+            toml::from_str::<Task>("retry = 2").unwrap().retry,
+            RetryPolicy::times(2)
+        );
+    }
+}
@@ -0,0 +1,128 @@
+use chrono::{Datelike, Weekday};
+use serde::Deserialize;
+
+use super::TimeOffset;
+use crate::{object, value::MAAValue};
+
+/// Parameters of a task rotated by weekday
+///
+/// A rotation is a map from weekday to parameters, which are merged into the parameters of the
+/// task on that weekday. A string value is a shorthand of `{ stage = "<value>" }`, which is the
+/// most common usage for `Fight` task, e.g. `{ Mon = "CE-6", Thu = "CA-5" }`.
+#[cfg_attr(test, derive(PartialEq, Debug))]
+#[derive(Clone, Default)]
+pub struct Rotation {
+    days: Vec<(Weekday, MAAValue)>,
+    timezone: TimeOffset,
+}
+
+impl Rotation {
+    /// Parameters of today in the time zone of the rotation
+    pub fn params(&self) -> Option<&MAAValue> {
+        self.params_on(self.timezone.naive_now().weekday())
+    }
+
+    fn params_on(&self, weekday: Weekday) -> Option<&MAAValue> {
+        self.days
+            .iter()
+            .find(|(day, _)| *day == weekday)
+            .map(|(_, params)| params)
+    }
+
+    /// Use the given time zone if the time zone of the rotation is not specified
+    pub fn set_default_timezone(&mut self, default: TimeOffset) -> &mut Self {
+        if matches!(self.timezone, TimeOffset::Local) {
+            self.timezone = default;
+        }
+        self
+    }
+}
+
+impl<'de> Deserialize<'de> for Rotation {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        #[derive(Deserialize)]
+        struct RotationHelper {
+            #[serde(default)]
+            timezone: TimeOffset,
+            #[serde(flatten)]
+            days: std::collections::BTreeMap<String, MAAValue>,
+        }
+
+        let helper = RotationHelper::deserialize(deserializer)?;
+
+        let mut days: Vec<(Weekday, MAAValue)> = Vec::with_capacity(helper.days.len());
+        for (key, value) in helper.days {
+            let weekday: Weekday = key
+                .parse()
+                .map_err(|_| D::Error::custom(format!("invalid weekday `{key}` in rotation")))?;
+            if days.iter().any(|(day, _)| *day == weekday) {
+                return Err(D::Error::custom(format!(
+                    "duplicate weekday `{key}` in rotation"
+                )));
+            }
+            let params = match value {
+                MAAValue::Primate(stage) => object!("stage" => stage),
+                value => value,
+            };
+            days.push((weekday, params));
+        }
+
+        Ok(Self {
+            days,
+            timezone: helper.timezone,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::task::ClientType;
+
+    #[test]
+    fn deserialize() {
+        let rotation: Rotation = toml::from_str(
+            r#"
+            Mon = "CE-6"
+            thursday = { stage = "CA-5", times = 2 }
+            timezone = "Official"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(rotation.timezone, TimeOffset::Client(ClientType::Official));
+        assert_eq!(
+            rotation.params_on(Weekday::Mon),
+            Some(&object!("stage" => "CE-6"))
+        );
+        assert_eq!(
+            rotation.params_on(Weekday::Thu),
+            Some(&object!("stage" => "CA-5", "times" => 2))
+        );
+        assert_eq!(rotation.params_on(Weekday::Tue), None);
+
+        assert!(toml::from_str::<Rotation>(r#"Someday = "CE-6""#).is_err());
+        assert!(toml::from_str::<Rotation>("Mon = \"CE-6\"\nMonday = \"CA-5\"").is_err());
+    }
+
+    #[test]
+    fn default_timezone() {
+        let server = TimeOffset::Client(ClientType::YoStarEN);
+
+        let mut rotation = Rotation::default();
+        rotation.set_default_timezone(server);
+        assert_eq!(rotation.timezone, server);
+
+        let mut rotation = Rotation {
+            timezone: TimeOffset::TimeZone(8),
+            ..Default::default()
+        };
+        rotation.set_default_timezone(server);
+        assert_eq!(rotation.timezone, TimeOffset::TimeZone(8));
+    }
+}
@@ -0,0 +1,430 @@
+//! Stage names, opening days of resource stages and other stage-specific checks
+//!
+//! Resource stages like chip and supply stages are only open on some days of a week,
+//! and MaaCore will fail to navigate to a closed stage. So a fight task of a closed stage
+//! is skipped, or a fallback stage is used instead. The days are the same as the ones of MAA,
+//! which are not in the resource of MaaCore, but all resource stages are open during the
+//! resource collection event given by the stage activity in resource.
+
+use anyhow::{bail, Result};
+use chrono::{Datelike, Weekday};
+use log::{info, warn};
+
+use super::{annihilation::is_annihilation, condition::TimeOffset, ClientType};
+use crate::{
+    activity,
+    value::{
+        userinput::{SelectD, UserInput},
+        MAAValue,
+    },
+};
+
+/// Resource stages resolved from names of items, and IDs of the items they mainly drop
+const RESOURCE_STAGES: &[(&str, &[&str])] = &[
+    // Battle records
+    ("LS-6", &["2001", "2002", "2003", "2004"]),
+    // LMD
+    ("CE-6", &["4001"]),
+    // Purchase certificate
+    ("AP-5", &["4006"]),
+    // Skill summaries
+    ("CA-5", &["3301", "3302", "3303"]),
+    // Carbon
+    ("SK-5", &["3112", "3113", "3114"]),
+    // Chips and chip packs of Defender and Medic
+    ("PR-A-1", &["3231", "3261"]),
+    ("PR-A-2", &["3232", "3262"]),
+    // Sniper and Caster
+    ("PR-B-1", &["3241", "3251"]),
+    ("PR-B-2", &["3242", "3252"]),
+    // Vanguard and Supporter
+    ("PR-C-1", &["3211", "3271"]),
+    ("PR-C-2", &["3212", "3272"]),
+    // Guard and Specialist
+    ("PR-D-1", &["3221", "3281"]),
+    ("PR-D-2", &["3222", "3282"]),
+];
+
+/// Nicknames of items, which are not their names in the item data, matched case-insensitively
+const NICKNAMES: &[(&[&str], &[&str])] = &[
+    (
+        &["狗粮", "经验", "exp", "battle record", "battle records"],
+        &["2001", "2002", "2003", "2004"],
+    ),
+    (&["钱", "lmd", "money"], &["4001"]),
+    (
+        &["红票", "purchase certificate", "purchase certificates"],
+        &["4006"],
+    ),
+    (&["技能书", "skill summary", "skill summaries"], &[
+        "3301", "3302", "3303",
+    ]),
+    (&["碳", "carbon"], &["3112", "3113", "3114"]),
+    (&["芯片", "chip", "chips"], &[
+        "3211", "3221", "3231", "3241", "3251", "3261", "3271", "3281",
+    ]),
+    (&["芯片组", "chip pack", "chip packs"], &[
+        "3212", "3222", "3232", "3242", "3252", "3262", "3272", "3282",
+    ]),
+];
+
+/// Names of annihilation, which drops no item
+const ANNIHILATION: &[&str] = &["剿灭", "annihilation"];
+
+/// Stage code in upper case if the stage looks like a code, e.g. `ce-6` or `pr-a-1`
+fn normalize_code(stage: &str) -> Option<String> {
+    let (_, number) = stage.rsplit_once('-')?;
+    let is_code = !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit())
+        && stage.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+    is_code.then(|| stage.to_ascii_uppercase())
+}
+
+/// Stages referred by a stage code, or a name of item dropped by resource stages
+///
+/// Items are found by `item_ids`, which returns IDs of items with given name in the item data.
+/// `None` if it's neither a stage code nor a name of such item.
+fn candidates(stage: &str, item_ids: impl Fn(&str) -> Vec<String>) -> Option<Vec<String>> {
+    if let Some(code) = normalize_code(stage) {
+        return Some(vec![code]);
+    }
+    let stage = stage.trim();
+    let name = stage.to_lowercase();
+    if ANNIHILATION.contains(&name.as_str()) {
+        return Some(vec!["Annihilation".to_owned()]);
+    }
+
+    let mut ids = item_ids(stage);
+    if let Some((_, nicknamed)) = NICKNAMES
+        .iter()
+        .find(|(names, _)| names.contains(&name.as_str()))
+    {
+        ids.extend(nicknamed.iter().map(|id| id.to_string()));
+    }
+    let stages: Vec<String> = RESOURCE_STAGES
+        .iter()
+        .filter(|(_, drops)| drops.iter().any(|drop| ids.iter().any(|id| id == drop)))
+        .map(|(stage, _)| stage.to_string())
+        .collect();
+    (!stages.is_empty()).then_some(stages)
+}
+
+/// Stages referred by the name, including event stages dropping the item with the name
+fn stages_of(stage: &str, client_type: ClientType) -> Vec<String> {
+    candidates(stage, |name| activity::item_ids_named(name, client_type))
+        .unwrap_or_else(|| activity::event_stages_dropping(stage, client_type))
+}
+
+/// Resolve the stage to fight given by a human to its stage code, asking the user to choose
+/// one if it refers to multiple stages, used by `maa fight` before running
+pub fn choose_stage(stage: &str, client_type: ClientType) -> Result<String> {
+    let mut stages = stages_of(stage, client_type);
+    Ok(match stages.len() {
+        0 => stage.to_owned(),
+        1 => stages.swap_remove(0),
+        _ => SelectD::<String>::new(
+            stages,
+            None,
+            Some(&format!("a stage to fight for {stage}")),
+            false,
+        )?
+        .value()?,
+    })
+}
+
+/// Resolve the stage and fallback stages to fight given by a human to their stage codes
+///
+/// Besides exact stage codes, codes in any case like `ce-6`, nicknames like `狗粮` or `chips`,
+/// and names of items dropped by resource stages or opening event stages in the language of
+/// the client are accepted. A stage referring to multiple stages is an error, as tasks may be
+/// run unattended, but a fallback stage is expanded to all of them. Unknown stages are passed to
+/// MaaCore as is.
+pub fn resolve(params: &mut MAAValue, client_type: ClientType) -> Result<()> {
+    if let Some(stage) = params
+        .get("stage")
+        .and_then(MAAValue::as_str)
+        .filter(|s| !s.is_empty())
+    {
+        let resolved = match stages_of(stage, client_type).as_slice() {
+            [] => None,
+            [resolved] => Some(resolved.clone()),
+            stages => bail!(
+                "Stage {stage} may refer to {}, specify one of them",
+                stages.join(", ")
+            ),
+        };
+        if let Some(resolved) = resolved.filter(|resolved| resolved != stage) {
+            info!("Stage {stage} is resolved to {resolved}");
+            params.insert("stage", resolved);
+        }
+    }
+
+    let fallback: Vec<String> = match params.get("fallback_stages") {
+        Some(MAAValue::Array(stages)) => stages
+            .iter()
+            .filter_map(MAAValue::as_str)
+            .map(str::to_owned)
+            .collect(),
+        Some(stage) => stage.as_str().map(str::to_owned).into_iter().collect(),
+        None => return Ok(()),
+    };
+    let mut resolved = Vec::new();
+    for stage in fallback {
+        match stages_of(&stage, client_type) {
+            stages if stages.is_empty() => resolved.push(stage),
+            stages => resolved.extend(stages),
+        }
+    }
+    params.insert("fallback_stages", resolved);
+
+    Ok(())
+}
+
+/// Weekdays on which the resource stage is open, `None` if the stage is always open
+fn open_days(stage: &str) -> Option<&'static [Weekday]> {
+    use Weekday::*;
+
+    let (prefix, _) = stage.rsplit_once('-')?;
+    let days: &[Weekday] = match prefix.to_ascii_uppercase().as_str() {
+        // LMD
+        "CE" => &[Tue, Thu, Sat, Sun],
+        // Skill summary
+        "CA" => &[Tue, Wed, Fri, Sun],
+        // Purchase certificate
+        "AP" => &[Mon, Thu, Sat, Sun],
+        // Carbon
+        "SK" => &[Mon, Wed, Fri, Sat],
+        // Medic and Defender chips
+        "PR-A" => &[Mon, Thu, Fri, Sun],
+        // Sniper and Caster chips
+        "PR-B" => &[Mon, Tue, Fri, Sat],
+        // Vanguard and Supporter chips
+        "PR-C" => &[Wed, Thu, Sat, Sun],
+        // Guard and Specialist chips
+        "PR-D" => &[Tue, Wed, Sat, Sun],
+        _ => return None,
+    };
+    Some(days)
+}
+
+fn is_open(stage: &str, weekday: Weekday) -> bool {
+    open_days(stage).is_none_or(|days| days.contains(&weekday))
+}
+
+/// Whether the stage is open today in given time zone, always true for non-resource stages
+pub fn is_open_now(stage: &str, timezone: TimeOffset, client_type: ClientType) -> bool {
+    activity::is_resource_collection_open(client_type)
+        || is_open(stage, timezone.naive_now().weekday())
+}
+
+/// Check whether the stage of a fight task is open today in the server time zone
+///
+/// If the stage is closed, the first open stage in `fallback_stages` will be used instead,
+/// or the task will be disabled if there is no open fallback stage. `fallback_stages` is
+/// removed in any case, which is not a parameter of MaaCore.
+pub fn prepare(params: &mut MAAValue, timezone: TimeOffset, client_type: ClientType) {
+    if activity::is_resource_collection_open(client_type) {
+        if let Some(params) = params.as_object_mut() {
+            params.remove("fallback_stages");
+        }
+        return;
+    }
+    prepare_on(params, timezone.naive_now().weekday())
+}
+
+fn prepare_on(params: &mut MAAValue, weekday: Weekday) {
+    let fallback = params
+        .as_object_mut()
+        .and_then(|params| params.remove("fallback_stages"));
+    if !params.get_or("enable", true) {
+        return;
+    }
+
+    let Some(stage) = params.get("stage").and_then(MAAValue::as_str) else {
+        return;
+    };
+    if is_open(stage, weekday) {
+        return;
+    }
+
+    let fallback = match &fallback {
+        Some(MAAValue::Array(stages)) => stages
+            .iter()
+            .filter_map(MAAValue::as_str)
+            .find(|s| is_open(s, weekday)),
+        Some(value) => value.as_str().filter(|s| is_open(s, weekday)),
+        None => None,
+    };
+
+    match fallback {
+        Some(fallback) => {
+            info!("Stage {stage} is not open today, fight {fallback} instead");
+            params.insert("stage", fallback);
+        }
+        None => {
+            warn!("Stage {stage} is not open today, skip it");
+            params.insert("enable", false);
+        }
+    }
+}
+
+/// Check whether the `series` parameter is compatible with the stage
+///
+/// The series (proxy combat of multiple runs in one battle) can be 1 to 6, 0 to use the
+/// maximum times allowed by current sanity, or -1 to disable it. Annihilation has no series.
+pub fn check_series(params: &MAAValue) -> Result<()> {
+    let Some(series) = params.get("series") else {
+        return Ok(());
+    };
+    let Some(series) = series.as_int() else {
+        bail!("series must be an integer");
+    };
+    if !(-1..=6).contains(&series) {
+        bail!("series must be between -1 and 6, but got {series}");
+    }
+
+    let stage = params.get("stage").and_then(MAAValue::as_str).unwrap_or("");
+    if series > 1 && is_annihilation(stage) {
+        bail!("series is not supported by stage {stage}");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object;
+
+    #[test]
+    fn stage_candidates() {
+        // A stub of the item data
+        let item_ids = |name: &str| match name {
+            "龙门币" | "LMD" => vec!["4001".to_owned()],
+            "Guard Chip" => vec!["3221".to_owned()],
+            "Guard Chip Pack" => vec!["3222".to_owned()],
+            "Orundum" => vec!["4003".to_owned()],
+            _ => Vec::new(),
+        };
+        let candidates = |stage: &str| candidates(stage, item_ids);
+
+        assert_eq!(candidates("ce-6").unwrap(), ["CE-6"]);
+        assert_eq!(candidates("pr-a-1").unwrap(), ["PR-A-1"]);
+        assert_eq!(candidates("1-7").unwrap(), ["1-7"]);
+        assert_eq!(candidates("龙门币").unwrap(), ["CE-6"]);
+        assert_eq!(candidates(" LMD ").unwrap(), ["CE-6"]);
+        assert_eq!(candidates("Guard Chip").unwrap(), ["PR-D-1"]);
+        assert_eq!(candidates("Guard Chip Pack").unwrap(), ["PR-D-2"]);
+        assert_eq!(candidates("狗粮").unwrap(), ["LS-6"]);
+        assert_eq!(candidates("红票").unwrap(), ["AP-5"]);
+        assert_eq!(candidates("Chips").unwrap(), [
+            "PR-A-1", "PR-B-1", "PR-C-1", "PR-D-1"
+        ]);
+        assert_eq!(candidates("annihilation").unwrap(), ["Annihilation"]);
+        // Items not dropped by resource stages
+        assert_eq!(candidates("Orundum"), None);
+        assert_eq!(candidates("Chernobog@Annihilation"), None);
+        assert_eq!(candidates("SSReopen-FC"), None);
+    }
+
+    #[test]
+    fn resolve_stage() {
+        let mut params = object!("stage" => "ce-6");
+        resolve(&mut params, ClientType::Official).unwrap();
+        assert_eq!(params, object!("stage" => "CE-6"));
+
+        // Tasks may be run unattended, so an ambiguous stage is never prompted
+        let mut params = object!("stage" => "芯片");
+        assert!(resolve(&mut params, ClientType::Official).is_err());
+
+        let mut params = object!("stage" => "");
+        resolve(&mut params, ClientType::Official).unwrap();
+        assert_eq!(params, object!("stage" => ""));
+
+        // Fallback stages are expanded to all stages they refer to
+        let mut params = object!(
+            "stage" => "CE-6",
+            "fallback_stages" => ["狗粮", "芯片", "1-7", "SSReopen-FC"],
+        );
+        resolve(&mut params, ClientType::Official).unwrap();
+        assert_eq!(
+            params,
+            object!(
+                "stage" => "CE-6",
+                "fallback_stages" => [
+                    "LS-6", "PR-A-1", "PR-B-1", "PR-C-1", "PR-D-1", "1-7", "SSReopen-FC"
+                ],
+            )
+        );
+
+        let mut params = object!("stage" => "CE-6", "fallback_stages" => "pr-a-1");
+        resolve(&mut params, ClientType::Official).unwrap();
+        assert_eq!(
+            params,
+            object!("stage" => "CE-6", "fallback_stages" => ["PR-A-1"])
+        );
+    }
+
+    #[test]
+    fn stage_open() {
+        use Weekday::*;
+
+        assert!(is_open("1-7", Mon));
+        assert!(is_open("LS-6", Mon));
+        assert!(is_open("Annihilation", Mon));
+        assert!(is_open("CE-6", Tue));
+        assert!(!is_open("CE-6", Mon));
+        assert!(is_open("ce-5", Sun));
+        assert!(is_open("PR-A-1", Mon));
+        assert!(!is_open("PR-A-2", Tue));
+        assert!(is_open("PR-D-2", Sun));
+        assert!(!is_open("SK-5", Sun));
+    }
+
+    #[test]
+    fn prepare_params() {
+        use Weekday::*;
+
+        let mut params = object!("stage" => "CE-6", "fallback_stages" => ["CA-5", "1-7"]);
+        prepare_on(&mut params, Tue);
+        assert_eq!(params, object!("stage" => "CE-6"));
+
+        let mut params = object!("stage" => "CE-6", "fallback_stages" => ["CA-5", "1-7"]);
+        prepare_on(&mut params, Mon);
+        assert_eq!(params, object!("stage" => "1-7"));
+
+        let mut params = object!("stage" => "CE-6", "fallback_stages" => "CA-5");
+        prepare_on(&mut params, Wed);
+        assert_eq!(params, object!("stage" => "CA-5"));
+
+        let mut params = object!("stage" => "CE-6", "fallback_stages" => "AP-5");
+        prepare_on(&mut params, Wed);
+        assert_eq!(params, object!("stage" => "CE-6", "enable" => false));
+
+        let mut params = object!("stage" => "CE-6");
+        prepare_on(&mut params, Mon);
+        assert_eq!(params, object!("stage" => "CE-6", "enable" => false));
+
+        let mut params = object!("stage" => "");
+        prepare_on(&mut params, Mon);
+        assert_eq!(params, object!("stage" => ""));
+
+        // Fallback stages of disabled tasks are not used
+        let mut params =
+            object!("stage" => "CE-6", "fallback_stages" => ["1-7"], "enable" => false);
+        prepare_on(&mut params, Mon);
+        assert_eq!(params, object!("stage" => "CE-6", "enable" => false));
+    }
+
+    #[test]
+    fn series() {
+        assert!(check_series(&object!("stage" => "1-7")).is_ok());
+        assert!(check_series(&object!("stage" => "1-7", "series" => 6)).is_ok());
+        assert!(check_series(&object!("stage" => "", "series" => 0)).is_ok());
+        assert!(check_series(&object!("stage" => "Annihilation", "series" => -1)).is_ok());
+
+        assert!(check_series(&object!("stage" => "1-7", "series" => 7)).is_err());
+        assert!(check_series(&object!("stage" => "1-7", "series" => "6")).is_err());
+        assert!(check_series(&object!("stage" => "Annihilation", "series" => 6)).is_err());
+    }
+}
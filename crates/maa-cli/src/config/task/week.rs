@@ -0,0 +1,110 @@
+use chrono::Weekday;
+use serde::Deserialize;
+
+/// Tasks to run on each day of a week, referred by their names
+///
+/// Tasks listed on any day only run on the listed days, while tasks not listed on any day,
+/// like StartUp and CloseDown tasks, run every day. Tasks still run in the order they
+/// are defined, no matter the order they are listed.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Default, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Week {
+    #[serde(default, alias = "mon")]
+    monday: Vec<String>,
+    #[serde(default, alias = "tue")]
+    tuesday: Vec<String>,
+    #[serde(default, alias = "wed")]
+    wednesday: Vec<String>,
+    #[serde(default, alias = "thu")]
+    thursday: Vec<String>,
+    #[serde(default, alias = "fri")]
+    friday: Vec<String>,
+    #[serde(default, alias = "sat")]
+    saturday: Vec<String>,
+    #[serde(default, alias = "sun")]
+    sunday: Vec<String>,
+}
+
+impl Week {
+    fn days(&self) -> [&Vec<String>; 7] {
+        [
+            &self.monday,
+            &self.tuesday,
+            &self.wednesday,
+            &self.thursday,
+            &self.friday,
+            &self.saturday,
+            &self.sunday,
+        ]
+    }
+
+    fn days_mut(&mut self) -> [&mut Vec<String>; 7] {
+        [
+            &mut self.monday,
+            &mut self.tuesday,
+            &mut self.wednesday,
+            &mut self.thursday,
+            &mut self.friday,
+            &mut self.saturday,
+            &mut self.sunday,
+        ]
+    }
+
+    /// Names of tasks listed on given day
+    pub fn tasks_on(&self, weekday: Weekday) -> &[String] {
+        self.days()[weekday.num_days_from_monday() as usize]
+    }
+
+    /// Names of tasks listed on any day
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.days().into_iter().flatten().map(String::as_str)
+    }
+
+    /// Whether a task with given name runs on given day
+    pub fn runs_on(&self, name: Option<&str>, weekday: Weekday) -> bool {
+        match name {
+            Some(name) if self.names().any(|n| n == name) => {
+                self.tasks_on(weekday).iter().any(|n| n == name)
+            }
+            _ => true,
+        }
+    }
+
+    /// Append tasks listed on each day of another week
+    pub fn merge(&mut self, other: Week) {
+        for (day, other) in self.days_mut().into_iter().zip(other.days()) {
+            day.extend(other.iter().cloned());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn runs_on() {
+        let week: Week = toml::from_str(
+            r#"
+            mon = ["Fight", "Mall"]
+            wednesday = ["Fight"]
+            sun = ["Roguelike"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(week.tasks_on(Weekday::Mon), ["Fight", "Mall"]);
+        assert!(week.tasks_on(Weekday::Tue).is_empty());
+
+        assert!(week.runs_on(Some("Fight"), Weekday::Wed));
+        assert!(!week.runs_on(Some("Fight"), Weekday::Sun));
+        assert!(week.runs_on(Some("Roguelike"), Weekday::Sun));
+        assert!(!week.runs_on(Some("Mall"), Weekday::Wed));
+        // Tasks not listed run every day
+        assert!(week.runs_on(Some("Start"), Weekday::Tue));
+        assert!(week.runs_on(None, Weekday::Tue));
+
+        assert!(toml::from_str::<Week>("someday = []").is_err());
+    }
+}
@@ -0,0 +1,93 @@
+use std::fs;
+
+use anyhow::{bail, Context, Result};
+
+use super::find_config;
+use crate::dirs::{self, Ensure};
+
+/// Templates of task files
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum Template {
+    /// Daily routine: startup, fight, infrast, mall, award and closedown
+    #[default]
+    Daily,
+    /// Weekly routine: annihilation, recruitment and chip stages rotated by weekday
+    Weekly,
+    /// Copilot with a copilot file from prts.plus
+    Copilot,
+    /// Integrated Strategies (roguelike)
+    Roguelike,
+}
+
+impl Template {
+    fn content(self) -> &'static str {
+        match self {
+            Template::Daily => include_str!("../../templates/tasks/daily.toml"),
+            Template::Weekly => include_str!("../../templates/tasks/weekly.toml"),
+            Template::Copilot => include_str!("../../templates/tasks/copilot.toml"),
+            Template::Roguelike => include_str!("../../templates/tasks/roguelike.toml"),
+        }
+    }
+
+    fn render(self, name: &str) -> String {
+        self.content().replace("{name}", name)
+    }
+}
+
+/// Create a new task file in the `tasks` directory
+///
+/// The task file is created from the template, or built by asking the user if `interactive`.
+pub fn new_task(name: &str, template: Template, interactive: bool, force: bool) -> Result<()> {
+    if name.is_empty() || name.contains(['/', '\\']) {
+        bail!("Invalid task name `{name}`");
+    }
+
+    let task_dir = dirs::config().join("tasks");
+    let path = task_dir.join(name);
+    let existing = find_config(&path);
+    if let (Some(existing), false) = (&existing, force) {
+        bail!(
+            "Task file {} already exists, use --force to overwrite it",
+            existing.display()
+        );
+    }
+
+    let content = if interactive {
+        super::builder::build(name)?
+    } else {
+        template.render(name)
+    };
+
+    if let Some(existing) = existing {
+        fs::remove_file(&existing)
+            .with_context(|| format!("Failed to remove {}", existing.display()))?;
+    }
+
+    let path = path.with_extension("toml");
+    task_dir.ensure()?;
+    fs::write(&path, content)?;
+    println!("Created {}", path.display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::ValueEnum;
+
+    use super::*;
+    use crate::config::task::TaskConfig;
+
+    #[test]
+    fn templates_are_valid() {
+        for template in Template::value_variants() {
+            let content = template.render("test");
+            assert!(!content.contains("{name}"));
+            assert!(content.contains("maa run test"));
+
+            let task: TaskConfig = toml::from_str(&content).unwrap();
+            task.init().unwrap();
+        }
+    }
+}
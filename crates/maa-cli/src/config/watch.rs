@@ -0,0 +1,236 @@
+//! Reload configuration files changed while running in long-running modes
+//!
+//! The scheduler and the daemon load their configuration once at start. To apply changes
+//! without restarting them, the directory of a configuration file is watched, and the file is
+//! reloaded between task executions when it's changed. The configuration is only replaced if
+//! the new one is loaded successfully, so a half-edited file never breaks a running process.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::Result;
+use log::{info, warn};
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde_json::Value as JsonValue;
+
+use super::FindFile;
+
+/// A configuration loaded from a file, which is reloaded when the file is changed
+pub struct Watched<T> {
+    /// Path of the file without extension, as found by [`FindFile`]
+    path: PathBuf,
+    value: T,
+    /// Content of the file when the value is loaded, used to find what is changed
+    raw: JsonValue,
+    changed: Arc<AtomicBool>,
+    /// The watcher stops watching when dropped, `None` if the file can not be watched
+    _watcher: Option<RecommendedWatcher>,
+}
+
+impl<T> Watched<T> {
+    /// Load the configuration by `load`, and watch the file at `path` without extension
+    pub fn new(path: PathBuf, load: impl FnOnce() -> Result<T>) -> Result<Self> {
+        let value = load()?;
+        let changed = Arc::new(AtomicBool::new(false));
+        let watcher = watch(&path, changed.clone())
+            .inspect_err(|err| {
+                warn!(
+                    "Failed to watch {}, changes are applied after restart: {err}",
+                    path.display()
+                )
+            })
+            .ok();
+        Ok(Self {
+            raw: raw_value(&path),
+            path,
+            value,
+            changed,
+            _watcher: watcher,
+        })
+    }
+
+    pub fn get(&self) -> &T {
+        &self.value
+    }
+
+    /// Reload the configuration by `load` if the file is changed since it's loaded
+    ///
+    /// Return keys of changed values if the configuration is reloaded. If the new one fails to
+    /// load, the error is logged and the current one is kept.
+    pub fn reload(&mut self, load: impl FnOnce() -> Result<T>) -> Option<Vec<String>> {
+        if !self.changed.swap(false, Ordering::Relaxed) {
+            return None;
+        }
+
+        let raw = raw_value(&self.path);
+        let mut keys = Vec::new();
+        changed_keys(&self.raw, &raw, String::new(), &mut keys);
+        // Files saved without changes are ignored
+        if keys.is_empty() {
+            return None;
+        }
+
+        match load() {
+            Ok(value) => {
+                info!(
+                    "Reloaded {}, changed: {}",
+                    self.path.display(),
+                    keys.join(", ")
+                );
+                self.value = value;
+                self.raw = raw;
+                Some(keys)
+            }
+            Err(err) => {
+                warn!(
+                    "Failed to reload {}, keep using the previous one: {err:#}",
+                    self.path.display()
+                );
+                None
+            }
+        }
+    }
+}
+
+/// Watch the directory of the file, because editors often replace the file instead of writing it
+fn watch(path: &Path, changed: Arc<AtomicBool>) -> notify::Result<RecommendedWatcher> {
+    let stem = path.to_owned();
+    let mut watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+        if let Ok(event) = event {
+            // Files are opened when they are read, including by the reload itself
+            if !event.kind.is_access()
+                && event
+                    .paths
+                    .iter()
+                    .any(|path| path.with_extension("") == stem)
+            {
+                changed.store(true, Ordering::Relaxed);
+            }
+        }
+    })?;
+    let dir = path.parent().unwrap_or(path);
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+    Ok(watcher)
+}
+
+/// Content of the file in any supported format, or null if it's missing or invalid
+fn raw_value(path: &Path) -> JsonValue {
+    JsonValue::find_file_or_none(path)
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+/// Collect dotted keys of values differing between two configurations, like `connection.address`
+/// or `schedule[1].cron`, the whole configuration is given by an empty key
+fn changed_keys(old: &JsonValue, new: &JsonValue, prefix: String, keys: &mut Vec<String>) {
+    use JsonValue::*;
+
+    match (old, new) {
+        (Object(old), Object(new)) => {
+            let null = Null;
+            let mut names: Vec<_> = old.keys().chain(new.keys()).collect();
+            names.sort();
+            names.dedup();
+            for name in names {
+                let key = if prefix.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{prefix}.{name}")
+                };
+                changed_keys(
+                    old.get(name).unwrap_or(&null),
+                    new.get(name).unwrap_or(&null),
+                    key,
+                    keys,
+                );
+            }
+        }
+        (Array(old), Array(new)) if old.len() == new.len() => {
+            for (i, (old, new)) in old.iter().zip(new).enumerate() {
+                changed_keys(old, new, format!("{prefix}[{i}]"), keys);
+            }
+        }
+        _ if old != new => keys.push(prefix),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use serde_json::json;
+
+    use super::*;
+
+    fn keys(old: JsonValue, new: JsonValue) -> Vec<String> {
+        let mut keys = Vec::new();
+        changed_keys(&old, &new, String::new(), &mut keys);
+        keys
+    }
+
+    #[test]
+    fn changed() {
+        assert!(keys(json!({ "a": 1 }), json!({ "a": 1 })).is_empty());
+        assert_eq!(
+            keys(
+                json!({ "connection": { "address": "a", "adb_path": "adb" }, "b": 1 }),
+                json!({ "connection": { "address": "b", "adb_path": "adb" }, "c": 1 }),
+            ),
+            ["b", "c", "connection.address"]
+        );
+        assert_eq!(
+            keys(
+                json!({ "schedule": [{ "cron": "0 4 * * *" }, { "cron": "0 16 * * *" }] }),
+                json!({ "schedule": [{ "cron": "0 4 * * *" }, { "cron": "0 17 * * *" }] }),
+            ),
+            ["schedule[1].cron"]
+        );
+        assert_eq!(
+            keys(json!({ "schedule": [1] }), json!({ "schedule": [1, 2] })),
+            ["schedule"]
+        );
+        assert_eq!(keys(JsonValue::Null, json!({})), [""]);
+    }
+
+    #[test]
+    fn reload() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config");
+        let load = || Ok(JsonValue::find_file(&path)?);
+        std::fs::write(path.with_extension("toml"), "a = 1\n").unwrap();
+
+        let mut watched = Watched::new(path.clone(), load).unwrap();
+        assert_eq!(watched.get(), &json!({ "a": 1 }));
+        assert_eq!(watched.reload(load), None);
+
+        // Changes of other files are ignored
+        std::fs::write(dir.path().join("other.toml"), "a = 2\n").unwrap();
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(!watched.changed.load(Ordering::Relaxed));
+
+        std::fs::write(path.with_extension("toml"), "a = 2\nb = 1\n").unwrap();
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let keys = loop {
+            if let Some(keys) = watched.reload(load) {
+                break keys;
+            }
+            assert!(Instant::now() < deadline, "Change is not noticed");
+            std::thread::sleep(Duration::from_millis(10));
+        };
+        assert_eq!(keys, ["a", "b"]);
+        assert_eq!(watched.get(), &json!({ "a": 2, "b": 1 }));
+
+        // An invalid file is not applied
+        watched.changed.store(true, Ordering::Relaxed);
+        std::fs::write(path.with_extension("toml"), "a = ").unwrap();
+        assert_eq!(watched.reload(load), None);
+        assert_eq!(watched.get(), &json!({ "a": 2, "b": 1 }));
+    }
+}
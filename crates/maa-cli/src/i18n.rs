@@ -0,0 +1,301 @@
+//! Localization of messages, summaries and errors shown to users
+//!
+//! Messages are written in [Fluent](https://projectfluent.org) in `locales/<language>.ftl`,
+//! which are embedded into the binary. The language is set by `language` in `cli.toml`, or
+//! detected from `LC_ALL`, `LC_MESSAGES` and `LANG`. Messages missing in a language fall back
+//! to English, so that a translation can be incomplete.
+//!
+//! Messages are formatted by the [`tr!`] macro with the id and arguments of the message:
+//!
+//! ```ignore
+//! info!("{}", tr!("current-sanity", current = 100, max = 135));
+//! ```
+
+use std::sync::LazyLock;
+
+use fluent_bundle::{concurrent::FluentBundle, FluentArgs, FluentResource};
+use serde::Deserialize;
+
+use crate::config::cli::CLI_CONFIG;
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone, Copy, Default)]
+pub enum Language {
+    #[default]
+    English,
+    SimplifiedChinese,
+    TraditionalChinese,
+    Japanese,
+    Korean,
+}
+
+use Language::*;
+
+impl Language {
+    #[cfg(test)]
+    const ALL: [Language; 5] = [
+        English,
+        SimplifiedChinese,
+        TraditionalChinese,
+        Japanese,
+        Korean,
+    ];
+    const NAMES: [&'static str; 5] = ["en-US", "zh-CN", "zh-TW", "ja-JP", "ko-KR"];
+
+    fn to_str(self) -> &'static str {
+        Self::NAMES[self as usize]
+    }
+
+    /// Parse a language tag like `zh-CN` or a locale like `zh_CN.UTF-8`
+    ///
+    /// Only the language is matched except for Chinese, where Taiwan, Hong Kong, Macau and the
+    /// Han Traditional script are matched to Traditional Chinese.
+    fn from_str_opt(s: &str) -> Option<Self> {
+        let tag = s
+            .split(['.', '@'])
+            .next()
+            .unwrap_or_default()
+            .replace('_', "-")
+            .to_ascii_lowercase();
+        let mut subtags = tag.split('-');
+        match subtags.next()? {
+            "en" => Some(English),
+            "zh" if subtags.any(|subtag| matches!(subtag, "tw" | "hk" | "mo" | "hant")) => {
+                Some(TraditionalChinese)
+            }
+            "zh" => Some(SimplifiedChinese),
+            "ja" => Some(Japanese),
+            "ko" => Some(Korean),
+            _ => None,
+        }
+    }
+
+    /// Language of the first non-empty one of `LC_ALL`, `LC_MESSAGES` and `LANG`
+    fn from_env() -> Option<Self> {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| std::env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::from_str_opt(&value))
+    }
+
+    fn source(self) -> &'static str {
+        match self {
+            English => include_str!("../locales/en-US.ftl"),
+            SimplifiedChinese => include_str!("../locales/zh-CN.ftl"),
+            TraditionalChinese => include_str!("../locales/zh-TW.ftl"),
+            Japanese => include_str!("../locales/ja-JP.ftl"),
+            Korean => include_str!("../locales/ko-KR.ftl"),
+        }
+    }
+
+    fn bundle(self) -> FluentBundle<FluentResource> {
+        let id = self.to_str().parse().expect("Invalid language identifier");
+        let mut bundle = FluentBundle::new_concurrent(vec![id]);
+        // Isolation marks are only needed for right-to-left languages
+        bundle.set_use_isolating(false);
+        // Invalid entries are skipped and fall back to English, which are checked by tests
+        let resource = FluentResource::try_new(self.source().to_owned())
+            .unwrap_or_else(|(resource, _)| resource);
+        // Duplicated messages are ignored, which are checked by tests as well
+        let _ = bundle.add_resource(resource);
+        bundle
+    }
+}
+
+impl<'de> Deserialize<'de> for Language {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct LanguageVisitor;
+
+        impl serde::de::Visitor<'_> for LanguageVisitor {
+            type Value = Language;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a string representing a language")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                Language::from_str_opt(value)
+                    .ok_or_else(|| E::unknown_variant(value, &Language::NAMES))
+            }
+        }
+
+        deserializer.deserialize_str(LanguageVisitor)
+    }
+}
+
+struct Localizer {
+    bundle: FluentBundle<FluentResource>,
+    /// English messages, used if a message is missing in the language
+    fallback: Option<FluentBundle<FluentResource>>,
+}
+
+impl Localizer {
+    fn new(language: Language) -> Self {
+        Self {
+            bundle: language.bundle(),
+            fallback: match language {
+                English => None,
+                _ => Some(English.bundle()),
+            },
+        }
+    }
+
+    fn format(&self, id: &str, args: Option<&FluentArgs>) -> String {
+        std::iter::once(&self.bundle)
+            .chain(&self.fallback)
+            .find_map(|bundle| {
+                let pattern = bundle.get_message(id)?.value()?;
+                let mut errors = Vec::new();
+                Some(
+                    bundle
+                        .format_pattern(pattern, args, &mut errors)
+                        .into_owned(),
+                )
+            })
+            .unwrap_or_else(|| id.to_owned())
+    }
+}
+
+fn language() -> Language {
+    // Tests are run in English, whatever the language of the environment is
+    if cfg!(test) {
+        return English;
+    }
+    CLI_CONFIG
+        .language()
+        .or_else(Language::from_env)
+        .unwrap_or_default()
+}
+
+static LOCALIZER: LazyLock<Localizer> = LazyLock::new(|| Localizer::new(language()));
+
+/// Format the message with given id in the language of user, prefer the [`tr!`] macro
+pub fn format(id: &str, args: Option<&FluentArgs>) -> String {
+    LOCALIZER.format(id, args)
+}
+
+/// Format a localized message by its id and arguments given by `name = value`
+macro_rules! tr {
+    ($id:literal) => {
+        $crate::i18n::format($id, None)
+    };
+    ($id:literal, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = ::fluent_bundle::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::format($id, Some(&args))
+    }};
+}
+
+pub(crate) use tr;
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use fluent_bundle::FluentResource;
+    use fluent_syntax::ast::Entry;
+
+    use super::*;
+
+    #[test]
+    fn parse_language() {
+        assert_eq!(Language::from_str_opt("en-US"), Some(English));
+        assert_eq!(Language::from_str_opt("en_GB.UTF-8"), Some(English));
+        assert_eq!(Language::from_str_opt("zh-CN"), Some(SimplifiedChinese));
+        assert_eq!(
+            Language::from_str_opt("zh_CN.UTF-8"),
+            Some(SimplifiedChinese)
+        );
+        assert_eq!(Language::from_str_opt("zh-Hans"), Some(SimplifiedChinese));
+        assert_eq!(
+            Language::from_str_opt("zh_TW.UTF-8"),
+            Some(TraditionalChinese)
+        );
+        assert_eq!(Language::from_str_opt("zh-HK"), Some(TraditionalChinese));
+        assert_eq!(
+            Language::from_str_opt("zh-Hant-HK"),
+            Some(TraditionalChinese)
+        );
+        assert_eq!(Language::from_str_opt("ja_JP.eucJP"), Some(Japanese));
+        assert_eq!(Language::from_str_opt("ko"), Some(Korean));
+        assert_eq!(Language::from_str_opt("C"), None);
+        assert_eq!(Language::from_str_opt("fr_FR@euro"), None);
+        assert_eq!(Language::from_str_opt(""), None);
+
+        for language in Language::ALL {
+            assert_eq!(Language::from_str_opt(language.to_str()), Some(language));
+        }
+    }
+
+    #[test]
+    fn deserialize() {
+        use serde_test::{assert_de_tokens, assert_de_tokens_error, Token};
+
+        assert_de_tokens(&SimplifiedChinese, &[Token::Str("zh-CN")]);
+        assert_de_tokens(&Japanese, &[Token::Str("ja")]);
+        assert_de_tokens_error::<Language>(
+            &[Token::Str("fr-FR")],
+            "unknown variant `fr-FR`, expected one of \
+            `en-US`, `zh-CN`, `zh-TW`, `ja-JP`, `ko-KR`",
+        );
+    }
+
+    fn message_ids(language: Language) -> BTreeSet<String> {
+        let resource = match FluentResource::try_new(language.source().to_owned()) {
+            Ok(resource) => resource,
+            Err((_, errors)) => panic!("Invalid {}.ftl: {errors:?}", language.to_str()),
+        };
+        let mut ids = BTreeSet::new();
+        for entry in resource.entries() {
+            if let Entry::Message(message) = entry {
+                assert!(
+                    ids.insert(message.id.name.to_owned()),
+                    "Duplicated message {} in {}.ftl",
+                    message.id.name,
+                    language.to_str()
+                );
+            }
+        }
+        ids
+    }
+
+    #[test]
+    fn resources() {
+        let english = message_ids(English);
+        for language in Language::ALL {
+            let ids = message_ids(language);
+            assert!(
+                ids.is_subset(&english),
+                "Unknown messages in {}.ftl: {:?}",
+                language.to_str(),
+                ids.difference(&english).collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn format() {
+        let english = Localizer::new(English);
+        let mut args = FluentArgs::new();
+        args.set("current", 100);
+        args.set("max", 135);
+        assert_eq!(
+            english.format("current-sanity", Some(&args)),
+            "Current sanity: 100/135"
+        );
+        assert_eq!(english.format("unknown-message", None), "unknown-message");
+
+        let chinese = Localizer::new(SimplifiedChinese);
+        assert_eq!(
+            chinese.format("current-sanity", Some(&args)),
+            "当前理智：100/135"
+        );
+
+        assert_eq!(
+            tr!("current-sanity", current = 1, max = "135"),
+            "Current sanity: 1/135"
+        );
+        assert_eq!(tr!("summary"), "Summary");
+    }
+}
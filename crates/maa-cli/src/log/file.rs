@@ -0,0 +1,215 @@
+//! Log file in the state directory, rotated by date or size
+//!
+//! The current log is always written to `maa-cli.log`, and rotated to
+//! `maa-cli.<date or time>.log` when the date changes or it grows too large.
+//! Only the newest rotated files are kept, the others are removed when rotating.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+use chrono::{DateTime, Local, NaiveDate};
+
+use crate::config::cli::log::{Config, Rotation};
+
+const NAME: &str = "maa-cli";
+
+pub struct LogFile {
+    dir: PathBuf,
+    rotation: Rotation,
+    max_size: u64,
+    keep: usize,
+    file: File,
+    /// Size of current log file
+    size: u64,
+    /// Date when current log file is created
+    date: NaiveDate,
+}
+
+fn open_append(path: &Path) -> io::Result<File> {
+    OpenOptions::new().create(true).append(true).open(path)
+}
+
+impl LogFile {
+    pub fn open(dir: PathBuf, config: &Config, now: DateTime<Local>) -> io::Result<Self> {
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(format!("{NAME}.log"));
+
+        // Continue the log file left by last run, which may need to be rotated first
+        let (size, date) = match fs::metadata(&path) {
+            Ok(metadata) => (
+                metadata.len(),
+                DateTime::<Local>::from(metadata.modified()?).date_naive(),
+            ),
+            Err(_) => (0, now.date_naive()),
+        };
+
+        let mut log_file = Self {
+            file: open_append(&path)?,
+            dir,
+            rotation: config.rotation(),
+            max_size: config.max_size(),
+            keep: config.keep(),
+            size,
+            date,
+        };
+
+        if log_file.should_rotate(now) {
+            log_file.rotate(now)?;
+        }
+
+        Ok(log_file)
+    }
+
+    fn path(&self) -> PathBuf {
+        self.dir.join(format!("{NAME}.log"))
+    }
+
+    fn should_rotate(&self, now: DateTime<Local>) -> bool {
+        self.size > 0
+            && match self.rotation {
+                Rotation::Daily => now.date_naive() != self.date,
+                Rotation::Size => self.size >= self.max_size,
+            }
+    }
+
+    fn rotate(&mut self, now: DateTime<Local>) -> io::Result<()> {
+        let suffix = match self.rotation {
+            Rotation::Daily => self.date.format("%Y-%m-%d").to_string(),
+            Rotation::Size => now.format("%Y-%m-%dT%H-%M-%S").to_string(),
+        };
+        let mut rotated = self.dir.join(format!("{NAME}.{suffix}.log"));
+        let mut n = 1;
+        while rotated.exists() {
+            rotated = self.dir.join(format!("{NAME}.{suffix}-{n}.log"));
+            n += 1;
+        }
+
+        fs::rename(self.path(), rotated)?;
+        self.file = open_append(&self.path())?;
+        self.size = 0;
+        self.date = now.date_naive();
+
+        self.remove_old()
+    }
+
+    /// Rotated log files, sorted from the oldest to the newest
+    fn rotated_files(&self) -> io::Result<Vec<PathBuf>> {
+        let current = format!("{NAME}.log");
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            let Some(name) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if name != current && name.starts_with(NAME) && name.ends_with(".log") {
+                files.push(path);
+            }
+        }
+        // Rotated files are named by date or time, so sorting by name sorts them by time
+        files.sort();
+        Ok(files)
+    }
+
+    fn remove_old(&self) -> io::Result<()> {
+        let files = self.rotated_files()?;
+        let excess = files.len().saturating_sub(self.keep);
+        for file in &files[..excess] {
+            fs::remove_file(file)?;
+        }
+        Ok(())
+    }
+
+    /// Write a line to the log file, rotating it before if needed
+    pub fn write_line(&mut self, line: &str, now: DateTime<Local>) -> io::Result<()> {
+        if self.should_rotate(now) {
+            self.rotate(now)?;
+        }
+        writeln!(self.file, "{line}")?;
+        self.size += line.len() as u64 + 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeDelta;
+
+    use super::*;
+
+    fn config(content: &str) -> Config {
+        toml::from_str(content).unwrap()
+    }
+
+    fn file_names(log_file: &LogFile) -> Vec<String> {
+        log_file
+            .rotated_files()
+            .unwrap()
+            .iter()
+            .map(|path| path.file_name().unwrap().to_str().unwrap().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn daily_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Local::now();
+        let mut log_file = LogFile::open(dir.path().to_owned(), &config("keep = 2"), now).unwrap();
+
+        log_file.write_line("first day", now).unwrap();
+        log_file.write_line("first day again", now).unwrap();
+        assert!(file_names(&log_file).is_empty());
+
+        for day in 1..=3 {
+            let now = now + TimeDelta::days(day);
+            log_file.write_line("another day", now).unwrap();
+        }
+
+        let date = |day| (now + TimeDelta::days(day)).format("%Y-%m-%d");
+        assert_eq!(file_names(&log_file), [
+            format!("maa-cli.{}.log", date(1)),
+            format!("maa-cli.{}.log", date(2)),
+        ]);
+        assert_eq!(
+            fs::read_to_string(dir.path().join("maa-cli.log")).unwrap(),
+            "another day\n"
+        );
+    }
+
+    #[test]
+    fn size_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let now = Local::now();
+        let mut log_file = LogFile::open(
+            dir.path().to_owned(),
+            &config("rotation = \"size\"\nkeep = 3"),
+            now,
+        )
+        .unwrap();
+        // Rotate after every line to test without writing megabytes
+        log_file.max_size = 1;
+
+        for second in 0..5 {
+            log_file
+                .write_line("line", now + TimeDelta::seconds(second))
+                .unwrap();
+        }
+        assert_eq!(file_names(&log_file).len(), 3);
+
+        // The log file left by last run is rotated when it's too large
+        drop(log_file);
+        let log_file = LogFile::open(
+            dir.path().to_owned(),
+            &config("rotation = \"size\"\nmax_size = 0\nkeep = 0"),
+            now + TimeDelta::seconds(10),
+        )
+        .unwrap();
+        assert!(file_names(&log_file).is_empty());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("maa-cli.log")).unwrap(),
+            ""
+        );
+    }
+}
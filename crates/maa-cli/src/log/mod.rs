@@ -0,0 +1,642 @@
+mod file;
+
+use std::{
+    cell::Cell,
+    collections::VecDeque,
+    io::{IsTerminal, Write},
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+};
+
+use file::LogFile;
+
+/// Target of structured events, which are only logged in JSON format
+const EVENT_TARGET: &str = "maa_event";
+
+/// Number of recent log messages kept for notifications
+const RECENT_LEN: usize = 200;
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Whether messages in console are styled by colors, decided when the logger is initialized
+static COLORED: AtomicBool = AtomicBool::new(false);
+
+static VERBOSITY: OnceLock<log::LevelFilter> = OnceLock::new();
+
+/// Format of timestamps prefixed to logs in console, set by `log.timestamp` in cli.toml
+static TIMESTAMP: OnceLock<String> = OnceLock::new();
+
+/// Format of log messages
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone, Copy, Default, clap::ValueEnum)]
+enum LogFormat {
+    /// Human readable text
+    #[default]
+    Text,
+    /// One JSON object per line, with structured events of tasks
+    Json,
+}
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[arg(
+        short = 'v',
+        long,
+        action = clap::ArgAction::Count,
+        global = true,
+    )]
+    /// Increase verbosity, repeat for more verbosity
+    verbose: u8,
+    #[arg(
+        short = 'q',
+        long,
+        action = clap::ArgAction::Count,
+        global = true,
+    )]
+    /// Decrease verbosity, repeat for more quiet
+    quiet: u8,
+    /// Redirect log to file instead of stderr
+    ///
+    /// If no log file is specified, the log will be written to
+    /// `$(maa dir log)/YYYY/MM/DD/HH:MM:SS.log`.
+    #[arg(long, global = true, require_equals = true, value_name = "PATH")]
+    log_file: Option<Option<PathBuf>>,
+    /// Format of log messages
+    ///
+    /// In `json` format, each log message is an object with `time`, `level` and `message`,
+    /// and events of tasks, like the start and end of tasks and subtasks, drops and errors,
+    /// are logged as objects with `time`, `level`, `event` and `details` regardless of
+    /// the verbosity, so that maa-cli can be used by other programs.
+    #[arg(long, global = true, value_enum, default_value_t, verbatim_doc_comment)]
+    log_format: LogFormat,
+}
+
+impl Args {
+    fn log_level(&self) -> u8 {
+        let default_level = std::env::var_os("MAA_LOG")
+            .and_then(|s| s.to_str().and_then(|s| s.parse().ok()))
+            .unwrap_or(log::Level::Warn);
+
+        (default_level as u8 + self.verbose).saturating_sub(self.quiet)
+    }
+
+    fn to_filter(&self) -> log::LevelFilter {
+        use log::LevelFilter::*;
+        match self.log_level() {
+            0 => Off,
+            1 => Error,
+            2 => Warn,
+            3 => Info,
+            4 => Debug,
+            _ => Trace,
+        }
+    }
+
+    // Accessors only used in tests
+    // this function consumes entire self, while in init_logger()
+    // we need to use self.log_file, which only consumes part of self
+    #[cfg(test)]
+    fn log_file(self) -> Option<PathBuf> {
+        log_path(self.log_file)
+    }
+
+    pub fn init_logger(self) -> anyhow::Result<()> {
+        let mut builder = env_logger::Builder::new();
+
+        let _ = VERBOSITY.set(self.to_filter());
+        builder.filter_level(self.to_filter());
+        match self.log_format {
+            LogFormat::Text => {
+                builder.format(LogPrefix::from_env().format(self.log_file.is_some()));
+            }
+            LogFormat::Json => {
+                JSON.store(true, Ordering::Relaxed);
+                builder.filter(Some(EVENT_TARGET), log::LevelFilter::Trace);
+                builder.format(json_format);
+            }
+        }
+
+        // Messages styled by `color_print` are only colored if the console shows colors,
+        // the same choice is given to the logger, which styles the level of messages
+        let colored = matches!(self.log_format, LogFormat::Text)
+            && self.log_file.is_none()
+            && console_supports_color();
+        COLORED.store(colored, Ordering::Relaxed);
+        builder.write_style(if colored {
+            env_logger::WriteStyle::Always
+        } else {
+            env_logger::WriteStyle::Never
+        });
+
+        if let Some(path) = log_path(self.log_file) {
+            if let Some(dir) = path.parent() {
+                use crate::dirs::Ensure;
+                dir.ensure()?;
+            }
+            let file = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)?;
+            builder.target(env_logger::Target::Pipe(Box::new(file)));
+        }
+
+        let console = builder.build();
+        log::set_max_level(console.filter());
+        log::set_logger(LOGGER.get_or_init(|| Logger {
+            console,
+            file: OnceLock::new(),
+            recent: OnceLock::new(),
+        }))?;
+
+        // The log file is configured in cli.toml, which is loaded after the logger is set,
+        // so that warnings of the configuration are not lost
+        if let Some(logger) = LOGGER.get() {
+            let config = crate::config::cli::CLI_CONFIG.log_config();
+            let _ = TIMESTAMP.set(config.timestamp().to_owned());
+            logger.open_file(&config);
+        }
+
+        Ok(())
+    }
+}
+
+static LOGGER: OnceLock<Logger> = OnceLock::new();
+
+/// Logger writing to console and the log file in state directory if enabled
+struct Logger {
+    console: env_logger::Logger,
+    file: OnceLock<(log::LevelFilter, Mutex<LogFile>)>,
+    /// Recent messages of level info or higher, only kept if requested by `keep_recent`
+    recent: OnceLock<Mutex<VecDeque<String>>>,
+}
+
+impl Logger {
+    fn open_file(&self, config: &crate::config::cli::log::Config) {
+        if !config.enabled() {
+            return;
+        }
+
+        let dir = crate::dirs::state().join("log");
+        match LogFile::open(dir, config, chrono::Local::now()) {
+            Ok(file) => {
+                let level = config.level();
+                let _ = self.file.set((level, Mutex::new(file)));
+                log::set_max_level(log::max_level().max(level));
+            }
+            Err(err) => log::warn!("Failed to open log file: {err}"),
+        }
+    }
+
+    fn write_file(&self, level: log::Level, message: std::fmt::Arguments) {
+        let Some((max_level, file)) = self.file.get() else {
+            return;
+        };
+        if level > *max_level {
+            return;
+        }
+        let now = chrono::Local::now();
+        if let Ok(mut file) = file.lock() {
+            // Errors can not be logged here, and should not stop running tasks
+            let _ = file.write_line(&format_line(now, level, message), now);
+        }
+    }
+
+    fn push_recent(&self, level: log::Level, message: std::fmt::Arguments) {
+        let Some(recent) = self.recent.get() else {
+            return;
+        };
+        if level > log::Level::Info {
+            return;
+        }
+        let line = format_line(chrono::Local::now(), level, message);
+        if let Ok(mut recent) = recent.lock() {
+            if recent.len() == RECENT_LEN {
+                recent.pop_front();
+            }
+            recent.push_back(line);
+        }
+    }
+}
+
+fn format_line(
+    now: chrono::DateTime<chrono::Local>,
+    level: log::Level,
+    message: std::fmt::Arguments,
+) -> String {
+    format!(
+        "[{} {:<5}] {}",
+        now.format("%Y-%m-%d %H:%M:%S"),
+        level,
+        strip_ansi(&message.to_string())
+    )
+}
+
+/// Remove ANSI escape sequences like colors from a message, which are only for terminals
+fn strip_ansi(message: &str) -> String {
+    let mut stripped = String::with_capacity(message.len());
+    let mut chars = message.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            stripped.push(c);
+            continue;
+        }
+        // Control sequences are ended by a byte in range `@` to `~`
+        if chars.next() == Some('[') {
+            for c in chars.by_ref() {
+                if ('@'..='~').contains(&c) {
+                    break;
+                }
+            }
+        }
+    }
+    stripped
+}
+
+/// Keep recent log messages of level info or higher from now on, regardless of the verbosity
+pub fn keep_recent() {
+    if let Some(logger) = LOGGER.get() {
+        if logger.recent.set(Mutex::new(VecDeque::new())).is_ok() {
+            log::set_max_level(log::max_level().max(log::LevelFilter::Info));
+        }
+    }
+}
+
+/// Recent log messages kept since `keep_recent` is called, from the oldest to the newest
+pub fn recent() -> Vec<String> {
+    LOGGER
+        .get()
+        .and_then(|logger| logger.recent.get())
+        .and_then(|recent| recent.lock().ok())
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+thread_local! {
+    static CONSOLE_MUTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f` with logs of level info or lower hidden from the console
+///
+/// Warnings and errors are still printed, and all logs are still written to the log file.
+pub fn mute_console<T>(f: impl FnOnce() -> T) -> T {
+    let muted = CONSOLE_MUTED.replace(true);
+    let ret = f();
+    CONSOLE_MUTED.set(muted);
+    ret
+}
+
+/// Write an error only to the log file, which is printed to console by other ways
+pub fn error_to_file(err: &anyhow::Error) {
+    if let Some(logger) = LOGGER.get() {
+        logger.write_file(log::Level::Error, format_args!("{err:#}"));
+    }
+}
+
+impl log::Log for Logger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.console.enabled(metadata)
+            || self
+                .file
+                .get()
+                .is_some_and(|(level, _)| metadata.level() <= *level)
+            || (self.recent.get().is_some() && metadata.level() <= log::Level::Info)
+    }
+
+    fn log(&self, record: &log::Record) {
+        let muted = record.level() >= log::Level::Info
+            && record.target() != EVENT_TARGET
+            && CONSOLE_MUTED.get();
+        if !muted {
+            if self.console.enabled(record.metadata()) {
+                crate::run::progress::clear();
+            }
+            self.console.log(record);
+        }
+        // Events are only for other programs reading the console log
+        if record.target() != EVENT_TARGET {
+            self.write_file(record.level(), *record.args());
+            self.push_recent(record.level(), *record.args());
+        }
+    }
+
+    fn flush(&self) {
+        self.console.flush();
+    }
+}
+
+fn log_path(path: Option<Option<PathBuf>>) -> Option<PathBuf> {
+    path.map(|path| {
+        path.unwrap_or_else(|| {
+            let now = chrono::Local::now();
+            let dir = crate::dirs::log()
+                .join(now.format("%Y").to_string())
+                .join(now.format("%m").to_string())
+                .join(now.format("%d").to_string());
+
+            dir.join(format!("{}.log", now.format("%H:%M:%S")))
+        })
+    })
+}
+
+/// Whether or not to print log prefix [YYYY-MM-DD HH:MM:SS LEVEL]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone, Copy, Default)]
+enum LogPrefix {
+    /// Print log prefix if log to file, not print log prefix if log to stderr
+    Auto,
+    /// Always print log prefix
+    #[default]
+    Always,
+    /// Never print log prefix
+    Never,
+}
+
+impl LogPrefix {
+    fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "ALWAYS" | "Always" | "always" => Some(LogPrefix::Always),
+            "NEVER" | "Never" | "never" => Some(LogPrefix::Never),
+            "AUTO" | "Auto" | "auto" => Some(LogPrefix::Auto),
+            _ => None,
+        }
+    }
+
+    fn from_env() -> Self {
+        std::env::var_os("MAA_LOG_PREFIX")
+            .and_then(|s| s.to_str().and_then(LogPrefix::from_str))
+            .unwrap_or_default()
+    }
+
+    fn format(
+        &self,
+        log_file: bool,
+    ) -> fn(&mut env_logger::fmt::Formatter, &log::Record) -> std::io::Result<()> {
+        match self {
+            LogPrefix::Always => prefixed_format,
+            LogPrefix::Never => plain_format,
+            LogPrefix::Auto => {
+                if log_file {
+                    prefixed_format
+                } else {
+                    plain_format
+                }
+            }
+        }
+    }
+}
+
+fn prefixed_format(
+    buf: &mut env_logger::fmt::Formatter,
+    record: &log::Record,
+) -> std::io::Result<()> {
+    let timestamp = TIMESTAMP
+        .get()
+        .map_or(crate::config::cli::log::DEFAULT_TIMESTAMP, String::as_str);
+    if !timestamp.is_empty() {
+        write!(buf, "[{} ", chrono::Local::now().format(timestamp))?;
+    } else {
+        write!(buf, "[")?;
+    }
+    writeln!(
+        buf,
+        "{}{:<5}{}] {}",
+        buf.default_level_style(record.level()),
+        record.level(),
+        env_logger::fmt::style::Reset,
+        record.args()
+    )
+}
+
+fn plain_format(buf: &mut env_logger::fmt::Formatter, record: &log::Record) -> std::io::Result<()> {
+    writeln!(buf, "{}", record.args())
+}
+
+fn json_format(buf: &mut env_logger::fmt::Formatter, record: &log::Record) -> std::io::Result<()> {
+    // Events are already formatted as JSON objects
+    if record.target() == EVENT_TARGET {
+        return writeln!(buf, "{}", record.args());
+    }
+    writeln!(
+        buf,
+        "{}",
+        serde_json::json!({
+            "time": chrono::Local::now().to_rfc3339(),
+            "level": record.level().as_str(),
+            "message": strip_ansi(&record.args().to_string()),
+        })
+    )
+}
+
+/// Level of logs printed to console, given by `-v` and `-q`
+pub fn verbosity() -> log::LevelFilter {
+    VERBOSITY.get().copied().unwrap_or(log::LevelFilter::Warn)
+}
+
+/// Whether messages in console are styled by colors
+///
+/// Colors are disabled if the console is not a terminal, the logs are in JSON format or written
+/// to a file, or `NO_COLOR` is set.
+pub fn colored() -> bool {
+    COLORED.load(Ordering::Relaxed)
+}
+
+/// Whether stderr shows colors, which can be forced by `CLICOLOR_FORCE`
+fn console_supports_color() -> bool {
+    let set = |name| std::env::var_os(name).is_some_and(|value| !value.is_empty());
+    if set("NO_COLOR") {
+        return false;
+    }
+    if set("CLICOLOR_FORCE") {
+        return true;
+    }
+    std::io::stderr().is_terminal() && std::env::var_os("TERM").is_none_or(|term| term != "dumb")
+}
+
+/// Whether logs are in JSON format
+pub fn is_json() -> bool {
+    JSON.load(Ordering::Relaxed)
+}
+
+fn event_json(level: log::Level, event: &str, details: &serde_json::Value) -> serde_json::Value {
+    serde_json::json!({
+        "time": chrono::Local::now().to_rfc3339(),
+        "level": level.as_str(),
+        "event": event,
+        "details": details,
+    })
+}
+
+/// Log a structured event with given name and details, which is only logged in JSON format
+pub fn event(level: log::Level, event: &str, details: &serde_json::Value) {
+    if is_json() {
+        log::log!(target: EVENT_TARGET, level, "{}", event_json(level, event, details));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod cmd_args {
+        use std::env;
+
+        use crate::command::parse_from;
+
+        #[test]
+        fn to_filter() {
+            env::remove_var("MAA_LOG");
+
+            use log::LevelFilter::*;
+            assert_eq!(parse_from(["maa", "list"]).log.to_filter(), Warn);
+            assert_eq!(parse_from(["maa", "-v", "list"]).log.to_filter(), Info);
+            assert_eq!(parse_from(["maa", "list", "-v"]).log.to_filter(), Info);
+
+            assert_eq!(
+                parse_from(["maa", "list", "--verbose"]).log.to_filter(),
+                Info
+            );
+            assert_eq!(
+                parse_from(["maa", "--verbose", "list"]).log.to_filter(),
+                Info
+            );
+            assert_eq!(
+                parse_from(["maa", "list", "--quiet"]).log.to_filter(),
+                Error
+            );
+
+            assert_eq!(parse_from(["maa", "list", "-vvvv"]).log.to_filter(), Trace);
+            assert_eq!(parse_from(["maa", "list", "-vvv"]).log.to_filter(), Trace);
+            assert_eq!(parse_from(["maa", "list", "-vv"]).log.to_filter(), Debug);
+            assert_eq!(parse_from(["maa", "list", "-v"]).log.to_filter(), Info);
+            assert_eq!(parse_from(["maa", "list"]).log.to_filter(), Warn);
+            assert_eq!(parse_from(["maa", "list", "-vq"]).log.to_filter(), Warn);
+            assert_eq!(parse_from(["maa", "list", "-q"]).log.to_filter(), Error);
+            assert_eq!(parse_from(["maa", "list", "-qq"]).log.to_filter(), Off);
+            assert_eq!(parse_from(["maa", "list", "-qqq"]).log.to_filter(), Off);
+
+            assert_eq!(parse_from(["maa", "list", "-vv"]).log.to_filter(), Debug);
+
+            assert_eq!(parse_from(["maa", "list", "-q"]).log.to_filter(), Error);
+
+            env::set_var("MAA_LOG", "Info");
+            assert_eq!(parse_from(["maa", "list"]).log.to_filter(), Info);
+            env::set_var("MAA_LOG", "Debug");
+            assert_eq!(parse_from(["maa", "list"]).log.to_filter(), Debug);
+            env::set_var("MAA_LOG", "Trace");
+            assert_eq!(parse_from(["maa", "list"]).log.to_filter(), Trace);
+
+            env::remove_var("MAA_LOG");
+        }
+
+        #[test]
+        fn log_path() {
+            use std::path::Path;
+            assert!(parse_from(["maa", "list"]).log.log_file().is_none());
+            assert!(parse_from(["maa", "list", "--log-file"])
+                .log
+                .log_file()
+                .is_some_and(|x| {
+                    let now = chrono::Local::now();
+                    let dir = crate::dirs::log()
+                        .join(now.format("%Y").to_string())
+                        .join(now.format("%m").to_string())
+                        .join(now.format("%d").to_string());
+
+                    // the file name is dependent on the current time, it's hard to test
+                    x.starts_with(dir)
+                }));
+            assert!(parse_from(["maa", "list", "--log-file=path"])
+                .log
+                .log_file()
+                .is_some_and(|x| x == Path::new("path")));
+        }
+
+        #[test]
+        fn log_format() {
+            use crate::log::LogFormat;
+
+            assert_eq!(parse_from(["maa", "list"]).log.log_format, LogFormat::Text);
+            assert_eq!(
+                parse_from(["maa", "list", "--log-format", "json"])
+                    .log
+                    .log_format,
+                LogFormat::Json
+            );
+        }
+    }
+
+    #[test]
+    fn strip_ansi() {
+        assert_eq!(super::strip_ansi("plain"), "plain");
+        assert_eq!(
+            super::strip_ansi(&color_print::cformat!("<y,bold>★★★★</> 白面鸮(5★)")),
+            "★★★★ 白面鸮(5★)"
+        );
+    }
+
+    #[test]
+    fn event_json() {
+        let event = super::event_json(
+            log::Level::Info,
+            "TaskChainStart",
+            &serde_json::json!({ "taskchain": "Fight" }),
+        );
+        assert_eq!(event["level"], "INFO");
+        assert_eq!(event["event"], "TaskChainStart");
+        assert_eq!(event["details"]["taskchain"], "Fight");
+        assert!(event["time"].is_string());
+    }
+
+    mod log_prefix {
+        use super::*;
+
+        #[test]
+        fn from_str() {
+            assert_eq!(LogPrefix::from_str("Always"), Some(LogPrefix::Always));
+            assert_eq!(LogPrefix::from_str("always"), Some(LogPrefix::Always));
+            assert_eq!(LogPrefix::from_str("NEVER"), Some(LogPrefix::Never));
+            assert_eq!(LogPrefix::from_str("never"), Some(LogPrefix::Never));
+            assert_eq!(LogPrefix::from_str("AUTO"), Some(LogPrefix::Auto));
+            assert_eq!(LogPrefix::from_str("auto"), Some(LogPrefix::Auto));
+            assert_eq!(LogPrefix::from_str("unknown"), None);
+        }
+
+        #[test]
+        fn from_env() {
+            std::env::remove_var("MAA_LOG_PREFIX");
+            assert_eq!(LogPrefix::from_env(), LogPrefix::Always);
+
+            std::env::set_var("MAA_LOG_PREFIX", "Always");
+            assert_eq!(LogPrefix::from_env(), LogPrefix::Always);
+
+            std::env::set_var("MAA_LOG_PREFIX", "Never");
+            assert_eq!(LogPrefix::from_env(), LogPrefix::Never);
+
+            std::env::set_var("MAA_LOG_PREFIX", "Auto");
+            assert_eq!(LogPrefix::from_env(), LogPrefix::Auto);
+
+            std::env::set_var("MAA_LOG_PREFIX", "unknown");
+            assert_eq!(LogPrefix::from_env(), LogPrefix::Always);
+        }
+
+        #[test]
+        fn format() {
+            let pff = prefixed_format
+                as fn(&mut env_logger::fmt::Formatter, &log::Record) -> std::io::Result<()>;
+            let plf = plain_format
+                as fn(&mut env_logger::fmt::Formatter, &log::Record) -> std::io::Result<()>;
+
+            assert_eq!(LogPrefix::Always.format(true), pff);
+            assert_eq!(LogPrefix::Always.format(false), pff);
+
+            assert_eq!(LogPrefix::Never.format(true), plf);
+            assert_eq!(LogPrefix::Never.format(false), plf);
+
+            assert_eq!(LogPrefix::Auto.format(true), pff);
+            assert_eq!(LogPrefix::Auto.format(false), plf);
+        }
+    }
+}
@@ -8,8 +8,10 @@ mod activity;
 mod cleanup;
 mod command;
 mod config;
+mod i18n;
 mod installer;
 mod run;
+mod schedule;
 mod value;
 
 use anyhow::{Context, Result};
@@ -18,11 +20,41 @@ use clap::{CommandFactory, Parser};
 use crate::command::{Cli, Command, Component, Dir};
 
 fn main() -> Result<()> {
+    let ret = run_cli();
+
+    if let Err(err) = &ret {
+        crate::log::error_to_file(err);
+
+        // Errors are events as well, so that they can be handled by other programs
+        if crate::log::is_json() {
+            crate::log::event(
+                ::log::Level::Error,
+                "Error",
+                &serde_json::json!({ "message": format!("{err:#}") }),
+            );
+            std::process::exit(run::exit_code(err));
+        }
+
+        // Exit with a distinct code for each kind of failure, so that it can be handled by scripts
+        let code = run::exit_code(err);
+        if code != 1 {
+            eprintln!("Error: {err:?}");
+            std::process::exit(code);
+        }
+    }
+
+    ret
+}
+
+fn run_cli() -> Result<()> {
     let cli = command::Cli::parse();
 
     cli.log.init_logger()?;
 
-    if cli.batch {
+    if let Some(answers) = cli.answers.as_deref() {
+        config::task::answers::load(answers)?;
+        value::userinput::enable_strict_batch_mode()
+    } else if cli.batch {
         value::userinput::enable_batch_mode()
     }
 
@@ -75,7 +107,26 @@ fn main() -> Result<()> {
                 println!("MaaCore {}", run::core_version()?);
             }
         },
-        Command::Run { task, common } => run::run_custom(task, common)?,
+        Command::Run {
+            tasks,
+            task_json,
+            account,
+            sanity,
+            overrides,
+            today: true,
+            common,
+            ..
+        } => run::print_today(tasks, task_json, account, sanity, overrides, common)?,
+        Command::Run {
+            tasks,
+            task_json,
+            account,
+            sanity,
+            overrides,
+            resume,
+            common,
+            ..
+        } => run::run_custom(tasks, task_json, account, sanity, overrides, resume, common)?,
         Command::StartUp { params, common } => run::run_preset(params, common)?,
         Command::CloseDown { params, common } => run::run_preset(params, common)?,
         Command::Fight { params, common } => run::run_preset(params, common)?,
@@ -83,6 +134,14 @@ fn main() -> Result<()> {
         Command::Copilot { params, common } => run::run_preset(params, common)?,
         Command::SSSCopilot { params, common } => run::run_preset(params, common)?,
         Command::Reclamation { params, common } => run::run_preset(params, common)?,
+        Command::Infrast { params, common } => run::run_preset(params, common)?,
+        Command::Mall { params, common } => run::run_preset(params, common)?,
+        Command::Award { params, common } => run::run_preset(params, common)?,
+        Command::Recruit { params, common } => run::run_preset(params, common)?,
+        Command::Daily { params, common } => run::run_preset(params, common)?,
+        Command::Depot { args, common } => run::export::depot(args, common)?,
+        Command::OperBox { args, common } => run::export::operbox(args, common)?,
+        Command::ConvertVideo { args, common } => run::export::convert_video(args, common)?,
         Command::Convert {
             input,
             output,
@@ -100,18 +159,22 @@ fn main() -> Result<()> {
             );
         }
         Command::Cleanup { targets } => cleanup::cleanup(&targets)?,
-        Command::List => {
-            let task_dir = dirs::config().join("tasks");
-            if !task_dir.exists() {
-                eprintln!("No tasks found");
+        Command::List { names } => run::list::list(names)?,
+        Command::History { last, task } => run::history::show(last, task.as_deref())?,
+        Command::Daemon(daemon_c) => match daemon_c {
+            command::DaemonCommand::Start { common } => run::daemon::start(common)?,
+            command::DaemonCommand::Submit { task, account } => run::daemon::submit(task, account)?,
+        },
+        Command::Stop { target } => run::control::send(run::control::Request::Stop, target.pid)?,
+        Command::Pause { target } => run::control::send(run::control::Request::Pause, target.pid)?,
+        Command::Resume { target } => {
+            run::control::send(run::control::Request::Resume, target.pid)?
+        }
+        Command::Schedule { list } => {
+            if list {
+                schedule::list()?
             } else {
-                for entry in task_dir.read_dir()? {
-                    let entry = entry?;
-                    let path = entry.path();
-                    if path.is_file() {
-                        println!("{}", path.file_stem().unwrap().to_str().unwrap());
-                    }
-                }
+                schedule::run()?
             }
         }
         Command::Import {
@@ -119,6 +182,27 @@ fn main() -> Result<()> {
             force,
             config_type,
         } => config::import(&path, force, &config_type)?,
+        Command::Config(config_c) => match config_c {
+            command::ConfigCommand::Migrate => config::migrate::migrate()?,
+            command::ConfigCommand::Edit { config_type, name } => {
+                config::edit::edit(config_type, name.as_deref())?
+            }
+            command::ConfigCommand::Diff { config_type, name } => {
+                config::diff::diff(config_type, name.as_deref())?
+            }
+            command::ConfigCommand::NewTask {
+                name,
+                template,
+                interactive,
+                force,
+            } => config::template::new_task(&name, template, interactive, force)?,
+            command::ConfigCommand::Encrypt { config_type, name } => {
+                config::crypt::encrypt_file(config_type, name.as_deref())?
+            }
+            command::ConfigCommand::Decrypt { config_type, name } => {
+                config::crypt::decrypt_file(config_type, name.as_deref())?
+            }
+        },
         Command::Complete { shell } => {
             clap_complete::generate(shell, &mut Cli::command(), "maa", &mut std::io::stdout());
         }
@@ -1,13 +1,67 @@
 pub mod summary;
-use std::{fmt::Write, sync::atomic::AtomicBool};
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    sync::{atomic::AtomicBool, LazyLock, Mutex},
+};
 
+use color_print::cformat;
 use log::{debug, error, info, trace, warn};
-use maa_types::primitive::{AsstMsgId, AsstTaskId};
+use maa_types::primitive::{AsstAsyncCallId, AsstMsgId, AsstTaskId};
 use serde_json::{Map, Value};
-use summary::{edit_current_task_detail, end_current_task, start_task};
+use summary::{edit_current_task_detail, end_current_task, start_task, FormattedDuration};
+
+use super::{drops, farm, progress, report, sanity};
+use crate::{
+    config::{
+        cli::{
+            log::{MessageCategory, Messages},
+            CLI_CONFIG,
+        },
+        task::annihilation,
+    },
+    i18n::tr,
+};
 
 pub static MAA_CORE_ERRORED: AtomicBool = AtomicBool::new(false);
 
+/// Categories of messages to show or hide in console, set in `cli.toml`
+static MESSAGES: LazyLock<Messages> = LazyLock::new(|| CLI_CONFIG.log_config().messages().clone());
+
+/// Details of the latest message of recognition tasks like Depot and OperBox, keyed by taskchain
+///
+/// The recognition result is updated progressively, so only the latest one is kept.
+static RECOGNITION_RESULTS: Mutex<BTreeMap<String, Value>> = Mutex::new(BTreeMap::new());
+
+/// Ids of tasks failed with an error
+static ERRORED_TASKS: Mutex<Vec<AsstTaskId>> = Mutex::new(Vec::new());
+
+/// Ids of asynchronous calls like connecting which are finished, successfully or not
+static FINISHED_CALLS: Mutex<Vec<AsstAsyncCallId>> = Mutex::new(Vec::new());
+
+/// Accounts to switch to by StartUp tasks, keyed by task id
+static ACCOUNTS: Mutex<BTreeMap<AsstTaskId, String>> = Mutex::new(BTreeMap::new());
+
+/// Record the account to switch to by the StartUp task with given id
+pub fn expect_account(id: AsstTaskId, account: &str) {
+    ACCOUNTS.lock().unwrap().insert(id, account.to_owned());
+}
+
+/// Whether the task with given id failed with an error
+pub fn task_errored(id: AsstTaskId) -> bool {
+    ERRORED_TASKS.lock().unwrap().contains(&id)
+}
+
+/// Whether the asynchronous call with given id is finished
+pub fn async_call_finished(id: AsstAsyncCallId) -> bool {
+    FINISHED_CALLS.lock().unwrap().contains(&id)
+}
+
+/// Take the latest recognition result of given taskchain
+pub fn take_recognition_result(taskchain: &str) -> Option<Value> {
+    RECOGNITION_RESULTS.lock().unwrap().remove(taskchain)
+}
+
 pub unsafe extern "C" fn default_callback(
     code: AsstMsgId,
     json_raw: *const ::std::os::raw::c_char,
@@ -19,6 +73,7 @@ pub unsafe extern "C" fn default_callback(
 }
 
 #[repr(i32)]
+#[derive(Clone, Copy)]
 enum AsstMsg {
     /* Global Info */
     InternalError = 0,
@@ -73,34 +128,125 @@ impl From<AsstMsgId> for AsstMsg {
     }
 }
 
+impl AsstMsg {
+    const fn to_str(self) -> &'static str {
+        match self {
+            AsstMsg::InternalError => "InternalError",
+            AsstMsg::InitFailed => "InitFailed",
+            AsstMsg::ConnectionInfo => "ConnectionInfo",
+            AsstMsg::AllTasksCompleted => "AllTasksCompleted",
+            AsstMsg::AsyncCallInfo => "AsyncCallInfo",
+            AsstMsg::Destroyed => "Destroyed",
+            AsstMsg::TaskChainError => "TaskChainError",
+            AsstMsg::TaskChainStart => "TaskChainStart",
+            AsstMsg::TaskChainCompleted => "TaskChainCompleted",
+            AsstMsg::TaskChainExtraInfo => "TaskChainExtraInfo",
+            AsstMsg::TaskChainStopped => "TaskChainStopped",
+            AsstMsg::SubTaskError => "SubTaskError",
+            AsstMsg::SubTaskStart => "SubTaskStart",
+            AsstMsg::SubTaskCompleted => "SubTaskCompleted",
+            AsstMsg::SubTaskExtraInfo => "SubTaskExtraInfo",
+            AsstMsg::SubTaskStopped => "SubTaskStopped",
+            AsstMsg::Unknown => "Unknown",
+        }
+    }
+
+    const fn is_error(self) -> bool {
+        matches!(
+            self,
+            AsstMsg::InternalError
+                | AsstMsg::InitFailed
+                | AsstMsg::TaskChainError
+                | AsstMsg::SubTaskError
+        )
+    }
+}
+
 fn process_message(code: AsstMsgId, json: Value) {
+    // Every message is an event, including the ones not processed below
+    let msg = AsstMsg::from(code);
+    let level = if msg.is_error() {
+        log::Level::Error
+    } else {
+        log::Level::Info
+    };
+    crate::log::event(level, msg.to_str(), &json);
+
     if !json.is_object() {
         return;
     }
 
     let message = json.as_object().unwrap();
 
+    // Messages of hidden categories are still processed for the summary and others
+    let ret = if MESSAGES.shows(category(msg, message), crate::log::verbosity()) {
+        process_object(msg, message)
+    } else {
+        crate::log::mute_console(|| process_object(msg, message))
+    };
+
+    // if ret is None, which means the message is not processed well
+    // we should print the message to trace the error
+    if ret.is_none() {
+        debug!(
+            "FailedToProcessMessage, code: {}, message: {}",
+            code,
+            serde_json::to_string_pretty(message).unwrap()
+        )
+    }
+}
+
+/// Category of a message, by its type and the task chain and details it's about
+fn category(msg: AsstMsg, message: &Map<String, Value>) -> MessageCategory {
+    use AsstMsg::*;
+
+    match msg {
+        ConnectionInfo => return MessageCategory::Connection,
+        AllTasksCompleted | TaskChainError | TaskChainStart | TaskChainCompleted
+        | TaskChainStopped => return MessageCategory::Task,
+        _ => {}
+    }
+    if message.get("what").and_then(Value::as_str) == Some("StageDrops") {
+        return MessageCategory::Drops;
+    }
+    match message.get("taskchain").and_then(Value::as_str) {
+        Some("Fight") => MessageCategory::Fight,
+        Some("Recruit") => MessageCategory::Recruit,
+        Some("Infrast") => MessageCategory::Infrast,
+        Some("Roguelike") => MessageCategory::Roguelike,
+        Some("Copilot" | "SSSCopilot" | "ParadoxCopilot" | "VideoRecognition") => {
+            MessageCategory::Copilot
+        }
+        _ => MessageCategory::Other,
+    }
+}
+
+fn process_object(msg: AsstMsg, message: &Map<String, Value>) -> Option<()> {
     use AsstMsg::*;
 
-    let ret = match code.into() {
+    match msg {
         InternalError => Some(()),
         InitFailed => {
-            error!("InitializationError");
+            error!("{}", tr!("initialization-error"));
             Some(())
         }
         ConnectionInfo => process_connection_info(message),
         AllTasksCompleted => {
-            info!("AllTasksCompleted");
+            info!("{}", tr!("all-tasks-completed"));
+            Some(())
+        }
+        AsyncCallInfo => {
+            let id = message.get("async_call_id")?.as_i64()?;
+            FINISHED_CALLS.lock().unwrap().push(id as AsstAsyncCallId);
             Some(())
         }
-        AsyncCallInfo => Some(()),
         Destroyed => {
             debug!("Instance destroyed");
             Some(())
         }
 
         TaskChainError | TaskChainStart | TaskChainCompleted | TaskChainExtraInfo
-        | TaskChainStopped => process_taskchain(code.into(), message),
+        | TaskChainStopped => process_taskchain(msg, message),
 
         SubTaskError => process_subtask_error(message),
         SubTaskStart => process_subtask_start(message),
@@ -109,16 +255,6 @@ fn process_message(code: AsstMsgId, json: Value) {
         SubTaskStopped => Some(()),
 
         Unknown => None,
-    };
-
-    // if ret is None, which means the message is not processed well
-    // we should print the message to trace the error
-    if ret.is_none() {
-        debug!(
-            "FailedToProcessMessage, code: {}, message: {}",
-            code,
-            serde_json::to_string_pretty(message).unwrap()
-        )
     }
 }
 
@@ -131,9 +267,12 @@ fn process_connection_info(message: &Map<String, Value>) -> Option<()> {
             message.get("details")?.get("uuid")?.as_str()?
         ),
         "ConnectFailed" => error!(
-            "Failed to connect to android device, {}, Please check your connect configuration: {}",
-            message.get("why")?.as_str()?,
-            serde_json::to_string_pretty(message.get("details")?).unwrap()
+            "{}",
+            tr!(
+                "connect-failed",
+                why = message.get("why")?.as_str()?,
+                details = serde_json::to_string_pretty(message.get("details")?).unwrap(),
+            )
         ),
         // Resolution
         "ResolutionGot" => debug!(
@@ -141,27 +280,30 @@ fn process_connection_info(message: &Map<String, Value>) -> Option<()> {
             message.get("details")?.get("width")?.as_i64()?,
             message.get("details")?.get("height")?.as_i64()?
         ),
-        "UnsupportedResolution" => error!("{}", "UnsupportedResolution"),
-        "ResolutionError" => error!("{}", "ResolutionAcquisitionFailure"),
+        "UnsupportedResolution" => error!("{}", tr!("unsupported-resolution")),
+        "ResolutionError" => error!("{}", tr!("resolution-error")),
 
         // Connection
-        "Connected" => info!("{}", "Connected"),
-        "Disconnect" => warn!("{}", "Disconnected"),
+        "Connected" => info!("{}", tr!("connected")),
+        "Disconnect" => warn!("{}", tr!("disconnected")),
         "Reconnecting" => warn!(
-            "{} {} {}",
-            "Reconnect",
-            message.get("details")?.get("times")?.as_i64()?,
-            "times"
+            "{}",
+            tr!(
+                "reconnecting",
+                times = message.get("details")?.get("times")?.as_i64()?
+            )
         ),
-        "Reconnected" => info!("{}", "ReconnectSuccess"),
+        "Reconnected" => info!("{}", tr!("reconnected")),
 
         // Screen Capture
-        "ScreencapFailed" => error!("{}", "ScreencapFailed"),
+        "ScreencapFailed" => error!("{}", tr!("screencap-failed")),
         "FastestWayToScreencap" => info!(
-            "{} {} {}",
-            "FastestWayToScreencap",
-            message.get("details")?.get("method")?.as_str()?,
-            message.get("details")?.get("cost")?.as_i64()?,
+            "{}",
+            tr!(
+                "fastest-way-to-screencap",
+                method = message.get("details")?.get("method")?.as_str()?,
+                cost = message.get("details")?.get("cost")?.as_i64()?,
+            )
         ),
         "ScreencapCost" => debug!(
             "{} {} ({} ~ {})",
@@ -171,7 +313,7 @@ fn process_connection_info(message: &Map<String, Value>) -> Option<()> {
             message.get("details")?.get("max")?.as_i64()?,
         ),
 
-        "TouchModeNotAvailable" => error!("{}", "TouchModeNotAvailable"),
+        "TouchModeNotAvailable" => error!("{}", tr!("touch-mode-not-available")),
         _ => {
             trace!(
                 "{}: {}",
@@ -191,20 +333,46 @@ fn process_taskchain(code: AsstMsg, message: &Map<String, Value>) -> Option<()>
 
     match code {
         TaskChainStart => {
-            info!("{} {}", taskchain, "Start");
+            info!("{}", tr!("taskchain-start", taskchain = taskchain));
             start_task(message.get("taskid")?.as_i64()? as AsstTaskId);
         }
-        TaskChainCompleted => {
-            info!("{} {}", taskchain, "Completed");
-            end_current_task(summary::Reason::Completed);
-        }
-        TaskChainStopped => {
-            warn!("{} {}", taskchain, "Stopped");
-            end_current_task(summary::Reason::Stopped);
-        }
+        TaskChainCompleted => match end_current_task(summary::Reason::Completed) {
+            Some(duration) => info!(
+                "{}",
+                tr!(
+                    "taskchain-completed-in",
+                    taskchain = taskchain,
+                    duration = FormattedDuration::from(duration).to_string()
+                )
+            ),
+            None => info!("{}", tr!("taskchain-completed", taskchain = taskchain)),
+        },
+        TaskChainStopped => match end_current_task(summary::Reason::Stopped) {
+            Some(duration) => warn!(
+                "{}",
+                tr!(
+                    "taskchain-stopped-after",
+                    taskchain = taskchain,
+                    duration = FormattedDuration::from(duration).to_string()
+                )
+            ),
+            None => warn!("{}", tr!("taskchain-stopped", taskchain = taskchain)),
+        },
         TaskChainError => {
-            error!("{} {}", taskchain, "Error");
-            end_current_task(summary::Reason::Error);
+            match end_current_task(summary::Reason::Error) {
+                Some(duration) => error!(
+                    "{}",
+                    tr!(
+                        "taskchain-error-after",
+                        taskchain = taskchain,
+                        duration = FormattedDuration::from(duration).to_string()
+                    )
+                ),
+                None => error!("{}", tr!("taskchain-error", taskchain = taskchain)),
+            }
+            if let Some(id) = message.get("taskid").and_then(Value::as_i64) {
+                ERRORED_TASKS.lock().unwrap().push(id as AsstTaskId);
+            }
             MAA_CORE_ERRORED.store(true, std::sync::atomic::Ordering::Relaxed);
         }
         TaskChainExtraInfo => {}
@@ -219,15 +387,32 @@ fn process_subtask_error(message: &Map<String, Value>) -> Option<()> {
     let subtask = message.get("subtask")?.as_str()?;
 
     match subtask {
-        "StartGameTask" => error!("{}", "FailedToStartGame"),
-        "AutoRecruitTask" => error!("{} {}", message.get("why")?.as_str()?, "HasReturned"),
-        "RecognizeDrops" => error!("{}", "FailedToRecognizeDrops"),
+        "StartGameTask" => error!("{}", tr!("failed-to-start-game")),
+        "AutoRecruitTask" => error!(
+            "{}",
+            tr!("recruit-returned", why = message.get("why")?.as_str()?)
+        ),
+        "RecognizeDrops" => error!("{}", tr!("failed-to-recognize-drops")),
         "ReportToPenguinStats" => error!(
-            "{}, {}",
-            "FailedToReportToPenguinStats",
-            message.get("why")?.as_str()?,
+            "{}",
+            tr!(
+                "failed-to-report-to-penguin-stats",
+                why = message.get("why")?.as_str()?
+            )
         ),
-        "CheckStageValid" => error!("TheEX"),
+        "CheckStageValid" => error!("{}", tr!("stage-not-supported")),
+        "AccountSwitchTask" => {
+            let account = message
+                .get("taskid")
+                .and_then(Value::as_i64)
+                .and_then(|id| ACCOUNTS.lock().unwrap().get(&(id as AsstTaskId)).cloned());
+            match account {
+                Some(account) => {
+                    error!("{}", tr!("failed-to-switch-account-to", account = account))
+                }
+                None => error!("{}", tr!("failed-to-switch-account")),
+            }
+        }
         _ => trace!(
             "{}: {}",
             "UnknownSubTaskError",
@@ -254,7 +439,8 @@ fn process_subtask_start(message: &Map<String, Value>) -> Option<()> {
                         detail.set_times(exec_times);
                     }
                 });
-                info!("{} {} {}", "MissionStart", exec_times, "times");
+                progress::times(exec_times);
+                info!("{}", tr!("mission-start-times", times = exec_times));
             }
             "StoneConfirm" => {
                 let exec_times = details.get("exec_times")?.as_i64()?;
@@ -263,9 +449,9 @@ fn process_subtask_start(message: &Map<String, Value>) -> Option<()> {
                         detail.set_stone(exec_times)
                     }
                 });
-                info!("Use {} stones", exec_times);
+                info!("{}", tr!("use-stones", count = exec_times));
             }
-            "AbandonAction" => warn!("{}", "PRTS error"),
+            "AbandonAction" => warn!("{}", tr!("prts-error")),
             // Recruit
             "RecruitRefreshConfirm" => {
                 edit_current_task_detail(|detail| {
@@ -273,7 +459,8 @@ fn process_subtask_start(message: &Map<String, Value>) -> Option<()> {
                         detail.refresh()
                     }
                 });
-                info!("{}", "Refresh Tags")
+                progress::step(tr!("progress-refreshing-tags"));
+                info!("{}", tr!("refresh-tags"))
             }
             "RecruitConfirm" => {
                 edit_current_task_detail(|detail| {
@@ -281,10 +468,13 @@ fn process_subtask_start(message: &Map<String, Value>) -> Option<()> {
                         detail.recruit()
                     }
                 });
-                info!("{}", "Recruit")
+                progress::step(tr!("progress-recruiting"));
+                info!("{}", tr!("recruit"))
             }
             // Infrast
-            "InfrastDormDoubleConfirmButton" => warn!("{}", "InfrastDormDoubleConfirmed"),
+            "InfrastDormDoubleConfirmButton" => {
+                warn!("{}", tr!("infrast-dorm-double-confirmed"))
+            }
             // RogueLike
             "StartExplore" => {
                 let exec_times = details.get("exec_times")?.as_i64()?;
@@ -293,7 +483,8 @@ fn process_subtask_start(message: &Map<String, Value>) -> Option<()> {
                         detail.start_exploration()
                     }
                 });
-                info!("Start exploration {} times", exec_times)
+                progress::step(tr!("progress-exploration", times = exec_times));
+                info!("{}", tr!("start-exploration", times = exec_times))
             }
             "ExitThenAbandon" => {
                 edit_current_task_detail(|detail| {
@@ -301,10 +492,10 @@ fn process_subtask_start(message: &Map<String, Value>) -> Option<()> {
                         detail.set_state(summary::ExplorationState::Abandoned)
                     }
                 });
-                info!("Exploration Abandoned")
+                info!("{}", tr!("exploration-abandoned"))
             }
-            "ExitThenConfirm" => info!("{}", "ExplorationConfirmed"),
-            "MissionCompletedFlag" => info!("{}", "MissionCompleted"),
+            "ExitThenConfirm" => info!("{}", tr!("exploration-confirmed")),
+            "MissionCompletedFlag" => info!("{}", tr!("mission-completed")),
             "MissionFailedFlag" => {
                 // Deposit In some cases a failed mission doesn't mean failed exploration
                 // If a exploration was not failed, it's state would be overwritten later
@@ -315,19 +506,23 @@ fn process_subtask_start(message: &Map<String, Value>) -> Option<()> {
                         }
                     });
                 }
-                info!("MissionFailed")
+                info!("{}", tr!("mission-failed"))
+            }
+            "StageTraderEnter" => info!("{}", tr!("stage-trader-enter")),
+            "StageSafeHouseEnter" => info!("{}", tr!("stage-safe-house-enter")),
+            "StageCambatDpsEnter" => info!("{}", tr!("stage-combat-dps-enter")),
+            "StageEmergencyDps" => info!("{}", tr!("stage-emergency-dps-enter")),
+            "StageDreadfulFoe" | "StageDreadfulFoe-5Enter" => {
+                info!("{}", tr!("stage-dreadful-foe-enter"))
+            }
+            "StageTraderInvestSystemFull" => warn!("{}", tr!("trader-invest-system-full")),
+            "GamePass" => info!("{}", tr!("roguelike-game-pass")),
+
+            "OfflineConfirm" => warn!("{}", tr!("game-offline")),
+            "BattleStartAll" => info!("{}", tr!("mission-start")),
+            "StageTraderSpecialShoppingAfterRefresh" => {
+                info!("{}", tr!("roguelike-special-item-bought"))
             }
-            "StageTraderEnter" => info!("{}", "StageTraderEnter"),
-            "StageSafeHouseEnter" => info!("{}", "StageSafeHouseEnter"),
-            "StageCambatDpsEnter" => info!("{}", "StageCambatDpsEnter"),
-            "StageEmergencyDps" => info!("{}", "EmergencyDpsEnter"),
-            "StageDreadfulFoe" | "StageDreadfulFoe-5Enter" => info!("{}", "DreadfulFoe"),
-            "StageTraderInvestSystemFull" => warn!("{}", "TraderInvestSystemFull"),
-            "GamePass" => info!("{}", "RoguelikeGamePass"),
-
-            "OfflineConfirm" => warn!("{}", "GameOffline"),
-            "BattleStartAll" => info!("{}", "MissionStart"),
-            "StageTraderSpecialShoppingAfterRefresh" => info!("{}", "RoguelikeSpecialItemBought"),
             _ => trace!(
                 "{}: {}",
                 "UnknownSubTaskStart",
@@ -344,18 +539,26 @@ fn process_subtask_completed(_: &Map<String, Value>) -> Option<()> {
 fn process_subtask_extra_info(message: &Map<String, Value>) -> Option<()> {
     let taskchain = message.get("taskchain")?.as_str()?;
 
-    match taskchain {
-        "Depot" => info!(
-            "{}: {}",
-            "Depot",
-            serde_json::to_string_pretty(message).unwrap()
-        ),
-        "OperBox" => info!(
+    let keep_details = match taskchain {
+        "Depot" | "OperBox" => true,
+        // Only the message with the path of generated copilot file is kept
+        "VideoRecognition" => message
+            .get("details")
+            .is_some_and(|details| details.get("filename").is_some()),
+        _ => false,
+    };
+    if keep_details {
+        debug!(
             "{}: {}",
-            "OperBox",
+            taskchain,
             serde_json::to_string_pretty(message).unwrap()
-        ),
-        _ => {}
+        );
+        if let Some(details) = message.get("details") {
+            RECOGNITION_RESULTS
+                .lock()
+                .unwrap()
+                .insert(taskchain.to_owned(), details.clone());
+        }
     }
 
     let what = message.get("what")?.as_str()?;
@@ -363,6 +566,10 @@ fn process_subtask_extra_info(message: &Map<String, Value>) -> Option<()> {
 
     match what {
         "StageDrops" => {
+            if let Some(id) = message.get("taskid").and_then(Value::as_i64) {
+                report::record(id as AsstTaskId, details);
+            }
+            drops::record(details);
             let drops = details.get("drops")?.as_array()?;
             let mut all_drops = summary::Map::new();
             for drop in drops {
@@ -373,13 +580,15 @@ fn process_subtask_extra_info(message: &Map<String, Value>) -> Option<()> {
             }
 
             info!(
-                "{}: {}",
-                "Drops",
-                all_drops
-                    .iter()
-                    .map(|(item, count)| format!("{} × {}", item, count))
-                    .join(", ")
-                    .unwrap_or_else(|| "none".to_owned())
+                "{}",
+                tr!(
+                    "drops",
+                    drops = all_drops
+                        .iter()
+                        .map(|(item, count)| format!("{} × {}", item, count))
+                        .join(", ")
+                        .unwrap_or_else(|| tr!("none"))
+                )
             );
 
             edit_current_task_detail(|detail| {
@@ -388,20 +597,54 @@ fn process_subtask_extra_info(message: &Map<String, Value>) -> Option<()> {
                 }
             });
 
+            let item_drops = drops.iter().filter_map(|drop| {
+                let drop = drop.as_object()?;
+                Some((
+                    drop.get("itemId")?.as_str()?,
+                    drop.get("quantity")?.as_i64()?,
+                ))
+            });
+            if let Some(stage_id) = details
+                .get("stage")
+                .and_then(|stage| stage.get("stageId"))
+                .and_then(Value::as_str)
+            {
+                annihilation::record_drops(stage_id, item_drops.clone());
+            }
+
+            if let Some(target) = farm::record(item_drops) {
+                info!(
+                    "{}",
+                    tr!(
+                        "farmed",
+                        item = target.item,
+                        farmed = target.farmed,
+                        quantity = target.quantity,
+                    )
+                );
+            }
+
             let stage = details.get("stage")?.get("stageCode")?.as_str()?;
             edit_current_task_detail(|detail| {
                 if let Some(detail) = detail.as_fight_mut() {
                     detail.set_stage(stage);
                 }
             });
+            progress::stage(stage);
         }
 
         // Sanity and Medicines
-        "SanityBeforeStage" => info!(
-            "Current sanity: {}/{}",
-            details.get("current_sanity")?.as_i64()?,
-            details.get("max_sanity")?.as_i64()?
-        ),
+        "SanityBeforeStage" => {
+            let current = details.get("current_sanity")?.as_i64()?;
+            let max = details.get("max_sanity")?.as_i64()?;
+            sanity::record(current, max);
+            edit_current_task_detail(|detail| {
+                if let Some(detail) = detail.as_fight_mut() {
+                    detail.record_sanity(current);
+                }
+            });
+            info!("{}", tr!("current-sanity", current = current, max = max))
+        }
         "UseMedicine" => {
             let count = details.get("count")?.as_i64()?;
             let is_expiring = details.get("is_expiring")?.as_bool()?;
@@ -412,23 +655,35 @@ fn process_subtask_extra_info(message: &Map<String, Value>) -> Option<()> {
             });
 
             if is_expiring {
-                info!("Use {} expiring medicine", count);
+                info!("{}", tr!("use-expiring-medicine", count = count));
             } else {
-                info!("Use {} medicine", count);
+                info!("{}", tr!("use-medicine", count = count));
             }
         }
 
         // Infrast
-        "EnterFacility" => info!(
-            "{} {} #{}",
-            "EnterFacility",
-            details.get("facility")?.as_str()?,
-            details.get("index")?.as_i64()?,
-        ),
-        "ProductIncorrect" => warn!("{}", "ProductIncorrect"),
-        "ProductUnknown" => error!("{}", "ProductUnknown"),
-        "ProductChanged" => info!("{}", "ProductChanged"),
-        "NotEnoughStaff" => error!("{}", "NotEnoughStaff"),
+        "EnterFacility" => {
+            let facility = details.get("facility")?.as_str()?;
+            let index = details.get("index")?.as_i64()?;
+            edit_current_task_detail(|detail| {
+                if let Some(detail) = detail.as_infrast_mut() {
+                    detail.enter(facility.parse().unwrap(), index);
+                }
+            });
+            progress::step(tr!(
+                "progress-entering-facility",
+                facility = facility,
+                index = index
+            ));
+            info!(
+                "{}",
+                tr!("enter-facility", facility = facility, index = index)
+            )
+        }
+        "ProductIncorrect" => warn!("{}", tr!("product-incorrect")),
+        "ProductUnknown" => error!("{}", tr!("product-unknown")),
+        "ProductChanged" => info!("{}", tr!("product-changed")),
+        "NotEnoughStaff" => error!("{}", tr!("not-enough-staff")),
         "ProductOfFacility" => {
             let facility = details.get("facility")?.as_str()?;
             let index = details.get("index")?.as_i64()?;
@@ -440,7 +695,7 @@ fn process_subtask_extra_info(message: &Map<String, Value>) -> Option<()> {
                 }
             });
 
-            info!("{}: {}", "ProductOfFacility", product)
+            info!("{}", tr!("product-of-facility", product = product))
         }
         "CustomInfrastRoomOperators" => {
             let facility = details.get("facility")?.as_str()?;
@@ -466,22 +721,26 @@ fn process_subtask_extra_info(message: &Map<String, Value>) -> Option<()> {
             });
 
             info!(
-                "{}: {}",
-                "CustomInfrastRoomOperators",
-                details
-                    .get("names")?
-                    .as_array()?
-                    .iter()
-                    .filter_map(|x| x.as_str())
-                    .join(", ")
-                    .unwrap_or_else(|| "none".to_owned())
+                "{}",
+                tr!(
+                    "custom-infrast-room-operators",
+                    operators = details
+                        .get("names")?
+                        .as_array()?
+                        .iter()
+                        .filter_map(|x| x.as_str())
+                        .join(", ")
+                        .unwrap_or_else(|| tr!("none"))
+                )
             )
         }
 
         // Recruit
         "RecruitTagsDetected" => (), // this info is contained in RecruitResult, so ignore it
-        "RecruitSpecialTag" => info!("{}: {}", "RecruitingTips", details.get("tag")?.as_str()?),
-        "RecruitRobotTag" => info!("{}: {}", "RecruitingTips", details.get("tag")?.as_str()?),
+        "RecruitSpecialTag" | "RecruitRobotTag" => info!(
+            "{}",
+            tr!("recruiting-tips", tag = details.get("tag")?.as_str()?)
+        ),
         "RecruitResult" => {
             let level = details.get("level")?.as_u64()?;
             let tags = details.get("tags")?.as_array()?;
@@ -495,32 +754,36 @@ fn process_subtask_extra_info(message: &Map<String, Value>) -> Option<()> {
                 }
             });
 
-            info!(
-                "{}: {} {}",
-                "RecruitResult",
-                "★".repeat(level as usize),
-                tags.iter()
+            for line in recruit_result(details)? {
+                info!("{}", line);
+            }
+        }
+        "RecruitTagsSelected" => info!(
+            "{}",
+            tr!(
+                "recruit-tags-selected",
+                tags = details
+                    .get("tags")?
+                    .as_array()?
+                    .iter()
                     .filter_map(|x| x.as_str())
                     .join(", ")
-                    .unwrap_or_else(|| "none".to_owned())
+                    .unwrap_or_else(|| tr!("none"))
             )
-        }
-        "RecruitTagsSelected" => info!("{}: {}", "RecruitTagsSelected", {
-            details
-                .get("tags")?
-                .as_array()?
-                .iter()
-                .filter_map(|x| x.as_str())
-                .join(", ")
-                .unwrap_or_else(|| "none".to_owned())
-        }),
-        "RecruitTagsRefreshed" => info!("{}: {}", "RecruitTagsRefreshed", {
-            let count = details.get("count")?.as_i64()?;
-            format!("{} times", count)
-        }),
+        ),
+        "RecruitTagsRefreshed" => info!(
+            "{}",
+            tr!(
+                "recruit-tags-refreshed",
+                count = details.get("count")?.as_i64()?
+            )
+        ),
         // RogueLike
-        "StageInfo" => info!("{} {}", "StartCombat", details.get("name")?.as_str()?),
-        "StageInfoError" => error!("{}", "StageInfoError"),
+        "StageInfo" => info!(
+            "{}",
+            tr!("stage-info", stage = details.get("name")?.as_str()?)
+        ),
+        "StageInfoError" => error!("{}", tr!("stage-info-error")),
         "RoguelikeInvestment" => {
             let count = details.get("count")?.as_i64()?;
             let total = details.get("total")?.as_i64()?;
@@ -532,7 +795,15 @@ fn process_subtask_extra_info(message: &Map<String, Value>) -> Option<()> {
                 }
             });
 
-            info!("Deposit {count} / {total} / {deposit} originium ingots")
+            info!(
+                "{}",
+                tr!(
+                    "roguelike-investment",
+                    count = count,
+                    total = total,
+                    deposit = deposit,
+                )
+            )
         }
         "RoguelikeSettlement" => {
             let exp = details.get("exp")?.as_i64()?;
@@ -541,37 +812,76 @@ fn process_subtask_extra_info(message: &Map<String, Value>) -> Option<()> {
                     detail.set_exp(exp)
                 }
             });
-            info!("Gain {} exp during this exploration", exp);
+            info!("{}", tr!("roguelike-settlement", exp = exp));
         }
 
         // Copilot
         "BattleFormation" => info!(
-            "{} {}",
-            "BattleFormation",
-            details
-                .get("formation")?
-                .as_array()?
-                .iter()
-                .filter_map(|x| x.as_str())
-                .join(", ")
-                .unwrap_or_else(|| "none".to_owned())
+            "{}",
+            tr!(
+                "battle-formation",
+                operators = details
+                    .get("formation")?
+                    .as_array()?
+                    .iter()
+                    .filter_map(|x| x.as_str())
+                    .join(", ")
+                    .unwrap_or_else(|| tr!("none"))
+            )
         ),
         "BattleFormationSelected" => info!(
-            "{} {}",
-            "BattleFormationSelected",
-            details.get("selected")?.as_str()?
+            "{}",
+            tr!(
+                "battle-formation-selected",
+                operator = details.get("selected")?.as_str()?
+            )
         ),
         "CopilotAction" => info!(
-            "{} {} {}",
-            "CurrentSteps",
-            details.get("action")?.as_str()?,
-            details.get("target")?.as_str()?,
+            "{}",
+            tr!(
+                "copilot-action",
+                action = details.get("action")?.as_str()?,
+                target = details.get("target")?.as_str()?,
+            )
         ),
         // SSS
-        "SSSStage" => info!("{} {}", "CurrentStage", details.get("stage")?.as_str()?),
-        "SSSSettlement" => info!("{} {}", "SSSSettlement", details.get("why")?.as_str()?),
-        "SSSGamePass" => info!("{}", "SSSGamePass"),
-        "UnsupportedLevel" => error!("{}", "UnsupportedLevel"),
+        "SSSStage" => {
+            let stage = details.get("stage")?.as_str()?;
+            edit_current_task_detail(|detail| {
+                if let Some(detail) = detail.as_sss_copilot_mut() {
+                    detail.start_stage(stage);
+                }
+            });
+            info!("{}", tr!("current-stage", stage = stage))
+        }
+        "SSSSettlement" => {
+            let why = details.get("why")?.as_str()?;
+            edit_current_task_detail(|detail| {
+                if let Some(detail) = detail.as_sss_copilot_mut() {
+                    detail.settle(why);
+                }
+            });
+            info!("{}", tr!("sss-settlement", why = why))
+        }
+        "SSSGamePass" => {
+            edit_current_task_detail(|detail| {
+                if let Some(detail) = detail.as_sss_copilot_mut() {
+                    detail.pass();
+                }
+            });
+            info!("{}", tr!("sss-game-pass"))
+        }
+        "UnsupportedLevel" => error!("{}", tr!("unsupported-level")),
+
+        // StartUp
+        "AccountSwitch" => info!(
+            "{}",
+            tr!(
+                "account-switched",
+                from = details.get("current_account")?.as_str()?,
+                to = details.get("account_name")?.as_str()?,
+            )
+        ),
         _ => {
             trace!(
                 "{}: {}",
@@ -584,6 +894,96 @@ fn process_subtask_extra_info(message: &Map<String, Value>) -> Option<()> {
     Some(())
 }
 
+/// Stars of given rarity, highlighted in gold for 4★ and above and in red for 1★
+fn rarity(level: u64) -> String {
+    let stars = "★".repeat(level as usize);
+    match level {
+        _ if !crate::log::colored() => stars,
+        4.. => cformat!("<y,bold>{stars}</>"),
+        1 => cformat!("<r,bold>{stars}</>"),
+        _ => stars,
+    }
+}
+
+/// Format the result of recruitment of a slot, including the detected tags,
+/// the best tag combination and possible operators of each tag combination
+fn recruit_result(details: &Value) -> Option<Vec<String>> {
+    let level = details.get("level")?.as_u64()?;
+    let tags = details
+        .get("tags")?
+        .as_array()?
+        .iter()
+        .filter_map(|x| x.as_str())
+        .join(", ")
+        .unwrap_or_else(|| tr!("none"));
+    let combinations = details
+        .get("result")
+        .and_then(Value::as_array)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+
+    let mut lines = vec![
+        tr!("recruit-result", stars = rarity(level)),
+        format!("  {}", tr!("recruit-result-tags", tags = tags)),
+    ];
+    // Combinations are sorted by MaaCore, but the best one is searched to be robust
+    let best = combinations
+        .iter()
+        .filter_map(|combination| Some((combination.get("level")?.as_u64()?, combination)))
+        .fold(
+            None,
+            |best: Option<(u64, &Value)>, (level, combination)| match best {
+                Some((best_level, _)) if best_level >= level => best,
+                _ => Some((level, combination)),
+            },
+        );
+    if let Some((level, combination)) = best {
+        let tags = combination
+            .get("tags")?
+            .as_array()?
+            .iter()
+            .filter_map(|x| x.as_str())
+            .join(", ")
+            .unwrap_or_else(|| tr!("none"));
+        lines.push(format!(
+            "  {}",
+            tr!("recruit-result-best", tags = tags, stars = rarity(level))
+        ));
+    }
+    lines.extend(combinations.iter().filter_map(recruit_combination));
+    Some(lines)
+}
+
+/// Format a tag combination of recruitment with possible operators
+///
+/// 1★ operators are highlighted in red, as selecting the tags risks getting one of them.
+fn recruit_combination(combination: &Value) -> Option<String> {
+    let level = combination.get("level")?.as_u64()?;
+    let tags = combination
+        .get("tags")?
+        .as_array()?
+        .iter()
+        .filter_map(|x| x.as_str())
+        .join(", ")?;
+    let opers = combination
+        .get("opers")?
+        .as_array()?
+        .iter()
+        .filter_map(|oper| {
+            let name = oper.get("name")?.as_str()?;
+            let level = oper.get("level")?.as_u64()?;
+            Some(if level == 1 && crate::log::colored() {
+                cformat!("<r>{name}({level}★)</>")
+            } else {
+                format!("{name}({level}★)")
+            })
+        })
+        .join(", ")
+        .unwrap_or_else(|| tr!("none"));
+
+    Some(format!("  {} {tags}: {opers}", rarity(level)))
+}
+
 trait IterJoin: Iterator {
     fn join(&mut self, sep: &str) -> Option<String>
     where
@@ -615,4 +1015,112 @@ mod tests {
         assert_eq!([1, 2, 3].iter().join(","), Some("1,2,3".to_owned()));
         assert_eq!(Vec::<i32>::new().iter().join(","), None);
     }
+
+    #[test]
+    fn message_category() {
+        fn category_of(msg: AsstMsg, message: Value) -> MessageCategory {
+            category(msg, message.as_object().unwrap())
+        }
+
+        use serde_json::json;
+        use AsstMsg::*;
+        use MessageCategory::*;
+
+        assert_eq!(
+            category_of(ConnectionInfo, json!({ "what": "ScreencapCost" })),
+            Connection
+        );
+        assert_eq!(
+            category_of(TaskChainStart, json!({ "taskchain": "Fight" })),
+            Task
+        );
+        assert_eq!(
+            category_of(
+                SubTaskExtraInfo,
+                json!({ "taskchain": "Fight", "what": "StageDrops" })
+            ),
+            Drops
+        );
+        assert_eq!(
+            category_of(SubTaskStart, json!({ "taskchain": "Fight" })),
+            Fight
+        );
+        assert_eq!(
+            category_of(SubTaskExtraInfo, json!({ "taskchain": "SSSCopilot" })),
+            Copilot
+        );
+        assert_eq!(
+            category_of(SubTaskExtraInfo, json!({ "taskchain": "Mall" })),
+            Other
+        );
+    }
+
+    // Messages are not colored without a terminal
+    #[test]
+    fn format_recruit_combination() {
+        let combination = serde_json::json!({
+            "tags": ["高级资深干员", "输出"],
+            "level": 6,
+            "opers": [
+                { "name": "艾雅法拉", "level": 6 },
+                { "name": "能天使", "level": 6 },
+            ],
+        });
+        assert_eq!(
+            recruit_combination(&combination).unwrap(),
+            "  ★★★★★★ 高级资深干员, 输出: 艾雅法拉(6★), 能天使(6★)"
+        );
+
+        let combination = serde_json::json!({
+            "tags": ["支援机械"],
+            "level": 1,
+            "opers": [{ "name": "Lancet-2", "level": 1 }],
+        });
+        assert_eq!(
+            recruit_combination(&combination).unwrap(),
+            "  ★ 支援机械: Lancet-2(1★)"
+        );
+
+        assert!(recruit_combination(&serde_json::json!({ "level": 3 })).is_none());
+    }
+
+    #[test]
+    fn format_recruit_result() {
+        let details = serde_json::json!({
+            "tags": ["近卫干员", "输出", "支援机械", "远程位", "治疗"],
+            "level": 4,
+            "result": [
+                {
+                    "tags": ["近卫干员", "输出"],
+                    "level": 3,
+                    "opers": [{ "name": "芙兰卡", "level": 5 }, { "name": "杜宾", "level": 4 }],
+                },
+                {
+                    "tags": ["远程位", "治疗"],
+                    "level": 4,
+                    "opers": [{ "name": "白面鸮", "level": 5 }],
+                },
+                {
+                    "tags": ["支援机械"],
+                    "level": 1,
+                    "opers": [{ "name": "Lancet-2", "level": 1 }],
+                },
+            ],
+        });
+        assert_eq!(recruit_result(&details).unwrap(), vec![
+            "Recruit result: ★★★★",
+            "  Tags: 近卫干员, 输出, 支援机械, 远程位, 治疗",
+            "  Best: 远程位, 治疗 → ★★★★",
+            "  ★★★ 近卫干员, 输出: 芙兰卡(5★), 杜宾(4★)",
+            "  ★★★★ 远程位, 治疗: 白面鸮(5★)",
+            "  ★ 支援机械: Lancet-2(1★)",
+        ]);
+
+        let details = serde_json::json!({ "tags": [], "level": 3 });
+        assert_eq!(recruit_result(&details).unwrap(), vec![
+            "Recruit result: ★★★".to_owned(),
+            "  Tags: none".to_owned(),
+        ]);
+        assert!(recruit_result(&serde_json::json!({ "tags": [] })).is_none());
+    }
 }
@@ -1,11 +1,12 @@
 pub use std::collections::BTreeMap as Map;
-use std::sync::Mutex;
+use std::{collections::BTreeSet, sync::Mutex};
 
 use chrono;
 use maa_sys::TaskType;
 use maa_types::primitive::AsstTaskId;
 
 use super::IterJoin;
+use crate::i18n::tr;
 
 static SUMMARY: Mutex<Option<Summary>> = Mutex::new(None);
 
@@ -26,74 +27,147 @@ fn with_summary_mut<T>(f: impl FnOnce(&mut Summary) -> T) -> Option<T> {
     SUMMARY.lock().unwrap().as_mut().map(f)
 }
 
+pub(crate) fn insert(id: AsstTaskId, name: Option<String>, task: TaskType) -> Option<()> {
+    with_summary_mut(|summary| summary.insert(id, name, task))
+}
+
+/// Record a task which is skipped without being appended, e.g. because its dependency failed
+pub(crate) fn skip(name: Option<String>, task: TaskType) -> Option<()> {
+    with_summary_mut(|summary| summary.skip(name, task))
+}
+
 pub(crate) fn display() -> Option<()> {
     with_summary(|summary| print!("{}", summary))
 }
 
+/// Summary as text and tasks in JSON, used by notifications
+pub(crate) fn export() -> Option<(String, serde_json::Value)> {
+    with_summary(|summary| (summary.to_string(), summary.to_json()))
+}
+
 pub(super) fn start_task(id: AsstTaskId) -> Option<()> {
     with_summary_mut(|summary| summary.start_task(id)).flatten()
 }
 
-pub(super) fn end_current_task(reason: Reason) -> Option<()> {
+/// End the running task with given reason, returning how long it took
+pub(super) fn end_current_task(reason: Reason) -> Option<chrono::Duration> {
     with_summary_mut(|summary| summary.end_current_task(reason)).flatten()
 }
 
+/// Mark the running task as timed out, before it's stopped
+pub(crate) fn time_out_current_task() -> Option<()> {
+    end_current_task(Reason::TimedOut).map(|_| ())
+}
+
 pub(super) fn edit_current_task_detail(f: impl FnOnce(&mut Detail)) -> Option<()> {
     with_summary_mut(|summary| summary.edit_current_task_detail(f)).flatten()
 }
 
 pub struct Summary {
-    task_summarys: Map<AsstTaskId, TaskSummary>,
+    /// Summaries of tasks in the order they are appended or skipped,
+    /// with ids of appended tasks, skipped tasks have no id
+    task_summarys: Vec<(Option<AsstTaskId>, TaskSummary)>,
     current_task: Option<AsstTaskId>,
 }
 
 impl Summary {
     pub fn new() -> Self {
         Self {
-            task_summarys: Map::new(),
+            task_summarys: Vec::new(),
             current_task: None,
         }
     }
 
     pub fn insert(&mut self, id: AsstTaskId, name: Option<String>, task: impl Into<TaskType>) {
         self.task_summarys
-            .insert(id, TaskSummary::new(name, task.into()));
+            .push((Some(id), TaskSummary::new(name, task.into())));
+    }
+
+    fn skip(&mut self, name: Option<String>, task: impl Into<TaskType>) {
+        let mut summary = TaskSummary::new(name, task.into());
+        summary.reason = Reason::Skipped;
+        self.task_summarys.push((None, summary));
+    }
+
+    fn tasks(&self) -> impl Iterator<Item = &TaskSummary> + Clone {
+        self.task_summarys.iter().map(|(_, summary)| summary)
+    }
+
+    fn get_mut(&mut self, id: AsstTaskId) -> Option<&mut TaskSummary> {
+        self.task_summarys
+            .iter_mut()
+            .find(|(task_id, _)| *task_id == Some(id))
+            .map(|(_, summary)| summary)
     }
 
     fn current_mut(&mut self) -> Option<&mut TaskSummary> {
-        self.current_task
-            .and_then(|id| self.task_summarys.get_mut(&id))
+        self.current_task.and_then(|id| self.get_mut(id))
     }
 
     fn start_task(&mut self, id: AsstTaskId) -> Option<()> {
-        self.task_summarys.get_mut(&id).map(|summary| {
-            self.current_task = Some(id);
-            summary.start();
-        })
+        self.get_mut(id)?.start();
+        self.current_task = Some(id);
+        Some(())
     }
 
-    fn end_current_task(&mut self, reason: Reason) -> Option<()> {
-        self.current_mut()
-            .map(|summary| summary.end(reason))
-            .map(|_| self.current_task = None)
+    fn end_current_task(&mut self, reason: Reason) -> Option<chrono::Duration> {
+        let duration = self.current_mut().map(|summary| summary.end(reason))?;
+        self.current_task = None;
+        duration
     }
 
     fn edit_current_task_detail(&mut self, f: impl FnOnce(&mut Detail)) -> Option<()> {
         self.current_mut().map(|summary| summary.edit_detail(f))
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        self.tasks().map(TaskSummary::to_json).collect()
+    }
 }
 
 const LINE_SEP: &str = "----------------------------------------";
 
 impl std::fmt::Display for Summary {
-    // we print literal but it will be replace by a localizable string, so it's fine
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if !self.task_summarys.is_empty() {
-            #[allow(clippy::write_literal)]
-            writeln!(f, "{}", "Summary")?;
-            for task_summary in self.task_summarys.values() {
+            writeln!(f, "{}", tr!("summary"))?;
+            for task_summary in self.tasks() {
                 write!(f, "{LINE_SEP}\n{task_summary}")?;
             }
+
+            let overview = Overview::new(self);
+            if overview.duration.is_some() {
+                write!(f, "{LINE_SEP}\n{overview}")?;
+            }
+
+            // Overview of stages in a copilot battle list
+            let copilots: Vec<&TaskSummary> = self
+                .tasks()
+                .filter(|summary| summary.task == TaskType::Copilot)
+                .collect();
+            if copilots.len() > 1 {
+                let completed = copilots
+                    .iter()
+                    .filter(|summary| matches!(summary.reason, Reason::Completed))
+                    .count();
+                writeln!(
+                    f,
+                    "{LINE_SEP}\n{}",
+                    tr!(
+                        "copilot-completed",
+                        completed = completed,
+                        total = copilots.len()
+                    )
+                )?;
+                if let Some(failed) = copilots
+                    .iter()
+                    .filter(|summary| !matches!(summary.reason, Reason::Completed))
+                    .map(|summary| summary.name.as_deref().unwrap_or(summary.task.to_str()))
+                    .join(", ")
+                {
+                    writeln!(f, "{}", tr!("not-completed", tasks = failed))?;
+                }
+            }
         }
         Ok(())
     }
@@ -117,6 +191,7 @@ impl TaskSummary {
             Infrast => Detail::Infrast(InfrastDetail::new()),
             Recruit => Detail::Recruit(RecruitDetail::new()),
             Roguelike => Detail::Roguelike(RoguelikeDetail::new()),
+            SSSCopilot => Detail::SSSCopilot(SSSCopilotDetail::new()),
             _ => Detail::None,
         };
 
@@ -135,14 +210,38 @@ impl TaskSummary {
         self.reason = Reason::Unfinished;
     }
 
-    fn end(&mut self, reason: Reason) {
-        self.end_time = Some(chrono::Local::now());
+    /// End the task, returning the time since it started if it did
+    fn end(&mut self, reason: Reason) -> Option<chrono::Duration> {
+        let end = chrono::Local::now();
+        self.end_time = Some(end);
         self.reason = reason;
+        self.start_time.map(|start| end - start)
     }
 
     fn edit_detail(&mut self, f: impl FnOnce(&mut Detail)) {
         f(&mut self.detail);
     }
+
+    fn to_json(&self) -> serde_json::Value {
+        let mut value = serde_json::json!({
+            "name": self.name.as_deref().unwrap_or(self.task.to_str()),
+            "type": self.task.to_str(),
+            "result": self.reason.to_str(),
+            "start_time": self.start_time.map(|time| time.to_rfc3339()),
+            "end_time": self.end_time.map(|time| time.to_rfc3339()),
+            "duration": self
+                .start_time
+                .zip(self.end_time)
+                .map(|(start, end)| (end - start).num_seconds()),
+        });
+        if let Detail::Fight(fight) = &self.detail {
+            value["stage"] = fight.stage.as_deref().into();
+            value["times"] = fight.times.into();
+            value["sanity"] = fight.sanity_spent().into();
+            value["drops"] = serde_json::json!(fight.total_drops());
+        }
+        value
+    }
 }
 
 impl std::fmt::Display for TaskSummary {
@@ -166,15 +265,7 @@ impl std::fmt::Display for TaskSummary {
             (None, None) => Ok(()),
         }?;
 
-        match self.reason {
-            Reason::Completed => write!(f, " Completed")?,
-            Reason::Stopped => write!(f, " Stopped")?,
-            Reason::Error => write!(f, " Error")?,
-            Reason::Unfinished => write!(f, " Unfinished")?,
-            Reason::Unstarted => write!(f, " Unstarted")?,
-        }
-
-        writeln!(f)?;
+        writeln!(f, " {}", self.reason.title())?;
 
         if !matches!(self.detail, Detail::None) {
             write!(f, "{}", self.detail)?;
@@ -184,15 +275,206 @@ impl std::fmt::Display for TaskSummary {
     }
 }
 
+/// Overview of a run, aggregated from the details of all tasks
+struct Overview {
+    /// Time from the start of the first task to the end of the last one
+    duration: Option<chrono::Duration>,
+    /// Number of tasks of each result
+    results: Map<&'static str, usize>,
+    /// Times of battles of each stage
+    stages: Map<String, i64>,
+    /// Sanity spent by fight tasks whose cost of a battle is known
+    sanity: Option<i64>,
+    drops: Map<String, i64>,
+    /// Number of recruitments of each level
+    recruited: Map<u64, i64>,
+    refreshed: i64,
+    /// Number of rooms whose operators are shifted
+    shifted_rooms: usize,
+}
+
+impl Overview {
+    fn new(summary: &Summary) -> Self {
+        let tasks = summary.tasks();
+        let start = tasks.clone().filter_map(|task| task.start_time).min();
+        let end = tasks.clone().filter_map(|task| task.end_time).max();
+
+        let mut overview = Self {
+            duration: start.zip(end).map(|(start, end)| end - start),
+            results: Map::new(),
+            stages: Map::new(),
+            sanity: None,
+            drops: Map::new(),
+            recruited: Map::new(),
+            refreshed: 0,
+            shifted_rooms: 0,
+        };
+
+        for task in tasks {
+            *overview.results.entry(task.reason.to_str()).or_default() += 1;
+            match &task.detail {
+                Detail::Fight(fight) => {
+                    if let (Some(stage), Some(times)) = (&fight.stage, fight.times) {
+                        insert_or_add_by_ref(&mut overview.stages, stage, times);
+                    }
+                    if let Some(sanity) = fight.sanity_spent() {
+                        overview.sanity = Some(overview.sanity.unwrap_or_default() + sanity);
+                    }
+                    for (item, count) in fight.total_drops() {
+                        insert_or_add_by_ref(&mut overview.drops, &item, count);
+                    }
+                }
+                Detail::Recruit(recruit) => {
+                    for (level, _, state) in &recruit.record {
+                        if let RecruitState::Recruited = state {
+                            *overview.recruited.entry(*level).or_default() += 1;
+                        }
+                    }
+                    overview.refreshed += recruit.refresh_times.unwrap_or_default();
+                }
+                Detail::Infrast(infrast) => overview.shifted_rooms += infrast.shifted_rooms(),
+                _ => {}
+            }
+        }
+
+        overview
+    }
+}
+
+fn format_counts<K: std::fmt::Display>(counts: impl IntoIterator<Item = (K, i64)>) -> String {
+    counts
+        .into_iter()
+        .map(|(key, count)| format!("{key} × {count}"))
+        .join(", ")
+        .unwrap_or_default()
+}
+
+impl std::fmt::Display for Overview {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", tr!("overview"))?;
+        if let Some(duration) = self.duration {
+            writeln!(
+                f,
+                "{}",
+                tr!(
+                    "overview-duration",
+                    duration = FormattedDuration::from(duration).to_string()
+                )
+            )?;
+        }
+        // Results are sorted by the order of `Reason`, not by name
+        let results = [
+            Reason::Completed,
+            Reason::Stopped,
+            Reason::Error,
+            Reason::TimedOut,
+            Reason::Unfinished,
+            Reason::Unstarted,
+            Reason::Skipped,
+        ]
+        .iter()
+        .filter_map(|reason| {
+            let count = self.results.get(reason.to_str())?;
+            Some(reason.count(*count))
+        })
+        .join(", ")
+        .unwrap_or_default();
+        writeln!(f, "{}", tr!("overview-tasks", results = results))?;
+        if !self.stages.is_empty() {
+            let stages = format_counts(self.stages.iter().map(|(stage, times)| (stage, *times)));
+            writeln!(f, "{}", tr!("overview-stages", stages = stages))?;
+        }
+        if let Some(sanity) = self.sanity {
+            writeln!(f, "{}", tr!("overview-sanity", sanity = sanity))?;
+        }
+        if !self.drops.is_empty() {
+            let drops = format_counts(self.drops.iter().map(|(item, count)| (item, *count)));
+            writeln!(f, "{}", tr!("drops", drops = drops))?;
+        }
+        if !self.recruited.is_empty() || self.refreshed > 0 {
+            let total: i64 = self.recruited.values().sum();
+            let recruit = if self.recruited.is_empty() {
+                tr!(
+                    "overview-recruit",
+                    total = total,
+                    refreshed = self.refreshed
+                )
+            } else {
+                // Higher levels first, as they are rarer
+                let levels = format_counts(
+                    self.recruited
+                        .iter()
+                        .rev()
+                        .map(|(level, count)| ("★".repeat(*level as usize), *count)),
+                );
+                tr!(
+                    "overview-recruit-levels",
+                    total = total,
+                    levels = levels,
+                    refreshed = self.refreshed
+                )
+            };
+            writeln!(f, "{recruit}")?;
+        }
+        if self.shifted_rooms > 0 {
+            writeln!(f, "{}", tr!("overview-infrast", rooms = self.shifted_rooms))?;
+        }
+        Ok(())
+    }
+}
+
 pub(super) enum Reason {
     Completed,
     Stopped,
     Error,
+    TimedOut,
     Unstarted,
     Unfinished,
+    /// Not run because its dependency failed or the run is aborted
+    Skipped,
 }
 
-struct FormattedDuration {
+impl Reason {
+    fn to_str(&self) -> &'static str {
+        match self {
+            Reason::Completed => "completed",
+            Reason::Stopped => "stopped",
+            Reason::Error => "error",
+            Reason::TimedOut => "timed out",
+            Reason::Unstarted => "unstarted",
+            Reason::Unfinished => "unfinished",
+            Reason::Skipped => "skipped",
+        }
+    }
+
+    /// Localized name of the result, e.g. `Completed`
+    fn title(&self) -> String {
+        match self {
+            Reason::Completed => tr!("result-completed"),
+            Reason::Stopped => tr!("result-stopped"),
+            Reason::Error => tr!("result-error"),
+            Reason::TimedOut => tr!("result-timed-out"),
+            Reason::Unstarted => tr!("result-unstarted"),
+            Reason::Unfinished => tr!("result-unfinished"),
+            Reason::Skipped => tr!("result-skipped"),
+        }
+    }
+
+    /// Localized number of tasks with the result, e.g. `2 completed`
+    fn count(&self, count: usize) -> String {
+        match self {
+            Reason::Completed => tr!("overview-completed", count = count),
+            Reason::Stopped => tr!("overview-stopped", count = count),
+            Reason::Error => tr!("overview-error", count = count),
+            Reason::TimedOut => tr!("overview-timed-out", count = count),
+            Reason::Unstarted => tr!("overview-unstarted", count = count),
+            Reason::Unfinished => tr!("overview-unfinished", count = count),
+            Reason::Skipped => tr!("overview-skipped", count = count),
+        }
+    }
+}
+
+pub(super) struct FormattedDuration {
     hours: i64,
     minutes: i64,
     seconds: i64,
@@ -244,6 +526,7 @@ pub enum Detail {
     Fight(FightDetail),
     Recruit(RecruitDetail),
     Roguelike(RoguelikeDetail),
+    SSSCopilot(SSSCopilotDetail),
 }
 
 impl Detail {
@@ -278,6 +561,14 @@ impl Detail {
             None
         }
     }
+
+    pub fn as_sss_copilot_mut(&mut self) -> Option<&mut SSSCopilotDetail> {
+        if let Detail::SSSCopilot(detail) = self {
+            Some(detail)
+        } else {
+            None
+        }
+    }
 }
 
 impl std::fmt::Display for Detail {
@@ -288,13 +579,18 @@ impl std::fmt::Display for Detail {
             Detail::Infrast(detail) => detail.fmt(f)?,
             Detail::Recruit(detail) => detail.fmt(f)?,
             Detail::Roguelike(detail) => detail.fmt(f)?,
+            Detail::SSSCopilot(detail) => detail.fmt(f)?,
         }
 
         Ok(())
     }
 }
 
-pub struct InfrastDetail(Map<Facility, Map<i64, InfrastRoomInfo>>);
+pub struct InfrastDetail {
+    rooms: Map<Facility, Map<i64, InfrastRoomInfo>>,
+    // rooms entered to shift operators
+    entered: BTreeSet<(Facility, i64)>,
+}
 
 struct InfrastRoomInfo {
     product: Option<String>,
@@ -304,14 +600,36 @@ struct InfrastRoomInfo {
 
 impl InfrastDetail {
     pub fn new() -> Self {
-        Self(Map::new())
+        Self {
+            rooms: Map::new(),
+            entered: BTreeSet::new(),
+        }
+    }
+
+    pub(super) fn enter(&mut self, facility: Facility, id: i64) {
+        self.entered.insert((facility, id));
+    }
+
+    /// Number of rooms entered or set with operators
+    fn shifted_rooms(&self) -> usize {
+        let with_operators = self.rooms.iter().flat_map(|(facility, map)| {
+            map.iter()
+                .filter(|(_, room_info)| !room_info.operators.is_empty())
+                .map(|(id, _)| (*facility, *id))
+        });
+        self.entered
+            .iter()
+            .copied()
+            .chain(with_operators)
+            .collect::<BTreeSet<_>>()
+            .len()
     }
 
     pub(super) fn set_product(&mut self, facility: Facility, id: i64, info: &str) {
         use Facility::*;
         // only the product of Mfg and Trade is useful
         if matches!(facility, Mfg | Trade) {
-            self.0
+            self.rooms
                 .entry(facility)
                 .or_default()
                 .entry(id)
@@ -327,7 +645,7 @@ impl InfrastDetail {
         operators: Vec<String>,
         candidates: Vec<String>,
     ) {
-        let map = self.0.entry(facility).or_default();
+        let map = self.rooms.entry(facility).or_default();
 
         if let Some(room_info) = map.get_mut(&id) {
             room_info.set_operators(operators, candidates);
@@ -342,7 +660,7 @@ impl InfrastDetail {
 
 impl std::fmt::Display for InfrastDetail {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        for (facility, map) in &self.0 {
+        for (facility, map) in &self.rooms {
             for room_info in map.values() {
                 writeln!(f, "{}{}", facility, room_info)?;
             }
@@ -443,14 +761,12 @@ impl std::fmt::Display for InfrastRoomInfo {
         if let Some(info) = self.product.as_ref() {
             write!(f, "({})", info)?;
         }
-        write!(
-            f,
-            " with operators: {}",
-            self.operators
-                .iter()
-                .join(", ")
-                .unwrap_or_else(|| "unknown".to_owned())
-        )?;
+        let operators = self
+            .operators
+            .iter()
+            .join(", ")
+            .unwrap_or_else(|| tr!("unknown"));
+        write!(f, " {}", tr!("infrast-operators", operators = operators))?;
         if !self.candidates.is_empty() {
             write!(
                 f,
@@ -472,6 +788,10 @@ pub struct FightDetail {
     medicine: Option<(i64, i64)>,
     // used stone
     stone: Option<i64>,
+    // sanity reported before the last battle, and whether it's restored after that
+    last_sanity: Option<(i64, bool)>,
+    // sanity cost of a battle, estimated by the drop of sanity between two battles
+    sanity_cost: Option<i64>,
     // [(item, count), ...], each element is corresponding to a fight
     // the length of this vector may smaller than times,
     // because some fight may not drop anything or failed to recognize the drop
@@ -485,6 +805,8 @@ impl FightDetail {
             times: None,
             medicine: None,
             stone: None,
+            last_sanity: None,
+            sanity_cost: None,
             drops: Vec::new(),
         }
     }
@@ -506,53 +828,87 @@ impl FightDetail {
         if is_expiring {
             expiring += count
         }
-        self.medicine = Some((all, expiring))
+        self.medicine = Some((all, expiring));
+        self.restore_sanity();
     }
 
     pub fn set_stone(&mut self, stone: i64) {
         self.stone = Some(stone);
+        self.restore_sanity();
+    }
+
+    /// Record the sanity reported before a battle
+    pub fn record_sanity(&mut self, current: i64) {
+        if let (Some((last, false)), None) = (self.last_sanity, self.sanity_cost) {
+            if last > current {
+                self.sanity_cost = Some(last - current);
+            }
+        }
+        self.last_sanity = Some((current, false));
+    }
+
+    fn restore_sanity(&mut self) {
+        if let Some((_, restored)) = &mut self.last_sanity {
+            *restored = true;
+        }
+    }
+
+    /// Sanity spent by all battles, if the cost of a battle is known
+    fn sanity_spent(&self) -> Option<i64> {
+        let times = self.times.unwrap_or(self.drops.len() as i64);
+        Some(self.sanity_cost? * times)
     }
 
     pub fn push_drop(&mut self, drop: Map<String, i64>) {
         self.drops.push(drop);
     }
+
+    fn total_drops(&self) -> Map<String, i64> {
+        let mut total_drops = Map::new();
+        for (item, count) in self.drops.iter().flatten() {
+            insert_or_add_by_ref(&mut total_drops, item, *count);
+        }
+        total_drops
+    }
 }
 
 impl std::fmt::Display for FightDetail {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some(stage) = self.stage.as_ref() {
-            write!(f, "Fight {stage}")?;
+            write!(f, "{}", tr!("fight-stage", stage = stage.as_str()))?;
         } else {
             return Ok(());
         }
 
         if let Some(times) = self.times {
-            write!(f, " {times} times")?;
+            write!(f, " {}", tr!("fight-times", times = times))?;
         }
         if let Some((all, expiring)) = self.medicine {
-            write!(f, ", used {all} medicine ({expiring} expiring)")?;
+            write!(
+                f,
+                ", {}",
+                tr!("fight-medicine", count = all, expiring = expiring)
+            )?;
         }
         if let Some(stone) = self.stone {
-            write!(f, ", used {stone} stone")?;
+            write!(f, ", {}", tr!("fight-stone", count = stone))?;
         }
         if !self.drops.is_empty() {
-            writeln!(f, ", drops:")?;
-            let mut total_drop = Map::new();
+            writeln!(f, ", {}", tr!("fight-drops"))?;
             for (i, drop) in self.drops.iter().enumerate() {
                 write!(f, "{}.", i + 1)?;
                 let mut iter = drop.iter();
                 if let Some((item, count)) = iter.next() {
                     write!(f, " {} × {}", item, count)?;
-                    insert_or_add_by_ref(&mut total_drop, item, *count);
                 }
                 for (item, count) in iter {
                     write!(f, ", {} × {}", item, count)?;
-                    insert_or_add_by_ref(&mut total_drop, item, *count);
                 }
                 writeln!(f)?;
             }
-            write!(f, "total drops:")?;
-            let mut iter = total_drop.iter();
+            write!(f, "{}", tr!("fight-total-drops"))?;
+            let total_drops = self.total_drops();
+            let mut iter = total_drops.iter();
             if let Some((item, count)) = iter.next() {
                 write!(f, " {} × {}", item, count)?;
             }
@@ -610,29 +966,27 @@ impl RecruitDetail {
 impl std::fmt::Display for RecruitDetail {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if !self.record.is_empty() {
-            writeln!(f, "Detected tags:")?;
+            writeln!(f, "{}", tr!("recruit-detected-tags"))?;
             for (i, (level, tags, state)) in self.record.iter().enumerate() {
                 write!(
                     f,
                     "{}. {} {}",
                     i + 1,
                     "★".repeat(*level as usize),
-                    tags.iter()
-                        .join(", ")
-                        .unwrap_or_else(|| "unknown".to_owned())
+                    tags.iter().join(", ").unwrap_or_else(|| tr!("unknown"))
                 )?;
                 match state {
-                    RecruitState::Refreshed => write!(f, ", Refreshed")?,
-                    RecruitState::Recruited => write!(f, ", Recruited")?,
+                    RecruitState::Refreshed => write!(f, ", {}", tr!("recruit-refreshed"))?,
+                    RecruitState::Recruited => write!(f, ", {}", tr!("recruit-recruited"))?,
                     RecruitState::None => (),
                 }
                 writeln!(f)?
             }
             if let Some(times) = self.recruit_times {
-                writeln!(f, "Recruited {} times", times)?;
+                writeln!(f, "{}", tr!("recruit-recruited-times", times = times))?;
             }
             if let Some(times) = self.refresh_times {
-                writeln!(f, "Refreshed {} times", times)?;
+                writeln!(f, "{}", tr!("recruit-refreshed-times", times = times))?;
             }
         }
         Ok(())
@@ -683,14 +1037,14 @@ impl std::fmt::Display for RoguelikeDetail {
             let mut total_invest = 0;
             let mut total_exp = 0;
             let mut state_count = [0; ExplorationState::total_type()];
-            writeln!(f, "Explorations:")?;
+            writeln!(f, "{}", tr!("explorations"))?;
             for (i, exploration) in self.explorations.iter().enumerate() {
                 state_count[exploration.state.to_index()] += 1;
                 total_exp += exploration.exp.unwrap_or_default();
                 total_invest += exploration.invest.unwrap_or_default();
                 writeln!(f, "{}. {};", i + 1, exploration)?;
             }
-            writeln!(f, "Summary:")?;
+            writeln!(f, "{}", tr!("explorations-summary"))?;
             for (i, count) in state_count.iter().enumerate() {
                 if *count > 0 {
                     write!(f, "{} {}", ExplorationState::from(i), count)?;
@@ -700,8 +1054,8 @@ impl std::fmt::Display for RoguelikeDetail {
                 }
             }
             writeln!(f)?;
-            writeln!(f, "Total invest {} originium ingotes", total_invest)?;
-            writeln!(f, "Total gained {} exp", total_exp)?;
+            writeln!(f, "{}", tr!("explorations-invest", count = total_invest))?;
+            writeln!(f, "{}", tr!("explorations-exp", exp = total_exp))?;
         }
         Ok(())
     }
@@ -717,16 +1071,6 @@ pub(super) enum ExplorationState {
 }
 
 impl ExplorationState {
-    fn to_str(self) -> &'static str {
-        use ExplorationState::*;
-        match self {
-            Passed => "Passed",
-            Failed => "Failed",
-            Abandoned => "Abandoned",
-            Unknown => "Unknown",
-        }
-    }
-
     const fn total_type() -> usize {
         4
     }
@@ -751,7 +1095,14 @@ impl From<usize> for ExplorationState {
 
 impl std::fmt::Display for ExplorationState {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(self.to_str())
+        use ExplorationState::*;
+        let state = match self {
+            Passed => tr!("exploration-state-passed"),
+            Failed => tr!("exploration-state-failed"),
+            Abandoned => tr!("exploration-state-abandoned"),
+            Unknown => tr!("exploration-state-unknown"),
+        };
+        f.write_str(&state)
     }
 }
 
@@ -793,10 +1144,10 @@ impl std::fmt::Display for ExplorationDetail {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}", self.state)?;
         if let Some(invest) = self.invest {
-            write!(f, ", invest {} originium ingotes", invest)?;
+            write!(f, ", {}", tr!("exploration-invest", count = invest))?;
         }
         if let Some(exp) = self.exp {
-            write!(f, ", gained {} exp", exp)?;
+            write!(f, ", {}", tr!("exploration-exp", exp = exp))?;
         }
         Ok(())
     }
@@ -810,6 +1161,51 @@ pub fn insert_or_add_by_ref(map: &mut Map<String, i64>, key: &str, value: i64) {
     }
 }
 
+pub struct SSSCopilotDetail {
+    stages: Vec<String>,
+    settlement: Option<String>,
+    passed: bool,
+}
+
+impl SSSCopilotDetail {
+    fn new() -> Self {
+        Self {
+            stages: Vec::new(),
+            settlement: None,
+            passed: false,
+        }
+    }
+
+    pub(super) fn start_stage(&mut self, stage: impl Into<String>) {
+        self.stages.push(stage.into());
+    }
+
+    pub(super) fn settle(&mut self, why: impl Into<String>) {
+        self.settlement = Some(why.into());
+    }
+
+    pub(super) fn pass(&mut self) {
+        self.passed = true;
+    }
+}
+
+impl std::fmt::Display for SSSCopilotDetail {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.stages.is_empty() {
+            writeln!(f, "{}", tr!("sss-stages"))?;
+            for (i, stage) in self.stages.iter().enumerate() {
+                writeln!(f, "{}. {}", i + 1, stage)?;
+            }
+        }
+        if self.passed {
+            writeln!(f, "{}", tr!("sss-passed"))?;
+        } else if let Some(why) = self.settlement.as_deref() {
+            writeln!(f, "{}", tr!("sss-settled", why = why))?;
+        }
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -848,6 +1244,15 @@ mod tests {
         use super::*;
         use crate::assert_matches;
 
+        impl Summary {
+            fn get(&self, id: AsstTaskId) -> Option<&TaskSummary> {
+                self.task_summarys
+                    .iter()
+                    .find(|(task_id, _)| *task_id == Some(id))
+                    .map(|(_, summary)| summary)
+            }
+        }
+
         #[test]
         fn task_summary() {
             use TaskType::*;
@@ -864,7 +1269,10 @@ mod tests {
                 let detail = detail.as_fight_mut().unwrap();
                 detail.set_stage("TS-9");
             });
-            summary.end_current_task(Reason::Completed);
+            let duration = summary.end_current_task(Reason::Completed).unwrap();
+            assert!(duration >= chrono::Duration::zero());
+            // No task is running now
+            assert!(summary.end_current_task(Reason::Completed).is_none());
 
             summary.start_task(2);
             summary.edit_current_task_detail(|detail| {
@@ -883,27 +1291,27 @@ mod tests {
 
             summary.start_task(4);
 
-            let task1 = summary.task_summarys.get(&1).unwrap();
+            let task1 = summary.get(1).unwrap();
             assert!(task1.start_time.is_some());
             assert!(task1.end_time.is_some());
             assert_matches!(task1.reason, Reason::Completed);
 
-            let task2 = summary.task_summarys.get(&2).unwrap();
+            let task2 = summary.get(2).unwrap();
             assert!(task2.start_time.is_some());
             assert!(task2.end_time.is_some());
             assert_matches!(task2.reason, Reason::Stopped);
 
-            let task3 = summary.task_summarys.get(&3).unwrap();
+            let task3 = summary.get(3).unwrap();
             assert!(task3.start_time.is_some());
             assert!(task3.end_time.is_some());
             assert_matches!(task3.reason, Reason::Error);
 
-            let task4 = summary.task_summarys.get(&4).unwrap();
+            let task4 = summary.get(4).unwrap();
             assert!(task4.start_time.is_some());
             assert!(task4.end_time.is_none());
             assert_matches!(task4.reason, Reason::Unfinished);
 
-            let task5 = summary.task_summarys.get(&5).unwrap();
+            let task5 = summary.get(5).unwrap();
             assert!(task5.start_time.is_none());
             assert!(task5.end_time.is_none());
             assert_matches!(task5.reason, Reason::Unstarted);
@@ -928,6 +1336,159 @@ mod tests {
 
             assert!(re.is_match(&summary.to_string()));
         }
+
+        #[test]
+        fn skipped_tasks() {
+            use TaskType::*;
+
+            let mut summary = Summary::new();
+            summary.insert(1, None, StartUp);
+            summary.skip(Some("Daily Fight".to_owned()), Fight);
+            summary.insert(2, None, CloseDown);
+
+            summary.start_task(1);
+            summary.end_current_task(Reason::Error);
+            summary.start_task(2);
+            summary.end_current_task(Reason::Completed);
+
+            // Skipped tasks are kept in order, and ids of other tasks still work
+            let names: Vec<_> = summary.tasks().map(|task| task.task).collect();
+            assert_eq!(names, [StartUp, Fight, CloseDown]);
+            assert_matches!(summary.get(2).unwrap().reason, Reason::Completed);
+
+            let text = summary.to_string();
+            assert!(text.contains("[Daily Fight] Skipped\n"));
+            assert!(text.contains("Tasks: 1 completed, 1 error, 1 skipped\n"));
+            assert_eq!(summary.to_json()[1]["result"], "skipped");
+        }
+
+        #[test]
+        fn copilot_overview() {
+            let mut summary = Summary::new();
+            summary.insert(1, Some("1-7 Normal (1/2)".to_owned()), TaskType::Copilot);
+            summary.insert(2, Some("1-7 Normal (2/2)".to_owned()), TaskType::Copilot);
+            summary.insert(3, Some("1-8 Normal".to_owned()), TaskType::Copilot);
+
+            summary.start_task(1);
+            summary.end_current_task(Reason::Completed);
+            summary.start_task(2);
+            summary.end_current_task(Reason::Error);
+
+            assert!(summary.to_string().ends_with(
+                "Copilot: 1/3 completed\n\
+                 Not completed: 1-7 Normal (2/2), 1-8 Normal\n"
+            ));
+
+            // No overview for a single copilot task
+            let mut summary = Summary::new();
+            summary.insert(1, None, TaskType::Copilot);
+            assert!(!summary.to_string().contains("Copilot:"));
+        }
+
+        #[test]
+        fn overview() {
+            use TaskType::*;
+
+            let mut summary = Summary::new();
+            summary.insert(1, None, Fight);
+            summary.insert(2, None, Fight);
+            summary.insert(3, None, Recruit);
+            summary.insert(4, None, Infrast);
+            summary.insert(5, None, Mall);
+
+            summary.start_task(1);
+            summary.edit_current_task_detail(|detail| {
+                let fight = detail.as_fight_mut().unwrap();
+                fight.set_stage("1-7");
+                fight.set_times(2);
+                fight.record_sanity(20);
+                fight.record_sanity(14);
+                fight.push_drop(Map::from([("A".to_owned(), 1)]));
+                fight.push_drop(Map::from([("A".to_owned(), 2)]));
+            });
+            summary.end_current_task(Reason::Completed);
+
+            summary.start_task(2);
+            summary.edit_current_task_detail(|detail| {
+                let fight = detail.as_fight_mut().unwrap();
+                fight.set_stage("CE-6");
+                fight.set_times(1);
+                fight.push_drop(Map::from([("A".to_owned(), 1), ("B".to_owned(), 5)]));
+            });
+            summary.end_current_task(Reason::Completed);
+
+            summary.start_task(3);
+            summary.edit_current_task_detail(|detail| {
+                let recruit = detail.as_recruit_mut().unwrap();
+                recruit.push_recruit(3, ["A".to_owned()]);
+                recruit.refresh();
+                recruit.push_recruit(4, ["B".to_owned()]);
+                recruit.recruit();
+                recruit.push_recruit(3, ["C".to_owned()]);
+                recruit.recruit();
+            });
+            summary.end_current_task(Reason::Completed);
+
+            summary.start_task(4);
+            summary.edit_current_task_detail(|detail| {
+                let infrast = detail.as_infrast_mut().unwrap();
+                infrast.enter(Facility::Mfg, 1);
+                infrast.enter(Facility::Trade, 1);
+                infrast.enter(Facility::Mfg, 1);
+            });
+            summary.end_current_task(Reason::Error);
+
+            let re = Regex::new(
+                "----------------------------------------\n\
+                 Overview\n\
+                 Duration: \\d+s\n\
+                 Tasks: 3 completed, 1 error, 1 unstarted\n\
+                 Stages: 1-7 × 2, CE-6 × 1\n\
+                 Sanity spent: 12\n\
+                 Drops: A × 4, B × 5\n\
+                 Recruit: 2 recruited \\(★★★★ × 1, ★★★ × 1\\), 1 refreshed\n\
+                 Infrast: 2 rooms shifted\n$",
+            )
+            .unwrap();
+            assert!(re.is_match(&summary.to_string()));
+
+            // No overview if no task is started
+            let mut summary = Summary::new();
+            summary.insert(1, None, Fight);
+            assert!(!summary.to_string().contains("Overview"));
+        }
+
+        #[test]
+        fn to_json() {
+            let mut summary = Summary::new();
+            summary.insert(1, Some("Daily".to_owned()), TaskType::Fight);
+            summary.insert(2, None, TaskType::Mall);
+
+            summary.start_task(1);
+            summary.edit_current_task_detail(|detail| {
+                let fight = detail.as_fight_mut().unwrap();
+                fight.set_stage("1-7");
+                fight.set_times(2);
+                fight.push_drop(Map::from([("A".to_owned(), 1)]));
+                fight.push_drop(Map::from([("A".to_owned(), 2)]));
+            });
+            summary.end_current_task(Reason::Completed);
+
+            let json = summary.to_json();
+            assert_eq!(json[0]["name"], "Daily");
+            assert_eq!(json[0]["type"], "Fight");
+            assert_eq!(json[0]["result"], "completed");
+            assert!(json[0]["start_time"].is_string());
+            assert!(json[0]["duration"].is_i64());
+            assert_eq!(json[0]["stage"], "1-7");
+            assert_eq!(json[0]["times"], 2);
+            assert!(json[0]["sanity"].is_null());
+            assert_eq!(json[0]["drops"], serde_json::json!({ "A": 3 }));
+            assert_eq!(json[1]["name"], "Mall");
+            assert_eq!(json[1]["result"], "unstarted");
+            assert!(json[1]["end_time"].is_null());
+            assert!(json[1]["duration"].is_null());
+        }
     }
 
     mod detail {
@@ -941,6 +1502,7 @@ mod tests {
             assert!(detail.as_fight_mut().is_none());
             assert!(detail.as_recruit_mut().is_none());
             assert!(detail.as_roguelike_mut().is_none());
+            assert!(detail.as_sss_copilot_mut().is_none());
 
             detail = Detail::Infrast(InfrastDetail::new());
             assert!(detail.as_infrast_mut().is_some());
@@ -965,6 +1527,10 @@ mod tests {
             assert!(detail.as_fight_mut().is_none());
             assert!(detail.as_recruit_mut().is_none());
             assert!(detail.as_roguelike_mut().is_some());
+
+            detail = Detail::SSSCopilot(SSSCopilotDetail::new());
+            assert!(detail.as_roguelike_mut().is_none());
+            assert!(detail.as_sss_copilot_mut().is_some());
         }
 
         #[test]
@@ -1011,6 +1577,10 @@ mod tests {
                 "Mfg(Product) with operators: A, B, [C, D]\n\
                  Office with operators: unknown\n",
             );
+
+            detail.enter(Facility::Mfg, 1);
+            detail.enter(Facility::Dorm, 2);
+            assert_eq!(detail.shifted_rooms(), 2);
         }
 
         #[test]
@@ -1050,6 +1620,23 @@ mod tests {
             assert_eq!(detail.to_string(), "");
         }
 
+        #[test]
+        fn sanity_spent() {
+            let mut detail = FightDetail::new();
+            detail.record_sanity(30);
+            assert_eq!(detail.sanity_spent(), None);
+
+            // Restored sanity can not be used to estimate the cost
+            detail.use_medicine(1, false);
+            detail.record_sanity(100);
+            assert_eq!(detail.sanity_spent(), None);
+
+            detail.record_sanity(82);
+            detail.record_sanity(64);
+            detail.set_times(4);
+            assert_eq!(detail.sanity_spent(), Some(18 * 4));
+        }
+
         #[test]
         fn recruit() {
             let mut detail = RecruitDetail::new();
@@ -1141,5 +1728,25 @@ mod tests {
                 Total gained 300 exp\n",
             );
         }
+
+        #[test]
+        fn sss_copilot() {
+            let mut detail = SSSCopilotDetail::new();
+            assert_eq!(detail.to_string(), "");
+
+            detail.start_stage("多索雷斯在建地块");
+            detail.start_stage("乌萨斯大使馆");
+            detail.settle("战斗失败");
+            assert_eq!(
+                detail.to_string(),
+                "Stages:\n\
+                1. 多索雷斯在建地块\n\
+                2. 乌萨斯大使馆\n\
+                Settled: 战斗失败\n",
+            );
+
+            detail.pass();
+            assert!(detail.to_string().ends_with("Passed all stages\n"));
+        }
     }
 }
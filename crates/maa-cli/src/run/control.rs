@@ -0,0 +1,270 @@
+//! Stop, pause and resume a running instance
+//!
+//! A running instance can be stopped by signals: the first one stops it after the current task,
+//! the second one stops the current task immediately, and the third one terminates the process.
+//! Other processes can also control it by `maa stop`, `maa pause` and `maa resume`, which write
+//! a request file next to the registration file of the instance in the state directory.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Result};
+use log::{debug, warn};
+use maa_dirs::{self as dirs, Ensure};
+use signal_hook::consts::TERM_SIGNALS;
+
+/// How long to wait for a running instance to accept a request
+const ACCEPT_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[cfg_attr(test, derive(Debug))]
+#[derive(Clone, Copy, PartialEq)]
+pub enum Request {
+    /// Stop the current task immediately
+    Stop,
+    /// Stop the current task and wait, the task is run again after resumed
+    Pause,
+    /// Resume the paused task
+    Resume,
+}
+
+impl Request {
+    const fn to_str(self) -> &'static str {
+        match self {
+            Request::Stop => "stop",
+            Request::Pause => "pause",
+            Request::Resume => "resume",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "stop" => Some(Request::Stop),
+            "pause" => Some(Request::Pause),
+            "resume" => Some(Request::Resume),
+            _ => None,
+        }
+    }
+}
+
+fn control_dir() -> PathBuf {
+    dirs::state().join("control")
+}
+
+/// Stop flags set by signals and requests from other processes
+pub struct Control {
+    /// Stop after the current task, set by the first signal
+    graceful: Arc<AtomicBool>,
+    /// Stop the current task immediately, set by the second signal or a stop request
+    force: Arc<AtomicBool>,
+    /// Registration file of this instance, requests are written next to it
+    path: PathBuf,
+}
+
+impl Control {
+    /// Register signal handlers and this instance, so that it can be controlled
+    pub fn register() -> Result<Self> {
+        let graceful = Arc::new(AtomicBool::new(false));
+        let force = Arc::new(AtomicBool::new(false));
+        for &sig in TERM_SIGNALS {
+            // Handlers are called in the order of registration
+            signal_hook::flag::register_conditional_default(sig, Arc::clone(&force))
+                .context("Failed to register signal handler!")?;
+            let (graceful_ref, force_ref) = (Arc::clone(&graceful), Arc::clone(&force));
+            // SAFETY: only atomic operations are used in the handler, which are signal safe
+            unsafe {
+                signal_hook::low_level::register(sig, move || {
+                    if graceful_ref.load(Ordering::Relaxed) {
+                        force_ref.store(true, Ordering::Relaxed);
+                    }
+                })
+            }
+            .context("Failed to register signal handler!")?;
+            signal_hook::flag::register(sig, Arc::clone(&graceful))
+                .context("Failed to register signal handler!")?;
+        }
+
+        let path = control_dir().ensure()?.join(std::process::id().to_string());
+        let command_line = std::env::args_os()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect::<Vec<_>>()
+            .join(" ");
+        std::fs::write(&path, command_line).context("Failed to register instance")?;
+
+        Ok(Self {
+            graceful,
+            force,
+            path,
+        })
+    }
+
+    /// Whether to stop after the current task
+    pub fn stop_after_task(&self) -> bool {
+        self.graceful.load(Ordering::Relaxed) || self.stop_now()
+    }
+
+    /// Whether to stop the current task immediately
+    pub fn stop_now(&self) -> bool {
+        self.force.load(Ordering::Relaxed)
+    }
+
+    /// Take the request from other processes if any
+    ///
+    /// A stop request is not returned, but sets the flag checked by [`Control::stop_now`].
+    pub fn poll(&self) -> Option<Request> {
+        let path = self.path.with_extension("request");
+        let request = std::fs::read_to_string(&path).ok()?;
+        // Removing the file tells the sender that the request is accepted
+        let _ = std::fs::remove_file(&path);
+        match Request::parse(&request) {
+            Some(Request::Stop) => {
+                self.force.store(true, Ordering::Relaxed);
+                None
+            }
+            Some(request) => Some(request),
+            None => {
+                warn!("Unknown request `{}`", request.trim());
+                None
+            }
+        }
+    }
+
+    /// Sleep for given duration, unless it's interrupted or stopped by a request
+    ///
+    /// Return whether the sleep is finished without being stopped.
+    pub fn sleep(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+        while Instant::now() < deadline {
+            self.poll();
+            if self.stop_after_task() {
+                return false;
+            }
+            std::thread::sleep(Duration::from_millis(500));
+        }
+        true
+    }
+
+    /// Stop handling signals, so that the next signal terminates the process
+    pub fn finish(&self) {
+        self.graceful.store(true, Ordering::Relaxed);
+        self.force.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Drop for Control {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+        let _ = std::fs::remove_file(self.path.with_extension("request"));
+    }
+}
+
+/// Registered instances, which are pairs of process id and command line
+fn instances(dir: &Path) -> Result<Vec<(u32, String)>> {
+    let mut instances = Vec::new();
+    if !dir.exists() {
+        return Ok(instances);
+    }
+    for entry in dir.read_dir()? {
+        let path = entry?.path();
+        let Some(pid) = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.parse().ok())
+        else {
+            continue;
+        };
+        let command_line = std::fs::read_to_string(&path).unwrap_or_default();
+        instances.push((pid, command_line));
+    }
+    instances.sort();
+    Ok(instances)
+}
+
+/// Send a request to a running instance and wait for it to be accepted
+pub fn send(request: Request, pid: Option<u32>) -> Result<()> {
+    send_to(&control_dir(), request, pid, ACCEPT_TIMEOUT)
+}
+
+fn send_to(dir: &Path, request: Request, pid: Option<u32>, timeout: Duration) -> Result<()> {
+    let instances = instances(dir)?;
+    let pid = match (pid, instances.as_slice()) {
+        (Some(pid), _) if instances.iter().any(|(p, _)| *p == pid) => pid,
+        (Some(pid), _) => bail!("No running instance with pid {pid}"),
+        (None, []) => bail!("No running instance"),
+        (None, [(pid, _)]) => *pid,
+        (None, _) => {
+            let list = instances
+                .iter()
+                .map(|(pid, command_line)| format!("  {pid}: {command_line}"))
+                .collect::<Vec<_>>()
+                .join("\n");
+            bail!("Multiple running instances, specify one by `--pid`:\n{list}");
+        }
+    };
+
+    let path = dir.join(pid.to_string());
+    let request_path = path.with_extension("request");
+    std::fs::write(&request_path, request.to_str()).context("Failed to send request")?;
+    debug!("Sent {} request to instance {pid}", request.to_str());
+
+    let deadline = Instant::now() + timeout;
+    while request_path.exists() {
+        if Instant::now() >= deadline {
+            // The instance may have exited unexpectedly, so its registration is stale
+            let _ = std::fs::remove_file(&request_path);
+            let _ = std::fs::remove_file(&path);
+            bail!("Instance {pid} is not responding, it may have exited unexpectedly");
+        }
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn request() {
+        for request in [Request::Stop, Request::Pause, Request::Resume] {
+            assert_eq!(Request::parse(request.to_str()), Some(request));
+        }
+        assert_eq!(Request::parse("stop\n"), Some(Request::Stop));
+        assert_eq!(Request::parse("kill"), None);
+    }
+
+    #[test]
+    fn send_request() {
+        let dir = std::env::temp_dir().join("maa-test-control");
+        let _ = std::fs::remove_dir_all(&dir);
+        let timeout = Duration::from_millis(200);
+
+        assert!(send_to(&dir, Request::Stop, None, timeout).is_err());
+
+        dir.as_path().ensure().unwrap();
+        std::fs::write(dir.join("1"), "maa run daily").unwrap();
+        std::fs::write(dir.join("2"), "maa daemon start").unwrap();
+        std::fs::write(dir.join("2.request"), "stop").unwrap();
+        assert_eq!(instances(&dir).unwrap(), vec![
+            (1, "maa run daily".to_owned()),
+            (2, "maa daemon start".to_owned()),
+        ]);
+
+        // Ambiguous or unknown instance
+        assert!(send_to(&dir, Request::Pause, None, timeout).is_err());
+        assert!(send_to(&dir, Request::Pause, Some(3), timeout).is_err());
+
+        // Stale instance is removed if the request is not accepted
+        assert!(send_to(&dir, Request::Pause, Some(1), timeout).is_err());
+        assert!(!dir.join("1").exists());
+        assert!(!dir.join("1.request").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,358 @@
+//! A long-lived assistant running jobs from a queue
+//!
+//! The daemon loads MaaCore and connects to the device only once, and then runs jobs submitted
+//! to its queue one by one. The queue is a directory in the state directory, where each job is
+//! a JSON file named by its submission time, so jobs can be submitted by any process, like
+//! `maa daemon submit` or the scheduler, even when the daemon is not running.
+//!
+//! Task files are loaded by each job, and changes of the profile are applied before the next
+//! job, so the daemon doesn't need to be restarted after editing them.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::atomic,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{bail, Context, Result};
+use log::{info, warn};
+use maa_dirs::{self as dirs, Ensure};
+use maa_sys::Assistant;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    callback::{self, summary},
+    connect,
+    control::Control,
+    drops,
+    external::{self, ExternalApp},
+    find_profile, history, idle, init_task_config, load_core, load_task_file, profile_path,
+    run_tasks, setup_core, CommonArgs, Failure,
+};
+use crate::{
+    config::{
+        asst::AsstConfig,
+        task::{duration, ClientType},
+        watch::Watched,
+        FromFile,
+    },
+    i18n::tr,
+    installer::resource,
+};
+
+/// Interval to check the queue when it's empty
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Serialize, Deserialize)]
+pub struct Job {
+    /// Name or path of the task file
+    task: String,
+    /// Account to switch to before running tasks
+    account: Option<String>,
+}
+
+fn queue_dir() -> PathBuf {
+    join!(dirs::state(), "daemon", "queue")
+}
+
+/// Submit a job to the queue of daemon
+pub fn submit(task: String, account: Option<String>) -> Result<()> {
+    submit_to(&queue_dir(), &Job { task, account })
+}
+
+fn submit_to(queue: &Path, job: &Job) -> Result<()> {
+    let time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
+    // Names are padded, so that jobs are sorted by submission time
+    let name = format!("{:020}-{}", time.as_nanos(), std::process::id());
+    let path = queue.ensure()?.join(&name).with_extension("json");
+    // Write to a temporary file first, so that the daemon never reads a partial job
+    let tmp = path.with_extension("tmp");
+    std::fs::write(&tmp, serde_json::to_string_pretty(job)?)?;
+    std::fs::rename(&tmp, &path).context("Failed to submit job")?;
+    info!("Job {} submitted: {}", name, job.task);
+    Ok(())
+}
+
+/// Take the earliest job from the queue, the job file is removed
+fn take_job(queue: &Path) -> Result<Option<Result<Job>>> {
+    if !queue.exists() {
+        return Ok(None);
+    }
+
+    let mut earliest: Option<PathBuf> = None;
+    for entry in queue.read_dir()? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == "json")
+            && earliest.as_ref().is_none_or(|earliest| path < *earliest)
+        {
+            earliest = Some(path);
+        }
+    }
+
+    let Some(path) = earliest else {
+        return Ok(None);
+    };
+    let job =
+        Job::from_file(&path).with_context(|| format!("Failed to read job {}", path.display()));
+    // Removed before running, so that a job crashing the daemon is never run again
+    std::fs::remove_file(&path)?;
+    Ok(Some(job))
+}
+
+/// Run the daemon until it's terminated by a signal
+pub fn start(args: CommonArgs) -> Result<()> {
+    if args.dry_run {
+        bail!("Dry run is not supported by daemon");
+    }
+    if args.post_action.is_some() && args.closedown_after.is_none() {
+        bail!("Post action is only supported by daemon with --closedown-after");
+    }
+    if args.looping.is_enabled() {
+        bail!("Loop is not supported by daemon");
+    }
+
+    // Auto update hot update resource
+    resource::update(true)?;
+
+    let mut profile = Watched::new(
+        profile_path(dirs::config(), args.profile.as_deref()),
+        || load_profile(&args),
+    )?;
+
+    load_core().context(Failure::load_core(tr!("error-load-core")))?;
+    setup_core(profile.get()).context(Failure::load_core(tr!("error-setup-core")))?;
+    let mut loaded = profile.get().resource.resource_dirs();
+
+    let control = Control::register()?;
+
+    let asst = Assistant::new(Some(callback::default_callback), None);
+    profile.get().instance_options.apply_to(&asst)?;
+    // Whether to connect again before the next job, because the connection is changed
+    let mut reconnect = false;
+
+    let rt = tokio::runtime::Runtime::new().context("Failed to create tokio runtime")?;
+    let queue = queue_dir();
+
+    info!("Daemon started, waiting for jobs in {}", queue.display());
+
+    // Client type of the game left running by the last job, and when the job finished
+    let mut idle_game: Option<(ClientType, Instant)> = None;
+
+    while !control.stop_after_task() {
+        // Jobs are only paused while running, but a stop request is handled here as well
+        control.poll();
+        let job = match take_job(&queue)? {
+            Some(Ok(job)) => job,
+            Some(Err(err)) => {
+                warn!("{err:#}");
+                continue;
+            }
+            None => {
+                if let (Some(idle), Some((client_type, since))) = (args.closedown_after, idle_game)
+                {
+                    if since.elapsed() >= idle {
+                        idle_game = None;
+                        close_idle_game(&asst, profile.get(), &args, client_type, idle, &control);
+                    }
+                }
+                std::thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+        };
+
+        if let Some(keys) = profile.reload(|| load_profile(&args)) {
+            if keys.iter().any(|key| key.starts_with("static_options")) {
+                warn!("Static options are only applied after the daemon is restarted");
+            }
+            if let Err(err) = profile.get().instance_options.apply_to(&asst) {
+                warn!("Failed to apply instance options: {err:#}");
+            }
+            reconnect |= keys.iter().any(|key| key.starts_with("connection"));
+        }
+
+        info!("Running job {}", job.task);
+        let start_time = chrono::Local::now();
+        let ret = run_job(
+            &asst,
+            profile.get(),
+            &args,
+            &job,
+            &mut loaded,
+            std::mem::take(&mut reconnect),
+            &rt,
+            &control,
+        );
+        summary::display();
+        drops::save();
+        let tasks = summary::export().map(|(_, tasks)| tasks);
+        let error = ret.as_ref().err().map(|err| format!("{err:#}"));
+        let end_time = chrono::Local::now();
+        let task_files = [job.task.clone()];
+        if let Err(err) =
+            history::record_run(start_time, end_time, error, &task_files, tasks.as_ref())
+        {
+            warn!("{err:#}");
+        }
+        idle_game = match ret {
+            Ok(game) => {
+                if callback::MAA_CORE_ERRORED.load(atomic::Ordering::Relaxed) {
+                    warn!("Job {} finished with errors", job.task)
+                } else {
+                    info!("Job {} finished", job.task)
+                }
+                game.map(|client_type| (client_type, Instant::now()))
+            }
+            Err(err) => {
+                warn!("Job {} failed: {err:#}", job.task);
+                None
+            }
+        };
+    }
+
+    info!("Daemon stopped");
+
+    Ok(())
+}
+
+/// Close the game left idle by the last job, and then take the post action
+fn close_idle_game(
+    asst: &Assistant,
+    asst_config: &AsstConfig,
+    args: &CommonArgs,
+    client_type: ClientType,
+    idle: Duration,
+    control: &Control,
+) {
+    info!(
+        "No job is run in {}, closing the game",
+        duration::format(idle)
+    );
+    if !args.no_summary {
+        summary::init(summary::Summary::new());
+    }
+    let ret = idle::closedown(asst, &asst_config.hooks, client_type, control);
+    summary::display();
+    if let Err(err) = ret {
+        warn!("Failed to close the game: {err:#}");
+        return;
+    }
+    if let Some(action) = args.post_action {
+        if let Err(err) = action.run(&asst_config.connection, control) {
+            warn!("Failed to take post action: {err:#}");
+        }
+    }
+}
+
+/// Load the profile with options given by command line arguments applied
+fn load_profile(args: &CommonArgs) -> Result<AsstConfig> {
+    let mut asst_config = find_profile(dirs::config(), args.profile.as_deref())
+        .context(Failure::config(tr!("error-load-profile")))?;
+    args.apply_to(&mut asst_config)
+        .context(Failure::config(tr!("error-apply-options")))?;
+    if let Some(resource) = asst_config.client_type.and_then(|c| c.resource()) {
+        asst_config.resource.use_global_resource(resource);
+    }
+    Ok(asst_config)
+}
+
+/// Run a job, and return the client type of the game if it's left running
+///
+/// The device is connected again if `reconnect` is true, otherwise only if it's disconnected.
+#[allow(clippy::too_many_arguments)]
+fn run_job(
+    asst: &Assistant,
+    asst_config: &AsstConfig,
+    args: &CommonArgs,
+    job: &Job,
+    loaded: &mut Vec<PathBuf>,
+    reconnect: bool,
+    rt: &tokio::runtime::Runtime,
+    control: &Control,
+) -> Result<Option<ClientType>> {
+    // Errors of the previous job must not be reported for this one
+    callback::MAA_CORE_ERRORED.store(false, atomic::Ordering::Relaxed);
+
+    let mut task = load_task_file(&job.task)?;
+    if let Some(account) = job.account.clone() {
+        task.set_account(account);
+    }
+
+    let mut asst_config = asst_config.clone();
+    let task_config = init_task_config(task, args, &mut asst_config)?;
+    if let Some(path) = args.drops_output.clone() {
+        drops::enable(path);
+    }
+
+    // Resources are only reloaded when the job needs different ones
+    let resource_dirs = asst_config.resource.resource_dirs();
+    if resource_dirs != *loaded {
+        asst_config.resource.load()?;
+        *loaded = resource_dirs;
+    }
+
+    if !args.no_summary {
+        summary::init(summary::Summary::new());
+    }
+
+    // Time spent on reconnecting is also counted
+    let deadline = task_config
+        .timeout
+        .map(|timeout| std::time::Instant::now() + timeout);
+
+    // Keep the connection alive, only reconnect when it's lost or changed
+    if reconnect || !asst.connected() {
+        let (adb_path, address, config) = asst_config.connection.connect_args();
+        if let Some(emulator) = asst_config.connection.emulator() {
+            let emulator = external::EmulatorApp::new(adb_path, address.as_ref(), &emulator);
+            rt.block_on(emulator.open(deadline))
+                .context(Failure::connection(tr!("error-launch-emulator")))?;
+        }
+        connect(asst, (adb_path, &address, config), deadline, control)?;
+    }
+
+    let ret = run_tasks(
+        asst,
+        &task_config.tasks,
+        &asst_config.hooks,
+        control,
+        deadline,
+        None,
+    );
+    asst.stop()?;
+    ret?;
+    Ok((!idle::closes_game(&task_config)).then_some(task_config.client_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn queue() {
+        let queue = std::env::temp_dir().join("maa-test-daemon-queue");
+        let _ = std::fs::remove_dir_all(&queue);
+
+        assert!(take_job(&queue).unwrap().is_none());
+
+        let daily = Job {
+            task: "daily".to_owned(),
+            account: None,
+        };
+        let weekly = Job {
+            task: "weekly".to_owned(),
+            account: Some("alt".to_owned()),
+        };
+        submit_to(&queue, &daily).unwrap();
+        submit_to(&queue, &weekly).unwrap();
+        std::fs::write(queue.join("invalid.json"), "not a job").unwrap();
+
+        // Jobs are taken in the order of submission
+        assert_eq!(take_job(&queue).unwrap().unwrap().unwrap(), daily);
+        assert_eq!(take_job(&queue).unwrap().unwrap().unwrap(), weekly);
+        assert!(take_job(&queue).unwrap().unwrap().is_err());
+        assert!(take_job(&queue).unwrap().is_none());
+
+        std::fs::remove_dir_all(&queue).unwrap();
+    }
+}
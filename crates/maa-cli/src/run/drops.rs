@@ -0,0 +1,242 @@
+//! Export drops of each stage run to a file given by `--drops-output`
+//!
+//! Drops are recorded from the `StageDrops` messages of MaaCore and appended to the file
+//! after all tasks are finished, so that the file keeps growing across runs. The format
+//! is chosen by the extension of the file: a JSON array for `.json`, CSV otherwise.
+
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use serde_json::{json, Value};
+
+use super::export::csv_field;
+
+/// Header of the CSV file, written when the file is created
+const CSV_HEADER: &str = "time,stage,stars,drops";
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+struct StageRun {
+    time: String,
+    stage: String,
+    stars: i64,
+    /// Drops of the run as `(item id, item name, quantity)`
+    drops: Vec<(String, String, i64)>,
+}
+
+impl StageRun {
+    /// Parse the details of a `StageDrops` message
+    fn from_details(time: String, details: &Value) -> Option<Self> {
+        let drops = details
+            .get("drops")?
+            .as_array()?
+            .iter()
+            .filter_map(|drop| {
+                Some((
+                    drop.get("itemId")?.as_str()?.to_owned(),
+                    drop.get("itemName")?.as_str()?.to_owned(),
+                    drop.get("quantity")?.as_i64()?,
+                ))
+            })
+            .collect();
+        Some(Self {
+            time,
+            stage: details.get("stage")?.get("stageCode")?.as_str()?.to_owned(),
+            stars: details.get("stars").and_then(Value::as_i64).unwrap_or(0),
+            drops,
+        })
+    }
+
+    /// A line of CSV, with drops given by `item:quantity` separated by `;`
+    fn to_csv(&self) -> String {
+        let drops = self
+            .drops
+            .iter()
+            .map(|(_, name, quantity)| format!("{name}:{quantity}"))
+            .collect::<Vec<_>>()
+            .join(";");
+        format!(
+            "{},{},{},{}",
+            self.time,
+            csv_field(&self.stage),
+            self.stars,
+            csv_field(&drops)
+        )
+    }
+
+    fn to_json(&self) -> Value {
+        json!({
+            "time": self.time,
+            "stage": self.stage,
+            "stars": self.stars,
+            "drops": self
+                .drops
+                .iter()
+                .map(|(id, name, quantity)| json!({
+                    "itemId": id,
+                    "itemName": name,
+                    "quantity": quantity,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+struct Recorder {
+    path: PathBuf,
+    runs: Vec<StageRun>,
+}
+
+static RECORDER: Mutex<Option<Recorder>> = Mutex::new(None);
+
+/// Start recording drops, which are written to given file by [`save`]
+pub fn enable(path: PathBuf) {
+    *RECORDER.lock().unwrap() = Some(Recorder {
+        path,
+        runs: Vec::new(),
+    });
+}
+
+/// Record drops of a stage run from the details of a `StageDrops` message
+pub fn record(details: &Value) {
+    if let Some(recorder) = RECORDER.lock().unwrap().as_mut() {
+        let time = chrono::Local::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, false);
+        if let Some(run) = StageRun::from_details(time, details) {
+            recorder.runs.push(run);
+        }
+    }
+}
+
+/// Append recorded drops to the file
+pub fn save() {
+    let Some(recorder) = RECORDER.lock().unwrap().take() else {
+        return;
+    };
+    if recorder.runs.is_empty() {
+        return;
+    }
+    let path = &recorder.path;
+    let is_json = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"));
+    let ret = if is_json {
+        append_json(path, &recorder.runs)
+    } else {
+        append_csv(path, &recorder.runs)
+    };
+    match ret {
+        Ok(()) => info!(
+            "Exported drops of {} stage runs to {}",
+            recorder.runs.len(),
+            path.display()
+        ),
+        Err(err) => warn!("Failed to export drops to {}: {err:#}", path.display()),
+    }
+}
+
+fn append_csv(path: &Path, runs: &[StageRun]) -> Result<()> {
+    let is_new = fs::metadata(path).map_or(true, |meta| meta.len() == 0);
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    let mut content = String::new();
+    if is_new {
+        content.push_str(CSV_HEADER);
+        content.push('\n');
+    }
+    for run in runs {
+        content.push_str(&run.to_csv());
+        content.push('\n');
+    }
+    file.write_all(content.as_bytes())?;
+    Ok(())
+}
+
+fn append_json(path: &Path, runs: &[StageRun]) -> Result<()> {
+    let mut all = match fs::read_to_string(path) {
+        Ok(content) if !content.trim().is_empty() => {
+            serde_json::from_str::<Vec<Value>>(&content)
+                .context("Existing file is not a JSON array")?
+        }
+        _ => Vec::new(),
+    };
+    all.extend(runs.iter().map(StageRun::to_json));
+    fs::write(path, serde_json::to_string_pretty(&all)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stage_run() -> StageRun {
+        StageRun::from_details(
+            "2024-01-01T04:00:00+08:00".to_owned(),
+            &json!({
+                "stars": 3,
+                "stage": { "stageCode": "1-7", "stageId": "main_01-07" },
+                "drops": [
+                    { "dropType": "NORMAL_DROP", "itemId": "30012", "itemName": "固源岩", "quantity": 2 },
+                    { "dropType": "EXP_LMB_DROP", "itemId": "4001", "itemName": "龙门币", "quantity": 12 },
+                ],
+            }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn format() {
+        let run = stage_run();
+        assert_eq!(
+            run.to_csv(),
+            "2024-01-01T04:00:00+08:00,1-7,3,固源岩:2;龙门币:12"
+        );
+        assert_eq!(
+            run.to_json(),
+            json!({
+                "time": "2024-01-01T04:00:00+08:00",
+                "stage": "1-7",
+                "stars": 3,
+                "drops": [
+                    { "itemId": "30012", "itemName": "固源岩", "quantity": 2 },
+                    { "itemId": "4001", "itemName": "龙门币", "quantity": 12 },
+                ],
+            })
+        );
+        assert!(StageRun::from_details(String::new(), &json!({ "stars": 3 })).is_none());
+    }
+
+    #[test]
+    fn append() {
+        let dir = std::env::temp_dir().join("maa-test-drops");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let csv = dir.join("drops.csv");
+        append_csv(&csv, &[stage_run()]).unwrap();
+        append_csv(&csv, &[stage_run()]).unwrap();
+        let content = fs::read_to_string(&csv).unwrap();
+        let lines: Vec<_> = content.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert_eq!(lines[1], lines[2]);
+
+        let json = dir.join("drops.json");
+        append_json(&json, &[stage_run()]).unwrap();
+        append_json(&json, &[stage_run(), stage_run()]).unwrap();
+        let all: Vec<Value> = serde_json::from_str(&fs::read_to_string(&json).unwrap()).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[2], stage_run().to_json());
+
+        fs::write(&json, "{}").unwrap();
+        assert!(append_json(&json, &[stage_run()]).is_err());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,591 @@
+//! Export results of recognition tasks to formats accepted by other tools
+
+use std::{collections::BTreeMap, io::Write, path::PathBuf};
+
+use anyhow::{Context, Result};
+use clap::Args;
+use log::warn;
+use maa_sys::TaskType;
+use serde_json::Value;
+
+use super::{callback, hooks, CommonArgs};
+use crate::{
+    config::{
+        asst::Hooks,
+        task::{Task, TaskConfig},
+    },
+    dirs::{self, Ensure},
+    object,
+    value::MAAValue,
+};
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum DepotFormat {
+    /// Format of ArkPlanner, which can be imported by the planner of penguin-statistics
+    #[value(name = "arkplanner", alias = "penguin")]
+    ArkPlanner,
+    /// Format of Arknights Toolbox (yituliu)
+    Lolicon,
+}
+
+impl DepotFormat {
+    fn key(self) -> &'static str {
+        match self {
+            DepotFormat::ArkPlanner => "arkplanner",
+            DepotFormat::Lolicon => "lolicon",
+        }
+    }
+
+    /// Extract the object of this format from the details of `DepotInfo`
+    fn extract(self, details: &Value) -> Option<&Value> {
+        details.get(self.key())?.get("object")
+    }
+}
+
+#[derive(Args)]
+pub struct DepotArgs {
+    /// Format of exported depot
+    #[arg(short, long, default_value = "arkplanner")]
+    format: DepotFormat,
+    /// Path of output file, print to stdout if not given
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+    /// Print items gained and spent since the last scan instead of exporting the depot
+    ///
+    /// Every scan is saved in the state directory, and the diff is against the last one.
+    #[arg(long, conflicts_with = "format", verbatim_doc_comment)]
+    diff: bool,
+}
+
+/// Items in depot keyed by item id, with their names and quantities
+type Depot = BTreeMap<String, (String, i64)>;
+
+/// Extract items from the object of ArkPlanner format
+fn depot_items(object: &Value) -> Option<Depot> {
+    object
+        .get("items")?
+        .as_array()?
+        .iter()
+        .map(|item| {
+            let id = item.get("id")?.as_str()?.to_owned();
+            let name = item.get("name")?.as_str()?.to_owned();
+            let have = item.get("have")?.as_i64()?;
+            Some((id, (name, have)))
+        })
+        .collect()
+}
+
+/// Directory of saved depot scans, named by the time of scan
+fn depot_dir() -> PathBuf {
+    dirs::state().join("depot")
+}
+
+/// Load the last saved depot scan and its time
+fn last_depot() -> Result<Option<(String, Depot)>> {
+    let dir = depot_dir();
+    if !dir.exists() {
+        return Ok(None);
+    }
+    let last = std::fs::read_dir(&dir)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .max();
+    let Some(path) = last else {
+        return Ok(None);
+    };
+    let object: Value = serde_json::from_reader(std::fs::File::open(&path)?)
+        .with_context(|| format!("Failed to load depot scan {}", path.display()))?;
+    let time = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    Ok(depot_items(&object).map(|depot| (time, depot)))
+}
+
+fn save_depot(object: &Value) -> Result<()> {
+    let dir = depot_dir();
+    dir.ensure()?;
+    let name = chrono::Local::now().format("%Y-%m-%dT%H-%M-%S");
+    std::fs::write(
+        dir.join(format!("{name}.json")),
+        serde_json::to_string(object)?,
+    )
+    .context("Failed to save depot scan")
+}
+
+/// Items whose quantities changed, with their names, changes and current quantities
+fn diff_depot(old: &Depot, new: &Depot) -> Vec<(String, i64, i64)> {
+    let mut diff: Vec<_> = new
+        .iter()
+        .map(|(id, (name, have))| {
+            let before = old.get(id).map_or(0, |(_, have)| *have);
+            (name.clone(), have - before, *have)
+        })
+        .chain(
+            old.iter()
+                .filter(|(id, _)| !new.contains_key(*id))
+                .map(|(_, (name, have))| (name.clone(), -have, 0)),
+        )
+        .filter(|(_, change, _)| *change != 0)
+        .collect();
+    // Most gained items first
+    diff.sort_by_key(|(_, change, _)| std::cmp::Reverse(*change));
+    diff
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+pub enum OperBoxFormat {
+    /// JSON array of owned operators, same as the `own_opers` in MaaCore's result
+    Json,
+    /// CSV with a header line, one owned operator per line
+    Csv,
+}
+
+/// Fields of an owned operator exported to CSV
+const OPER_FIELDS: [&str; 6] = ["id", "name", "rarity", "elite", "level", "potential"];
+
+impl OperBoxFormat {
+    /// Format owned operators from the details of `OperBoxInfo`
+    fn format(self, details: &Value) -> Option<String> {
+        let opers = details.get("own_opers")?;
+        match self {
+            OperBoxFormat::Json => serde_json::to_string_pretty(opers).ok(),
+            OperBoxFormat::Csv => {
+                let mut csv = OPER_FIELDS.join(",");
+                for oper in opers.as_array()? {
+                    csv.push('\n');
+                    let fields = OPER_FIELDS.map(|field| match oper.get(field) {
+                        Some(Value::String(s)) => csv_field(s),
+                        Some(Value::Null) | None => String::new(),
+                        Some(v) => v.to_string(),
+                    });
+                    csv.push_str(&fields.join(","));
+                }
+                Some(csv)
+            }
+        }
+    }
+}
+
+/// Quote a field of CSV if needed
+pub(super) fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+#[derive(Args)]
+pub struct OperBoxArgs {
+    /// Format of exported operators
+    #[arg(short, long, default_value = "json")]
+    format: OperBoxFormat,
+    /// Path of output file, print to stdout if not given
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Path of owned operators recognized by the last `maa operbox`
+///
+/// It's used to check requirements of operators before running copilot tasks.
+pub fn owned_operators_path() -> PathBuf {
+    dirs::state().join("operbox.json")
+}
+
+/// Save owned operators, and return changes since the last saved ones
+fn save_owned_operators(details: &Value) -> Result<Vec<String>> {
+    let opers = details
+        .get("own_opers")
+        .context("Owned operators not found in operator box recognition result")?;
+    let path = owned_operators_path();
+    // A corrupt file is replaced by the new result, changes are not known in this case
+    let changes = match std::fs::read_to_string(&path).map(|s| serde_json::from_str(&s)) {
+        Ok(Ok(old)) => operator_changes(&old, opers),
+        Ok(Err(err)) => {
+            warn!(
+                "Failed to parse owned operators in {}, overwriting it: {err}",
+                path.display()
+            );
+            Vec::new()
+        }
+        Err(_) => Vec::new(),
+    };
+    dirs::state().ensure()?;
+    std::fs::write(path, serde_json::to_string(opers)?)
+        .context("Failed to save owned operators")?;
+    Ok(changes)
+}
+
+/// Describe newly owned, promoted, leveled and potential-ranked-up operators
+fn operator_changes(old: &Value, new: &Value) -> Vec<String> {
+    fn int(oper: &Value, field: &str) -> i64 {
+        oper.get(field).and_then(Value::as_i64).unwrap_or_default()
+    }
+
+    let old = old.as_array().map(Vec::as_slice).unwrap_or_default();
+    let mut changes = Vec::new();
+    for oper in new.as_array().map(Vec::as_slice).unwrap_or_default() {
+        let (Some(id), Some(name)) = (
+            oper.get("id").and_then(Value::as_str),
+            oper.get("name").and_then(Value::as_str),
+        ) else {
+            continue;
+        };
+        let progress = |oper: &Value| format!("E{} Lv.{}", int(oper, "elite"), int(oper, "level"));
+        let Some(before) = old
+            .iter()
+            .find(|o| o.get("id").and_then(Value::as_str) == Some(id))
+        else {
+            changes.push(format!("New operator: {name} ({})", progress(oper)));
+            continue;
+        };
+        if (int(oper, "elite"), int(oper, "level")) != (int(before, "elite"), int(before, "level"))
+        {
+            changes.push(format!(
+                "{name}: {} -> {}",
+                progress(before),
+                progress(oper)
+            ));
+        }
+        let (potential, potential_before) = (int(oper, "potential"), int(before, "potential"));
+        if potential != potential_before {
+            changes.push(format!(
+                "{name}: potential {potential_before} -> {potential}"
+            ));
+        }
+    }
+    changes
+}
+
+#[derive(Args)]
+pub struct ConvertVideoArgs {
+    /// Path of the video of a clear, e.g. `clear.mp4`
+    video: PathBuf,
+    /// Path of generated copilot file, print to stdout if not given
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Run a task with given type and parameters, and return the recognition result
+///
+/// Hooks in the profile are also returned, which are called by some recognition results.
+fn recognize(
+    task_type: TaskType,
+    params: MAAValue,
+    common: CommonArgs,
+) -> Result<(Option<Value>, Hooks)> {
+    let mut hooks = Hooks::default();
+    super::run(
+        |config| {
+            hooks = config.hooks.clone();
+            let mut task_config = TaskConfig::new();
+            task_config.push(Task::new(task_type, params));
+            Ok(task_config)
+        },
+        common,
+    )?;
+
+    Ok((callback::take_recognition_result(task_type.to_str()), hooks))
+}
+
+fn write_output(output: Option<&PathBuf>, content: &str) -> Result<()> {
+    match output {
+        Some(path) => {
+            std::fs::write(path, content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            log::info!("Exported to {}", path.display());
+        }
+        None => writeln!(std::io::stdout(), "{content}")?,
+    }
+    Ok(())
+}
+
+/// Recognize items in depot and export them
+pub fn depot(args: DepotArgs, common: CommonArgs) -> Result<()> {
+    let dry_run = common.dry_run;
+    let (Some(details), _) = recognize(TaskType::Depot, MAAValue::new(), common)? else {
+        if !dry_run {
+            log::warn!("No depot recognition result, nothing to export");
+        }
+        return Ok(());
+    };
+
+    // Items are always saved in the format of ArkPlanner, which contains names of items
+    let last = last_depot()?;
+    match DepotFormat::ArkPlanner.extract(&details) {
+        Some(object) => save_depot(object)?,
+        None => log::warn!("Depot recognition result in format of ArkPlanner not found"),
+    }
+
+    if args.diff {
+        let new = DepotFormat::ArkPlanner
+            .extract(&details)
+            .and_then(depot_items)
+            .context("Items not found in depot recognition result")?;
+        let Some((time, old)) = last else {
+            log::warn!("No previous depot scan, nothing to compare");
+            return Ok(());
+        };
+        let mut content = format!("Changes since {time}:");
+        for (name, change, have) in diff_depot(&old, &new) {
+            content.push_str(&format!("\n{name}: {change:+} ({have})"));
+        }
+        return write_output(args.output.as_ref(), &content);
+    }
+
+    let object = args
+        .format
+        .extract(&details)
+        .context("Depot recognition result in given format not found")?;
+    write_output(args.output.as_ref(), &serde_json::to_string_pretty(object)?)
+}
+
+/// Recognize operators in operator box and export owned ones
+pub fn operbox(args: OperBoxArgs, common: CommonArgs) -> Result<()> {
+    let dry_run = common.dry_run;
+    let (Some(details), hooks) = recognize(TaskType::OperBox, MAAValue::new(), common)? else {
+        if !dry_run {
+            log::warn!("No operator box recognition result, nothing to export");
+        }
+        return Ok(());
+    };
+
+    match save_owned_operators(&details) {
+        Ok(changes) if !changes.is_empty() => {
+            log::info!("Changes since the last scan:");
+            for change in &changes {
+                log::info!("{change}");
+            }
+            hooks::operbox_changed(&hooks, &changes);
+        }
+        Ok(_) => {}
+        Err(err) => log::warn!("{err:#}"),
+    }
+
+    let content = args
+        .format
+        .format(&details)
+        .context("Owned operators not found in operator box recognition result")?;
+    write_output(args.output.as_ref(), &content)
+}
+
+/// Recognize a video of a clear and export it as a copilot file
+pub fn convert_video(args: ConvertVideoArgs, common: CommonArgs) -> Result<()> {
+    // MaaCore may run in another directory, so the path must be absolute
+    let video = args
+        .video
+        .canonicalize()
+        .with_context(|| format!("Video {} not found", args.video.display()))?;
+    let params = object!("filename" => video.to_str().context("Invalid video path")?);
+
+    let dry_run = common.dry_run;
+    let (Some(details), _) = recognize(TaskType::VideoRecognition, params, common)? else {
+        if !dry_run {
+            log::warn!("No copilot file is generated from the video");
+        }
+        return Ok(());
+    };
+
+    let file = generated_copilot_file(&details)
+        .context("Path of generated copilot file not found in video recognition result")?;
+    let content = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read generated copilot file {file}"))?;
+    write_output(args.output.as_ref(), &content)
+}
+
+fn generated_copilot_file(details: &Value) -> Option<&str> {
+    details.get("filename")?.as_str()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn extract_depot() {
+        let details = json!({
+            "done": true,
+            "arkplanner": {
+                "object": {
+                    "items": [{ "id": "2004", "have": 4, "name": "高级作战记录" }],
+                    "@type": "@penguin-statistics/depot"
+                },
+                "data": "{}"
+            },
+            "lolicon": {
+                "object": { "2004": 4 },
+                "data": "{}"
+            }
+        });
+
+        assert_eq!(
+            DepotFormat::ArkPlanner.extract(&details),
+            Some(&json!({
+                "items": [{ "id": "2004", "have": 4, "name": "高级作战记录" }],
+                "@type": "@penguin-statistics/depot"
+            }))
+        );
+        assert_eq!(
+            DepotFormat::Lolicon.extract(&details),
+            Some(&json!({ "2004": 4 }))
+        );
+        assert_eq!(
+            DepotFormat::Lolicon.extract(&json!({ "done": false })),
+            None
+        );
+    }
+
+    #[test]
+    fn diff_depot_items() {
+        let old = depot_items(&json!({
+            "items": [
+                { "id": "2004", "have": 4, "name": "高级作战记录" },
+                { "id": "30012", "have": 100, "name": "固源岩" },
+                { "id": "4001", "have": 5000, "name": "龙门币" },
+                { "id": "3003", "have": 2, "name": "赤金" }
+            ]
+        }))
+        .unwrap();
+        let new = depot_items(&json!({
+            "items": [
+                { "id": "2004", "have": 4, "name": "高级作战记录" },
+                { "id": "30012", "have": 130, "name": "固源岩" },
+                { "id": "4001", "have": 3000, "name": "龙门币" },
+                { "id": "30011", "have": 7, "name": "源岩" }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(diff_depot(&old, &new), vec![
+            ("固源岩".to_owned(), 30, 130),
+            ("源岩".to_owned(), 7, 7),
+            ("赤金".to_owned(), -2, 0),
+            ("龙门币".to_owned(), -2000, 3000),
+        ]);
+        assert!(depot_items(&json!({ "items": [{ "id": "2004" }] })).is_none());
+    }
+
+    #[test]
+    fn parse_depot_args() {
+        let command = crate::command::parse_from(["maa", "depot"]).command;
+        let crate::Command::Depot { args, .. } = command else {
+            panic!("Not a Depot command");
+        };
+        assert_eq!(args.format, DepotFormat::ArkPlanner);
+        assert_eq!(args.output, None);
+
+        let command =
+            crate::command::parse_from(["maa", "depot", "-f", "penguin", "-o", "depot.json"])
+                .command;
+        let crate::Command::Depot { args, .. } = command else {
+            panic!("Not a Depot command");
+        };
+        assert_eq!(args.format, DepotFormat::ArkPlanner);
+        assert_eq!(args.output, Some(PathBuf::from("depot.json")));
+
+        let command = crate::command::parse_from(["maa", "depot", "--format", "lolicon"]).command;
+        let crate::Command::Depot { args, .. } = command else {
+            panic!("Not a Depot command");
+        };
+        assert_eq!(args.format, DepotFormat::Lolicon);
+        assert!(!args.diff);
+
+        let command = crate::command::parse_from(["maa", "depot", "--diff"]).command;
+        let crate::Command::Depot { args, .. } = command else {
+            panic!("Not a Depot command");
+        };
+        assert!(args.diff);
+    }
+
+    #[test]
+    fn parse_convert_video_args() {
+        let command = crate::command::parse_from([
+            "maa",
+            "convert-video",
+            "clear.mp4",
+            "--output",
+            "copilot.json",
+        ])
+        .command;
+        let crate::Command::ConvertVideo { args, .. } = command else {
+            panic!("Not a ConvertVideo command");
+        };
+        assert_eq!(args.video, PathBuf::from("clear.mp4"));
+        assert_eq!(args.output, Some(PathBuf::from("copilot.json")));
+
+        assert_eq!(
+            generated_copilot_file(&json!({ "filename": "/tmp/1-7.json" })),
+            Some("/tmp/1-7.json")
+        );
+        assert_eq!(generated_copilot_file(&json!({})), None);
+    }
+
+    #[test]
+    fn operbox_changes() {
+        let old = json!([
+            { "id": "char_002_amiya", "name": "阿米娅", "elite": 1, "level": 50, "potential": 6 },
+            { "id": "char_003_kalts", "name": "凯尔希", "elite": 2, "level": 1, "potential": 1 },
+            { "id": "char_010_chen", "name": "陈", "elite": 2, "level": 90, "potential": 1 }
+        ]);
+        let new = json!([
+            { "id": "char_002_amiya", "name": "阿米娅", "elite": 2, "level": 1, "potential": 6 },
+            { "id": "char_003_kalts", "name": "凯尔希", "elite": 2, "level": 1, "potential": 2 },
+            { "id": "char_010_chen", "name": "陈", "elite": 2, "level": 90, "potential": 1 },
+            { "id": "char_017_huang", "name": "煌", "elite": 0, "level": 1, "potential": 1 }
+        ]);
+
+        assert_eq!(operator_changes(&old, &new), [
+            "阿米娅: E1 Lv.50 -> E2 Lv.1",
+            "凯尔希: potential 1 -> 2",
+            "New operator: 煌 (E0 Lv.1)",
+        ]);
+        assert!(operator_changes(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn format_operbox() {
+        let details = json!({
+            "done": true,
+            "all_opers": [
+                { "id": "char_002_amiya", "name": "阿米娅", "own": true, "rarity": 5 },
+                { "id": "char_003_kalts", "name": "凯尔希", "own": false, "rarity": 6 }
+            ],
+            "own_opers": [
+                {
+                    "id": "char_002_amiya",
+                    "name": "阿米娅",
+                    "own": true,
+                    "rarity": 5,
+                    "elite": 2,
+                    "level": 50,
+                    "potential": 6
+                },
+                { "id": "char_x", "name": "a,\"b\"", "rarity": 1 }
+            ]
+        });
+
+        assert_eq!(
+            OperBoxFormat::Csv.format(&details).unwrap(),
+            "id,name,rarity,elite,level,potential\n\
+             char_002_amiya,阿米娅,5,2,50,6\n\
+             char_x,\"a,\"\"b\"\"\",1,,,"
+        );
+        assert_eq!(
+            serde_json::from_str::<Value>(&OperBoxFormat::Json.format(&details).unwrap()).unwrap(),
+            details["own_opers"]
+        );
+        assert_eq!(OperBoxFormat::Csv.format(&json!({ "done": false })), None);
+
+        let command = crate::command::parse_from(["maa", "operbox", "-f", "csv"]).command;
+        let crate::Command::OperBox { args, .. } = command else {
+            panic!("Not an OperBox command");
+        };
+        assert_eq!(args.format, OperBoxFormat::Csv);
+        assert_eq!(args.output, None);
+    }
+}
@@ -0,0 +1,117 @@
+use std::{
+    process::{Command, Stdio},
+    time::Instant,
+};
+
+use anyhow::{bail, Context, Result};
+use log::{debug, info, trace};
+
+use super::super::TimedOut;
+use crate::config::asst::Emulator;
+
+pub struct EmulatorApp<'a> {
+    adb_path: &'a str,
+    address: &'a str,
+    emulator: &'a Emulator,
+}
+
+impl<'a> EmulatorApp<'a> {
+    pub const fn new(adb_path: &'a str, address: &'a str, emulator: &'a Emulator) -> Self {
+        Self {
+            adb_path,
+            address,
+            emulator,
+        }
+    }
+
+    /// Check if the device is online by `adb get-state`
+    ///
+    /// For network devices like `127.0.0.1:5555`, `adb connect` is required before checking.
+    fn online(&self) -> bool {
+        if self.address.contains(':') {
+            let _ = Command::new(self.adb_path)
+                .args(["connect", self.address])
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status();
+        }
+
+        Command::new(self.adb_path)
+            .args(["-s", self.address, "get-state"])
+            .stderr(Stdio::null())
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "device")
+    }
+
+    /// Check if the system of device has booted by the property `sys.boot_completed`
+    ///
+    /// A device is online as soon as adb daemon is started, but the game can't be started
+    /// until the system has booted.
+    fn booted(&self) -> bool {
+        Command::new(self.adb_path)
+            .args(["-s", self.address, "shell", "getprop", "sys.boot_completed"])
+            .stderr(Stdio::null())
+            .output()
+            .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "1")
+    }
+
+    fn launch(&self) -> Result<()> {
+        let (program, args) = self
+            .emulator
+            .launch
+            .split_first()
+            .context("Empty launch command of emulator")?;
+        info!("Launching emulator: {}", self.emulator.launch.join(" "));
+        Command::new(program)
+            .args(args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .context("Failed to launch emulator!")?;
+
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl super::ExternalApp for EmulatorApp<'_> {
+    async fn open(&self, deadline: Option<Instant>) -> Result<()> {
+        // The emulator may be launched by others but not booted yet
+        let launched = self.online();
+        if launched && self.booted() {
+            debug!("Device {} is online", self.address);
+            return Ok(());
+        }
+
+        if !launched {
+            self.launch()?;
+        }
+
+        // Wait for device online and booted
+        let start = Instant::now();
+        loop {
+            if self.online() && self.booted() {
+                info!("Emulator ready!");
+                break;
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                bail!(TimedOut(None));
+            }
+            if start.elapsed() > self.emulator.boot_timeout {
+                bail!(
+                    "Emulator is not ready after {} seconds",
+                    self.emulator.boot_timeout.as_secs()
+                );
+            }
+            trace!("Waiting for emulator ready...");
+            std::thread::sleep(std::time::Duration::from_secs(1));
+        }
+
+        Ok(())
+    }
+
+    async fn close(&self) -> Result<()> {
+        // Emulator is kept running after tasks finished
+        Ok(())
+    }
+}
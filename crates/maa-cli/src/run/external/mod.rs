@@ -1,10 +1,18 @@
+use std::time::Instant;
+
 #[async_trait::async_trait]
 pub(super) trait ExternalApp {
-    async fn open(&self) -> anyhow::Result<()>;
+    /// Open the app and wait until it's ready, fail with [`TimedOut`] after the deadline
+    ///
+    /// [`TimedOut`]: super::TimedOut
+    async fn open(&self, deadline: Option<Instant>) -> anyhow::Result<()>;
 
     async fn close(&self) -> anyhow::Result<()>;
 }
 
+mod emulator;
+pub(super) use emulator::EmulatorApp;
+
 #[cfg(target_os = "macos")]
 mod playcover;
 #[cfg(target_os = "macos")]
@@ -1,18 +1,26 @@
-use anyhow::{Context, Result};
+use std::time::Instant;
+
+use anyhow::{bail, Context, Result};
 use log::{info, trace};
 use tokio::net::TcpStream;
 
+use super::super::TimedOut;
 use crate::config::task::ClientType;
 
 #[cfg_attr(test, derive(PartialEq, Debug))]
 pub struct PlayCoverApp<'a> {
     client: ClientType,
     address: &'a str,
+    bundle_id: Option<&'a str>,
 }
 
 impl<'a> PlayCoverApp<'a> {
-    pub const fn new(client: ClientType, address: &'a str) -> Self {
-        Self { client, address }
+    pub const fn new(client: ClientType, address: &'a str, bundle_id: Option<&'a str>) -> Self {
+        Self {
+            client,
+            address,
+            bundle_id,
+        }
     }
 
     async fn connect(&self) -> Result<TcpStream> {
@@ -26,19 +34,23 @@ impl<'a> PlayCoverApp<'a> {
 
 #[async_trait::async_trait]
 impl super::ExternalApp for PlayCoverApp<'_> {
-    async fn open(&self) -> Result<()> {
+    async fn open(&self, deadline: Option<Instant>) -> Result<()> {
         if self.connect().await.is_ok() {
             info!("Game is already running!");
             return Ok(());
         }
 
-        let app = self.client.app();
-        info!("Starting app: {}", app);
-        std::process::Command::new("open")
-            .arg("-a")
-            .arg(app)
-            .status()
-            .context("Failed to start game!")?;
+        let mut cmd = std::process::Command::new("open");
+        // Prefer bundle identifier if given, which is independent of the app name
+        if let Some(bundle_id) = self.bundle_id {
+            info!("Starting app with bundle identifier: {}", bundle_id);
+            cmd.arg("-b").arg(bundle_id);
+        } else {
+            let app = self.client.app();
+            info!("Starting app: {}", app);
+            cmd.arg("-a").arg(app);
+        }
+        cmd.status().context("Failed to start game!")?;
 
         // Wait for game ready
         loop {
@@ -46,6 +58,9 @@ impl super::ExternalApp for PlayCoverApp<'_> {
                 info!("Game ready!");
                 break;
             }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                bail!(TimedOut(None));
+            }
             trace!("Waiting for game ready...");
             std::thread::sleep(std::time::Duration::from_millis(500));
         }
@@ -67,10 +82,19 @@ mod tests {
     fn from() {
         use crate::config::task::ClientType::*;
         assert_eq!(
-            PlayCoverApp::new(Official, "localhost:1717"),
+            PlayCoverApp::new(Official, "localhost:1717", None),
             PlayCoverApp {
                 client: Official,
                 address: "localhost:1717",
+                bundle_id: None,
+            },
+        );
+        assert_eq!(
+            PlayCoverApp::new(YoStarEN, "localhost:1717", Some("com.YoStarEN.Arknights")),
+            PlayCoverApp {
+                client: YoStarEN,
+                address: "localhost:1717",
+                bundle_id: Some("com.YoStarEN.Arknights"),
             },
         );
     }
@@ -0,0 +1,123 @@
+//! Failures of a run by their kinds, for which maa exits with distinct codes
+//!
+//! The exit codes are stable, so that wrapper scripts and schedulers can handle failures by
+//! their kinds, e.g. retry later if the device is not connected:
+//!
+//! - `1`: other errors;
+//! - `2`: invalid command line arguments, given by clap;
+//! - `3`: invalid profile or task file;
+//! - `4`: MaaCore or its resources can't be loaded;
+//! - `5`: the device can't be connected, including launching the emulator;
+//! - `6`: some tasks failed;
+//! - `124`: tasks timed out, see [`TimedOut`];
+//! - `130`: interrupted by the user.
+
+use std::borrow::Cow;
+
+use super::TimedOut;
+use crate::i18n::tr;
+
+#[derive(Debug, Clone, Copy)]
+enum Kind {
+    Config,
+    LoadCore,
+    Connection,
+    Task,
+    Interrupted,
+}
+
+/// Error of a specific kind of failure, used as the error or a context of it
+#[derive(Debug)]
+pub struct Failure {
+    kind: Kind,
+    message: Cow<'static, str>,
+}
+
+impl Failure {
+    fn new(kind: Kind, message: impl Into<Cow<'static, str>>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+        }
+    }
+
+    pub fn config(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(Kind::Config, message)
+    }
+
+    pub fn load_core(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(Kind::LoadCore, message)
+    }
+
+    pub fn connection(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(Kind::Connection, message)
+    }
+
+    pub fn task(message: impl Into<Cow<'static, str>>) -> Self {
+        Self::new(Kind::Task, message)
+    }
+
+    pub fn interrupted() -> Self {
+        Self::new(Kind::Interrupted, tr!("error-interrupted"))
+    }
+
+    const fn exit_code(&self) -> i32 {
+        match self.kind {
+            Kind::Config => 3,
+            Kind::LoadCore => 4,
+            Kind::Connection => 5,
+            Kind::Task => 6,
+            Kind::Interrupted => 130,
+        }
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for Failure {}
+
+/// Code to exit with for given error, `1` if it's not a failure of a known kind
+///
+/// The outermost failure is used if there are multiple ones in the chain of contexts.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    if err.is::<TimedOut>() {
+        TimedOut::EXIT_CODE
+    } else {
+        err.downcast_ref::<Failure>().map_or(1, Failure::exit_code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Context;
+
+    use super::*;
+
+    #[test]
+    fn codes() {
+        assert_eq!(exit_code(&anyhow::anyhow!("unknown")), 1);
+        assert_eq!(exit_code(&Failure::config("Invalid").into()), 3);
+        assert_eq!(exit_code(&TimedOut(None).into()), 124);
+        assert_eq!(exit_code(&Failure::interrupted().into()), 130);
+
+        // Failures are found through contexts
+        let err = Err::<(), _>(anyhow::anyhow!("no device"))
+            .context(Failure::connection("Failed to connect"))
+            .context("Failed to run")
+            .unwrap_err();
+        assert_eq!(exit_code(&err), 5);
+        assert_eq!(
+            format!("{err:#}"),
+            "Failed to run: Failed to connect: no device"
+        );
+
+        let err = Err::<(), _>(Failure::load_core("Failed to load MaaCore!"))
+            .context(Failure::task("Task failed"))
+            .unwrap_err();
+        assert_eq!(exit_code(&err), 6);
+    }
+}
@@ -0,0 +1,128 @@
+//! Progress of farming an item to a target quantity, tracked from drops of fight tasks
+//!
+//! MaaCore stops a fight task once the target given by `drops` is met in the task, while the
+//! progress here is kept across runs of a loop, so that the loop ends once the target is met.
+//! So only the remaining quantity is given to MaaCore when the task is run again.
+
+use std::sync::Mutex;
+
+use crate::value::MAAValue;
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone)]
+pub struct Target {
+    pub item_id: String,
+    pub item: String,
+    pub quantity: i64,
+    pub farmed: i64,
+}
+
+impl Target {
+    pub fn is_met(&self) -> bool {
+        self.farmed >= self.quantity
+    }
+
+    fn remaining_drops(&self, params: &MAAValue) -> Option<MAAValue> {
+        let remaining = self.quantity - self.farmed;
+        if self.farmed == 0 || remaining <= 0 {
+            return None;
+        }
+        params.get("drops")?.get(&self.item_id)?;
+
+        let mut params = params.clone();
+        params
+            .get_mut("drops")?
+            .insert(self.item_id.as_str(), remaining as i32);
+        Some(params)
+    }
+
+    fn record<'a>(&mut self, drops: impl IntoIterator<Item = (&'a str, i64)>) {
+        self.farmed += drops
+            .into_iter()
+            .filter(|(item_id, _)| *item_id == self.item_id)
+            .map(|(_, quantity)| quantity)
+            .sum::<i64>();
+    }
+}
+
+static TARGET: Mutex<Option<Target>> = Mutex::new(None);
+
+/// Set the item and quantity to farm
+pub fn set_target(item_id: &str, item: &str, quantity: i64) {
+    *TARGET.lock().unwrap() = Some(Target {
+        item_id: item_id.to_owned(),
+        item: item.to_owned(),
+        quantity,
+        farmed: 0,
+    });
+}
+
+/// Record drops of a battle as pairs of item id and quantity, and return the progress
+///
+/// Returns `None` if no target is set.
+pub fn record<'a>(drops: impl IntoIterator<Item = (&'a str, i64)>) -> Option<Target> {
+    let mut target = TARGET.lock().unwrap();
+    let target = target.as_mut()?;
+    target.record(drops);
+    Some(target.clone())
+}
+
+/// Parameters of a fight task with the quantity of the target item in `drops` replaced by the
+/// remaining one, `None` if there is no farmed quantity to subtract
+pub fn remaining_drops(params: &MAAValue) -> Option<MAAValue> {
+    let target = TARGET.lock().unwrap().clone()?;
+    target.remaining_drops(params)
+}
+
+/// Whether the target is set and met
+pub fn is_met() -> bool {
+    TARGET.lock().unwrap().as_ref().is_some_and(Target::is_met)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object;
+
+    #[test]
+    fn record_drops() {
+        let mut target = Target {
+            item_id: "30012".to_owned(),
+            item: "固源岩".to_owned(),
+            quantity: 5,
+            farmed: 0,
+        };
+
+        target.record([("30012", 2), ("30011", 3)]);
+        assert_eq!(target.farmed, 2);
+        assert!(!target.is_met());
+
+        target.record([("4001", 12), ("30012", 3)]);
+        assert_eq!(target.farmed, 5);
+        assert!(target.is_met());
+    }
+
+    #[test]
+    fn remaining_drops() {
+        let mut target = Target {
+            item_id: "30012".to_owned(),
+            item: "固源岩".to_owned(),
+            quantity: 5,
+            farmed: 0,
+        };
+        let params = object!("stage" => "1-7", "drops" => object!("30012" => 5));
+
+        assert_eq!(target.remaining_drops(&params), None);
+
+        target.record([("30012", 2)]);
+        assert_eq!(
+            target.remaining_drops(&params),
+            Some(object!("stage" => "1-7", "drops" => object!("30012" => 3)))
+        );
+        // Other tasks are not changed
+        assert_eq!(target.remaining_drops(&object!("stage" => "1-7")), None);
+
+        target.record([("30012", 3)]);
+        assert_eq!(target.remaining_drops(&params), None);
+    }
+}
@@ -0,0 +1,519 @@
+//! Runs with their tasks recorded in a SQLite database, browsed by `maa history`
+//!
+//! Every run of tasks, by `maa run`, presets and jobs of the daemon, is recorded in
+//! `history.db` in the state directory after its summary is printed, with the task files run,
+//! the result and duration of each task, drops of fight tasks and the error the run failed with.
+//! Dry runs are not recorded.
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use maa_dirs::Ensure;
+use rusqlite::{params, Connection};
+use serde_json::Value;
+
+use crate::{config::task::duration, dirs};
+
+/// Version of the schema, stored as `user_version` of the database
+const SCHEMA_VERSION: i64 = 1;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id INTEGER PRIMARY KEY,
+    start_time TEXT NOT NULL,
+    end_time TEXT NOT NULL,
+    error TEXT,
+    task_files TEXT
+);
+CREATE TABLE IF NOT EXISTS tasks (
+    id INTEGER PRIMARY KEY,
+    run_id INTEGER NOT NULL REFERENCES runs(id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    type TEXT NOT NULL,
+    result TEXT NOT NULL,
+    duration INTEGER,
+    stage TEXT,
+    times INTEGER,
+    sanity INTEGER,
+    drops TEXT
+);
+CREATE INDEX IF NOT EXISTS tasks_run_id ON tasks (run_id);
+";
+
+fn database_path() -> PathBuf {
+    dirs::state().join("history.db")
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+struct Run {
+    start_time: DateTime<Local>,
+    end_time: DateTime<Local>,
+    /// Error the run failed with
+    error: Option<String>,
+    /// Names of task files run, stored in JSON
+    task_files: Vec<String>,
+    tasks: Vec<Task>,
+}
+
+/// The last run of a task file
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone, Copy)]
+pub struct LastRun {
+    pub time: DateTime<Local>,
+    pub succeeded: bool,
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+struct Task {
+    name: String,
+    task_type: String,
+    result: String,
+    /// Duration in seconds, if the task is started and ended
+    duration: Option<i64>,
+    stage: Option<String>,
+    times: Option<i64>,
+    sanity: Option<i64>,
+    /// Total drops of fight tasks, keyed by the name of item
+    drops: BTreeMap<String, i64>,
+}
+
+impl Task {
+    /// Parse a task in the summary exported in JSON
+    fn from_summary(value: &Value) -> Option<Self> {
+        let str_of = |key| value.get(key).and_then(Value::as_str).map(str::to_owned);
+        let int_of = |key| value.get(key).and_then(Value::as_i64);
+        Some(Self {
+            name: str_of("name")?,
+            task_type: str_of("type")?,
+            result: str_of("result")?,
+            duration: int_of("duration"),
+            stage: str_of("stage"),
+            times: int_of("times"),
+            sanity: int_of("sanity"),
+            drops: value
+                .get("drops")
+                .and_then(|drops| serde_json::from_value(drops.clone()).ok())
+                .unwrap_or_default(),
+        })
+    }
+
+    /// Whether the task is of given type or has given name, ignoring cases
+    fn matches(&self, task: &str) -> bool {
+        self.task_type.eq_ignore_ascii_case(task) || self.name.eq_ignore_ascii_case(task)
+    }
+}
+
+impl std::fmt::Display for Task {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "[{}]", self.name)?;
+        if self.name != self.task_type {
+            write!(f, " ({})", self.task_type)?;
+        }
+        write!(f, " {}", self.result)?;
+        if let Some(duration) = self.duration {
+            write!(f, " in {}", format_seconds(duration))?;
+        }
+        if let Some(stage) = &self.stage {
+            write!(f, ", {stage}")?;
+            if let Some(times) = self.times {
+                write!(f, " x {times}")?;
+            }
+        }
+        if let Some(sanity) = self.sanity {
+            write!(f, ", {sanity} sanity")?;
+        }
+        if !self.drops.is_empty() {
+            let drops = self
+                .drops
+                .iter()
+                .map(|(item, count)| format!("{item} x {count}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            write!(f, "\n    Drops: {drops}")?;
+        }
+        Ok(())
+    }
+}
+
+fn format_seconds(seconds: i64) -> String {
+    duration::format(std::time::Duration::from_secs(seconds.max(0) as u64))
+}
+
+impl std::fmt::Display for Run {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({})",
+            self.start_time.format("%Y-%m-%d %H:%M:%S"),
+            format_seconds((self.end_time - self.start_time).num_seconds())
+        )?;
+        match &self.error {
+            Some(error) => writeln!(f, " failed: {error}")?,
+            None => writeln!(f, " succeeded")?,
+        }
+        for task in &self.tasks {
+            writeln!(f, "  {task}")?;
+        }
+        Ok(())
+    }
+}
+
+struct Database(Connection);
+
+impl Database {
+    fn open(path: &std::path::Path) -> Result<Self> {
+        let connection = Connection::open(path)
+            .with_context(|| format!("Failed to open run history {}", path.display()))?;
+        Self::init(connection)
+    }
+
+    fn init(connection: Connection) -> Result<Self> {
+        let version: i64 = connection.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        if version > SCHEMA_VERSION {
+            bail!("Run history is created by a newer version of maa-cli");
+        }
+        connection.execute_batch(SCHEMA)?;
+        connection.pragma_update(None, "user_version", SCHEMA_VERSION)?;
+        connection.pragma_update(None, "foreign_keys", true)?;
+        Ok(Self(connection))
+    }
+
+    fn insert(&mut self, run: &Run) -> Result<()> {
+        let transaction = self.0.transaction()?;
+        transaction.execute(
+            "INSERT INTO runs (start_time, end_time, error, task_files) VALUES (?1, ?2, ?3, ?4)",
+            params![
+                run.start_time.to_rfc3339(),
+                run.end_time.to_rfc3339(),
+                run.error,
+                serde_json::to_string(&run.task_files)?
+            ],
+        )?;
+        let run_id = transaction.last_insert_rowid();
+        for task in &run.tasks {
+            let drops = (!task.drops.is_empty())
+                .then(|| serde_json::to_string(&task.drops))
+                .transpose()?;
+            transaction.execute(
+                "INSERT INTO tasks (run_id, name, type, result, duration, stage, times, sanity, drops)
+                VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    run_id,
+                    task.name,
+                    task.task_type,
+                    task.result,
+                    task.duration,
+                    task.stage,
+                    task.times,
+                    task.sanity,
+                    drops
+                ],
+            )?;
+        }
+        transaction.commit()?;
+        Ok(())
+    }
+
+    /// The latest `last` runs from the oldest to the newest
+    ///
+    /// If `filter` is given, only tasks matching it are kept, and runs without such tasks are
+    /// skipped.
+    fn runs(&self, last: usize, filter: Option<&str>) -> Result<Vec<Run>> {
+        let mut runs_statement = self.0.prepare(
+            "SELECT id, start_time, end_time, error, task_files FROM runs ORDER BY id DESC",
+        )?;
+        let mut tasks_statement = self.0.prepare(
+            "SELECT name, type, result, duration, stage, times, sanity, drops
+            FROM tasks WHERE run_id = ?1 ORDER BY id",
+        )?;
+
+        let mut runs = Vec::new();
+        let mut rows = runs_statement.query([])?;
+        while runs.len() < last {
+            let Some(row) = rows.next()? else {
+                break;
+            };
+            let id: i64 = row.get(0)?;
+            let tasks = tasks_statement
+                .query_map([id], |row| {
+                    let drops: Option<String> = row.get(7)?;
+                    Ok(Task {
+                        name: row.get(0)?,
+                        task_type: row.get(1)?,
+                        result: row.get(2)?,
+                        duration: row.get(3)?,
+                        stage: row.get(4)?,
+                        times: row.get(5)?,
+                        sanity: row.get(6)?,
+                        drops: drops
+                            .and_then(|drops| serde_json::from_str(&drops).ok())
+                            .unwrap_or_default(),
+                    })
+                })?
+                .collect::<rusqlite::Result<Vec<_>>>()?
+                .into_iter()
+                .filter(|task| filter.is_none_or(|filter| task.matches(filter)))
+                .collect::<Vec<_>>();
+            if filter.is_some() && tasks.is_empty() {
+                continue;
+            }
+            let task_files: Option<String> = row.get(4)?;
+            runs.push(Run {
+                start_time: parse_time(row.get(1)?)?,
+                end_time: parse_time(row.get(2)?)?,
+                error: row.get(3)?,
+                task_files: task_files
+                    .and_then(|files| serde_json::from_str(&files).ok())
+                    .unwrap_or_default(),
+                tasks,
+            });
+        }
+        runs.reverse();
+        Ok(runs)
+    }
+
+    /// The last run of each task file, keyed by the name of task file
+    fn last_runs(&self) -> Result<BTreeMap<String, LastRun>> {
+        let mut statement = self.0.prepare(
+            "SELECT file.value, runs.end_time, runs.error IS NULL
+            FROM runs, json_each(runs.task_files) AS file
+            WHERE runs.task_files IS NOT NULL ORDER BY runs.id",
+        )?;
+        let mut rows = statement.query([])?;
+        let mut last_runs = BTreeMap::new();
+        // Later runs replace earlier ones
+        while let Some(row) = rows.next()? {
+            last_runs.insert(row.get(0)?, LastRun {
+                time: parse_time(row.get(1)?)?,
+                succeeded: row.get(2)?,
+            });
+        }
+        Ok(last_runs)
+    }
+}
+
+fn parse_time(time: String) -> Result<DateTime<Local>> {
+    Ok(DateTime::parse_from_rfc3339(&time)
+        .with_context(|| format!("Invalid time {time} in run history"))?
+        .with_timezone(&Local))
+}
+
+/// Record a run of given task files with tasks in its summary exported in JSON
+pub fn record_run(
+    start_time: DateTime<Local>,
+    end_time: DateTime<Local>,
+    error: Option<String>,
+    task_files: &[String],
+    tasks: Option<&Value>,
+) -> Result<()> {
+    let run = Run {
+        start_time,
+        end_time,
+        error,
+        task_files: task_files.to_vec(),
+        tasks: tasks
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(Task::from_summary)
+            .collect(),
+    };
+    dirs::state().ensure()?;
+    Database::open(&database_path())?
+        .insert(&run)
+        .context("Failed to record the run in history")
+}
+
+/// The last run of each task file, empty if no run is recorded
+pub fn last_runs() -> Result<BTreeMap<String, LastRun>> {
+    let path = database_path();
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    Database::open(&path)?.last_runs()
+}
+
+/// Print the latest `last` runs, only with tasks of given type or name if `task` is given
+pub fn show(last: usize, task: Option<&str>) -> Result<()> {
+    let path = database_path();
+    if !path.exists() {
+        eprintln!("No runs recorded");
+        return Ok(());
+    }
+
+    let runs = Database::open(&path)?.runs(last, task)?;
+    if runs.is_empty() {
+        eprintln!("No runs recorded");
+    }
+    for run in runs {
+        print!("{run}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn time(time: &str) -> DateTime<Local> {
+        DateTime::parse_from_rfc3339(time)
+            .unwrap()
+            .with_timezone(&Local)
+    }
+
+    fn fight_task() -> Task {
+        Task {
+            name: "Fight TS".to_owned(),
+            task_type: "Fight".to_owned(),
+            result: "completed".to_owned(),
+            duration: Some(754),
+            stage: Some("TS-9".to_owned()),
+            times: Some(5),
+            sanity: Some(105),
+            drops: BTreeMap::from([("Orirock Cube".to_owned(), 10)]),
+        }
+    }
+
+    fn mall_task() -> Task {
+        Task {
+            name: "Mall".to_owned(),
+            task_type: "Mall".to_owned(),
+            result: "error".to_owned(),
+            duration: None,
+            stage: None,
+            times: None,
+            sanity: None,
+            drops: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn parse_summary() {
+        assert_eq!(
+            Task::from_summary(&json!({
+                "name": "Fight TS",
+                "type": "Fight",
+                "result": "completed",
+                "start_time": "2024-01-01T04:00:00+08:00",
+                "end_time": "2024-01-01T04:12:34+08:00",
+                "duration": 754,
+                "stage": "TS-9",
+                "times": 5,
+                "sanity": 105,
+                "drops": { "Orirock Cube": 10 },
+            })),
+            Some(fight_task())
+        );
+        assert_eq!(
+            Task::from_summary(&json!({
+                "name": "Mall",
+                "type": "Mall",
+                "result": "error",
+                "start_time": "2024-01-01T04:00:00+08:00",
+                "end_time": null,
+                "duration": null,
+            })),
+            Some(mall_task())
+        );
+        assert_eq!(Task::from_summary(&json!({ "name": "Mall" })), None);
+    }
+
+    #[test]
+    fn insert_and_query() {
+        let mut database = Database::init(Connection::open_in_memory().unwrap()).unwrap();
+        assert_eq!(database.runs(10, None).unwrap(), Vec::new());
+
+        let runs = [
+            Run {
+                start_time: time("2024-01-01T04:00:00+08:00"),
+                end_time: time("2024-01-01T04:15:00+08:00"),
+                error: None,
+                task_files: vec!["daily".to_owned()],
+                tasks: vec![fight_task(), mall_task()],
+            },
+            Run {
+                start_time: time("2024-01-01T12:00:00+08:00"),
+                end_time: time("2024-01-01T12:01:00+08:00"),
+                error: Some("Failed to connect".to_owned()),
+                task_files: vec!["daily".to_owned(), "farm".to_owned()],
+                tasks: Vec::new(),
+            },
+            Run {
+                start_time: time("2024-01-02T04:00:00+08:00"),
+                end_time: time("2024-01-02T04:05:00+08:00"),
+                error: None,
+                task_files: Vec::new(),
+                tasks: vec![mall_task()],
+            },
+        ];
+        for run in &runs {
+            database.insert(run).unwrap();
+        }
+
+        let [first, second, third] = runs;
+        let all = database.runs(10, None).unwrap();
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0], first);
+        assert_eq!(database.runs(2, None).unwrap(), vec![second, third]);
+
+        // Runs without matched tasks are skipped, and only matched tasks are kept
+        assert_eq!(database.runs(10, Some("fight")).unwrap(), vec![Run {
+            tasks: vec![fight_task()],
+            ..first
+        }]);
+        assert_eq!(database.runs(10, Some("fight ts")).unwrap().len(), 1);
+        assert_eq!(
+            database.runs(1, Some("Mall")).unwrap()[0].start_time,
+            all[2].start_time
+        );
+        assert_eq!(database.runs(10, Some("Infrast")).unwrap(), Vec::new());
+
+        // The last run of each task file, not changed by runs without task files
+        assert_eq!(
+            database.last_runs().unwrap(),
+            BTreeMap::from([
+                ("daily".to_owned(), LastRun {
+                    time: time("2024-01-01T12:01:00+08:00"),
+                    succeeded: false,
+                }),
+                ("farm".to_owned(), LastRun {
+                    time: time("2024-01-01T12:01:00+08:00"),
+                    succeeded: false,
+                }),
+            ])
+        );
+    }
+
+    #[test]
+    fn newer_schema() {
+        let connection = Connection::open_in_memory().unwrap();
+        connection
+            .pragma_update(None, "user_version", SCHEMA_VERSION + 1)
+            .unwrap();
+        assert!(Database::init(connection).is_err());
+    }
+
+    #[test]
+    fn display() {
+        let run = Run {
+            start_time: time("2024-01-01T04:00:00Z"),
+            end_time: time("2024-01-01T04:15:00Z"),
+            error: Some("Task Mall failed".to_owned()),
+            task_files: vec!["daily".to_owned()],
+            tasks: vec![fight_task(), mall_task()],
+        };
+        assert_eq!(
+            run.to_string(),
+            format!(
+                "{} (15m) failed: Task Mall failed\n\
+                \x20 [Fight TS] (Fight) completed in 12m34s, TS-9 x 5, 105 sanity\n\
+                \x20   Drops: Orirock Cube x 10\n\
+                \x20 [Mall] error\n",
+                run.start_time.format("%Y-%m-%d %H:%M:%S")
+            )
+        );
+    }
+}
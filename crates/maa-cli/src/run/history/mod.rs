@@ -0,0 +1,7 @@
+//! History of runs
+//!
+//! Every run with its tasks is recorded in a database, which is shown by `maa history`,
+//! and the last run of each task file in it is shown by `maa list`.
+
+mod database;
+pub use database::{last_runs, record_run, show};
@@ -0,0 +1,153 @@
+//! Run shell commands set by `hooks` in profile on events of tasks
+//!
+//! Information of the task is passed by environment variables:
+//!
+//! - `MAA_TASK_INDEX`: index of the task among tasks to run, starting from 0, tasks whose
+//!   conditions are not matched are not counted;
+//! - `MAA_TASK_NAME`: name of the task, default to its type;
+//! - `MAA_TASK_TYPE`: type of the task, like `Fight`;
+//! - `MAA_TASK_PARAMS`: parameters passed to MaaCore in JSON;
+//! - `MAA_TASK_RESULT`: `success` or `failure`, not set for `on_task_start`;
+//! - `MAA_TASK_ERROR`: the error stopped the task, like timeout, only set if there is one.
+//!
+//! Changes of owned operators recognized by `maa operbox` are passed to `on_operbox_change`
+//! by `MAA_OPERBOX_CHANGES`, one change per line.
+//!
+//! A failed hook never stops the tasks, its failure is only logged. A hook not exited in the
+//! timeout is killed, so that a hung command can't block the run.
+
+use std::{
+    process::{Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use anyhow::Result;
+use log::{debug, warn};
+
+use crate::config::{asst::Hooks, task::InitializedTask};
+
+/// Call the `on_task_start` hook
+pub fn task_started(hooks: &Hooks, index: usize, task: &InitializedTask) {
+    if let Some(command) = &hooks.on_task_start {
+        call(hooks, command, index, task, &[]);
+    }
+}
+
+/// Call the `on_task_success` or `on_task_failure` hook by the result of the task
+///
+/// The result is whether the task succeeded, or the error stopped it.
+pub fn task_finished(hooks: &Hooks, index: usize, task: &InitializedTask, result: &Result<bool>) {
+    match result {
+        Ok(true) => {
+            if let Some(command) = &hooks.on_task_success {
+                call(hooks, command, index, task, &[(
+                    "MAA_TASK_RESULT",
+                    "success".into(),
+                )]);
+            }
+        }
+        Ok(false) => {
+            if let Some(command) = &hooks.on_task_failure {
+                call(hooks, command, index, task, &[(
+                    "MAA_TASK_RESULT",
+                    "failure".into(),
+                )]);
+            }
+        }
+        Err(err) => {
+            if let Some(command) = &hooks.on_task_failure {
+                call(hooks, command, index, task, &[
+                    ("MAA_TASK_RESULT", "failure".into()),
+                    ("MAA_TASK_ERROR", format!("{err:#}")),
+                ]);
+            }
+        }
+    }
+}
+
+/// Call the `on_operbox_change` hook with changes of owned operators
+pub fn operbox_changed(hooks: &Hooks, changes: &[String]) {
+    if let Some(command) = &hooks.on_operbox_change {
+        let mut shell = shell(command);
+        shell.env("MAA_OPERBOX_CHANGES", changes.join("\n"));
+        run(command, shell, hooks.timeout());
+    }
+}
+
+fn call(
+    hooks: &Hooks,
+    command: &str,
+    index: usize,
+    task: &InitializedTask,
+    extra: &[(&str, String)],
+) {
+    let params = serde_json::to_string(&task.params).unwrap_or_default();
+    let mut shell = shell(command);
+    shell
+        .env("MAA_TASK_INDEX", index.to_string())
+        .env("MAA_TASK_NAME", task.name_or_default())
+        .env("MAA_TASK_TYPE", task.task_type.to_str())
+        .env("MAA_TASK_PARAMS", params)
+        .envs(extra.iter().map(|(key, value)| (key, value)));
+    run(command, shell, hooks.timeout());
+}
+
+fn run(command: &str, mut shell: Command, timeout: Duration) {
+    debug!("Running hook: {command}");
+
+    let mut child = match shell.stdin(Stdio::null()).spawn() {
+        Ok(child) => child,
+        Err(err) => return warn!("Failed to run hook `{command}`: {err}"),
+    };
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        match child.try_wait() {
+            Ok(Some(status)) => break Ok(status),
+            Ok(None) if Instant::now() >= deadline => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return warn!(
+                    "Hook `{command}` is killed, because it's not exited in {}s",
+                    timeout.as_secs()
+                );
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(50)),
+            Err(err) => break Err(err),
+        }
+    };
+
+    match status {
+        Ok(status) if status.success() => {}
+        Ok(status) => warn!("Hook `{command}` failed with {status}"),
+        Err(err) => warn!("Failed to run hook `{command}`: {err}"),
+    }
+}
+
+fn shell(command: &str) -> Command {
+    if cfg!(windows) {
+        let mut shell = Command::new("cmd");
+        shell.arg("/C").arg(command);
+        shell
+    } else {
+        let mut shell = Command::new("sh");
+        shell.arg("-c").arg(command);
+        shell
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(unix)]
+    fn hung_hook() {
+        let started = Instant::now();
+        run("sleep 10", shell("sleep 10"), Duration::from_millis(200));
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let started = Instant::now();
+        run("true", shell("true"), Duration::from_secs(5));
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
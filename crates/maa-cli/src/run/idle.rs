@@ -0,0 +1,62 @@
+//! Close the game once it's left idle for a while, so that a forgotten game doesn't keep running
+
+use std::time::Duration;
+
+use anyhow::Result;
+use log::{info, warn};
+use maa_sys::{Assistant, TaskType};
+
+use super::{control::Control, run_tasks};
+use crate::{
+    config::{
+        asst::Hooks,
+        task::{duration, ClientType, InitializedTaskConfig, Task, TaskConfig},
+    },
+    value::MAAValue,
+};
+
+/// Whether the game is closed by the last task
+pub fn closes_game(task_config: &InitializedTaskConfig) -> bool {
+    task_config
+        .tasks
+        .last()
+        .is_some_and(|task| task.task_type == TaskType::CloseDown)
+}
+
+/// Close the game after waiting for given time
+///
+/// The waiting can be canceled by interrupting or stopping maa, then the game is left running.
+pub fn closedown_after(
+    asst: &Assistant,
+    hooks: &Hooks,
+    client_type: ClientType,
+    idle: Duration,
+    control: &Control,
+) -> Result<()> {
+    warn!(
+        "The game will be closed in {}, press Ctrl-C or run `maa stop` to cancel",
+        duration::format(idle)
+    );
+    if !control.sleep(idle) {
+        info!("Canceled, the game will not be closed");
+        return Ok(());
+    }
+    closedown(asst, hooks, client_type, control)
+}
+
+/// Close the game of given client type by a CloseDown task
+pub fn closedown(
+    asst: &Assistant,
+    hooks: &Hooks,
+    client_type: ClientType,
+    control: &Control,
+) -> Result<()> {
+    let mut task_config = TaskConfig::new();
+    task_config.push(Task::new(TaskType::CloseDown, MAAValue::new()));
+    task_config.set_default_client_type(client_type);
+    let task_config = task_config.init()?;
+
+    let ret = run_tasks(asst, &task_config.tasks, hooks, control, None, None);
+    asst.stop()?;
+    ret
+}
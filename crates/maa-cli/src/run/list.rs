@@ -0,0 +1,146 @@
+//! List task files with their descriptions, active variants and last runs
+
+use std::path::Path;
+
+use anyhow::Result;
+
+use super::{find_profile, history};
+use crate::{
+    config::{task::TaskConfig, FromFile},
+    dirs,
+};
+
+/// List task files in the `tasks` directory
+///
+/// If `names_only` is true, only names of task files are printed, which is used by completions.
+pub fn list(names_only: bool) -> Result<()> {
+    let task_dir = dirs::config().join("tasks");
+    if !task_dir.exists() {
+        eprintln!("No tasks found");
+        return Ok(());
+    }
+
+    let mut paths = Vec::new();
+    for entry in task_dir.read_dir()? {
+        let path = entry?.path();
+        if path.is_file() {
+            paths.push(path);
+        }
+    }
+    paths.sort();
+
+    if names_only {
+        for path in &paths {
+            println!("{}", name_of(path));
+        }
+        return Ok(());
+    }
+
+    // Conditions without explicit time zone are evaluated in the time zone of default server
+    let server = find_profile(dirs::config(), None)
+        .ok()
+        .and_then(|config| config.server);
+    let history = history::last_runs().unwrap_or_else(|err| {
+        log::warn!("{err:#}");
+        Default::default()
+    });
+
+    for path in &paths {
+        let name = name_of(path);
+        let task_config = TaskConfig::from_file(path).map(|mut task_config| {
+            if let Some(server) = server {
+                task_config.set_server(server);
+            }
+            task_config
+        });
+
+        match &task_config {
+            Ok(task_config) => match task_config.description() {
+                Some(description) => println!("{name}: {description}"),
+                None => println!("{name}"),
+            },
+            Err(err) => println!("{name}: invalid, {err:#}"),
+        }
+
+        if let Some(last) = history.get(name) {
+            println!(
+                "  last run: {} ({})",
+                last.time.format("%Y-%m-%d %H:%M"),
+                if last.succeeded {
+                    "succeeded"
+                } else {
+                    "failed"
+                }
+            );
+        }
+
+        if let Ok(task_config) = &task_config {
+            for line in describe_tasks(task_config) {
+                println!("  {line}");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn name_of(path: &Path) -> &str {
+    path.file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default()
+}
+
+/// Describe tasks in the task file and which of them run now
+fn describe_tasks(task_config: &TaskConfig) -> Vec<String> {
+    task_config
+        .active_variants()
+        .into_iter()
+        .map(|(name, variants)| match variants {
+            None => format!("{name} (inactive)"),
+            Some(variants) if variants.is_empty() => name.to_owned(),
+            Some(variants) => {
+                let variants: Vec<String> = variants.iter().map(|i| (i + 1).to_string()).collect();
+                format!("{name} (variant {})", variants.join(", "))
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describe() {
+        let task_config: TaskConfig = serde_json::from_value(serde_json::json!({
+            "description": "Daily routine",
+            "tasks": [
+                { "type": "StartUp" },
+                {
+                    "name": "Never",
+                    "type": "Fight",
+                    "variants": [
+                        { "condition": { "type": "Not", "condition": { "type": "Always" } } }
+                    ]
+                },
+                {
+                    "type": "Fight",
+                    "strategy": "merge",
+                    "variants": [
+                        { "condition": { "type": "Not", "condition": { "type": "Always" } } },
+                        { "condition": { "type": "Always" } },
+                        { "condition": { "type": "Always" } }
+                    ]
+                }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(task_config.description(), Some("Daily routine"));
+        assert_eq!(describe_tasks(&task_config), [
+            "StartUp",
+            "Never (inactive)",
+            "Fight (variant 2, 3)",
+        ]);
+    }
+}
@@ -0,0 +1,229 @@
+//! Run tasks repeatedly on an interval or after sanity regenerates, until a stop condition
+
+use std::time::Duration;
+
+use chrono::{Local, NaiveDateTime, TimeDelta};
+use clap::Args;
+use log::{info, warn};
+
+use super::sanity::Sanity;
+use crate::config::task::duration;
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Args, Default, Clone)]
+pub struct LoopArgs {
+    /// Run tasks repeatedly until interrupted or a stop condition is met
+    ///
+    /// The loop ends if a run failed (see `--max-failures`), the given number of runs
+    /// is reached (see `--count`), the next run would start after given time (see `--until`),
+    /// or it's interrupted by Ctrl-C or `maa stop`.
+    #[arg(long = "loop", group = "looping", verbatim_doc_comment)]
+    pub repeat: bool,
+    /// Time between the starts of two runs of `--loop`, e.g. `4h`
+    ///
+    /// If a run takes longer than the interval, the next run starts right after it.
+    /// Default to 0, which starts the next run immediately.
+    #[arg(long, value_parser = duration::parse_arg, requires = "repeat", verbatim_doc_comment)]
+    pub interval: Option<Duration>,
+    /// Run tasks again after sanity regenerates, until interrupted
+    ///
+    /// After all tasks finished, wait until the sanity left reported by fight tasks
+    /// regenerates to given value, default to the maximum sanity, and then run all tasks again.
+    /// One point of sanity regenerates every 6 minutes.
+    /// Besides the stop conditions of `--loop`, the loop also ends if no sanity is reported.
+    #[arg(long, value_name = "SANITY", group = "looping", verbatim_doc_comment)]
+    pub sanity_loop: Option<Option<i64>>,
+    /// Don't start a new run after given local time, e.g. `2024-08-01T04:00`
+    #[arg(long, value_parser = parse_time, requires = "looping")]
+    pub until: Option<NaiveDateTime>,
+    /// Maximum number of runs in the loop
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..), requires = "looping")]
+    pub count: Option<u32>,
+    /// Stop the loop after given number of failed runs, default to 1
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..), requires = "looping")]
+    pub max_failures: Option<u32>,
+}
+
+fn parse_time(s: &str) -> Result<NaiveDateTime, String> {
+    [
+        "%Y-%m-%dT%H:%M:%S",
+        "%Y-%m-%dT%H:%M",
+        "%Y-%m-%d %H:%M:%S",
+        "%Y-%m-%d %H:%M",
+    ]
+    .iter()
+    .find_map(|format| NaiveDateTime::parse_from_str(s, format).ok())
+    .ok_or_else(|| format!("invalid time `{s}`, expected a time like `2024-08-01T04:00`"))
+}
+
+impl LoopArgs {
+    pub fn is_enabled(&self) -> bool {
+        self.repeat || self.sanity_loop.is_some()
+    }
+}
+
+/// State of a loop, deciding when to start the next run
+pub struct Looper {
+    args: LoopArgs,
+    runs: u32,
+    failures: u32,
+}
+
+impl Looper {
+    /// Create a looper if looping is enabled
+    pub fn new(args: &LoopArgs) -> Option<Self> {
+        args.is_enabled().then(|| Self {
+            args: args.clone(),
+            runs: 0,
+            failures: 0,
+        })
+    }
+
+    /// Time to wait before the next run, or `None` if the loop should end
+    ///
+    /// `elapsed` is the time spent on the finished run, and `sanity` is the sanity left
+    /// reported in the run.
+    pub fn next(
+        &mut self,
+        elapsed: Duration,
+        failed: bool,
+        sanity: Option<Sanity>,
+    ) -> Option<Duration> {
+        self.next_at(elapsed, failed, sanity, Local::now().naive_local())
+    }
+
+    fn next_at(
+        &mut self,
+        elapsed: Duration,
+        failed: bool,
+        sanity: Option<Sanity>,
+        now: NaiveDateTime,
+    ) -> Option<Duration> {
+        self.runs += 1;
+        if failed {
+            self.failures += 1;
+            let max_failures = self.args.max_failures.unwrap_or(1);
+            if self.failures >= max_failures {
+                warn!("{} runs failed, stop looping", self.failures);
+                return None;
+            }
+        }
+        if self.args.count.is_some_and(|count| self.runs >= count) {
+            info!("All {} runs finished", self.runs);
+            return None;
+        }
+
+        let wait = match self.args.sanity_loop {
+            Some(target) => {
+                let Some(sanity) = sanity else {
+                    warn!("No sanity is reported by fight tasks, stop looping");
+                    return None;
+                };
+                info!("Sanity left: {}/{}", sanity.current, sanity.max);
+                sanity.time_to(target)
+            }
+            None => self
+                .args
+                .interval
+                .unwrap_or_default()
+                .saturating_sub(elapsed),
+        };
+
+        if let Some(until) = self.args.until {
+            let start = now + TimeDelta::from_std(wait).unwrap_or(TimeDelta::MAX);
+            if start > until {
+                info!("The next run would start after {until}, stop looping");
+                return None;
+            }
+        }
+
+        info!("Run {} in {} minutes", self.runs + 1, wait.as_secs() / 60);
+        Some(wait)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    fn time(s: &str) -> NaiveDateTime {
+        parse_time(s).unwrap()
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(time("2024-08-01T04:00"), time("2024-08-01 04:00:00"));
+        assert!(parse_time("2024-08-01").is_err());
+    }
+
+    #[test]
+    fn interval() {
+        let args = LoopArgs {
+            repeat: true,
+            interval: Some(4 * HOUR),
+            until: Some(time("2024-08-01T04:00")),
+            ..Default::default()
+        };
+        let mut looper = Looper::new(&args).unwrap();
+        let now = time("2024-07-31T20:00");
+        assert_eq!(looper.next_at(HOUR, false, None, now), Some(3 * HOUR));
+        assert_eq!(
+            looper.next_at(5 * HOUR, false, None, now),
+            Some(Duration::ZERO)
+        );
+        let now = time("2024-08-01T01:30");
+        assert_eq!(looper.next_at(HOUR, false, None, now), None);
+
+        assert!(Looper::new(&LoopArgs::default()).is_none());
+    }
+
+    #[test]
+    fn count_and_failures() {
+        let args = LoopArgs {
+            repeat: true,
+            count: Some(3),
+            max_failures: Some(2),
+            ..Default::default()
+        };
+        let now = time("2024-07-31T20:00");
+
+        let mut looper = Looper::new(&args).unwrap();
+        assert!(looper.next_at(HOUR, false, None, now).is_some());
+        assert!(looper.next_at(HOUR, true, None, now).is_some());
+        assert!(looper.next_at(HOUR, false, None, now).is_none());
+
+        let mut looper = Looper::new(&args).unwrap();
+        assert!(looper.next_at(HOUR, true, None, now).is_some());
+        assert!(looper.next_at(HOUR, true, None, now).is_none());
+
+        // Stop at the first failure by default
+        let mut looper = Looper::new(&LoopArgs {
+            repeat: true,
+            ..Default::default()
+        })
+        .unwrap();
+        assert!(looper.next_at(HOUR, true, None, now).is_none());
+    }
+
+    #[test]
+    fn sanity() {
+        let args = LoopArgs {
+            sanity_loop: Some(Some(35)),
+            ..Default::default()
+        };
+        let now = time("2024-07-31T20:00");
+        let sanity = Sanity {
+            current: 5,
+            max: 135,
+        };
+
+        let mut looper = Looper::new(&args).unwrap();
+        assert_eq!(
+            looper.next_at(HOUR, false, Some(sanity), now),
+            Some(3 * HOUR)
+        );
+        assert_eq!(looper.next_at(HOUR, false, None, now), None);
+    }
+}
@@ -5,24 +5,81 @@ mod callback;
 use callback::summary;
 
 mod external;
+use external::ExternalApp;
 
 pub mod preset;
 
+pub mod export;
+
+mod parallel;
+
+mod session;
+
+pub mod control;
+use control::{Control, Request};
+
+pub mod daemon;
+
+mod hooks;
+
+mod notify;
+
+pub mod history;
+
+pub mod list;
+
+mod post_action;
+
+mod idle;
+
+mod resolution;
+
+mod sanity;
+
+mod farm;
+
+mod drops;
+
+mod failure;
+pub use failure::{exit_code, Failure};
+
+mod screenshot;
+
+pub mod progress;
+
+mod report;
+
+mod looping;
 use std::{
-    path::Path,
-    sync::{atomic, Arc},
+    io::Read,
+    path::{Path, PathBuf},
+    sync::atomic,
 };
 
 use anyhow::{bail, Context, Result};
 use clap::Args;
-use log::{debug, warn};
+use log::{debug, info, warn};
+use looping::{LoopArgs, Looper};
 use maa_dirs::{self as dirs, Ensure, MAA_CORE_LIB};
-use maa_sys::Assistant;
-use signal_hook::consts::TERM_SIGNALS;
+use maa_sys::{Assistant, TaskType};
+use maa_types::primitive::AsstTaskId;
+pub use post_action::PostAction;
+use session::Session;
 
 use crate::{
-    config::{asst::AsstConfig, task::TaskConfig, FindFile},
+    activity,
+    config::{
+        asst::{AsstConfig, Hooks, Preset},
+        schema::{warn_unknown_keys, Schema},
+        task::{
+            annihilation, duration, FailurePolicy, InitializedTask, InitializedTaskConfig, Task,
+            TaskConfig, TaskDefaults,
+        },
+        FindFile,
+    },
+    i18n::tr,
     installer::resource,
+    value::MAAValue,
 };
 
 #[cfg_attr(test, derive(Debug, PartialEq))]
@@ -38,6 +95,28 @@ pub struct CommonArgs {
     /// and then you can specify the address of MaaTools here.
     #[arg(short, long, verbatim_doc_comment)]
     pub addr: Option<String>,
+    /// Name of the emulator instance defined in profile
+    ///
+    /// Instances are defined in the `instances` section of profile,
+    /// which override the connection config when selected.
+    /// If the instance has a launch command, the emulator will be launched
+    /// before connecting if it's not running.
+    /// The address specified by `--addr` has higher priority than the instance.
+    #[arg(long, verbatim_doc_comment)]
+    pub instance: Option<String>,
+    /// Run tasks on multiple devices in parallel, can be specified multiple times
+    ///
+    /// Each device is the name of an emulator instance defined in profile,
+    /// or `all` to run tasks on all instances.
+    /// Tasks on each device are run by a child process in batch mode,
+    /// whose output is prefixed by the name of the device.
+    #[arg(
+        long = "device",
+        value_name = "INSTANCE",
+        conflicts_with = "instance",
+        verbatim_doc_comment
+    )]
+    pub devices: Vec<String>,
     /// Profile (asst config file) name
     ///
     /// A profile is a config file that contains the configuration passed to MaaCore.
@@ -67,24 +146,197 @@ pub struct CommonArgs {
     /// Use at your own risk!
     #[arg(long, verbatim_doc_comment)]
     pub user_resource: bool,
+    /// Add a resource overlay layer, can be specified multiple times
+    ///
+    /// The directory should be named `resource` and have the same structure as
+    /// the resource directory of MaaCore.
+    /// Layers are loaded after all other resources in the order they are given,
+    /// including the layers set by `resource.user_dirs` in the profile,
+    /// so the resources in later layers will overwrite the earlier ones.
+    /// This option is useful to test resource modifications without changing the profile.
+    #[arg(long = "resource-layer", value_name = "DIR", verbatim_doc_comment)]
+    pub resource_layers: Vec<PathBuf>,
+    /// Start the game before running tasks
+    ///
+    /// A StartUp task will be added before other tasks if there is no StartUp task,
+    /// which is useful to run a single task like `maa fight 1-7 --startup`.
+    /// This option overrides the `startup` field in the task file.
+    #[arg(long, verbatim_doc_comment)]
+    pub startup: bool,
+    /// Close the game after all tasks finished
+    ///
+    /// A CloseDown task will be added after other tasks if there is no CloseDown task.
+    /// This option overrides the `closedown` field in the task file.
+    #[arg(long, verbatim_doc_comment)]
+    pub closedown: bool,
+    /// Action after all tasks finished
+    ///
+    /// - `exit-game`: close the game, same as `--closedown`;
+    /// - `kill-emulator`: kill the emulator by the `kill` command in profile, or by adb for
+    ///   Android emulators like `emulator-5554`;
+    /// - `suspend`: suspend the computer;
+    /// - `shutdown`: shut down the computer.
+    ///
+    /// The computer is suspended or shut down after a grace period of 60 seconds,
+    /// which can be canceled by Ctrl-C or `maa stop`.
+    /// No action is taken if the run is interrupted.
+    #[arg(long, value_enum, verbatim_doc_comment)]
+    pub post_action: Option<PostAction>,
+    /// Close the game after it's left idle for given time, e.g. `30m`
+    ///
+    /// Instead of closing the game at once like `--closedown`, maa waits for given time
+    /// after all tasks finished, so that you can still play the game, and then closes it
+    /// in case it's forgotten. The post action like `kill-emulator` is taken after that.
+    /// The waiting can be canceled by Ctrl-C or `maa stop`, then the game is left running.
+    /// For `maa daemon start`, the game is closed once no job is run for given time.
+    #[arg(
+        long,
+        value_name = "DURATION",
+        value_parser = duration::parse_arg,
+        conflicts_with = "closedown",
+        verbatim_doc_comment
+    )]
+    pub closedown_after: Option<std::time::Duration>,
+    /// Change the resolution of the device to a supported one while running tasks
+    ///
+    /// MaaCore only supports 16:9 resolutions of at least 1280x720, so the resolution of
+    /// the device is checked by `adb shell wm size` before running tasks, and the run is
+    /// aborted if it's not supported. With this option, the resolution is changed to a
+    /// supported one by `adb shell wm size` instead, and restored after all tasks finished.
+    #[arg(long, verbatim_doc_comment)]
+    pub fix_resolution: bool,
+    #[command(flatten)]
+    pub looping: LoopArgs,
     /// Parse the your config but do not connect to the game
     ///
     /// This option is useful when you want to check your config file.
-    /// It will parse your config file and set the log level to debug.
+    /// It will evaluate conditions, merge defaults and overrides,
+    /// and print the resolved tasks and the exact parameters passed to MaaCore.
     /// If there are some errors in your config file,
     /// it will print the error message and exit.
     #[arg(long, verbatim_doc_comment)]
     pub dry_run: bool,
+    /// Maximum time to run all tasks, e.g. `30m` or `1h30m`
+    ///
+    /// If tasks are not finished in time, the assistant will be stopped
+    /// and maa will exit with code 124.
+    /// This option overrides the `timeout` field in the task file.
+    #[arg(long, value_parser = duration::parse_arg, verbatim_doc_comment)]
+    pub timeout: Option<std::time::Duration>,
     /// Do not display task summary
     ///
     /// By default, maa will display task summary after all tasks are finished.
     /// If you want to disable this behavior, you can use this option.
     #[arg(long, verbatim_doc_comment)]
     pub no_summary: bool,
+    /// Append drops of each stage run to given file, e.g. `drops.csv`
+    ///
+    /// One row is appended for each stage run, with the time, stage, stars and drops,
+    /// which can be used for spreadsheets and long-term farming analytics.
+    /// The file is written as a JSON array if its extension is `.json`, CSV otherwise.
+    #[arg(long, value_name = "PATH", verbatim_doc_comment)]
+    pub drops_output: Option<PathBuf>,
+}
+
+/// Sanity policy of fight tasks, overriding the ones in task file and profile
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Args, Default)]
+pub struct SanityArgs {
+    /// Number of medicine (Sanity Potion) used by all fight tasks
+    ///
+    /// This option overrides the `medicine` parameter of all fight tasks in the task file,
+    /// as well as the default one set by `defaults.fight.medicine` in the profile.
+    #[arg(short, long, verbatim_doc_comment)]
+    pub medicine: Option<i32>,
+    /// Number of expiring medicine (Sanity Potion) used by all fight tasks
+    ///
+    /// If no number is given, all potions expiring within 48 hours are used.
+    #[arg(
+        long,
+        num_args = 0..=1,
+        default_missing_value = preset::ALL_EXPIRING_MEDICINE,
+        verbatim_doc_comment
+    )]
+    pub expiring_medicine: Option<i32>,
+    /// Number of stone (Originite Prime) used by all fight tasks
+    #[arg(long)]
+    pub stone: Option<i32>,
+}
+
+impl SanityArgs {
+    pub fn task_overrides(&self) -> TaskDefaults {
+        let mut params = MAAValue::new();
+        params.maybe_insert("medicine", self.medicine);
+        params.maybe_insert("expiring_medicine", self.expiring_medicine);
+        params.maybe_insert("stone", self.stone);
+
+        let mut overrides = TaskDefaults::default();
+        if params.as_object().is_some_and(|params| !params.is_empty()) {
+            overrides.insert(TaskType::Fight, params);
+        }
+        overrides
+    }
+}
+
+/// Override of a parameter of tasks with given type, given by `<type>.<key>=<value>`
+///
+/// The key can be a dotted path to a nested parameter, and the value is parsed as a TOML value,
+/// or a string if it's not a valid TOML value, e.g. `fight.stage=CE-6` and `fight.times=3`.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone)]
+pub struct ParamOverride {
+    task_type: TaskType,
+    params: MAAValue,
+}
+
+impl std::str::FromStr for ParamOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (path, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("invalid override `{s}`, expected `<type>.<key>=<value>`"))?;
+        let (task_type, path) = path
+            .split_once('.')
+            .ok_or_else(|| format!("invalid override `{s}`, expected `<type>.<key>=<value>`"))?;
+        let task_type: TaskType = task_type.trim().parse().map_err(|e| format!("{e}"))?;
+
+        #[derive(serde::Deserialize)]
+        struct Helper {
+            value: MAAValue,
+        }
+        let mut params = toml::from_str::<Helper>(&format!("value = {value}"))
+            .map(|helper| helper.value)
+            .unwrap_or_else(|_| value.into());
+        for key in path.rsplit('.') {
+            let key = key.trim();
+            if key.is_empty() {
+                return Err(format!("invalid override `{s}`, key can not be empty"));
+            }
+            let mut object = MAAValue::new();
+            object.insert(key, params);
+            params = object;
+        }
+
+        Ok(Self { task_type, params })
+    }
+}
+
+impl ParamOverride {
+    /// Insert overrides into given task overrides, later ones take precedence
+    pub fn apply_to(overrides: Vec<Self>, task_overrides: &mut TaskDefaults) {
+        for o in overrides {
+            task_overrides.insert(o.task_type, o.params);
+        }
+    }
 }
 
 impl CommonArgs {
-    pub fn apply_to(&self, config: &mut AsstConfig) {
+    pub fn apply_to(&self, config: &mut AsstConfig) -> Result<()> {
+        if let Some(instance) = self.instance.as_deref() {
+            config.use_instance(instance)?;
+        }
+
         if let Some(addr) = self.addr.as_ref() {
             config.connection.set_address(addr);
         }
@@ -92,18 +344,49 @@ impl CommonArgs {
         if self.user_resource {
             config.resource.use_user_resource();
         }
+
+        for layer in &self.resource_layers {
+            config.resource.add_layer(std::path::absolute(layer)?);
+        }
+
+        Ok(())
     }
 }
 
+/// Path of the profile without extension, the same one loaded by [`find_profile`]
+fn profile_path(root: &Path, profile: Option<&str>) -> PathBuf {
+    let default_profile = join!(root, "profiles", "default");
+    let legacy_profile = join!(root, "asst");
+    match profile {
+        Some(profile) => join!(root, "profiles", profile),
+        None if !exists_with_extension(&default_profile)
+            && exists_with_extension(&legacy_profile) =>
+        {
+            legacy_profile
+        }
+        None => default_profile,
+    }
+}
+
+fn exists_with_extension(path: &Path) -> bool {
+    crate::config::SUPPORTED_EXTENSION
+        .iter()
+        .any(|ext| path.with_extension(ext).exists())
+}
+
 fn find_profile(root: impl AsRef<Path>, profile: Option<&str>) -> Result<AsstConfig> {
     let root = root.as_ref();
+    let default_profile = join!(root, "profiles", "default");
+    let legacy_profile = join!(root, "asst");
     if let Some(profile) = profile {
-        AsstConfig::find_file(join!(root, "profiles", profile))
-            .context("Failed to find profile file!")
-    } else if let Some(config) = AsstConfig::find_file_or_none(join!(root, "profiles", "default"))?
-    {
+        let path = join!(root, "profiles", profile);
+        warn_unknown_keys(&path, Schema::Asst);
+        AsstConfig::find_file(path).context("Failed to find profile file!")
+    } else if let Some(config) = AsstConfig::find_file_or_none(&default_profile)? {
+        warn_unknown_keys(default_profile, Schema::Asst);
         Ok(config)
-    } else if let Some(config) = AsstConfig::find_file_or_none(join!(root, "asst"))? {
+    } else if let Some(config) = AsstConfig::find_file_or_none(&legacy_profile)? {
+        warn_unknown_keys(legacy_profile, Schema::Asst);
         warn!("The config file `asst.toml` is deprecated, please use `profiles/default.toml` instead!");
         Ok(config)
     } else {
@@ -111,7 +394,12 @@ fn find_profile(root: impl AsRef<Path>, profile: Option<&str>) -> Result<AsstCon
     }
 }
 
-fn run_core<F>(f: F, args: CommonArgs) -> Result<()>
+fn run_core<F>(
+    f: F,
+    args: CommonArgs,
+    session: Option<Session>,
+    notifier: &mut notify::Notifier,
+) -> Result<()>
 where
     F: FnOnce(&AsstConfig) -> Result<TaskConfig>,
 {
@@ -119,57 +407,41 @@ where
     resource::update(true)?;
 
     // Load asst config
-    let mut asst_config = find_profile(dirs::config(), args.profile.as_deref())?;
+    let mut asst_config = find_profile(dirs::config(), args.profile.as_deref())
+        .context(Failure::config(tr!("error-load-profile")))?;
+    if !args.dry_run {
+        *notifier = notify::Notifier::new(asst_config.notify.clone());
+    }
 
-    args.apply_to(&mut asst_config);
+    args.apply_to(&mut asst_config)
+        .context(Failure::config(tr!("error-apply-options")))?;
 
-    let task = f(&asst_config)?;
-    let task_config = task.init()?;
-    if let Some(resource) = task_config.client_type.resource() {
-        asst_config.resource.use_global_resource(resource);
+    let task = f(&asst_config).context(Failure::config(tr!("error-load-tasks")))?;
+    let mut task_config = init_task_config(task, &args, &mut asst_config)
+        .context(Failure::config(tr!("error-resolve-tasks")))?;
+    if !args.dry_run {
+        report::enable(&asst_config.penguin, task_config.client_type);
+        if let Some(path) = args.drops_output.clone() {
+            drops::enable(path);
+        }
     }
 
-    // Load and setup MaaCore
-    load_core().context("Failed to load MaaCore!")?;
-    setup_core(&asst_config)?;
-
-    // Register signal handlers
-    let stop_bool = Arc::new(std::sync::atomic::AtomicBool::new(false));
-    for sig in TERM_SIGNALS {
-        signal_hook::flag::register_conditional_default(*sig, Arc::clone(&stop_bool))
-            .context("Failed to register signal handler!")?;
-        signal_hook::flag::register(*sig, Arc::clone(&stop_bool))
-            .context("Failed to register signal handler!")?;
+    // Print resolved tasks, so that task files can be checked without running them
+    if args.dry_run {
+        task_config.display(std::io::stdout().lock())?;
     }
 
+    // Load and setup MaaCore
+    load_core().context(Failure::load_core(tr!("error-load-core")))?;
+    setup_core(&asst_config).context(Failure::load_core(tr!("error-setup-core")))?;
+
     // Create and setup Assistant
     let asst = Assistant::new(Some(callback::default_callback), None);
     asst_config.instance_options.apply_to(&asst)?;
 
-    // Register tasks to Assistant and prepare summary
-    let mut task_summary = (!args.no_summary).then(summary::Summary::new);
-    for task in task_config.tasks {
-        let task_type = task.task_type;
-        let params = serde_json::to_string_pretty(&task.params)?;
-        debug!(
-            "Adding task [{}] with params: {params}",
-            task.name_or_default(),
-        );
-        let id = asst
-            .append_task(task_type, params.as_str())
-            .with_context(|| {
-                format!(
-                    "Failed to add task {} with params: {params}",
-                    task.name_or_default(),
-                )
-            })?;
-
-        if let Some(s) = task_summary.as_mut() {
-            s.insert(id, task.name, task_type);
-        }
-    }
-    if let Some(s) = task_summary {
-        summary::init(s);
+    // Prepare summary, tasks will be inserted when they are appended
+    if !args.no_summary {
+        summary::init(summary::Summary::new());
     }
 
     // Prepare connection
@@ -177,47 +449,554 @@ where
 
     // Launch external app like PlayCover or Emulator
     // Only support PlayCover on macOS now, may support more in the future
-    let app: Option<Box<dyn external::ExternalApp>> = match asst_config.connection.preset() {
+    let app: Option<Box<dyn ExternalApp>> = match asst_config.connection.preset() {
         #[cfg(target_os = "macos")]
         crate::config::asst::Preset::PlayCover => Some(Box::new(external::PlayCoverApp::new(
             task_config.client_type,
             address.as_ref(),
+            asst_config.connection.app(),
         ))),
         _ => None,
     };
 
-    if !args.dry_run {
+    if args.dry_run {
+        // Tasks are not run, but still appended to check the parameters
+        for task in &task_config.tasks {
+            append_task(&asst, task)?;
+        }
+    } else {
+        // Penguin Statistics is only queried when tasks are really run
+        preset::choose_stage_to_farm(&mut task_config.tasks)?;
+
+        let control = Control::register()?;
+
+        // Time spent on launching and connecting is also counted in the first run
+        let mut run_start = std::time::Instant::now();
+        let deadline = task_config.timeout.map(|timeout| run_start + timeout);
+
         let rt = tokio::runtime::Runtime::new().context("Failed to create tokio runtime")?;
 
+        // Launch emulator if it's not running
+        if let Some(emulator) = asst_config.connection.emulator() {
+            let emulator = external::EmulatorApp::new(adb_path, address.as_ref(), &emulator);
+            rt.block_on(emulator.open(deadline))
+                .context(Failure::connection(tr!("error-launch-emulator")))?;
+        }
+
         // Startup external app
         if let (Some(app), true) = (app.as_deref(), task_config.start_app) {
-            rt.block_on(app.open())
-                .context("Failed to open external app")?;
+            rt.block_on(app.open(deadline))
+                .context(Failure::connection(tr!("error-open-app")))?;
         }
 
+        // Check the resolution of the device, which is not needed for PlayCover
+        let resolution = match asst_config.connection.preset() {
+            Preset::PlayCover => None,
+            _ => resolution::check(adb_path, &address, args.fix_resolution)
+                .context(Failure::connection(tr!("error-check-resolution")))?,
+        };
+
         // Connect to game or emulator
-        asst.async_connect(adb_path, address.as_ref(), config, true)?;
+        connect(&asst, (adb_path, &address, config), deadline, &control)?;
 
-        asst.start()?;
+        let mut session = session;
+        let mut looper = Looper::new(&args.looping);
+        let ret = loop {
+            sanity::take();
+            let started = std::time::Instant::now();
+            let deadline = task_config.timeout.map(|timeout| run_start + timeout);
+            let ret = run_tasks(
+                &asst,
+                &task_config.tasks,
+                &asst_config.hooks,
+                &control,
+                deadline,
+                session.take(),
+            );
+
+            let Some(looper) = looper.as_mut() else {
+                break ret;
+            };
+            if control.stop_after_task() {
+                break ret;
+            }
+            if farm::is_met() {
+                info!("Target of farming is met, stop looping");
+                break ret;
+            }
+            // Aborted and timed out runs are failures of the loop like failed tasks
+            if let Err(err) = &ret {
+                warn!("{err:#}");
+            }
+            let failed = ret.is_err() || callback::MAA_CORE_ERRORED.load(atomic::Ordering::Relaxed);
+            let Some(wait) = looper.next(started.elapsed(), failed, sanity::take()) else {
+                break ret;
+            };
+            if !control.sleep(wait) {
+                break ret;
+            }
+            run_start = std::time::Instant::now();
+        };
 
-        while asst.running() {
-            if stop_bool.load(atomic::Ordering::Relaxed) {
-                bail!("Interrupted by user!");
+        // Take a screenshot for notifications while the game is in the state failed
+        let failed = ret.is_err() || callback::MAA_CORE_ERRORED.load(atomic::Ordering::Relaxed);
+        if failed && notifier.wants_screenshot() {
+            match screenshot::take(&asst) {
+                Ok(png) => notifier.set_screenshot(png),
+                Err(err) => warn!("Failed to take a screenshot: {err:#}"),
             }
-            std::thread::sleep(std::time::Duration::from_millis(500));
         }
 
         asst.stop()?;
 
+        // Close the game left running after a while, unless the run is interrupted
+        let ret = match args.closedown_after {
+            Some(idle) if !idle::closes_game(&task_config) && !control.stop_after_task() => ret
+                .and_then(|()| {
+                    idle::closedown_after(
+                        &asst,
+                        &asst_config.hooks,
+                        task_config.client_type,
+                        idle,
+                        &control,
+                    )
+                }),
+            _ => ret,
+        };
+
+        // Restore the resolution changed for MaaCore before closing the app or emulator,
+        // it's also restored if the run returns early by an error
+        drop(resolution);
+
         // Close external app
-        if let (Some(app), true) = (app.as_deref(), task_config.close_app) {
+        if let (Some(app), true, true) = (app.as_deref(), task_config.close_app, ret.is_ok()) {
             rt.block_on(app.close())
                 .context("Failed to close external app")?;
         }
+
+        // Unattended runs should clean up even if some tasks failed or timed out
+        if let Some(action) = args.post_action {
+            if control.stop_after_task() {
+                warn!("Skip post action, because the run is interrupted");
+            } else {
+                action.run(&asst_config.connection, &control)?;
+            }
+        }
+
+        ret?;
+
+        // TODO: Better ways to restore signal handlers?
+        control.finish();
     }
 
-    // TODO: Better ways to restore signal handlers?
-    stop_bool.store(true, atomic::Ordering::Relaxed);
+    Ok(())
+}
+
+/// Resolve the task config with given arguments and profile
+///
+/// The resource config of the profile is updated for the client type and event stages of tasks.
+fn init_task_config(
+    mut task: TaskConfig,
+    args: &CommonArgs,
+    asst_config: &mut AsstConfig,
+) -> Result<InitializedTaskConfig> {
+    if args.startup {
+        task.set_startup(true);
+    }
+    if args.closedown || args.post_action == Some(PostAction::ExitGame) {
+        task.set_closedown(true);
+    }
+    if let Some(timeout) = args.timeout {
+        task.set_timeout(timeout);
+    }
+    if let Some(client_type) = asst_config.client_type {
+        task.set_default_client_type(client_type);
+    }
+    task.set_task_defaults(asst_config.task_defaults.clone());
+    if let Some(server) = asst_config.server {
+        task.set_server(server);
+    }
+    let mut task_config = task.init()?;
+    if let Some(resource) = task_config.client_type.resource() {
+        asst_config.resource.use_global_resource(resource);
+    }
+
+    for task in task_config.tasks.iter_mut() {
+        asst_config
+            .yituliu
+            .apply_to(task.task_type, &mut task.params);
+    }
+
+    // Squads of copilot tasks can be given by their names in the profile
+    for task in task_config.tasks.iter_mut() {
+        if task.task_type != TaskType::Copilot {
+            continue;
+        }
+        if let Some(squad) = task
+            .params
+            .get("select_formation")
+            .and_then(MAAValue::as_str)
+        {
+            let index = asst_config.squad(squad)?;
+            task.params.insert("select_formation", index);
+        }
+    }
+
+    // Fetch stage activity from OTA if an event stage is not known yet, but not for dry runs
+    let stages = task_config
+        .tasks
+        .iter()
+        .filter(|task| task.task_type == TaskType::Fight)
+        .filter_map(|task| task.params.get("stage").and_then(MAAValue::as_str));
+    let ota_resource = if args.dry_run {
+        activity::ota_resource_dir()
+    } else {
+        activity::update_event_stages(stages, task_config.client_type)
+    };
+    if let Some(dir) = ota_resource {
+        asst_config.resource.use_ota_resource(dir);
+    }
+
+    Ok(task_config)
+}
+
+fn is_annihilation(task: &InitializedTask) -> bool {
+    task.task_type == TaskType::Fight
+        && task
+            .params
+            .get("stage")
+            .and_then(MAAValue::as_str)
+            .is_some_and(annihilation::is_annihilation)
+}
+
+fn append_task(asst: &Assistant, task: &InitializedTask) -> Result<AsstTaskId> {
+    // The target of farming is counted across runs, so only the remaining quantity is asked
+    let remaining = (task.task_type == TaskType::Fight)
+        .then(|| farm::remaining_drops(&task.params))
+        .flatten();
+    let params = serde_json::to_string_pretty(remaining.as_ref().unwrap_or(&task.params))?;
+    debug!(
+        "Adding task [{}] with params: {params}",
+        task.name_or_default(),
+    );
+    let id = asst
+        .append_task(task.type_name(), params.as_str())
+        .with_context(|| {
+            format!(
+                "Failed to add task {} with params: {params}",
+                task.name_or_default(),
+            )
+        })?;
+    summary::insert(
+        id,
+        task.name.clone().or_else(|| task.core_type.clone()),
+        task.task_type,
+    );
+    if task.task_type == TaskType::StartUp {
+        if let Some(account) = task.params.get("account_name").and_then(MAAValue::as_str) {
+            callback::expect_account(id, account);
+        }
+    }
+    if task.task_type == TaskType::Fight {
+        report::track(id, &task.params);
+    }
+
+    Ok(id)
+}
+
+/// Connect to the device by given adb path, address and config, until the deadline
+///
+/// Connecting may hang on an unresponsive device, so it's waited here instead of by MaaCore,
+/// and a [`TimedOut`] error is returned once the deadline is passed.
+fn connect(
+    asst: &Assistant,
+    (adb_path, address, config): (&str, &str, &str),
+    deadline: Option<std::time::Instant>,
+    control: &Control,
+) -> Result<()> {
+    let id = asst
+        .async_connect(adb_path, address, config, false)
+        .context(Failure::connection(tr!("error-connect")))?;
+    while !callback::async_call_finished(id) {
+        control.poll();
+        if control.stop_now() {
+            bail!(Failure::interrupted());
+        }
+        if deadline.is_some_and(|deadline| std::time::Instant::now() >= deadline) {
+            bail!(TimedOut(None));
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    // A failed connection is only reported by the callback
+    if !asst.connected() {
+        bail!(Failure::connection(tr!("error-connect")));
+    }
+
+    Ok(())
+}
+
+/// Error of tasks stopped because of timeout, maa exits with a distinct code for it
+#[derive(Debug)]
+pub struct TimedOut(Option<String>);
+
+impl TimedOut {
+    /// Same as the exit code of `timeout` command
+    pub const EXIT_CODE: i32 = 124;
+}
+
+impl std::fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.0 {
+            Some(task) => f.write_str(&tr!("error-task-timed-out", task = task.as_str())),
+            None => f.write_str(&tr!("error-timed-out")),
+        }
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// Run tasks one by one, so that dependencies and failure policies can be applied
+///
+/// If a task is not finished before its deadline, it's stopped and failed. If the whole run is
+/// not finished before its deadline, the assistant is stopped and a [`TimedOut`] error is
+/// returned.
+///
+/// A paused task is stopped and run again from the beginning after resumed.
+fn run_tasks(
+    asst: &Assistant,
+    tasks: &[InitializedTask],
+    hooks: &Hooks,
+    control: &Control,
+    deadline: Option<std::time::Instant>,
+    mut session: Option<Session>,
+) -> Result<()> {
+    let _progress = progress::enable();
+    let graceful_warned = std::cell::Cell::new(false);
+    // Postponed while paused, so the timeout doesn't count down
+    let deadline = std::cell::Cell::new(deadline);
+    // Check whether to stop waiting the running task or the backoff before retrying,
+    // a pause or resume request is returned
+    let check = |task: &InitializedTask, task_deadline: Option<std::time::Instant>| {
+        progress::tick();
+        let request = control.poll();
+        if control.stop_now() {
+            asst.stop()?;
+            bail!(Failure::interrupted());
+        }
+        if control.stop_after_task() && !graceful_warned.replace(true) {
+            warn!("Stopping after the current task, interrupt again to stop it immediately");
+        }
+        let now = std::time::Instant::now();
+        let timed_out = if task_deadline.is_some_and(|d| now >= d) {
+            TimedOut(Some(task.name_or_default().to_owned()))
+        } else if deadline.get().is_some_and(|d| now >= d) {
+            TimedOut(None)
+        } else {
+            return Ok(request);
+        };
+        summary::time_out_current_task();
+        screenshot::save_failure(asst, task.name_or_default());
+        asst.stop()?;
+        Err(timed_out.into())
+    };
+    // Wait until resumed, deadlines are postponed by the time paused
+    let wait_resumed =
+        |task: &InitializedTask, task_deadline: &mut Option<std::time::Instant>| -> Result<()> {
+            let paused_at = std::time::Instant::now();
+            let (run_deadline, paused_task_deadline) = (deadline.get(), *task_deadline);
+            loop {
+                let paused = paused_at.elapsed();
+                deadline.set(run_deadline.map(|d| d + paused));
+                *task_deadline = paused_task_deadline.map(|d| d + paused);
+                if check(task, *task_deadline)? == Some(Request::Resume) {
+                    return Ok(());
+                }
+                std::thread::sleep(std::time::Duration::from_millis(500));
+            }
+        };
+
+    // Run a task with retries, return whether it succeeded
+    let run_task = |task: &InitializedTask| -> Result<bool> {
+        let mut retry_times = task.retry.attempts;
+        // Retries are counted in the timeout of the task
+        let mut task_deadline = task
+            .timeout
+            .map(|timeout| std::time::Instant::now() + timeout);
+        loop {
+            let id = append_task(asst, task)?;
+            asst.start()?;
+            let mut paused = false;
+            while asst.running() {
+                if is_annihilation(task) && annihilation::reached() {
+                    info!(
+                        "Stop task {}, because the weekly cap is reached",
+                        task.name_or_default()
+                    );
+                    asst.stop()?;
+                    return Ok(true);
+                }
+                if check(task, task_deadline)? == Some(Request::Pause) {
+                    asst.stop()?;
+                    paused = true;
+                    break;
+                }
+                std::thread::sleep(std::time::Duration::from_millis(500));
+            }
+
+            if paused {
+                info!(
+                    "Paused, the task {} will be run again after resumed",
+                    task.name_or_default()
+                );
+                wait_resumed(task, &mut task_deadline)?;
+                info!("Resumed");
+                continue;
+            }
+
+            if !callback::task_errored(id) {
+                return Ok(true);
+            }
+            screenshot::save_failure(asst, task.name_or_default());
+            if retry_times == 0 {
+                return Ok(false);
+            }
+            retry_times -= 1;
+
+            let backoff = task.retry.backoff;
+            if backoff.is_zero() {
+                warn!("Task {} failed, retrying", task.name_or_default());
+            } else {
+                warn!(
+                    "Task {} failed, retrying in {}s",
+                    task.name_or_default(),
+                    backoff.as_secs()
+                );
+                let retry_at = std::time::Instant::now() + backoff;
+                while std::time::Instant::now() < retry_at {
+                    if check(task, task_deadline)? == Some(Request::Pause) {
+                        info!("Paused, waiting to be resumed");
+                        wait_resumed(task, &mut task_deadline)?;
+                        info!("Resumed");
+                    }
+                    std::thread::sleep(std::time::Duration::from_millis(500));
+                }
+            }
+        }
+    };
+
+    let skip = |task: &InitializedTask| {
+        summary::skip(
+            task.name.clone().or_else(|| task.core_type.clone()),
+            task.task_type,
+        );
+    };
+    let mut failed = vec![false; tasks.len()];
+    // The task aborted the run, remaining tasks are skipped except CloseDown
+    let mut aborted: Option<&InitializedTask> = None;
+    // Account switched to by the last StartUp task, whose annihilation cap is tracked
+    let mut account: Option<&str> = None;
+    for (index, task) in tasks.iter().enumerate() {
+        if control.stop_after_task() {
+            bail!(Failure::interrupted());
+        }
+
+        if let Some(aborted) = aborted.filter(|_| task.task_type != TaskType::CloseDown) {
+            warn!(
+                "Skip task {}, because the run is aborted by the task {}",
+                task.name_or_default(),
+                aborted.name_or_default(),
+            );
+            skip(task);
+            continue;
+        }
+
+        if let Some(&dependency) = task.depends_on.iter().find(|&&i| failed[i]) {
+            warn!(
+                "Skip task {}, because the task {} it depends on failed",
+                task.name_or_default(),
+                tasks[dependency].name_or_default(),
+            );
+            skip(task);
+            failed[index] = true;
+            continue;
+        }
+
+        // Following tasks run with the account of a StartUp task, even if it is skipped because
+        // it is finished in a resumed session
+        if task.task_type == TaskType::StartUp {
+            if let Some(name) = task.params.get("account_name").and_then(MAAValue::as_str) {
+                account = Some(name);
+            }
+        }
+
+        if session.as_ref().is_some_and(|s| s.is_finished(index, task)) {
+            info!(
+                "Skip task {}, because it's finished before",
+                task.name_or_default()
+            );
+            continue;
+        }
+
+        if is_annihilation(task) {
+            annihilation::track(&task.params, account);
+        }
+
+        hooks::task_started(hooks, index, task);
+        crate::log::event(
+            log::Level::Info,
+            "TaskStart",
+            &serde_json::json!({ "index": index, "name": task.name_or_default() }),
+        );
+        progress::start_task(index, tasks.len(), task);
+        let ret = run_task(task);
+        hooks::task_finished(hooks, index, task, &ret);
+        crate::log::event(
+            if matches!(ret, Ok(true)) {
+                log::Level::Info
+            } else {
+                log::Level::Error
+            },
+            "TaskEnd",
+            &serde_json::json!({
+                "index": index,
+                "name": task.name_or_default(),
+                "success": matches!(ret, Ok(true)),
+                "error": ret.as_ref().err().map(|err| format!("{err:#}")),
+            }),
+        );
+        failed[index] = match ret {
+            // Only the task is failed if it's timed out, the run goes on by its failure policy
+            Err(err) if matches!(err.downcast_ref(), Some(TimedOut(Some(_)))) => {
+                warn!("{err}");
+                true
+            }
+            ret => !ret?,
+        };
+
+        if let (false, Some(session)) = (failed[index], session.as_mut()) {
+            session.finish(index, task)?;
+        }
+
+        if failed[index] && task.on_failure == FailurePolicy::Abort && aborted.is_none() {
+            aborted = Some(task);
+        }
+    }
+
+    // A task succeeded after retrying is not an error
+    let errored = failed.contains(&true);
+    callback::MAA_CORE_ERRORED.store(errored, atomic::Ordering::Relaxed);
+
+    // Keep the session if any task failed, so that failed tasks can be resumed
+    if let (false, Some(session)) = (errored, session) {
+        session.complete()?;
+    }
+
+    if let Some(task) = aborted {
+        bail!(Failure::task(tr!(
+            "error-task-aborted",
+            task = task.name_or_default()
+        )));
+    }
 
     Ok(())
 }
@@ -228,38 +1007,268 @@ pub fn run<F>(f: F, args: CommonArgs) -> Result<()>
 where
     F: FnOnce(&AsstConfig) -> Result<TaskConfig>,
 {
-    let ret = run_core(f, args);
+    if !args.devices.is_empty() {
+        return parallel::run(&args.devices, args.profile.as_deref(), None);
+    }
+    run_with_session(f, args, None, &[])
+}
 
-    summary::display();
+/// Run tasks, and record the run of given task files in history unless it's a dry run
+fn run_with_session<F>(
+    f: F,
+    args: CommonArgs,
+    session: Option<Session>,
+    task_files: &[String],
+) -> Result<()>
+where
+    F: FnOnce(&AsstConfig) -> Result<TaskConfig>,
+{
+    let dry_run = args.dry_run;
+    let start_time = chrono::Local::now();
+    let mut notifier = notify::Notifier::default();
+    let ret = run_core(f, args, session, &mut notifier).and_then(|()| {
+        if callback::MAA_CORE_ERRORED.load(atomic::Ordering::Relaxed) {
+            bail!(Failure::task(tr!("error-task-failed")));
+        }
+        Ok(())
+    });
 
-    ret?;
+    summary::display();
+    report::send();
+    drops::save();
 
-    if callback::MAA_CORE_ERRORED.load(atomic::Ordering::Relaxed) {
-        bail!("Some error occurred during running task!");
+    let end_time = chrono::Local::now();
+    let error = ret.as_ref().err().map(|err| format!("{err:#}"));
+    let tasks = summary::export().map(|(_, tasks)| tasks);
+    if !dry_run {
+        if let Err(err) = history::record_run(
+            start_time,
+            end_time,
+            error.clone(),
+            task_files,
+            tasks.as_ref(),
+        ) {
+            warn!("{err:#}");
+        }
     }
 
-    Ok(())
+    // Notify after the summary is complete, whether the run succeeded or not
+    notifier.send(&notify::Run {
+        start_time,
+        end_time,
+        error,
+    });
+
+    ret
 }
 
 pub fn run_preset(params: impl preset::IntoTaskConfig, args: CommonArgs) -> Result<()> {
     run(|config| params.into_task_config(config), args)
 }
 
-pub fn run_custom(path: impl AsRef<Path>, args: CommonArgs) -> Result<()> {
-    run(
-        |_| {
-            let path = path.as_ref();
-            if let Some(abs_path) = dirs::abs_config(path, Some("tasks")) {
-                TaskConfig::find_file(abs_path)
-            } else {
-                TaskConfig::find_file(path)
+pub fn run_custom(
+    mut paths: Vec<String>,
+    task_json: Vec<String>,
+    account: Option<String>,
+    sanity: SanityArgs,
+    overrides: Vec<ParamOverride>,
+    resume: bool,
+    args: CommonArgs,
+) -> Result<()> {
+    if !args.devices.is_empty() {
+        // Children can't share the stdin, so tasks given by `--task-json -` are read here
+        let stdin = if task_json.iter().any(|json| json == "-") {
+            let mut input = Vec::new();
+            std::io::stdin()
+                .read_to_end(&mut input)
+                .context("Failed to read tasks from stdin")?;
+            Some(input)
+        } else {
+            None
+        };
+        return parallel::run(&args.devices, args.profile.as_deref(), stdin.as_deref());
+    }
+
+    let extra_tasks = parse_task_json(&task_json, std::io::stdin().lock())?;
+
+    let mut seen = std::collections::BTreeSet::new();
+    paths.retain(|path| {
+        let first = seen.insert(path.clone());
+        if !first {
+            warn!("Task {path} is given more than once, only the first one is run");
+        }
+        first
+    });
+
+    // Progress is only recorded when tasks in task files are really run,
+    // and separately for each combination of task files and each instance
+    let session = match (paths.is_empty(), args.dry_run) {
+        (false, false) => {
+            let mut name = paths.join("+");
+            if let Some(instance) = args.instance.as_deref() {
+                name = format!("{name}@{instance}");
             }
-            .context("Failed to find task file!")
-        },
+            Some(Session::open(&name, resume)?)
+        }
+        _ => None,
+    };
+
+    // The last run of each task file is shown by `maa list`
+    let names: Vec<String> = paths
+        .iter()
+        .filter_map(|path| Path::new(path).file_stem())
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .collect();
+
+    run_with_session(
+        |_| custom_task_config(&paths, extra_tasks, account, &sanity, overrides),
         args,
+        session,
+        &names,
     )
 }
 
+/// Tasks of `maa run`, loaded from task files and given in JSON, with options of the command
+fn custom_task_config(
+    paths: &[String],
+    extra_tasks: Vec<Task>,
+    account: Option<String>,
+    sanity: &SanityArgs,
+    overrides: Vec<ParamOverride>,
+) -> Result<TaskConfig> {
+    let mut task_config = load_task_files(paths)?;
+    for task in extra_tasks {
+        task_config.push(task);
+    }
+    let mut task_overrides = sanity.task_overrides();
+    ParamOverride::apply_to(overrides, &mut task_overrides);
+    task_config.set_task_overrides(task_overrides);
+    if let Some(account) = account {
+        task_config.set_account(account);
+    }
+    Ok(task_config)
+}
+
+/// Load task files and merge them in the given order
+fn load_task_files(paths: &[String]) -> Result<TaskConfig> {
+    let mut task_config = TaskConfig::new();
+    for (i, path) in paths.iter().enumerate() {
+        let loaded = load_task_file(path).with_context(|| format!("Failed to load task {path}"))?;
+        if i == 0 {
+            task_config = loaded;
+        } else {
+            task_config
+                .merge(loaded)
+                .with_context(|| format!("Failed to merge task {path}"))?;
+        }
+    }
+    Ok(task_config)
+}
+
+/// Print tasks to run today, selected by the weekly plan and conditions
+///
+/// Tasks are resolved like running them by [`run_custom`], with tasks given in JSON, options
+/// of the command and defaults of the profile, but event stages are not fetched.
+pub fn print_today(
+    paths: Vec<String>,
+    task_json: Vec<String>,
+    account: Option<String>,
+    sanity: SanityArgs,
+    overrides: Vec<ParamOverride>,
+    args: CommonArgs,
+) -> Result<()> {
+    let extra_tasks = parse_task_json(&task_json, std::io::stdin().lock())?;
+    let mut task_config = custom_task_config(&paths, extra_tasks, account, &sanity, overrides)?;
+    let mut asst_config = find_profile(dirs::config(), args.profile.as_deref())
+        .context(Failure::config(tr!("error-load-profile")))?;
+    args.apply_to(&mut asst_config)
+        .context(Failure::config(tr!("error-apply-options")))?;
+
+    // The day of the weekly plan is decided by the server, which is set when the tasks are
+    // resolved, so it's set here too
+    if let Some(server) = asst_config.server {
+        task_config.set_server(server);
+    }
+    let weekday = task_config.weekday();
+    let args = CommonArgs {
+        dry_run: true,
+        ..args
+    };
+    let task_config = init_task_config(task_config, &args, &mut asst_config)
+        .context(Failure::config(tr!("error-resolve-tasks")))?;
+    match weekday {
+        Some(weekday) => println!("Tasks planned on {weekday}:"),
+        None => println!("Tasks to run today:"),
+    }
+    for (index, task) in task_config.tasks.iter().enumerate() {
+        println!("{}. {}", index + 1, task.name_or_default());
+    }
+    Ok(())
+}
+
+/// Parse tasks given in JSON, `-` means reading tasks from the reader (usually stdin)
+///
+/// A task is given in the same format as the one in task files, e.g.
+/// `{"type": "Fight", "params": {"stage": "1-7"}}`, or as a task of MaaCore with its type and
+/// parameters in one object, e.g. `{"type": "Fight", "stage": "1-7"}`. The type of the latter
+/// can be unknown to maa-cli, then its parameters are passed to MaaCore as is. The input read
+/// from the reader can be a single task or an array of tasks.
+fn parse_task_json(task_json: &[String], reader: impl std::io::Read) -> Result<Vec<Task>> {
+    let mut tasks = Vec::with_capacity(task_json.len());
+    let mut reader = Some(reader);
+    for json in task_json {
+        if json == "-" {
+            let reader = reader
+                .take()
+                .context("Tasks can only be read from stdin once")?;
+            let value: serde_json::Value =
+                serde_json::from_reader(reader).context("Failed to read tasks from stdin")?;
+            match value {
+                serde_json::Value::Array(array) => {
+                    for value in array {
+                        tasks.push(parse_task(value).context("Invalid task")?);
+                    }
+                }
+                value => tasks.push(parse_task(value).context("Invalid task")?),
+            }
+        } else {
+            let value =
+                serde_json::from_str(json).with_context(|| format!("Invalid JSON: {json}"))?;
+            tasks.push(parse_task(value).with_context(|| format!("Invalid task: {json}"))?);
+        }
+    }
+    Ok(tasks)
+}
+
+fn parse_task(value: serde_json::Value) -> Result<Task> {
+    let task_err = match serde_json::from_value(value.clone()) {
+        Ok(task) => return Ok(task),
+        Err(err) => err,
+    };
+    let serde_json::Value::Object(mut params) = value else {
+        return Err(task_err.into());
+    };
+    let Some(serde_json::Value::String(task_type)) = params.remove("type") else {
+        return Err(task_err.into());
+    };
+    let params = serde_json::from_value(params.into())?;
+    Ok(match task_type.parse() {
+        Ok(task_type) => Task::new(task_type, params),
+        Err(_) => Task::raw(task_type, params),
+    })
+}
+
+/// Load a task file by its name in the `tasks` directory or its path
+fn load_task_file(path: impl AsRef<Path>) -> Result<TaskConfig> {
+    let path = path.as_ref();
+    if let Some(abs_path) = dirs::abs_config(path, Some("tasks")) {
+        TaskConfig::find_file(abs_path)
+    } else {
+        TaskConfig::find_file(path)
+    }
+    .context("Failed to find task file!")
+}
+
 pub fn core_version() -> Result<String> {
     load_core()?;
 
@@ -300,9 +1309,38 @@ fn load_core() -> Result<()> {
     Ok(())
 }
 
+/// File in the user directory to enable the debug mode of MaaCore
+const CORE_DEBUG_FILE: &str = "DEBUG.txt";
+
+/// Content of the debug file created by maa-cli, to tell it from the one created by user
+const CORE_DEBUG_CONTENT: &str = "Created by maa-cli due to -vv, removed at lower verbosity\n";
+
+/// Enable the debug mode of MaaCore if `debug` is true, or disable it if it's enabled by maa-cli
+///
+/// MaaCore has no log level option, but logs more details and saves images for debugging when
+/// the debug file exists in its user directory. A debug file created by user is never removed.
+fn set_core_debug(user_dir: &Path, debug: bool) -> Result<()> {
+    let path = user_dir.join(CORE_DEBUG_FILE);
+    let created_by_cli =
+        || std::fs::read_to_string(&path).is_ok_and(|content| content == CORE_DEBUG_CONTENT);
+
+    if debug && !path.exists() {
+        debug!("Enabling debug mode of MaaCore");
+        std::fs::write(&path, CORE_DEBUG_CONTENT)
+            .context("Failed to enable debug mode of MaaCore!")?;
+    } else if !debug && created_by_cli() {
+        debug!("Disabling debug mode of MaaCore");
+        std::fs::remove_file(&path).context("Failed to disable debug mode of MaaCore!")?;
+    }
+
+    Ok(())
+}
+
 fn setup_core(config: &AsstConfig) -> Result<()> {
     debug!("Setting user directory: {}", dirs::state().display());
-    Assistant::set_user_dir(dirs::state().ensure()?).context("Failed to set user directory!")?;
+    let user_dir = dirs::state().ensure()?;
+    set_core_debug(user_dir, crate::log::verbosity() >= log::LevelFilter::Debug)?;
+    Assistant::set_user_dir(user_dir).context("Failed to set user directory!")?;
 
     config.static_options.apply()?;
     config.resource.load()?;
@@ -326,6 +1364,60 @@ mod tests {
         assert_eq!(core_version().unwrap().as_str(), version);
     }
 
+    #[test]
+    fn core_debug() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CORE_DEBUG_FILE);
+
+        set_core_debug(dir.path(), false).unwrap();
+        assert!(!path.exists());
+
+        set_core_debug(dir.path(), true).unwrap();
+        assert!(path.exists());
+        set_core_debug(dir.path(), false).unwrap();
+        assert!(!path.exists());
+
+        // The debug file created by user is kept
+        std::fs::write(&path, "").unwrap();
+        set_core_debug(dir.path(), true).unwrap();
+        set_core_debug(dir.path(), false).unwrap();
+        assert!(path.exists());
+    }
+
+    #[test]
+    fn task_json() {
+        use crate::object;
+
+        let args = [
+            r#"{"type": "Fight", "params": {"stage": "1-7"}}"#.to_owned(),
+            "-".to_owned(),
+        ];
+        let stdin = r#"[{"type": "Custom", "params": {"task_names": ["Foo"]}}, {"type": "Mall"}]"#;
+        let tasks = parse_task_json(&args, stdin.as_bytes()).unwrap();
+        assert_eq!(tasks, vec![
+            Task::new(TaskType::Fight, object!("stage" => "1-7")),
+            Task::new(TaskType::Custom, object!("task_names" => ["Foo"])),
+            Task::new(TaskType::Mall, MAAValue::new()),
+        ]);
+
+        let mall = r#"{"type": "Mall"}"#;
+        assert!(parse_task_json(&["-".to_owned()], mall.as_bytes()).is_ok());
+        assert!(parse_task_json(&["-".to_owned(), "-".to_owned()], mall.as_bytes()).is_err());
+        assert!(parse_task_json(&["not json".to_owned()], "".as_bytes()).is_err());
+        assert!(parse_task_json(&[r#"{"stage": "1-7"}"#.to_owned()], "".as_bytes()).is_err());
+
+        // Tasks of MaaCore, whose type may be unknown to maa-cli
+        let args = [
+            r#"{"type": "Fight", "stage": "1-7"}"#.to_owned(),
+            r#"{"type": "Unknown", "foo": 1}"#.to_owned(),
+        ];
+        let tasks = parse_task_json(&args, "".as_bytes()).unwrap();
+        assert_eq!(tasks, vec![
+            Task::new(TaskType::Fight, object!("stage" => "1-7")),
+            Task::raw("Unknown".to_owned(), object!("foo" => 1)),
+        ]);
+    }
+
     #[test]
     fn test_find_profile() {
         let test_dir = temp_dir().join("maa_test_find_profile");
@@ -374,4 +1466,51 @@ mod tests {
 
         std::fs::remove_dir_all(&test_dir).unwrap();
     }
+
+    #[test]
+    fn param_override() {
+        use crate::object;
+
+        fn parse(s: &str) -> ParamOverride {
+            s.parse().unwrap()
+        }
+
+        assert_eq!(parse("fight.stage=CE-6"), ParamOverride {
+            task_type: TaskType::Fight,
+            params: object!("stage" => "CE-6"),
+        });
+        assert_eq!(parse("Fight.times=3").params, object!("times" => 3));
+        assert_eq!(
+            parse("mall.shopping=false").params,
+            object!("shopping" => false)
+        );
+        assert_eq!(
+            parse("mall.buy_first=[\"碳\", \"家具\"]").params,
+            object!("buy_first" => ["碳", "家具"])
+        );
+        assert_eq!(
+            parse("roguelike.a.b=\"1\"").params,
+            object!("a" => object!("b" => "1"))
+        );
+        assert_eq!(parse("fight.stage=").params, object!("stage" => ""));
+
+        assert!("fight=1".parse::<ParamOverride>().is_err());
+        assert!("fight.stage".parse::<ParamOverride>().is_err());
+        assert!("unknown.stage=1".parse::<ParamOverride>().is_err());
+        assert!("fight..stage=1".parse::<ParamOverride>().is_err());
+
+        let mut overrides = SanityArgs {
+            medicine: Some(1),
+            ..Default::default()
+        }
+        .task_overrides();
+        ParamOverride::apply_to(
+            vec![parse("fight.medicine=2"), parse("fight.stage=CE-6")],
+            &mut overrides,
+        );
+        assert_eq!(
+            overrides.get(TaskType::Fight),
+            Some(&object!("medicine" => 2, "stage" => "CE-6"))
+        );
+    }
 }
@@ -0,0 +1,143 @@
+//! Native desktop notifications, shown by the notification service of the system
+//!
+//! Notifications are shown by `notify-send` of libnotify on Linux, by `osascript` on macOS,
+//! and as a toast by PowerShell on Windows, so that no extra library is needed.
+//! The title and the body are passed as arguments or environment variables instead of being
+//! put into scripts, so that they never need to be escaped.
+
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+
+/// AppUserModelID of PowerShell, which is allowed to show toasts without being registered
+#[cfg(windows)]
+const POWERSHELL_APP_ID: &str =
+    r"{1AC14E77-02E7-4E5D-B744-2EB1AE5198B7}\WindowsPowerShell\v1.0\powershell.exe";
+
+/// Script of PowerShell to show a toast with the title and body in environment variables
+#[cfg(windows)]
+const TOAST_SCRIPT: &str = "\
+[Windows.UI.Notifications.ToastNotificationManager, Windows.UI.Notifications, ContentType = WindowsRuntime] > $null
+$template = [Windows.UI.Notifications.ToastNotificationManager]::GetTemplateContent([Windows.UI.Notifications.ToastTemplateType]::ToastText02)
+$text = $template.GetElementsByTagName('text')
+$text.Item(0).AppendChild($template.CreateTextNode($env:MAA_NOTIFY_TITLE)) > $null
+$text.Item(1).AppendChild($template.CreateTextNode($env:MAA_NOTIFY_BODY)) > $null
+$toast = [Windows.UI.Notifications.ToastNotification]::new($template)
+[Windows.UI.Notifications.ToastNotificationManager]::CreateToastNotifier($env:MAA_NOTIFY_APP_ID).Show($toast)";
+
+/// Command to show a notification, failed ones are shown as urgent if supported
+#[cfg_attr(
+    not(any(target_os = "linux", target_os = "macos", windows)),
+    allow(unused_variables)
+)]
+fn command(title: &str, body: &str, failed: bool) -> Result<Command> {
+    #[cfg(target_os = "linux")]
+    {
+        let mut command = Command::new("notify-send");
+        command
+            .arg("--app-name=maa-cli")
+            .arg(if failed {
+                "--urgency=critical"
+            } else {
+                "--urgency=normal"
+            })
+            .arg("--")
+            .arg(title)
+            .arg(body);
+        Ok(command)
+    }
+    #[cfg(target_os = "macos")]
+    {
+        let mut command = Command::new("osascript");
+        command
+            .args([
+                "-e",
+                "on run argv",
+                "-e",
+                "display notification (item 2 of argv) with title (item 1 of argv)",
+                "-e",
+                "end run",
+            ])
+            .arg(title)
+            .arg(body);
+        Ok(command)
+    }
+    #[cfg(windows)]
+    {
+        let mut command = Command::new("powershell");
+        command
+            .args(["-NoProfile", "-NonInteractive", "-Command", TOAST_SCRIPT])
+            .env("MAA_NOTIFY_TITLE", title)
+            .env("MAA_NOTIFY_BODY", body)
+            .env("MAA_NOTIFY_APP_ID", POWERSHELL_APP_ID);
+        Ok(command)
+    }
+    #[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+    bail!("Desktop notifications are not supported on this platform")
+}
+
+/// Show a notification on the desktop
+pub fn send(title: &str, body: &str, failed: bool) -> Result<()> {
+    let mut command = command(title, body, failed)?;
+    let program = command.get_program().to_string_lossy().into_owned();
+    let output = command
+        .stdin(Stdio::null())
+        .output()
+        .with_context(|| format!("Failed to run {program}"))?;
+    if !output.status.success() {
+        bail!(
+            "{program} failed with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn notify_send() {
+        let cmd = command("maa-cli: failure in 1m", "Error: timed out", true).unwrap();
+        assert_eq!(cmd.get_program(), "notify-send");
+        assert_eq!(cmd.get_args().collect::<Vec<_>>(), [
+            "--app-name=maa-cli",
+            "--urgency=critical",
+            "--",
+            "maa-cli: failure in 1m",
+            "Error: timed out",
+        ]);
+
+        let cmd = command("maa-cli: success in 1m", "-summary", false).unwrap();
+        assert_eq!(cmd.get_args().nth(1).unwrap(), "--urgency=normal");
+        assert_eq!(cmd.get_args().last().unwrap(), "-summary");
+    }
+
+    #[test]
+    #[cfg(target_os = "macos")]
+    fn osascript() {
+        let cmd = command("maa-cli: success in 1m", "\"Summary\"", false).unwrap();
+        assert_eq!(cmd.get_program(), "osascript");
+        let args: Vec<_> = cmd.get_args().collect();
+        assert_eq!(args[args.len() - 2..], [
+            "maa-cli: success in 1m",
+            "\"Summary\""
+        ]);
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn toast() {
+        let cmd = command("maa-cli: success in 1m", "Summary", false).unwrap();
+        assert_eq!(cmd.get_program(), "powershell");
+        let envs: Vec<_> = cmd.get_envs().collect();
+        assert!(envs.contains(&(
+            "MAA_NOTIFY_TITLE".as_ref(),
+            Some("maa-cli: success in 1m".as_ref())
+        )));
+        assert!(envs.contains(&("MAA_NOTIFY_BODY".as_ref(), Some("Summary".as_ref()))));
+    }
+}
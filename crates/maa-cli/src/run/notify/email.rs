@@ -0,0 +1,243 @@
+//! Send the summary of the run by email through an SMTP server
+//!
+//! The message is a multipart one with the summary as text, and attachments if the run failed.
+
+use anyhow::{bail, Context, Result};
+use lettre::{
+    message::{header::ContentType, Mailbox, MultiPart, SinglePart},
+    transport::smtp::authentication::Credentials,
+    Message, SmtpTransport, Transport,
+};
+use log::debug;
+
+use super::TIMEOUT;
+use crate::config::asst::{Email, SmtpSecurity};
+
+/// A file attached to the email
+pub struct Attachment<'a> {
+    pub name: &'a str,
+    pub content_type: &'a str,
+    pub data: &'a [u8],
+}
+
+/// An email to send
+pub struct Mail<'a> {
+    pub subject: &'a str,
+    pub body: &'a str,
+    pub attachments: Vec<Attachment<'a>>,
+}
+
+fn mailbox(mailbox: &str) -> Result<Mailbox> {
+    mailbox
+        .parse()
+        .with_context(|| format!("Invalid mailbox {mailbox}"))
+}
+
+/// The message with the body as the first part and attachments as the following ones
+fn message(email: &Email, mail: &Mail) -> Result<Message> {
+    let mut builder = Message::builder()
+        .from(mailbox(&email.from)?)
+        .subject(mail.subject);
+    for to in &email.to {
+        builder = builder.to(mailbox(to)?);
+    }
+
+    let mut parts = MultiPart::mixed().singlepart(SinglePart::plain(mail.body.to_owned()));
+    for attachment in &mail.attachments {
+        let content_type = ContentType::parse(attachment.content_type)
+            .with_context(|| format!("Invalid content type {}", attachment.content_type))?;
+        parts = parts.singlepart(
+            lettre::message::Attachment::new(attachment.name.to_owned())
+                .body(attachment.data.to_vec(), content_type),
+        );
+    }
+
+    Ok(builder.multipart(parts)?)
+}
+
+fn transport(email: &Email) -> Result<SmtpTransport> {
+    let builder = match email.security {
+        SmtpSecurity::Tls => SmtpTransport::relay(&email.server)?,
+        SmtpSecurity::Starttls => SmtpTransport::starttls_relay(&email.server)?,
+        SmtpSecurity::None => {
+            if email.username.is_some() {
+                bail!("Refuse to send the password in clear text, set `security` to `tls` or `starttls`");
+            }
+            SmtpTransport::builder_dangerous(&email.server)
+        }
+    };
+    let mut builder = builder.port(email.port()).timeout(Some(TIMEOUT));
+    if let Some(username) = &email.username {
+        let password = email.password.clone().unwrap_or_default();
+        builder = builder.credentials(Credentials::new(username.clone(), password));
+    }
+    Ok(builder.build())
+}
+
+/// Send the mail to all recipients by the SMTP server
+pub fn send(email: &Email, mail: &Mail) -> Result<()> {
+    debug!(
+        "Sending notification by email to {} through {}:{}",
+        email.to.join(", "),
+        email.server,
+        email.port()
+    );
+    if email.to.is_empty() {
+        bail!("No recipient is set");
+    }
+
+    let message = message(email, mail)?;
+    transport(email)?
+        .send(&message)
+        .with_context(|| format!("Failed to send email through {}", email.server))?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufRead, BufReader, Write};
+
+    use super::*;
+
+    fn email() -> Email {
+        Email {
+            server: "smtp.example.com".to_owned(),
+            port: None,
+            security: SmtpSecurity::Tls,
+            username: None,
+            password: None,
+            from: "maa-cli <maa@example.com>".to_owned(),
+            to: vec!["a@example.com".to_owned(), "b@example.com".to_owned()],
+            screenshot: true,
+        }
+    }
+
+    #[test]
+    fn smtp_session() {
+        use std::net::TcpListener;
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut writer = stream;
+            let mut received = Vec::new();
+            let mut reply = |reply: &str| writer.write_all(reply.as_bytes()).unwrap();
+            reply("220 smtp.example.com ready\r\n");
+            let mut in_data = false;
+            loop {
+                let mut line = String::new();
+                if reader.read_line(&mut line).unwrap() == 0 {
+                    break;
+                }
+                let line = line.trim_end().to_owned();
+                if in_data {
+                    if line == "." {
+                        in_data = false;
+                        reply("250 queued\r\n");
+                    }
+                    continue;
+                }
+                let command = line.split(' ').next().unwrap().to_owned();
+                match command.as_str() {
+                    "EHLO" => reply("250 smtp.example.com\r\n"),
+                    "DATA" => {
+                        in_data = true;
+                        reply("354 go ahead\r\n");
+                    }
+                    "QUIT" => {
+                        reply("221 bye\r\n");
+                        received.push(line);
+                        break;
+                    }
+                    _ => reply("250 ok\r\n"),
+                }
+                // The host name in EHLO depends on the machine
+                received.push(if command == "EHLO" { command } else { line });
+            }
+            received
+        });
+
+        let email = Email {
+            server: "127.0.0.1".to_owned(),
+            port: Some(port),
+            security: SmtpSecurity::None,
+            ..email()
+        };
+        let mail = Mail {
+            subject: "maa-cli: success in 1m",
+            body: "Summary",
+            attachments: Vec::new(),
+        };
+        send(&email, &mail).unwrap();
+
+        assert_eq!(server.join().unwrap(), [
+            "EHLO",
+            "MAIL FROM:<maa@example.com>",
+            "RCPT TO:<a@example.com>",
+            "RCPT TO:<b@example.com>",
+            "DATA",
+            "QUIT",
+        ]);
+    }
+
+    #[test]
+    fn clear_text_password() {
+        let email = Email {
+            security: SmtpSecurity::None,
+            username: Some("maa".to_owned()),
+            password: Some("secret".to_owned()),
+            ..email()
+        };
+        assert!(transport(&email).is_err());
+        assert!(transport(&Email {
+            security: SmtpSecurity::Starttls,
+            ..email
+        })
+        .is_ok());
+    }
+
+    #[test]
+    fn mime_message() {
+        let mail = Mail {
+            subject: "maa-cli: failure in 1m",
+            body: "Error: timed out",
+            attachments: vec![Attachment {
+                name: "screenshot.png",
+                content_type: "image/png",
+                data: b"png",
+            }],
+        };
+        let formatted = String::from_utf8(message(&email(), &mail).unwrap().formatted()).unwrap();
+
+        assert!(formatted.contains("From: maa-cli <maa@example.com>\r\n"));
+        assert!(formatted.contains("To: a@example.com, b@example.com\r\n"));
+        assert!(formatted.contains("Subject: maa-cli: failure in 1m\r\n"));
+        assert!(formatted.contains("Content-Type: multipart/mixed;"));
+        assert!(formatted.contains("Error: timed out"));
+        assert!(formatted.contains("Content-Disposition: attachment; filename=\"screenshot.png\""));
+        assert!(formatted.contains("Content-Type: image/png\r\n"));
+
+        let mail = Mail {
+            attachments: vec![Attachment {
+                content_type: "not a type",
+                ..mail.attachments[0]
+            }],
+            ..mail
+        };
+        assert!(message(&email(), &mail).is_err());
+        assert!(message(
+            &Email {
+                from: "not a mailbox".to_owned(),
+                ..email()
+            },
+            &Mail {
+                attachments: Vec::new(),
+                ..mail
+            }
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,587 @@
+//! Send notifications set by `notify` in profile when a run finishes or fails
+//!
+//! The Telegram bot sends the summary as a message, with a screenshot of the game if the run
+//! failed and `screenshot` is enabled. The Discord webhook receives an embed with the status,
+//! duration and drops of each task.
+//!
+//! The push services, ntfy, Gotify and Bark, push the result and the summary to phones.
+//!
+//! The email is sent through an SMTP server, with the screenshot if enabled and recent logs
+//! attached if the run failed.
+//!
+//! The desktop notification is shown by the notification service of the system, for runs in
+//! a background terminal.
+//!
+//! The webhook receives a JSON payload summarizing the run, or the `template` in which
+//! placeholders in strings are replaced by information of the run:
+//!
+//! - `{result}`: `success` or `failure`;
+//! - `{error}`: the error stopped the run, empty if there is none;
+//! - `{duration}`: how long the run took, like `1h2m3s`;
+//! - `{start_time}` and `{end_time}`: when the run started and ended;
+//! - `{summary}`: the summary of tasks printed after running.
+//!
+//! A failed notification never fails the run, its failure is only logged.
+
+mod desktop;
+mod email;
+mod push;
+
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local};
+use log::{debug, warn};
+use serde_json::Value;
+
+use super::summary;
+use crate::config::{
+    asst::{Discord, Notify, Telegram, Webhook},
+    task::duration,
+};
+
+/// Time to wait for the response of a webhook or bot
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Maximum length of a Telegram message in characters
+const TELEGRAM_MAX_LEN: usize = 4096;
+
+/// Maximum number of fields in a Discord embed
+const DISCORD_MAX_FIELDS: usize = 25;
+
+/// Maximum length of the value of a field in a Discord embed in characters
+const DISCORD_MAX_FIELD_LEN: usize = 1024;
+
+/// Maximum length of the description of a Discord embed in characters
+const DISCORD_MAX_DESCRIPTION_LEN: usize = 4096;
+
+/// Maximum length of the body of a desktop notification in characters,
+/// longer ones are cut by most notification services anyway
+const DESKTOP_MAX_LEN: usize = 500;
+
+/// Notifications set in profile, with the screenshot taken for them if the run failed
+#[derive(Default)]
+pub struct Notifier {
+    config: Notify,
+    screenshot: Option<Vec<u8>>,
+}
+
+impl Notifier {
+    pub fn new(config: Notify) -> Self {
+        // Logs are attached to the email if the run failed, even if they are not printed
+        if config.email.is_some() {
+            crate::log::keep_recent();
+        }
+        Self {
+            config,
+            screenshot: None,
+        }
+    }
+
+    /// Whether a screenshot should be taken for notifications if the run failed
+    pub fn wants_screenshot(&self) -> bool {
+        self.config
+            .telegram
+            .as_ref()
+            .is_some_and(|telegram| telegram.screenshot)
+            || self
+                .config
+                .email
+                .as_ref()
+                .is_some_and(|email| email.screenshot)
+    }
+
+    /// Set the screenshot in PNG
+    pub fn set_screenshot(&mut self, png: Vec<u8>) {
+        self.screenshot = Some(png);
+    }
+
+    /// Send the result of a run to all notification backends
+    pub fn send(self, run: &Run) {
+        let summary = summary::export();
+        if let Some(webhook) = &self.config.webhook {
+            let payload = run.payload(summary.clone(), webhook.template.as_ref());
+            if let Err(err) = post_webhook(webhook, &payload) {
+                warn!("Failed to send notification to webhook: {err:#}");
+            }
+        }
+        if let Some(discord) = &self.config.discord {
+            let tasks = summary.as_ref().map(|(_, tasks)| tasks);
+            if let Err(err) = send_discord(discord, &run.discord_embed(tasks)) {
+                warn!("Failed to send notification to Discord: {err:#}");
+            }
+        }
+        let summary_text = summary.as_ref().map(|(text, _)| text.as_str());
+        if let Some(telegram) = &self.config.telegram {
+            let text = run.text(summary_text);
+            let screenshot = self
+                .screenshot
+                .as_deref()
+                .filter(|_| telegram.screenshot && run.error.is_some());
+            if let Err(err) = send_telegram(telegram, &text, screenshot) {
+                warn!("Failed to send notification by Telegram: {err:#}");
+            }
+        }
+        if let Some(email) = &self.config.email {
+            let logs = crate::log::recent().join("\n");
+            let mut attachments = Vec::new();
+            if run.error.is_some() {
+                if let Some(png) = self.screenshot.as_deref().filter(|_| email.screenshot) {
+                    attachments.push(email::Attachment {
+                        name: "screenshot.png",
+                        content_type: "image/png",
+                        data: png,
+                    });
+                }
+                if !logs.is_empty() {
+                    attachments.push(email::Attachment {
+                        name: "maa-cli.log",
+                        content_type: "text/plain; charset=utf-8",
+                        data: logs.as_bytes(),
+                    });
+                }
+            }
+            let mail = email::Mail {
+                subject: &run.title(),
+                body: &run.body(summary_text),
+                attachments,
+            };
+            if let Err(err) = email::send(email, &mail) {
+                warn!("Failed to send notification by email: {err:#}");
+            }
+        }
+        push::send(&self.config, &push::Push {
+            title: &run.title(),
+            body: &run.body(summary_text),
+            failed: run.error.is_some(),
+        });
+        if self.config.desktop {
+            let mut body = run.body(summary_text);
+            truncate(&mut body, DESKTOP_MAX_LEN);
+            if let Err(err) = desktop::send(&run.title(), &body, run.error.is_some()) {
+                warn!("Failed to show desktop notification: {err:#}");
+            }
+        }
+    }
+}
+
+/// A finished run to notify
+pub struct Run {
+    pub start_time: DateTime<Local>,
+    pub end_time: DateTime<Local>,
+    pub error: Option<String>,
+}
+
+impl Run {
+    fn result(&self) -> &'static str {
+        if self.error.is_some() {
+            "failure"
+        } else {
+            "success"
+        }
+    }
+
+    fn duration(&self) -> Duration {
+        (self.end_time - self.start_time)
+            .to_std()
+            .unwrap_or_default()
+    }
+
+    /// Payload to send, with the text and tasks of the summary if any
+    fn payload(&self, summary: Option<(String, Value)>, template: Option<&Value>) -> Value {
+        let (summary, tasks) = summary.unwrap_or_else(|| (String::new(), Value::Array(Vec::new())));
+        match template {
+            Some(template) => {
+                let mut payload = template.clone();
+                fill(&mut payload, &|s| self.fill_str(s, &summary));
+                payload
+            }
+            None => serde_json::json!({
+                "result": self.result(),
+                "error": self.error,
+                "start_time": self.start_time.to_rfc3339(),
+                "end_time": self.end_time.to_rfc3339(),
+                "duration": self.duration().as_secs(),
+                "tasks": tasks,
+                "summary": summary,
+            }),
+        }
+    }
+
+    fn title(&self) -> String {
+        format!(
+            "maa-cli: {} in {}",
+            self.result(),
+            duration::format(self.duration())
+        )
+    }
+
+    /// The error if any and the summary, separated by a blank line
+    fn body(&self, summary: Option<&str>) -> String {
+        let mut body = String::new();
+        if let Some(error) = &self.error {
+            body.push_str(&format!("Error: {error}"));
+        }
+        if let Some(summary) = summary.filter(|summary| !summary.is_empty()) {
+            if !body.is_empty() {
+                body.push_str("\n\n");
+            }
+            body.push_str(summary);
+        }
+        body
+    }
+
+    /// Message of the run, truncated to the limit of Telegram
+    fn text(&self, summary: Option<&str>) -> String {
+        let mut text = self.title();
+        let body = self.body(summary);
+        if !body.is_empty() {
+            text.push_str("\n\n");
+            text.push_str(&body);
+        }
+        truncate(&mut text, TELEGRAM_MAX_LEN);
+        text
+    }
+
+    /// Embed of Discord with a field for each task
+    fn discord_embed(&self, tasks: Option<&Value>) -> Value {
+        let tasks = tasks
+            .and_then(Value::as_array)
+            .map_or(&[][..], Vec::as_slice);
+        let mut fields: Vec<Value> = tasks
+            .iter()
+            .take(DISCORD_MAX_FIELDS)
+            .map(discord_field)
+            .collect();
+        // The last field is replaced by the count of the omitted ones
+        if tasks.len() > DISCORD_MAX_FIELDS {
+            fields[DISCORD_MAX_FIELDS - 1] = serde_json::json!({
+                "name": "…",
+                "value": format!("{} more tasks", tasks.len() - DISCORD_MAX_FIELDS + 1),
+            });
+        }
+
+        let mut description = self.error.clone().unwrap_or_default();
+        truncate(&mut description, DISCORD_MAX_DESCRIPTION_LEN);
+
+        serde_json::json!({
+            "title": format!("maa-cli: {}", self.result()),
+            "description": description,
+            "color": if self.error.is_some() { 0xE74C3C } else { 0x2ECC71 },
+            "fields": fields,
+            "footer": { "text": format!("Duration: {}", duration::format(self.duration())) },
+            "timestamp": self.end_time.to_rfc3339(),
+        })
+    }
+
+    fn fill_str(&self, s: &str, summary: &str) -> String {
+        s.replace("{result}", self.result())
+            .replace("{error}", self.error.as_deref().unwrap_or_default())
+            .replace("{duration}", &duration::format(self.duration()))
+            .replace("{start_time}", &self.start_time.to_rfc3339())
+            .replace("{end_time}", &self.end_time.to_rfc3339())
+            .replace("{summary}", summary)
+    }
+}
+
+/// Field of a task in Discord embed, with its status, duration and drops
+fn discord_field(task: &Value) -> Value {
+    let result = task["result"].as_str().unwrap_or_default();
+    let icon = match result {
+        "completed" => "✅",
+        "error" => "❌",
+        "timed out" => "⏱️",
+        "stopped" => "⏹️",
+        _ => "➖",
+    };
+
+    let mut value = result.to_owned();
+    if let Some(secs) = task["duration"].as_u64() {
+        value.push_str(&format!(
+            " in {}",
+            duration::format(Duration::from_secs(secs))
+        ));
+    }
+    if let Some(stage) = task["stage"].as_str() {
+        value.push_str(&format!("\n{stage}"));
+        if let Some(times) = task["times"].as_i64() {
+            value.push_str(&format!(" × {times}"));
+        }
+    }
+    if let Some(drops) = task["drops"].as_object().filter(|drops| !drops.is_empty()) {
+        value.push_str("\n```\n");
+        for (item, count) in drops {
+            value.push_str(&format!("{item} × {count}\n"));
+        }
+        value.push_str("```");
+    }
+    truncate(&mut value, DISCORD_MAX_FIELD_LEN);
+
+    serde_json::json!({
+        "name": format!("{icon} {}", task["name"].as_str().unwrap_or_default()),
+        "value": value,
+    })
+}
+
+/// Truncate given text to at most `max` characters, with an ellipsis if truncated
+fn truncate(text: &mut String, max: usize) {
+    if let Some((index, _)) = text.char_indices().nth(max) {
+        let index = text[..index].char_indices().last().map_or(0, |(i, _)| i);
+        text.truncate(index);
+        text.push('…');
+    }
+}
+
+/// Replace placeholders in all strings of given value
+fn fill(value: &mut Value, f: &impl Fn(&str) -> String) {
+    match value {
+        Value::String(s) => *s = f(s),
+        Value::Array(array) => array.iter_mut().for_each(|value| fill(value, f)),
+        Value::Object(object) => object.values_mut().for_each(|value| fill(value, f)),
+        _ => {}
+    }
+}
+
+fn client() -> Result<reqwest::blocking::Client> {
+    reqwest::blocking::Client::builder()
+        .timeout(TIMEOUT)
+        .build()
+        .context("Failed to create HTTP client")
+}
+
+fn check_response(response: reqwest::blocking::Response) -> Result<()> {
+    let status = response.status();
+    if !status.is_success() {
+        bail!(
+            "Server responded with {status}: {}",
+            response.text().unwrap_or_default()
+        );
+    }
+    Ok(())
+}
+
+fn post_webhook(webhook: &Webhook, payload: &Value) -> Result<()> {
+    debug!("Sending notification to webhook {}", webhook.url);
+
+    let mut request = client()?.post(&webhook.url).json(payload);
+    for (name, value) in &webhook.headers {
+        request = request.header(name, value);
+    }
+
+    check_response(request.send()?)
+}
+
+fn send_discord(discord: &Discord, embed: &Value) -> Result<()> {
+    debug!("Sending notification to Discord");
+
+    let mut payload = serde_json::json!({ "embeds": [embed] });
+    if let Some(username) = &discord.username {
+        payload["username"] = username.as_str().into();
+    }
+
+    // The URL contains the token of the webhook, which should not be logged in errors
+    let response = client()?
+        .post(&discord.url)
+        .json(&payload)
+        .send()
+        .map_err(reqwest::Error::without_url)?;
+    check_response(response)
+}
+
+fn send_telegram(telegram: &Telegram, text: &str, screenshot: Option<&[u8]>) -> Result<()> {
+    debug!("Sending notification by Telegram to {}", telegram.chat_id);
+
+    let client = client()?;
+    let url = |method: &str| {
+        format!(
+            "{}/bot{}/{method}",
+            telegram.api_url.trim_end_matches('/'),
+            telegram.token
+        )
+    };
+    // The token is a part of the URL, which should not be logged in errors
+    let without_url = |err: reqwest::Error| err.without_url();
+
+    let response = client
+        .post(url("sendMessage"))
+        .json(&serde_json::json!({
+            "chat_id": telegram.chat_id.to_string(),
+            "text": text,
+        }))
+        .send()
+        .map_err(without_url)?;
+    check_response(response).context("Failed to send message")?;
+
+    if let Some(png) = screenshot {
+        // Multipart form is built by hand to send the image as a file
+        let boundary = format!("maa-cli-{}", chrono::Local::now().timestamp_micros());
+        let body = multipart(&boundary, &telegram.chat_id.to_string(), png);
+        let response = client
+            .post(url("sendPhoto"))
+            .header(
+                reqwest::header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(body)
+            .send()
+            .map_err(without_url)?;
+        check_response(response).context("Failed to send screenshot")?;
+    }
+
+    Ok(())
+}
+
+/// Body of a multipart form to send a photo in PNG
+fn multipart(boundary: &str, chat_id: &str, png: &[u8]) -> Vec<u8> {
+    let mut body = Vec::with_capacity(png.len() + 512);
+    body.extend_from_slice(
+        format!(
+            "--{boundary}\r\n\
+             Content-Disposition: form-data; name=\"chat_id\"\r\n\r\n\
+             {chat_id}\r\n\
+             --{boundary}\r\n\
+             Content-Disposition: form-data; name=\"photo\"; filename=\"screenshot.png\"\r\n\
+             Content-Type: image/png\r\n\r\n"
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(png);
+    body.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn run(error: Option<&str>) -> Run {
+        let start_time = Local::now();
+        Run {
+            start_time,
+            end_time: start_time + chrono::TimeDelta::seconds(62),
+            error: error.map(str::to_owned),
+        }
+    }
+
+    #[test]
+    fn payload() {
+        let tasks = json!([{ "name": "Fight", "result": "completed" }]);
+        let summary = Some(("Summary\n".to_owned(), tasks.clone()));
+
+        let payload = run(None).payload(summary.clone(), None);
+        assert_eq!(payload["result"], "success");
+        assert!(payload["error"].is_null());
+        assert_eq!(payload["duration"], 62);
+        assert_eq!(payload["tasks"], tasks);
+        assert_eq!(payload["summary"], "Summary\n");
+
+        let payload = run(Some("timed out")).payload(None, None);
+        assert_eq!(payload["result"], "failure");
+        assert_eq!(payload["error"], "timed out");
+        assert_eq!(payload["tasks"], json!([]));
+
+        let template = json!({
+            "text": "maa {result} in {duration}: {error}",
+            "blocks": [{ "text": "{summary}" }],
+            "count": 1,
+        });
+        assert_eq!(
+            run(Some("timed out")).payload(summary, Some(&template)),
+            json!({
+                "text": "maa failure in 1m2s: timed out",
+                "blocks": [{ "text": "Summary\n" }],
+                "count": 1,
+            })
+        );
+    }
+
+    #[test]
+    fn text() {
+        assert_eq!(
+            run(None).text(Some("Summary\n[Fight] Completed\n")),
+            "maa-cli: success in 1m2s\n\nSummary\n[Fight] Completed\n"
+        );
+        assert_eq!(
+            run(Some("timed out")).text(None),
+            "maa-cli: failure in 1m2s\n\nError: timed out"
+        );
+        assert_eq!(
+            run(Some("timed out")).body(Some("Summary\n")),
+            "Error: timed out\n\nSummary\n"
+        );
+        assert_eq!(run(None).body(Some("")), "");
+
+        let text = run(None).text(Some(&"摘要".repeat(TELEGRAM_MAX_LEN)));
+        assert_eq!(text.chars().count(), TELEGRAM_MAX_LEN);
+        assert!(text.ends_with('…'));
+    }
+
+    #[test]
+    fn discord_embed() {
+        let tasks = json!([
+            {
+                "name": "Fight",
+                "result": "completed",
+                "duration": 62,
+                "stage": "1-7",
+                "times": 2,
+                "drops": { "固源岩": 3 },
+            },
+            { "name": "Mall", "result": "unstarted", "duration": null },
+        ]);
+
+        let embed = run(Some("timed out")).discord_embed(Some(&tasks));
+        assert_eq!(embed["title"], "maa-cli: failure");
+        assert_eq!(embed["description"], "timed out");
+        assert_eq!(embed["color"], 0xE74C3C);
+        assert_eq!(embed["footer"]["text"], "Duration: 1m2s");
+        assert_eq!(
+            embed["fields"],
+            json!([
+                {
+                    "name": "✅ Fight",
+                    "value": "completed in 1m2s\n1-7 × 2\n```\n固源岩 × 3\n```",
+                },
+                { "name": "➖ Mall", "value": "unstarted" },
+            ])
+        );
+
+        let tasks = Value::Array(vec![json!({ "name": "Award", "result": "completed" }); 30]);
+        let embed = run(None).discord_embed(Some(&tasks));
+        assert_eq!(embed["color"], 0x2ECC71);
+        assert_eq!(
+            embed["fields"].as_array().unwrap().len(),
+            DISCORD_MAX_FIELDS
+        );
+        assert_eq!(embed["fields"][24]["value"], "6 more tasks");
+
+        let embed = run(None).discord_embed(None);
+        assert_eq!(embed["fields"], json!([]));
+    }
+
+    #[test]
+    fn truncate_text() {
+        let mut text = "摘要摘要".to_owned();
+        truncate(&mut text, 4);
+        assert_eq!(text, "摘要摘要");
+        truncate(&mut text, 3);
+        assert_eq!(text, "摘要…");
+    }
+
+    #[test]
+    fn multipart_body() {
+        let body = multipart("boundary", "42", b"png");
+        assert_eq!(
+            String::from_utf8(body).unwrap(),
+            "--boundary\r\n\
+             Content-Disposition: form-data; name=\"chat_id\"\r\n\r\n\
+             42\r\n\
+             --boundary\r\n\
+             Content-Disposition: form-data; name=\"photo\"; filename=\"screenshot.png\"\r\n\
+             Content-Type: image/png\r\n\r\n\
+             png\r\n--boundary--\r\n"
+        );
+    }
+}
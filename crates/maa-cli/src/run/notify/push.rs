@@ -0,0 +1,227 @@
+//! Push notification services for phones: ntfy, Gotify and Bark
+//!
+//! Notifications of failed runs are pushed with a higher priority by default,
+//! so that they are noticed even if the ones of succeeded runs are muted.
+
+use log::{debug, warn};
+use reqwest::blocking::{Client, Request};
+use serde_json::json;
+
+use super::{check_response, client};
+use crate::config::asst::{Bark, Gotify, Notify, Ntfy};
+
+/// A notification to push
+pub struct Push<'a> {
+    pub title: &'a str,
+    pub body: &'a str,
+    pub failed: bool,
+}
+
+fn ntfy_request(client: &Client, ntfy: &Ntfy, push: &Push) -> reqwest::Result<Request> {
+    let url = format!("{}/{}", ntfy.server.trim_end_matches('/'), ntfy.topic);
+    let (priority, tag) = if push.failed {
+        (ntfy.failure_priority, "x")
+    } else {
+        (ntfy.priority, "white_check_mark")
+    };
+    let mut request = client
+        .post(url)
+        .header("Title", push.title)
+        .header("Priority", priority.to_string())
+        .header("Tags", tag)
+        .body(push.body.to_owned());
+    if let Some(token) = &ntfy.token {
+        request = request.bearer_auth(token);
+    }
+    request.build()
+}
+
+fn gotify_request(client: &Client, gotify: &Gotify, push: &Push) -> reqwest::Result<Request> {
+    let url = format!("{}/message", gotify.server.trim_end_matches('/'));
+    let priority = if push.failed {
+        gotify.failure_priority
+    } else {
+        gotify.priority
+    };
+    client
+        .post(url)
+        .header("X-Gotify-Key", &gotify.token)
+        .json(&json!({
+            "title": push.title,
+            "message": push.body,
+            "priority": priority,
+        }))
+        .build()
+}
+
+fn bark_request(client: &Client, bark: &Bark, push: &Push) -> reqwest::Result<Request> {
+    let url = format!("{}/push", bark.server.trim_end_matches('/'));
+    let level = if push.failed {
+        bark.failure_level
+    } else {
+        bark.level
+    };
+    client
+        .post(url)
+        .json(&json!({
+            "device_key": bark.device_key,
+            "title": push.title,
+            "body": push.body,
+            "level": level.to_str(),
+            "group": "maa-cli",
+        }))
+        .build()
+}
+
+/// Push the notification by all services set in the config
+pub fn send(config: &Notify, push: &Push) {
+    if config.ntfy.is_none() && config.gotify.is_none() && config.bark.is_none() {
+        return;
+    }
+
+    let client = match client() {
+        Ok(client) => client,
+        Err(err) => {
+            warn!("Failed to push notifications: {err:#}");
+            return;
+        }
+    };
+
+    let requests = [
+        (
+            "ntfy",
+            config
+                .ntfy
+                .as_ref()
+                .map(|ntfy| ntfy_request(&client, ntfy, push)),
+        ),
+        (
+            "Gotify",
+            config
+                .gotify
+                .as_ref()
+                .map(|gotify| gotify_request(&client, gotify, push)),
+        ),
+        (
+            "Bark",
+            config
+                .bark
+                .as_ref()
+                .map(|bark| bark_request(&client, bark, push)),
+        ),
+    ];
+
+    for (name, request) in requests {
+        let Some(request) = request else {
+            continue;
+        };
+        debug!("Pushing notification by {name}");
+        // URLs may contain secrets like the topic of ntfy, which should not be logged in errors
+        let ret = request
+            .and_then(|request| client.execute(request))
+            .map_err(reqwest::Error::without_url)
+            .map_err(anyhow::Error::from)
+            .and_then(check_response);
+        if let Err(err) = ret {
+            warn!("Failed to push notification by {name}: {err:#}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::asst::BarkLevel;
+
+    fn body_json(request: &Request) -> serde_json::Value {
+        serde_json::from_slice(request.body().unwrap().as_bytes().unwrap()).unwrap()
+    }
+
+    const SUCCEEDED: Push = Push {
+        title: "maa-cli: success in 1m",
+        body: "Summary",
+        failed: false,
+    };
+
+    const FAILED: Push = Push {
+        title: "maa-cli: failure in 1m",
+        body: "Error: timed out",
+        failed: true,
+    };
+
+    #[test]
+    fn ntfy() {
+        let client = Client::new();
+        let ntfy = Ntfy {
+            server: "https://ntfy.example.com/".to_owned(),
+            topic: "maa".to_owned(),
+            token: Some("token".to_owned()),
+            priority: 3,
+            failure_priority: 5,
+        };
+
+        let request = ntfy_request(&client, &ntfy, &SUCCEEDED).unwrap();
+        assert_eq!(request.url().as_str(), "https://ntfy.example.com/maa");
+        assert_eq!(request.headers()["Title"], "maa-cli: success in 1m");
+        assert_eq!(request.headers()["Priority"], "3");
+        assert_eq!(request.headers()["Authorization"], "Bearer token");
+        assert_eq!(request.body().unwrap().as_bytes().unwrap(), b"Summary");
+
+        let request = ntfy_request(&client, &ntfy, &FAILED).unwrap();
+        assert_eq!(request.headers()["Priority"], "5");
+        assert_eq!(request.headers()["Tags"], "x");
+    }
+
+    #[test]
+    fn gotify() {
+        let client = Client::new();
+        let gotify = Gotify {
+            server: "https://gotify.example.com".to_owned(),
+            token: "token".to_owned(),
+            priority: 4,
+            failure_priority: 8,
+        };
+
+        let request = gotify_request(&client, &gotify, &FAILED).unwrap();
+        assert_eq!(request.url().as_str(), "https://gotify.example.com/message");
+        assert_eq!(request.headers()["X-Gotify-Key"], "token");
+        assert_eq!(
+            body_json(&request),
+            json!({
+                "title": "maa-cli: failure in 1m",
+                "message": "Error: timed out",
+                "priority": 8,
+            })
+        );
+
+        let request = gotify_request(&client, &gotify, &SUCCEEDED).unwrap();
+        assert_eq!(body_json(&request)["priority"], 4);
+    }
+
+    #[test]
+    fn bark() {
+        let client = Client::new();
+        let bark = Bark {
+            server: "https://api.day.app".to_owned(),
+            device_key: "key".to_owned(),
+            level: BarkLevel::Active,
+            failure_level: BarkLevel::TimeSensitive,
+        };
+
+        let request = bark_request(&client, &bark, &FAILED).unwrap();
+        assert_eq!(request.url().as_str(), "https://api.day.app/push");
+        assert_eq!(
+            body_json(&request),
+            json!({
+                "device_key": "key",
+                "title": "maa-cli: failure in 1m",
+                "body": "Error: timed out",
+                "level": "timeSensitive",
+                "group": "maa-cli",
+            })
+        );
+
+        let request = bark_request(&client, &bark, &SUCCEEDED).unwrap();
+        assert_eq!(body_json(&request)["level"], "active");
+    }
+}
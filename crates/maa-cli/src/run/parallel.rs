@@ -0,0 +1,185 @@
+//! Run the same tasks on multiple devices in parallel
+//!
+//! MaaCore reports the status of tasks by process-wide callbacks, so tasks on each device are
+//! run by a child process with the same arguments, except that `--device` is replaced by
+//! `--instance`. The output of children is prefixed by the name of the device, and the input
+//! read from stdin by the parent, if any, is written to the stdin of each child.
+
+use std::{
+    ffi::OsString,
+    io::{BufRead, BufReader, Read, Write},
+    process::{Command, Stdio},
+    thread,
+};
+
+use anyhow::{bail, Context, Result};
+use maa_dirs as dirs;
+
+use super::find_profile;
+
+/// Resolve devices to names of instances defined in the profile
+fn resolve_devices(devices: &[String], instances: Vec<String>) -> Result<Vec<String>> {
+    if devices.iter().any(|device| device == "all") {
+        if instances.is_empty() {
+            bail!("No instance defined in the profile");
+        }
+        return Ok(instances);
+    }
+
+    let mut resolved = Vec::with_capacity(devices.len());
+    for device in devices {
+        if !instances.contains(device) {
+            bail!("Instance `{device}` not found in the profile");
+        }
+        if !resolved.contains(device) {
+            resolved.push(device.clone());
+        }
+    }
+    Ok(resolved)
+}
+
+/// Arguments of child processes, which are the arguments of this process without `--device`
+fn child_args(args: impl IntoIterator<Item = OsString>) -> Vec<OsString> {
+    let mut child_args = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == "--device" {
+            args.next();
+        } else if !arg.to_str().is_some_and(|s| s.starts_with("--device=")) {
+            child_args.push(arg);
+        }
+    }
+    child_args
+}
+
+/// Copy lines from the reader to the writer with given prefix
+fn prefix_lines(reader: impl Read, mut writer: impl FnMut() -> Box<dyn Write>, prefix: &str) {
+    for line in BufReader::new(reader).lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        // Lines are written at once, so that lines of different devices are not mixed
+        let _ = writeln!(writer(), "[{prefix}] {line}");
+    }
+}
+
+/// Run this command on each device in parallel and wait for all of them
+///
+/// `stdin` is the input of each child, which is read from stdin by the parent beforehand.
+pub fn run(devices: &[String], profile: Option<&str>, stdin: Option<&[u8]>) -> Result<()> {
+    let asst_config = find_profile(dirs::config(), profile)?;
+    let devices = resolve_devices(devices, asst_config.instances.into_keys().collect())?;
+
+    let exe = std::env::current_exe()?;
+    let mut args = child_args(std::env::args_os().skip(1));
+    // Prompts can not be answered in parallel
+    if !args.iter().any(|arg| arg == "--batch") {
+        args.push("--batch".into());
+    }
+
+    let mut children = Vec::with_capacity(devices.len());
+    for device in &devices {
+        let mut child = Command::new(&exe)
+            .args(&args)
+            .arg("--instance")
+            .arg(device)
+            .stdin(if stdin.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .with_context(|| format!("Failed to run tasks on {device}"))?;
+
+        // Written by another thread to not block on a full pipe, and the pipe is closed when
+        // dropped, so that the child sees the end of input
+        if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+            let input = input.to_vec();
+            thread::spawn(move || pipe.write_all(&input));
+        }
+
+        let stdout = child.stdout.take().context("Failed to capture stdout")?;
+        let stderr = child.stderr.take().context("Failed to capture stderr")?;
+        let out_prefix = device.clone();
+        let err_prefix = device.clone();
+        let readers = [
+            thread::spawn(move || {
+                prefix_lines(stdout, || Box::new(std::io::stdout().lock()), &out_prefix)
+            }),
+            thread::spawn(move || {
+                prefix_lines(stderr, || Box::new(std::io::stderr().lock()), &err_prefix)
+            }),
+        ];
+        children.push((device, child, readers));
+    }
+
+    let mut failed = Vec::new();
+    let mut statuses = Vec::with_capacity(children.len());
+    for (device, mut child, readers) in children {
+        let status = child.wait()?;
+        for reader in readers {
+            let _ = reader.join();
+        }
+        if !status.success() {
+            failed.push(device.as_str());
+        }
+        statuses.push((device, status));
+    }
+
+    for (device, status) in statuses {
+        match status.code() {
+            Some(0) => println!("{device}: succeeded"),
+            Some(code) => println!("{device}: failed with exit code {code}"),
+            None => println!("{device}: terminated by signal"),
+        }
+    }
+
+    if !failed.is_empty() {
+        bail!("Tasks failed on {}", failed.join(", "));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(s: &[&str]) -> Vec<String> {
+        s.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn resolve() {
+        let instances = strings(&["a", "b", "c"]);
+        assert_eq!(
+            resolve_devices(&strings(&["b", "a", "b"]), instances.clone()).unwrap(),
+            strings(&["b", "a"])
+        );
+        assert_eq!(
+            resolve_devices(&strings(&["all"]), instances.clone()).unwrap(),
+            instances
+        );
+        assert!(resolve_devices(&strings(&["d"]), instances).is_err());
+        assert!(resolve_devices(&strings(&["all"]), Vec::new()).is_err());
+    }
+
+    #[test]
+    fn args() {
+        let args = [
+            "run",
+            "daily",
+            "--device",
+            "a",
+            "-v",
+            "--device=b",
+            "--batch",
+        ];
+        assert_eq!(
+            child_args(args.map(OsString::from)),
+            ["run", "daily", "-v", "--batch"].map(OsString::from)
+        );
+    }
+}
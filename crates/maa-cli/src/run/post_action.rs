@@ -0,0 +1,147 @@
+//! Actions after all tasks finished, so that unattended runs clean up after themselves
+
+use std::{
+    process::{Command, Stdio},
+    time::Duration,
+};
+
+use anyhow::{bail, Context, Result};
+use clap::ValueEnum;
+use log::{info, warn};
+use serde::Deserialize;
+
+use super::control::Control;
+use crate::config::asst::ConnectionConfig;
+
+/// Time to cancel suspending or shutting down the computer
+const GRACE: Duration = Duration::from_secs(60);
+
+#[cfg_attr(test, derive(Debug))]
+#[derive(ValueEnum, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PostAction {
+    /// Close the game, same as `--closedown`
+    ExitGame,
+    /// Kill the emulator
+    KillEmulator,
+    /// Suspend the computer
+    Suspend,
+    /// Shut down the computer
+    Shutdown,
+}
+
+impl PostAction {
+    /// Name of this action used in command line arguments
+    pub fn as_arg(self) -> &'static str {
+        match self {
+            PostAction::ExitGame => "exit-game",
+            PostAction::KillEmulator => "kill-emulator",
+            PostAction::Suspend => "suspend",
+            PostAction::Shutdown => "shutdown",
+        }
+    }
+
+    /// Run this action, the game is closed by a CloseDown task instead
+    ///
+    /// Suspending or shutting down the computer can be canceled in a grace period
+    /// by interrupting or stopping maa.
+    pub fn run(self, connection: &ConnectionConfig, control: &Control) -> Result<()> {
+        match self {
+            PostAction::ExitGame => Ok(()),
+            PostAction::KillEmulator => kill_emulator(connection),
+            PostAction::Suspend | PostAction::Shutdown => {
+                let what = if self == PostAction::Suspend {
+                    "suspended"
+                } else {
+                    "shut down"
+                };
+                warn!(
+                    "The computer will be {what} in {}s, press Ctrl-C or run `maa stop` to cancel",
+                    GRACE.as_secs()
+                );
+                if !control.sleep(GRACE) {
+                    info!("Canceled, the computer will not be {what}");
+                    return Ok(());
+                }
+                let (program, args) = power_command(self)?;
+                run_command(program, args)
+            }
+        }
+    }
+}
+
+fn kill_emulator(connection: &ConnectionConfig) -> Result<()> {
+    if let Some([program, args @ ..]) = connection.kill_command() {
+        info!("Killing emulator: {program} {}", args.join(" "));
+        return run_command(program, args);
+    }
+
+    // Android emulators can be killed by adb, others need a kill command
+    let (adb_path, address, _) = connection.connect_args();
+    if !address.starts_with("emulator-") {
+        bail!(
+            "Don't know how to kill the emulator at {address}, \
+             set `kill` of the connection or instance in profile"
+        );
+    }
+    info!("Killing emulator {address}");
+    run_command(adb_path, &["-s", &address, "emu", "kill"])
+}
+
+fn power_command(action: PostAction) -> Result<(&'static str, &'static [&'static str])> {
+    let command: &[&str] = match action {
+        #[cfg(target_os = "linux")]
+        PostAction::Suspend => &["systemctl", "suspend"],
+        #[cfg(target_os = "linux")]
+        PostAction::Shutdown => &["systemctl", "poweroff"],
+        #[cfg(target_os = "macos")]
+        PostAction::Suspend => &["pmset", "sleepnow"],
+        #[cfg(target_os = "macos")]
+        PostAction::Shutdown => &[
+            "osascript",
+            "-e",
+            "tell application \"System Events\" to shut down",
+        ],
+        #[cfg(windows)]
+        PostAction::Suspend => &["rundll32.exe", "powrprof.dll,SetSuspendState", "0,1,0"],
+        #[cfg(windows)]
+        PostAction::Shutdown => &["shutdown", "/s", "/t", "0"],
+        _ => bail!(
+            "Post action {} is not supported on this platform",
+            action.as_arg()
+        ),
+    };
+    Ok((command[0], &command[1..]))
+}
+
+fn run_command(program: impl AsRef<str>, args: &[impl AsRef<str>]) -> Result<()> {
+    let program = program.as_ref();
+    let status = Command::new(program)
+        .args(args.iter().map(AsRef::as_ref))
+        .stdin(Stdio::null())
+        .status()
+        .with_context(|| format!("Failed to run {program}"))?;
+    if !status.success() {
+        bail!("{program} failed with {status}");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names() {
+        for action in PostAction::value_variants() {
+            assert_eq!(
+                PostAction::from_str(action.as_arg(), false).unwrap(),
+                *action
+            );
+            assert_eq!(
+                serde_json::from_value::<PostAction>(action.as_arg().into()).unwrap(),
+                *action
+            );
+        }
+    }
+}
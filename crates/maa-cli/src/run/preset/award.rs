@@ -0,0 +1,92 @@
+use super::MAAValue;
+
+#[derive(clap::Args)]
+pub struct AwardParams {
+    /// Don't collect rewards of daily and weekly missions
+    #[arg(long)]
+    no_award: bool,
+    /// Collect rewards in mails
+    #[arg(long)]
+    mail: bool,
+    /// Do the free daily pull of limited headhunting
+    #[arg(long)]
+    recruit: bool,
+    /// Collect Orundum of limited events
+    #[arg(long)]
+    orundum: bool,
+    /// Collect Orundum of limited mining events
+    #[arg(long)]
+    mining: bool,
+    /// Collect the monthly card gift of 5th anniversary
+    #[arg(long)]
+    special_access: bool,
+}
+
+impl super::ToTaskType for AwardParams {
+    fn to_task_type(&self) -> super::TaskType {
+        super::TaskType::Award
+    }
+}
+
+impl From<AwardParams> for MAAValue {
+    fn from(params: AwardParams) -> Self {
+        let mut value = MAAValue::new();
+        value.insert("award", !params.no_award);
+        value.insert("mail", params.mail);
+        value.insert("recruit", params.recruit);
+        value.insert("orundum", params.orundum);
+        value.insert("mining", params.mining);
+        value.insert("specialaccess", params.special_access);
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object;
+
+    #[test]
+    fn parse_award_params() {
+        fn parse<I, T>(args: I) -> MAAValue
+        where
+            I: IntoIterator<Item = T>,
+            T: Into<std::ffi::OsString> + Clone,
+        {
+            let command = crate::command::parse_from(args).command;
+            match command {
+                crate::Command::Award { params, .. } => {
+                    use super::super::{TaskType, ToTaskType};
+                    assert_eq!(params.to_task_type(), TaskType::Award);
+                    params.into()
+                }
+                _ => panic!("Not an Award command"),
+            }
+        }
+
+        let default = object!(
+            "award" => true,
+            "mail" => false,
+            "recruit" => false,
+            "orundum" => false,
+            "mining" => false,
+            "specialaccess" => false,
+        );
+
+        assert_eq!(parse(["maa", "award"]), default);
+
+        assert_eq!(
+            parse(["maa", "award", "--mail", "--recruit"]),
+            default.join(object!("mail" => true, "recruit" => true))
+        );
+
+        assert_eq!(
+            parse(["maa", "award", "--no-award", "--mail", "--special-access"]),
+            default.join(object!(
+                "award" => false,
+                "mail" => true,
+                "specialaccess" => true,
+            ))
+        );
+    }
+}
@@ -1,7 +1,12 @@
-use std::{borrow::Cow, fs, io::Write, path::Path};
+use std::{
+    borrow::Cow,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
 
 use anyhow::{bail, Context, Result};
-use log::{debug, trace};
+use log::{debug, trace, warn};
 use maa_sys::TaskType;
 use prettytable::{format, row, Table};
 use serde_json::Value as JsonValue;
@@ -22,9 +27,14 @@ use crate::{
 pub struct CopilotParams {
     /// URI of the copilot task file
     ///
-    /// It can be a maa URI or a local file path. Multiple URIs can be provided to fight multiple
-    /// stages. For URI, it can be in the format of `maa://<code>`, `maa://<code>s`, `file://<path>`,
-    /// which represents a single copilot task, a copilot task set, and a local file respectively.
+    /// It can be a maa URI, a URL or a local file path. Multiple URIs can be provided to fight
+    /// multiple stages. For URI, it can be in the format of `maa://<code>`, `maa://<code>s`,
+    /// `file://<path>`, which represents a single copilot task, a copilot task set, and a local
+    /// file respectively. URLs shared from prts.plus, like `https://prts.plus/operation/<code>`
+    /// and `https://prts.plus/operation-set/<code>`, are also supported.
+    ///
+    /// Append `@<times>` to an URI to fight the stage, or each stage of a task set, multiple
+    /// times, e.g. `maa://40051@3`.
     uri_list: Vec<String>,
     /// Whether to fight stage in raid mode
     ///
@@ -35,7 +45,7 @@ pub struct CopilotParams {
     ///
     /// When multiple uri are provided or a copilot task set contains multiple stages, force to
     /// true. Otherwise, default to false.
-    #[arg(long)]
+    #[arg(long, alias = "auto-formation")]
     formation: bool,
     /// Whether to use sanity potion to restore sanity when it's not enough
     ///
@@ -52,14 +62,25 @@ pub struct CopilotParams {
     /// Whether to add operators to empty slots in the formation to earn trust
     #[arg(long)]
     add_trust: bool,
-    /// Select which formation to use [1-4]
+    /// Select which formation to use [1-4], or a name of squad defined in the profile
     ///
     /// If not provided, use the current formation
-    #[arg(long)]
-    select_formation: Option<i32>,
+    #[arg(long, alias = "squad")]
+    select_formation: Option<String>,
     /// Use given support unit name, don't use support unit if not provided
-    #[arg(long)]
+    #[arg(long, alias = "support")]
     support_unit_name: Option<String>,
+    /// JSON file of owned operators, used to check requirements of operators in copilot files
+    ///
+    /// Default to owned operators recognized by the last `maa operbox`, the check is skipped
+    /// if it's not found. Elite, level and potential are checked against the file, and skill
+    /// level is checked only if it's given in the file, because it's not recognized by MaaCore.
+    /// If any requirement is not met, you will be asked whether to continue.
+    #[arg(long)]
+    oper_box: Option<PathBuf>,
+    /// Don't check requirements of operators in copilot files
+    #[arg(long, conflicts_with = "oper_box")]
+    ignore_requirements: bool,
 }
 
 impl IntoTaskConfig for CopilotParams {
@@ -70,25 +91,41 @@ impl IntoTaskConfig for CopilotParams {
             .context("Failed to load default copilot task config")?;
 
         let mut copilot_files = Vec::new();
+        let mut repeats = Vec::new();
         for uri in &self.uri_list {
+            let (uri, times) = split_times(uri)?;
             let copilot_file = CopilotFile::from_uri(uri)?;
 
             copilot_file.push_path_to(&mut copilot_files, copilot_dir)?;
+            repeats.resize(copilot_files.len(), times);
         }
 
-        let is_task_list = copilot_files.len() > 1;
+        let is_task_list = repeats.iter().sum::<u32>() > 1;
         let formation = self.formation || is_task_list || default.get_or("formation", false);
         let need_navigate =
             self.need_navigate || is_task_list || default.get_or("need_navigate", false);
         let use_sanity_potion =
             self.use_sanity_potion || default.get_or("use_sanity_potion", false);
         let add_trust = self.add_trust || default.get_or("add_trust", false);
-        let select_formation = self
-            .select_formation
-            .unwrap_or_else(|| default.get_or("select_formation", 0));
+        // Names of squads are resolved to numbers by the profile when the task is initialized
+        let select_formation = match &self.select_formation {
+            Some(squad) => squad
+                .parse::<i32>()
+                .map_or_else(|_| MAAValue::from(squad.as_str()), MAAValue::from),
+            None => default
+                .get("select_formation")
+                .cloned()
+                .unwrap_or(MAAValue::from(0)),
+        };
+
+        let owned = if self.ignore_requirements {
+            None
+        } else {
+            load_owned_operators(self.oper_box.as_deref())?
+        };
 
         let mut task_config = TaskConfig::new();
-        for file in copilot_files {
+        for (file, times) in copilot_files.into_iter().zip(repeats) {
             let copilot_info = json_from_file(&file)?;
             let stage_id = copilot_info
                 .get("stage_name")
@@ -100,6 +137,20 @@ impl IntoTaskConfig for CopilotParams {
             let stage_code = get_str_key(&stage_info, "code")?;
             let stage_name = get_str_key(&stage_info, "name")?;
 
+            if let Some(owned) = &owned {
+                let problems =
+                    check_requirements(&copilot_info, owned, self.support_unit_name.as_deref());
+                if !problems.is_empty() {
+                    warn!("Operators don't meet requirements of {stage_code} {stage_name}:");
+                    for problem in &problems {
+                        warn!("  {problem}");
+                    }
+                    if !BoolInput::new(Some(false), Some("continue anyway")).value()? {
+                        bail!("Operators don't meet requirements of {stage_code} {stage_name}");
+                    }
+                }
+            }
+
             if !need_navigate {
                 println!(
                     "Fight Stage: {stage_code} {stage_name}, please navigate to the stage manually"
@@ -125,40 +176,46 @@ impl IntoTaskConfig for CopilotParams {
                 "navigate_name" => stage_code,
                 "use_sanity_potion" => use_sanity_potion,
                 "add_trust" => add_trust,
-                "select_formation" => select_formation,
+                "select_formation" => select_formation.clone(),
             );
 
             value.maybe_insert("support_unit_name", self.support_unit_name.clone());
 
-            match self.raid {
-                0 => {
-                    task_config.push(
-                        Task::new(TaskType::Copilot, value)
-                            .with_name(format!("{stage_code} {stage_name} Normal")),
-                    );
-                }
-                1 => {
-                    value.insert("is_raid", true);
-                    task_config.push(
-                        Task::new(TaskType::Copilot, value)
-                            .with_name(format!("{stage_code} {stage_name} Raid")),
-                    );
-                }
-                2 => {
-                    task_config.push(
-                        Task::new(TaskType::Copilot, value.clone())
-                            .with_name(format!("{stage_code} {stage_name} Normal")),
-                    );
-
-                    value.insert("is_raid", true);
-                    value.insert("need_navigate", true);
-                    value.insert("formation", false); // use the same formation as normal mode
-                    task_config.push(
-                        Task::new(TaskType::Copilot, value)
-                            .with_name(format!("{stage_code} {stage_name} Raid",)),
-                    );
+            for i in 1..=times {
+                let name = |mode: &str| {
+                    if times > 1 {
+                        format!("{stage_code} {stage_name} {mode} ({i}/{times})")
+                    } else {
+                        format!("{stage_code} {stage_name} {mode}")
+                    }
+                };
+
+                match self.raid {
+                    0 => {
+                        task_config.push(
+                            Task::new(TaskType::Copilot, value.clone()).with_name(name("Normal")),
+                        );
+                    }
+                    1 => {
+                        let mut value = value.clone();
+                        value.insert("is_raid", true);
+                        task_config
+                            .push(Task::new(TaskType::Copilot, value).with_name(name("Raid")));
+                    }
+                    2 => {
+                        task_config.push(
+                            Task::new(TaskType::Copilot, value.clone()).with_name(name("Normal")),
+                        );
+
+                        let mut value = value.clone();
+                        value.insert("is_raid", true);
+                        value.insert("need_navigate", true);
+                        value.insert("formation", false); // use the same formation as normal mode
+                        task_config
+                            .push(Task::new(TaskType::Copilot, value).with_name(name("Raid")));
+                    }
+                    n => bail!("Invalid raid mode {n}, should be 0, 1 or 2"),
                 }
-                n => bail!("Invalid raid mode {n}, should be 0, 1 or 2"),
             }
         }
 
@@ -166,6 +223,22 @@ impl IntoTaskConfig for CopilotParams {
     }
 }
 
+/// Split the number of times to fight from the end of an URI like `maa://40051@3`
+fn split_times(uri: &str) -> Result<(&str, u32)> {
+    match uri.rsplit_once('@') {
+        Some((uri, times)) if !times.is_empty() && times.bytes().all(|b| b.is_ascii_digit()) => {
+            let times = times
+                .parse()
+                .with_context(|| format!("Invalid times in {uri}"))?;
+            if times == 0 {
+                bail!("Times to fight must be positive in {uri}");
+            }
+            Ok((uri, times))
+        }
+        _ => Ok((uri, 1)),
+    }
+}
+
 fn get_stage_info<P, D>(stage_id: &str, base_dirs: D) -> Result<JsonValue>
 where
     P: AsRef<Path>,
@@ -197,9 +270,17 @@ where
 
 #[derive(clap::Args)]
 pub struct SSSCopilotParams {
+    /// URI of a SSS copilot file
+    ///
+    /// It can be a maa URI like `maa://12345`, a URL shared from prts.plus,
+    /// or a path to local file.
+    #[arg(verbatim_doc_comment)]
     uri: String,
     /// Loop times
-    #[arg(long, default_value = "1")]
+    ///
+    /// The first stage of each loop is the one shown in the game,
+    /// so navigate to the stage to start from before running.
+    #[arg(long, alias = "loops", default_value = "1", verbatim_doc_comment)]
     loop_times: i32,
 }
 
@@ -285,6 +366,8 @@ impl<'a> CopilotFile<'a> {
         // } else if let Some(code) = trimmed.strip_prefix("maas://") {
         //     let code_num = code.parse::<i64>().context("Invalid code")?;
         //     Ok(CopilotFile::RemoteSet(code_num))
+        } else if trimmed.starts_with("https://") || trimmed.starts_with("http://") {
+            Self::from_url(trimmed)
         } else if let Some(code) = trimmed.strip_prefix("file://") {
             Ok(CopilotFile::Local(Path::new(code)))
         } else {
@@ -292,6 +375,30 @@ impl<'a> CopilotFile<'a> {
         }
     }
 
+    /// Parse a URL of a copilot task or a copilot task set shared from prts.plus
+    ///
+    /// Supported formats are `https://prts.plus/operation/<code>`,
+    /// `https://prts.plus/operation-set/<code>` and `https://prts.plus/?op=<code>`,
+    /// the domain is not checked, so mirrors like zoot.plus are also supported.
+    fn from_url(url: &str) -> Result<Self> {
+        let parsed = reqwest::Url::parse(url).context("Invalid URL")?;
+        let parse_code = |code: &str| code.parse::<i64>().context("Invalid code in URL");
+
+        if let Some((_, code)) = parsed.query_pairs().find(|(key, _)| key == "op") {
+            return Ok(CopilotFile::Remote(parse_code(&code)?));
+        }
+
+        let segments: Vec<&str> = parsed
+            .path_segments()
+            .map(|segments| segments.filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default();
+        match segments.as_slice() {
+            [.., "operation", code] => Ok(CopilotFile::Remote(parse_code(code)?)),
+            [.., "operation-set", code] => Ok(CopilotFile::RemoteSet(parse_code(code)?)),
+            _ => bail!("Unsupported copilot URL: {url}"),
+        }
+    }
+
     pub fn push_path_to(
         self,
         paths: &mut Vec<Cow<'a, Path>>,
@@ -383,6 +490,105 @@ fn json_from_file(path: impl AsRef<Path>) -> Result<JsonValue> {
     Ok(serde_json::from_reader(fs::File::open(path)?)?)
 }
 
+/// Load owned operators from given file or the result of the last `maa operbox`
+fn load_owned_operators(path: Option<&Path>) -> Result<Option<Vec<JsonValue>>> {
+    let path = match path {
+        Some(path) => path.to_owned(),
+        None => {
+            let path = crate::run::export::owned_operators_path();
+            if !path.exists() {
+                debug!("No owned operators found, skip checking requirements of operators");
+                return Ok(None);
+            }
+            path
+        }
+    };
+
+    let opers = json_from_file(&path)
+        .with_context(|| format!("Failed to load owned operators from {}", path.display()))?;
+    match opers {
+        JsonValue::Array(opers) => Ok(Some(opers)),
+        _ => bail!("Owned operators in {} is not an array", path.display()),
+    }
+}
+
+/// Check owned operators against requirements of operators in a copilot file
+///
+/// Returns descriptions of unmet requirements, an operator used as support unit is not checked.
+/// For a group, it's only a problem if none of operators in the group meets the requirements.
+fn check_requirements(
+    copilot: &JsonValue,
+    owned: &[JsonValue],
+    support: Option<&str>,
+) -> Vec<String> {
+    let int = |value: &JsonValue, key: &str| value.get(key).and_then(JsonValue::as_i64);
+
+    let unmet = |oper: &JsonValue| -> Option<String> {
+        let name = oper.get("name")?.as_str()?;
+        if support == Some(name) {
+            return None;
+        }
+        let Some(owned) = owned.iter().find(|o| o["name"].as_str() == Some(name)) else {
+            return Some(format!("{name} is not owned"));
+        };
+        let required = oper.get("requirements")?;
+
+        let mut unmet = Vec::new();
+        let required_elite = int(required, "elite").unwrap_or_default();
+        match int(owned, "elite") {
+            Some(elite) if elite < required_elite => {
+                unmet.push(format!("elite {elite} < {required_elite}"))
+            }
+            // Level is reset after promotion, so it's only compared at the same elite
+            Some(elite) if elite > required_elite => {}
+            _ => {
+                if let (Some(level), Some(required)) = (int(owned, "level"), int(required, "level"))
+                {
+                    if level < required {
+                        unmet.push(format!("level {level} < {required}"));
+                    }
+                }
+            }
+        }
+        for (key, owned_key, desc) in [
+            ("skill_level", "skill_level", "skill level"),
+            ("potentiality", "potential", "potential"),
+        ] {
+            if let (Some(actual), Some(required)) = (int(owned, owned_key), int(required, key)) {
+                if actual < required {
+                    unmet.push(format!("{desc} {actual} < {required}"));
+                }
+            }
+        }
+
+        (!unmet.is_empty()).then(|| format!("{name}: {}", unmet.join(", ")))
+    };
+
+    let mut problems: Vec<String> = copilot["opers"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(unmet)
+        .collect();
+
+    for group in copilot["groups"].as_array().into_iter().flatten() {
+        let opers = group["opers"]
+            .as_array()
+            .map(Vec::as_slice)
+            .unwrap_or_default();
+        let group_problems: Vec<String> = opers.iter().filter_map(unmet).collect();
+        if !opers.is_empty() && group_problems.len() == opers.len() {
+            problems.push(format!(
+                "no operator in group {} meets requirements ({})",
+                group["name"].as_str().unwrap_or_default(),
+                group_problems.join("; ")
+            ));
+        }
+    }
+
+    problems
+}
+
 fn operator_table(value: &JsonValue) -> Result<Table> {
     let mut table = Table::new();
     table.set_format(*format::consts::FORMAT_NO_LINESEP_WITH_TITLE);
@@ -742,6 +948,18 @@ mod tests {
         }
     }
 
+    #[test]
+    fn split_uri_times() {
+        assert_eq!(split_times("maa://40051").unwrap(), ("maa://40051", 1));
+        assert_eq!(split_times("maa://40051@3").unwrap(), ("maa://40051", 3));
+        assert_eq!(split_times("maa://23125s@2").unwrap(), ("maa://23125s", 2));
+        assert_eq!(
+            split_times("./user@host/1234.json").unwrap(),
+            ("./user@host/1234.json", 1)
+        );
+        assert!(split_times("maa://40051@0").is_err());
+    }
+
     mod copilot_file {
         use super::*;
 
@@ -759,6 +977,24 @@ mod tests {
                 CopilotFile::Remote(30001)
             );
 
+            assert_eq!(
+                CopilotFile::from_uri("https://prts.plus/operation/40051").unwrap(),
+                CopilotFile::Remote(40051)
+            );
+
+            assert_eq!(
+                CopilotFile::from_uri("https://zoot.plus/operation-set/20001/").unwrap(),
+                CopilotFile::RemoteSet(20001)
+            );
+
+            assert_eq!(
+                CopilotFile::from_uri("https://prts.plus/?op=40051").unwrap(),
+                CopilotFile::Remote(40051)
+            );
+
+            assert!(CopilotFile::from_uri("https://prts.plus/operation/xyz").is_err());
+            assert!(CopilotFile::from_uri("https://prts.plus/").is_err());
+
             assert_eq!(
                 CopilotFile::from_uri("file://file.json").unwrap(),
                 CopilotFile::Local(Path::new("file.json"))
@@ -849,6 +1085,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn check_operator_requirements() {
+        let copilot = serde_json::json!({
+            "opers": [
+                { "name": "桃金娘", "skill": 1 },
+                {
+                    "name": "夜莺",
+                    "skill": 3,
+                    "requirements": { "elite": 2, "level": 60, "skill_level": 10 }
+                },
+                { "name": "能天使", "skill": 3, "requirements": { "elite": 1 } },
+                { "name": "维什戴尔", "skill": 3 },
+            ],
+            "groups": [
+                {
+                    "name": "行医",
+                    "opers": [
+                        { "name": "纯烬艾雅法拉", "skill": 1 },
+                        { "name": "蜜莓", "skill": 1, "requirements": { "potentiality": 6 } }
+                    ]
+                },
+                {
+                    "name": "狙击",
+                    "opers": [{ "name": "克洛丝", "skill": 1, "requirements": { "level": 50 } }]
+                }
+            ]
+        });
+        let owned: Vec<JsonValue> = serde_json::from_value(serde_json::json!([
+            { "name": "桃金娘", "elite": 1, "level": 1 },
+            { "name": "夜莺", "elite": 2, "level": 40, "skill_level": 7 },
+            { "name": "能天使", "elite": 2, "level": 1 },
+            { "name": "蜜莓", "elite": 1, "level": 40, "potential": 6 },
+            { "name": "克洛丝", "elite": 1, "level": 40 },
+        ]))
+        .unwrap();
+
+        assert_eq!(check_requirements(&copilot, &owned, Some("维什戴尔")), [
+            "夜莺: level 40 < 60, skill level 7 < 10",
+        ]);
+        assert_eq!(check_requirements(&copilot, &owned[1..], None), [
+            "桃金娘 is not owned",
+            "夜莺: level 40 < 60, skill level 7 < 10",
+            "维什戴尔 is not owned",
+        ]);
+
+        let owned = &owned[..3];
+        assert_eq!(check_requirements(&copilot, owned, Some("维什戴尔")), [
+            "夜莺: level 40 < 60, skill level 7 < 10",
+            "no operator in group 行医 meets requirements \
+             (纯烬艾雅法拉 is not owned; 蜜莓 is not owned)",
+            "no operator in group 狙击 meets requirements (克洛丝 is not owned)",
+        ]);
+    }
+
     #[test]
     fn gen_operator_table() {
         let json = serde_json::json!({
@@ -0,0 +1,205 @@
+//! Run common daily tasks in one command, with the defaults of each task
+
+use anyhow::Result;
+use clap::{Args, FromArgMatches};
+use maa_sys::TaskType;
+
+use super::{
+    overlaid_task, AsstConfig, AwardParams, FightParams, InfrastParams, IntoTaskConfig, MAAValue,
+    MallParams, RecruitParams, TaskConfig, ToTaskType,
+};
+
+#[derive(Clone, Copy, PartialEq, clap::ValueEnum)]
+#[cfg_attr(test, derive(Debug))]
+enum Step {
+    /// Start the game with the client type in profile
+    #[value(name = "startup")]
+    StartUp,
+    Fight,
+    Infrast,
+    Recruit,
+    Mall,
+    Award,
+    /// Close the game
+    #[value(name = "closedown")]
+    CloseDown,
+}
+
+#[derive(clap::Args)]
+pub struct DailyParams {
+    /// Stage to fight
+    ///
+    /// If not given, the stage in the `defaults` of profile is fought,
+    /// or the current/last stage if it's not set either.
+    #[arg(long)]
+    stage: Option<String>,
+    /// Steps to skip, separated by comma
+    #[arg(long, value_delimiter = ',', ignore_case = true)]
+    skip: Vec<Step>,
+}
+
+/// Parameters of a preset task given by command line arguments,
+/// same as the ones of `maa <task> [args]`
+fn preset_params<T>(args: &[String], config: &AsstConfig) -> Result<(TaskType, MAAValue)>
+where
+    T: Args + FromArgMatches + ToTaskType + TryInto<MAAValue>,
+    T::Error: Into<anyhow::Error>,
+{
+    let command = T::augment_args(clap::Command::new("maa").no_binary_name(true));
+    let preset = T::from_arg_matches(&command.try_get_matches_from(args)?)?;
+    let task_type = preset.to_task_type();
+    let mut params = preset.try_into().map_err(Into::into)?;
+    T::apply_profile(&mut params, config);
+    Ok((task_type, params))
+}
+
+/// Remove parameters set by the defaults of profile, so they are not overridden by the values
+/// of a preset without arguments, `kept` are the ones given by the user and always kept
+fn without_defaults(
+    config: &AsstConfig,
+    task_type: TaskType,
+    mut params: MAAValue,
+    kept: &[&str],
+) -> MAAValue {
+    let defaults = config
+        .task_defaults
+        .get(task_type)
+        .and_then(MAAValue::as_object);
+    if let (Some(defaults), Some(params)) = (defaults, params.as_object_mut()) {
+        params.retain(|key, _| kept.contains(&key.as_str()) || !defaults.contains_key(key));
+    }
+    params
+}
+
+impl IntoTaskConfig for DailyParams {
+    fn into_task_config(self, config: &AsstConfig) -> Result<TaskConfig> {
+        let runs = |step| !self.skip.contains(&step);
+
+        let mut task_config = TaskConfig::new();
+        task_config.set_startup(runs(Step::StartUp));
+        task_config.set_closedown(runs(Step::CloseDown));
+
+        if runs(Step::Fight) {
+            let stage: Vec<String> = self.stage.iter().cloned().collect();
+            let (task_type, mut params) = preset_params::<FightParams>(&stage, config)?;
+            // An empty stage means the current/last stage, which is left to MaaCore
+            if stage.is_empty() {
+                if let Some(params) = params.as_object_mut() {
+                    params.remove("stage");
+                }
+            }
+            let kept: &[&str] = if stage.is_empty() { &[] } else { &["stage"] };
+            let params = without_defaults(config, task_type, params, kept);
+            task_config.push(overlaid_task(task_type, params)?);
+        }
+
+        for (step, params) in [
+            (
+                Step::Infrast,
+                preset_params::<InfrastParams> as fn(&_, &_) -> _,
+            ),
+            (Step::Recruit, preset_params::<RecruitParams>),
+            (Step::Mall, preset_params::<MallParams>),
+            (Step::Award, preset_params::<AwardParams>),
+        ] {
+            if runs(step) {
+                let (task_type, params) = params(&[], config)?;
+                let params = without_defaults(config, task_type, params, &[]);
+                task_config.push(overlaid_task(task_type, params)?);
+            }
+        }
+
+        Ok(task_config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::command::{parse_from, Command};
+
+    fn task_types(args: &[&str]) -> Vec<TaskType> {
+        let Command::Daily { params, .. } = parse_from(["maa", "daily"].iter().chain(args)).command
+        else {
+            panic!("Not a Daily command");
+        };
+        params
+            .into_task_config(&AsstConfig::default())
+            .unwrap()
+            .init()
+            .unwrap()
+            .tasks
+            .into_iter()
+            .map(|task| task.task_type)
+            .collect()
+    }
+
+    #[test]
+    fn daily_tasks() {
+        use TaskType::*;
+
+        assert_eq!(task_types(&[]), [
+            StartUp, Fight, Infrast, Recruit, Mall, Award, CloseDown
+        ]);
+        assert_eq!(task_types(&["--skip", "fight,closedown", "--skip=Mall"]), [
+            StartUp, Infrast, Recruit, Award
+        ]);
+    }
+
+    #[test]
+    fn fight_stage() {
+        fn stage(args: &[&str]) -> Option<String> {
+            let Command::Daily { params, .. } =
+                parse_from(["maa", "daily"].iter().chain(args)).command
+            else {
+                panic!("Not a Daily command");
+            };
+            let mut task_config = params.into_task_config(&AsstConfig::default()).unwrap();
+            task_config.set_task_defaults(toml::from_str("fight.stage = \"1-7\"").unwrap());
+            task_config
+                .init()
+                .unwrap()
+                .tasks
+                .into_iter()
+                .find(|task| task.task_type == TaskType::Fight)
+                .and_then(|task| task.params.get("stage")?.as_str().map(str::to_owned))
+        }
+
+        assert_eq!(stage(&[]).as_deref(), Some("1-7"));
+        assert_eq!(stage(&["--stage", "CE-6"]).as_deref(), Some("CE-6"));
+    }
+
+    #[test]
+    fn profile_defaults() {
+        let Command::Daily { params, .. } = parse_from(["maa", "daily", "--stage", "CE-6"]).command
+        else {
+            panic!("Not a Daily command");
+        };
+        let config = AsstConfig {
+            task_defaults: toml::from_str(
+                "fight.stage = \"1-7\"\nmall.credit_fight = true\nmall.only_buy_discount = true",
+            )
+            .unwrap(),
+            ..Default::default()
+        };
+        let mut task_config = params.into_task_config(&config).unwrap();
+        task_config.set_task_defaults(config.task_defaults.clone());
+        let tasks = task_config.init().unwrap().tasks;
+        let params = |task_type| {
+            &tasks
+                .iter()
+                .find(|task| task.task_type == task_type)
+                .unwrap()
+                .params
+        };
+
+        assert_eq!(
+            params(TaskType::Fight).get("stage").unwrap().as_str(),
+            Some("CE-6")
+        );
+        let mall = params(TaskType::Mall);
+        assert_eq!(mall.get("credit_fight").unwrap().as_bool(), Some(true));
+        assert_eq!(mall.get("only_buy_discount").unwrap().as_bool(), Some(true));
+        assert_eq!(mall.get("shopping").unwrap().as_bool(), Some(true));
+    }
+}
@@ -1,17 +1,99 @@
+use std::sync::Mutex;
+
 use anyhow::{bail, Context};
+use log::info;
+
+use super::{super::farm, penguin, MAAValue, TaskType};
+use crate::{
+    config::task::{choose_stage, ClientType, InitializedTask},
+    value::userinput::{BoolInput, UserInput},
+};
+
+/// Number of expiring medicine large enough to use all of them, same as the GUI
+pub const ALL_EXPIRING_MEDICINE: &str = "999";
+
+/// Item given by `--item`, the stage to farm it is chosen when the task is run
+struct ItemToFarm {
+    item: String,
+    target: Option<i32>,
+    yes: bool,
+    client_type: ClientType,
+}
 
-use super::MAAValue;
-use crate::config::task::ClientType;
+/// Choosing the stage queries the Penguin Statistics, which should not be done for dry runs
+/// or when the task is only created, so the item is kept here until the task is run
+static ITEM_TO_FARM: Mutex<Option<ItemToFarm>> = Mutex::new(None);
+
+/// Set the stage of fight tasks to the most sanity-efficient one to farm the item given by
+/// `--item`, after confirmed by the user, which does nothing if no item is given
+pub fn choose_stage_to_farm(tasks: &mut [InitializedTask]) -> anyhow::Result<()> {
+    let Some(farm) = ITEM_TO_FARM.lock().unwrap().take() else {
+        return Ok(());
+    };
+
+    let best = penguin::best_stage(&farm.item, farm.client_type)?;
+    let question = format!(
+        "fight {}, the most sanity-efficient stage for {} ({:.2} sanity per item)",
+        best.code, best.item, best.sanity
+    );
+    if farm.yes {
+        info!("Fight {} for {}", best.code, best.item);
+    } else if !BoolInput::new(Some(true), Some(&question)).value()? {
+        bail!("Cancelled by user");
+    }
+
+    for task in tasks.iter_mut().filter(|t| t.task_type == TaskType::Fight) {
+        task.params.insert("stage", best.code.as_str());
+        if let Some(target) = farm.target {
+            let mut drops = MAAValue::new();
+            drops.insert(best.item_id.as_str(), target);
+            task.params.insert("drops", drops);
+        }
+    }
+    if let Some(target) = farm.target {
+        farm::set_target(&best.item_id, &best.item, target.into());
+    }
+
+    Ok(())
+}
 
 #[derive(clap::Args)]
 pub struct FightParams {
     /// Stage to fight, e.g. 1-7, leave empty to fight current/last stage
+    #[clap(conflicts_with_all = ["item", "yes", "target"])]
     stage: Option<String>,
+    #[clap(long)]
+    /// Item to farm, instead of the stage to fight
+    ///
+    /// The item can be given by its name in any language, alias or ID, e.g. "Orirock Cube".
+    /// The most sanity-efficient stage open now to farm it is chosen by drop rates
+    /// from the Penguin Statistics, and you will be asked to confirm it.
+    item: Option<String>,
+    #[clap(short, long, requires = "item")]
+    /// Fight the stage chosen for the item without confirmation
+    yes: bool,
+    #[clap(long, requires = "item", value_parser = clap::value_parser!(i32).range(1..))]
+    /// Stop after given quantity of the item given by `--item` is farmed
+    ///
+    /// Fight until the target is met or sanity is exhausted, instead of fighting given times.
+    /// With `--loop` or `--sanity-loop`, drops are counted across runs, and the loop ends
+    /// once the target is met.
+    target: Option<i32>,
+    #[clap(long, value_delimiter = ',')]
+    /// Stages to fight if the resource stage is not open today, separated by comma
+    ///
+    /// Resource stages like CE-6 are only open on some days of a week in the server time zone.
+    /// If the given stage is not open today, the first open stage in fallback stages will be
+    /// fought instead, or the task will be skipped if none of them is open.
+    fallback: Vec<String>,
     #[clap(short, long)]
     /// Number of medicine (Sanity Potion) used to fight, default to 0
     medicine: Option<i32>,
-    #[clap(long)]
+    #[clap(long, num_args = 0..=1, default_missing_value = ALL_EXPIRING_MEDICINE)]
     /// Number of expiring medicine (Sanity Potion) used to fight, default to 0
+    ///
+    /// Only potions expiring within 48 hours are used, which is decided by MaaCore.
+    /// If no number is given, all expiring potions are used.
     expiring_medicine: Option<i32>,
     #[clap(long)]
     /// Number of stone (Originite Prime) used to fight, default to 0
@@ -27,8 +109,11 @@ pub struct FightParams {
     /// You can specify multiple drops, by repeating this option,
     /// e.g. `-D30012=100 -D30011=100` to exit after get 100 Orirock or 100 Orirock Cube.
     drops: Vec<String>,
-    #[clap(long)]
-    /// Repeat times of single proxy combat, 1 ~ 6, default to 1
+    #[clap(long, value_parser = clap::value_parser!(i32).range(-1..=6))]
+    /// Times of runs in a single proxy combat, 1 ~ 6
+    ///
+    /// Each battle consumes sanity of given times, 0 to use the maximum times allowed by
+    /// current sanity, and -1 to disable it. Annihilation doesn't support series.
     series: Option<i32>,
     #[clap(long)]
     /// Whether report drops to the Penguin Statistics
@@ -65,7 +150,29 @@ impl TryFrom<FightParams> for MAAValue {
     fn try_from(args: FightParams) -> std::result::Result<Self, Self::Error> {
         let mut params = MAAValue::new();
 
-        params.insert("stage", args.stage.unwrap_or_default());
+        let stage = match args.item {
+            // The stage is chosen when the task is run
+            Some(item) => {
+                *ITEM_TO_FARM.lock().unwrap() = Some(ItemToFarm {
+                    item,
+                    target: args.target,
+                    yes: args.yes,
+                    client_type: args.client_type.unwrap_or_default(),
+                });
+                String::new()
+            }
+            // Ask which one to fight here, stages are never prompted when tasks are run
+            None => match args.stage {
+                Some(stage) if !stage.is_empty() => {
+                    choose_stage(&stage, args.client_type.unwrap_or_default())?
+                }
+                _ => String::new(),
+            },
+        };
+        params.insert("stage", stage);
+        if !args.fallback.is_empty() {
+            params.insert("fallback_stages", args.fallback);
+        }
 
         // Fight conditions
         params.maybe_insert("medicine", args.medicine);
@@ -75,6 +182,9 @@ impl TryFrom<FightParams> for MAAValue {
 
         let drops = args.drops;
         if !drops.is_empty() {
+            if args.target.is_some() {
+                bail!("Drops can't be given with a target of farming");
+            }
             let mut drop_map = std::collections::BTreeMap::new();
 
             for drop in drops {
@@ -124,9 +234,11 @@ impl TryFrom<FightParams> for MAAValue {
 
 #[cfg(test)]
 mod tests {
+    use clap::Parser;
+
     use super::*;
     use crate::{
-        command::{parse_from, Command},
+        command::{parse_from, Cli, Command},
         object,
     };
 
@@ -214,10 +326,12 @@ mod tests {
                 "--expiring-medicine=100",
                 "--stone=10",
                 "--dr-grandet",
+                "--fallback=CE-6,1-7",
             ])
             .unwrap(),
             object!(
                 "stage" => "1-7",
+                "fallback_stages" => ["CE-6", "1-7"],
                 "expiring_medicine" => 100,
                 "stone" => 10,
                 "series" => 6,
@@ -225,6 +339,31 @@ mod tests {
             )
         );
 
+        assert_eq!(
+            parse(["maa", "fight", "1-7", "--expiring-medicine"]).unwrap(),
+            default_params.join(object!(
+                "stage" => "1-7",
+                "expiring_medicine" => 999,
+            ))
+        );
+
         assert!(parse(["maa", "fight", "1-7", "-D30012=100", "-D30011"]).is_err());
+
+        assert!(Cli::try_parse_from(["maa", "fight", "1-7", "--item", "30012"]).is_err());
+        assert!(Cli::try_parse_from(["maa", "fight", "--yes"]).is_err());
+        assert!(Cli::try_parse_from(["maa", "fight", "1-7", "--yes"]).is_err());
+        assert!(Cli::try_parse_from(["maa", "fight", "1-7", "--target", "10"]).is_err());
+        assert!(Cli::try_parse_from(["maa", "fight", "--item", "30012", "--target", "0"]).is_err());
+
+        // The stage for an item is not chosen until the task is run
+        assert_eq!(
+            parse(["maa", "fight", "--item", "30012", "--target", "10", "-y"]).unwrap(),
+            default_params
+        );
+        let farm = ITEM_TO_FARM.lock().unwrap().take().unwrap();
+        assert_eq!(farm.item, "30012");
+        assert_eq!(farm.target, Some(10));
+        assert!(farm.yes);
+        assert!(choose_stage_to_farm(&mut []).is_ok());
     }
 }
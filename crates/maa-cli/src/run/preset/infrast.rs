@@ -0,0 +1,222 @@
+use std::path::PathBuf;
+
+use anyhow::bail;
+
+use super::MAAValue;
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+enum Facility {
+    Mfg,
+    Trade,
+    Power,
+    Control,
+    Reception,
+    Office,
+    Dorm,
+}
+
+impl Facility {
+    const ALL: [Facility; 7] = [
+        Facility::Mfg,
+        Facility::Trade,
+        Facility::Power,
+        Facility::Control,
+        Facility::Reception,
+        Facility::Office,
+        Facility::Dorm,
+    ];
+
+    const fn to_str(self) -> &'static str {
+        match self {
+            Facility::Mfg => "Mfg",
+            Facility::Trade => "Trade",
+            Facility::Power => "Power",
+            Facility::Control => "Control",
+            Facility::Reception => "Reception",
+            Facility::Office => "Office",
+            Facility::Dorm => "Dorm",
+        }
+    }
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+#[cfg_attr(test, derive(Debug, PartialEq))]
+enum Drones {
+    /// Don't use drones
+    NotUse,
+    /// Trading post, for LMD
+    Money,
+    /// Trading post, for Orundum
+    SyntheticJade,
+    /// Factory, for battle records
+    CombatRecord,
+    /// Factory, for pure gold
+    PureGold,
+    /// Factory, for originium shards
+    OriginStone,
+    /// Factory, for chips
+    Chip,
+}
+
+impl Drones {
+    const fn to_str(self) -> &'static str {
+        match self {
+            Drones::NotUse => "_NotUse",
+            Drones::Money => "Money",
+            Drones::SyntheticJade => "SyntheticJade",
+            Drones::CombatRecord => "CombatRecord",
+            Drones::PureGold => "PureGold",
+            Drones::OriginStone => "OriginStone",
+            Drones::Chip => "Chip",
+        }
+    }
+}
+
+#[derive(clap::Args)]
+pub struct InfrastParams {
+    /// Facilities to shift in order, separated by comma, default to all facilities
+    #[arg(short, long, value_delimiter = ',', ignore_case = true)]
+    facility: Vec<Facility>,
+    /// Usage of drones
+    #[arg(short, long, default_value = "not-use", ignore_case = true)]
+    drones: Drones,
+    /// Morale threshold in range [0, 1], below which operators will be moved to dormitory
+    #[arg(long, default_value = "0.3")]
+    threshold: f32,
+    /// Fill vacancies of dormitories with operators not in other facilities
+    #[arg(long)]
+    dorm_notstationed: bool,
+    /// Fill vacancies of dormitories with operators to gain trust
+    #[arg(long)]
+    dorm_trust: bool,
+    /// Replenish originium shards automatically
+    #[arg(long)]
+    replenish: bool,
+    /// Custom infrast plan file
+    ///
+    /// A relative path is resolved from the `infrast` subdirectory of config directory.
+    /// If a plan file is given, the plan is selected by time periods in the file
+    /// unless `--plan-index` is given.
+    #[arg(long, verbatim_doc_comment)]
+    plan: Option<PathBuf>,
+    /// Index of plan in the custom infrast plan file
+    #[arg(long, requires = "plan")]
+    plan_index: Option<i32>,
+}
+
+impl super::ToTaskType for InfrastParams {
+    fn to_task_type(&self) -> super::TaskType {
+        super::TaskType::Infrast
+    }
+}
+
+impl TryFrom<InfrastParams> for MAAValue {
+    type Error = anyhow::Error;
+
+    fn try_from(params: InfrastParams) -> Result<Self, Self::Error> {
+        if !(0.0..=1.0).contains(&params.threshold) {
+            bail!("Threshold must be in range [0, 1]");
+        }
+
+        let facility = if params.facility.is_empty() {
+            Facility::ALL.as_slice()
+        } else {
+            params.facility.as_slice()
+        };
+
+        let mut value = MAAValue::new();
+        value.insert(
+            "facility",
+            facility.iter().map(|f| f.to_str()).collect::<Vec<_>>(),
+        );
+        value.insert("drones", params.drones.to_str());
+        value.insert("threshold", params.threshold);
+        value.insert("dorm_notstationed_enabled", params.dorm_notstationed);
+        value.insert("dorm_trust_enabled", params.dorm_trust);
+        value.insert("replenish", params.replenish);
+
+        if let Some(plan) = params.plan {
+            value.insert(
+                "filename",
+                plan.to_str()
+                    .ok_or_else(|| anyhow::anyhow!("Invalid UTF-8 in plan file path"))?,
+            );
+            value.maybe_insert("plan_index", params.plan_index);
+        }
+
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object;
+
+    #[test]
+    fn parse_infrast_params() {
+        fn parse<I, T>(args: I) -> anyhow::Result<MAAValue>
+        where
+            I: IntoIterator<Item = T>,
+            T: Into<std::ffi::OsString> + Clone,
+        {
+            let command = crate::command::parse_from(args).command;
+            match command {
+                crate::Command::Infrast { params, .. } => {
+                    use super::super::{TaskType, ToTaskType};
+                    assert_eq!(params.to_task_type(), TaskType::Infrast);
+                    params.try_into()
+                }
+                _ => panic!("Not an Infrast command"),
+            }
+        }
+
+        let default = object!(
+            "facility" => ["Mfg", "Trade", "Power", "Control", "Reception", "Office", "Dorm"],
+            "drones" => "_NotUse",
+            "threshold" => 0.3,
+            "dorm_notstationed_enabled" => false,
+            "dorm_trust_enabled" => false,
+            "replenish" => false,
+        );
+
+        assert_eq!(parse(["maa", "infrast"]).unwrap(), default);
+
+        assert_eq!(
+            parse([
+                "maa",
+                "infrast",
+                "-f",
+                "Trade,Mfg",
+                "--facility",
+                "Dorm",
+                "--drones",
+                "money",
+                "--threshold",
+                "0.5",
+                "--dorm-trust",
+                "--replenish",
+            ])
+            .unwrap(),
+            default.join(object!(
+                "facility" => ["Trade", "Mfg", "Dorm"],
+                "drones" => "Money",
+                "threshold" => 0.5,
+                "dorm_trust_enabled" => true,
+                "replenish" => true,
+            ))
+        );
+
+        assert_eq!(
+            parse(["maa", "infrast", "--plan", "243.json", "--plan-index", "1"]).unwrap(),
+            default.join(object!("filename" => "243.json", "plan_index" => 1))
+        );
+
+        assert!(parse(["maa", "infrast", "--threshold", "1.5"]).is_err());
+        use clap::Parser;
+        assert!(
+            crate::command::Cli::try_parse_from(["maa", "infrast", "--plan-index", "1"]).is_err()
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use super::{AsstConfig, MAAValue, TaskType};
+
+#[derive(clap::Args)]
+pub struct MallParams {
+    /// Only collect credits without shopping
+    #[arg(long)]
+    no_shopping: bool,
+    /// Items to buy first, separated by comma
+    #[arg(short, long, value_delimiter = ',')]
+    buy_first: Vec<String>,
+    /// Items not to buy, separated by comma
+    #[arg(short = 'B', long, value_delimiter = ',')]
+    blacklist: Vec<String>,
+    /// Buy items in blacklist if credits are full
+    #[arg(long)]
+    force_if_full: bool,
+    /// Only buy discounted items
+    #[arg(long)]
+    only_discount: bool,
+    /// Stop shopping when credits are less than 300
+    #[arg(long)]
+    reserve_max_credit: bool,
+    /// Buy only when credits would exceed the cap before the next reset
+    ///
+    /// Credits of the day are collected by this task before shopping, so the credits above
+    /// the cap of 300 would overflow, and only they are spent on any item not in blacklist,
+    /// discounted or not.
+    #[arg(long, conflicts_with_all = ["no_shopping", "only_discount"])]
+    overflow_only: bool,
+    /// Fight a stage with support unit to gain extra credits
+    #[arg(long)]
+    credit_fight: bool,
+}
+
+impl super::ToTaskType for MallParams {
+    fn to_task_type(&self) -> TaskType {
+        TaskType::Mall
+    }
+
+    /// Items never to buy in the defaults of profile are kept besides the given ones
+    fn apply_profile(params: &mut MAAValue, config: &AsstConfig) {
+        let default = config
+            .task_defaults
+            .get(TaskType::Mall)
+            .and_then(|default| default.get("blacklist"));
+        if let (Some(MAAValue::Array(default)), Some(MAAValue::Array(blacklist))) =
+            (default, params.get_mut("blacklist"))
+        {
+            for item in default {
+                if !blacklist.iter().any(|i| i.as_str() == item.as_str()) {
+                    blacklist.push(item.clone());
+                }
+            }
+        }
+    }
+}
+
+impl From<MallParams> for MAAValue {
+    fn from(params: MallParams) -> Self {
+        let mut value = MAAValue::new();
+
+        value.insert("credit_fight", params.credit_fight);
+
+        let shopping = !params.no_shopping;
+        value.insert("shopping", shopping);
+        if !shopping {
+            return value;
+        }
+
+        if !params.buy_first.is_empty() {
+            value.insert("buy_first", params.buy_first);
+        }
+        if !params.blacklist.is_empty() {
+            value.insert("blacklist", params.blacklist);
+        }
+        value.insert("force_shopping_if_credit_full", params.force_if_full);
+        value.insert("only_buy_discount", params.only_discount);
+        value.insert(
+            "reserve_max_credit",
+            params.reserve_max_credit || params.overflow_only,
+        );
+
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::object;
+
+    #[test]
+    fn parse_mall_params() {
+        fn parse<I, T>(args: I) -> MAAValue
+        where
+            I: IntoIterator<Item = T>,
+            T: Into<std::ffi::OsString> + Clone,
+        {
+            let command = crate::command::parse_from(args).command;
+            match command {
+                crate::Command::Mall { params, .. } => {
+                    use super::super::{TaskType, ToTaskType};
+                    assert_eq!(params.to_task_type(), TaskType::Mall);
+                    params.into()
+                }
+                _ => panic!("Not a Mall command"),
+            }
+        }
+
+        let default = object!(
+            "credit_fight" => false,
+            "shopping" => true,
+            "force_shopping_if_credit_full" => false,
+            "only_buy_discount" => false,
+            "reserve_max_credit" => false,
+        );
+
+        assert_eq!(parse(["maa", "mall"]), default);
+
+        assert_eq!(
+            parse([
+                "maa",
+                "mall",
+                "-b",
+                "招聘许可,龙门币",
+                "--blacklist",
+                "碳,家具",
+                "-B",
+                "加急许可",
+                "--force-if-full",
+                "--reserve-max-credit",
+            ]),
+            default.join(object!(
+                "buy_first" => ["招聘许可", "龙门币"],
+                "blacklist" => ["碳", "家具", "加急许可"],
+                "force_shopping_if_credit_full" => true,
+                "reserve_max_credit" => true,
+            ))
+        );
+
+        assert_eq!(
+            parse(["maa", "mall", "--no-shopping", "--credit-fight", "-b", "碳"]),
+            object!("credit_fight" => true, "shopping" => false)
+        );
+
+        assert_eq!(
+            parse(["maa", "mall", "--overflow-only"]),
+            default.join(object!("reserve_max_credit" => true))
+        );
+        use clap::Parser;
+        assert!(crate::command::Cli::try_parse_from([
+            "maa",
+            "mall",
+            "--overflow-only",
+            "--only-discount"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn default_blacklist() {
+        use super::super::ToTaskType;
+
+        let config = AsstConfig {
+            task_defaults: toml::from_str("mall.blacklist = [\"家具零件\", \"碳\"]").unwrap(),
+            ..Default::default()
+        };
+
+        let mut params = object!("blacklist" => ["碳", "加急许可"]);
+        MallParams::apply_profile(&mut params, &config);
+        assert_eq!(
+            params,
+            object!("blacklist" => ["碳", "加急许可", "家具零件"])
+        );
+
+        // Blacklist in defaults is used by the task if not given
+        let mut params = object!();
+        MallParams::apply_profile(&mut params, &config);
+        assert_eq!(params, object!());
+    }
+}
@@ -18,8 +18,25 @@ fn default_file(task_type: TaskType) -> std::path::PathBuf {
     )
 }
 
+/// Create a task with given parameters, merged with the overlay of its task type
+fn overlaid_task(task_type: TaskType, mut params: MAAValue) -> Result<Task> {
+    let default = MAAValue::find_file_or_default(default_file(task_type))
+        .context("Failed to load default task config")?;
+
+    params.merge_mut(&default);
+
+    Ok(Task::new(task_type, params))
+}
+
 trait ToTaskType {
     fn to_task_type(&self) -> TaskType;
+
+    /// Adjust parameters by the profile, which does nothing for most tasks
+    fn apply_profile(_params: &mut MAAValue, _config: &AsstConfig)
+    where
+        Self: Sized,
+    {
+    }
 }
 
 pub trait IntoTaskConfig {
@@ -31,18 +48,14 @@ where
     T: ToTaskType + TryInto<MAAValue>,
     T::Error: Into<anyhow::Error>,
 {
-    fn into_task_config(self, _: &AsstConfig) -> Result<TaskConfig> {
+    fn into_task_config(self, config: &AsstConfig) -> Result<TaskConfig> {
         let task_type = self.to_task_type();
         let mut params: MAAValue = self.try_into().map_err(Into::into)?;
-
-        let default = MAAValue::find_file_or_default(default_file(task_type))
-            .context("Failed to load default task config")?;
-
-        params.merge_mut(&default);
+        T::apply_profile(&mut params, config);
 
         let mut task_config = TaskConfig::new();
 
-        task_config.push(Task::new(task_type, params));
+        task_config.push(overlaid_task(task_type, params)?);
 
         Ok(task_config)
     }
@@ -50,7 +63,14 @@ where
 
 #[derive(clap::Args)]
 pub(crate) struct StartUpParams {
+    /// Client type of the game client to start
+    ///
+    /// If not given, the game will not be started, and this task only enters the main screen
+    /// of a running game. Use `--startup` to start the game with the client type in profile.
     client_type: Option<ClientType>,
+    /// Account to switch to after entering the game
+    ///
+    /// A unique part of the login name is enough, like `4567` for `123****4567`.
     #[arg(long, alias = "account")]
     account_name: Option<String>,
 }
@@ -78,8 +98,10 @@ impl From<StartUpParams> for MAAValue {
 
 #[derive(clap::Args)]
 pub(crate) struct CloseDownParams {
-    #[arg(default_value = "Official")]
-    client: ClientType,
+    /// Client type of the game client to close
+    ///
+    /// If not given, the client type in profile will be used, default to Official.
+    client: Option<ClientType>,
 }
 
 impl ToTaskType for CloseDownParams {
@@ -91,13 +113,15 @@ impl ToTaskType for CloseDownParams {
 impl From<CloseDownParams> for MAAValue {
     fn from(args: CloseDownParams) -> Self {
         let mut value = MAAValue::new();
-        value.insert("client_type", args.client.to_str());
+        value.maybe_insert("client_type", args.client.map(ClientType::to_str));
         value
     }
 }
 
 mod fight;
-pub use fight::FightParams;
+
+mod penguin;
+pub use fight::{choose_stage_to_farm, FightParams, ALL_EXPIRING_MEDICINE};
 
 mod copilot;
 pub use copilot::{CopilotParams, SSSCopilotParams};
@@ -108,6 +132,21 @@ pub use roguelike::RoguelikeParams;
 mod reclamation;
 pub use reclamation::ReclamationParams;
 
+mod infrast;
+pub use infrast::InfrastParams;
+
+mod mall;
+pub use mall::MallParams;
+
+mod award;
+pub use award::AwardParams;
+
+mod recruit;
+pub use recruit::RecruitParams;
+
+mod daily;
+pub use daily::DailyParams;
+
 #[cfg(test)]
 mod tests {
     use maa_dirs::Ensure;
@@ -209,6 +248,15 @@ mod tests {
                 "account_name" => "account"
             )
         );
+
+        assert_eq!(
+            parse(["maa", "startup", "YoStarEN", "--account-name", "account"]),
+            object!(
+                "client_type" => "YoStarEN",
+                "start_game_enabled" => true,
+                "account_name" => "account"
+            )
+        );
     }
 
     #[test]
@@ -228,10 +276,7 @@ mod tests {
             }
         }
 
-        assert_eq!(
-            parse(["maa", "closedown"]),
-            object!("client_type" => "Official")
-        );
+        assert_eq!(parse(["maa", "closedown"]), object!());
 
         assert_eq!(
             parse(["maa", "closedown", "YoStarEN"]),
@@ -0,0 +1,224 @@
+//! Find the most sanity-efficient stage to farm an item by drop rates from Penguin Statistics
+
+use std::{collections::BTreeMap, time::Duration};
+
+use anyhow::{Context, Result};
+use log::debug;
+use serde::{de::DeserializeOwned, Deserialize};
+
+use crate::config::task::{is_open_now, ClientType, TimeOffset};
+
+const PENGUIN_API: &str = "https://penguin-stats.io/PenguinStats/api/v2";
+
+/// Minimum number of samples of a drop rate to be trusted
+const MIN_TIMES: i64 = 100;
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Item {
+    item_id: String,
+    name: String,
+    #[serde(default, rename = "name_i18n")]
+    name_i18n: BTreeMap<String, String>,
+    #[serde(default)]
+    alias: BTreeMap<String, Vec<String>>,
+}
+
+impl Item {
+    /// Whether the item is referred by given id, name in any language or alias
+    fn matches(&self, name: &str) -> bool {
+        self.item_id == name
+            || std::iter::once(&self.name)
+                .chain(self.name_i18n.values())
+                .chain(self.alias.values().flatten())
+                .any(|n| n.eq_ignore_ascii_case(name))
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Stage {
+    stage_id: String,
+    code: String,
+    #[serde(default)]
+    ap_cost: Option<i64>,
+    #[serde(default)]
+    existence: BTreeMap<String, Existence>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Existence {
+    exist: bool,
+    /// Timestamps in milliseconds
+    #[serde(default)]
+    open_time: Option<i64>,
+    #[serde(default)]
+    close_time: Option<i64>,
+}
+
+impl Existence {
+    fn is_open_at(&self, now: i64) -> bool {
+        self.exist
+            && self.open_time.is_none_or(|t| t <= now)
+            && self.close_time.is_none_or(|t| now < t)
+    }
+}
+
+#[derive(Deserialize)]
+struct Matrix {
+    matrix: Vec<DropRecord>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DropRecord {
+    stage_id: String,
+    item_id: String,
+    times: i64,
+    quantity: i64,
+    /// Drop records which are not collected any more have an end time
+    #[serde(default)]
+    end: Option<i64>,
+}
+
+/// The most sanity-efficient stage to farm an item
+pub struct BestStage {
+    pub code: String,
+    pub item_id: String,
+    pub item: String,
+    /// Average sanity cost per item
+    pub sanity: f64,
+}
+
+fn get<T: DeserializeOwned>(http: &reqwest::blocking::Client, path: &str) -> Result<T> {
+    let url = format!("{PENGUIN_API}/{path}");
+    debug!("Fetching {url}");
+    http.get(&url)
+        .send()
+        .and_then(|resp| resp.error_for_status())
+        .with_context(|| format!("Failed to fetch {url}"))?
+        .json()
+        .with_context(|| format!("Failed to parse response of {url}"))
+}
+
+/// Find the most sanity-efficient stage open now to farm given item
+pub fn best_stage(item: &str, client_type: ClientType) -> Result<BestStage> {
+    let server = client_type.server_report().unwrap_or("CN");
+    let http = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+
+    let items: Vec<Item> = get(&http, "items")?;
+    let item = items
+        .into_iter()
+        .find(|i| i.matches(item))
+        .with_context(|| format!("Item {item} is not found in Penguin Statistics"))?;
+    let stages: Vec<Stage> = get(&http, &format!("stages?server={server}"))?;
+    let matrix: Matrix = get(&http, &format!("result/matrix?server={server}"))?;
+
+    let now = chrono::Utc::now().timestamp_millis();
+    select(&item, &stages, &matrix.matrix, |stage| {
+        stage
+            .existence
+            .get(server)
+            .is_some_and(|e| e.is_open_at(now))
+            && is_open_now(&stage.code, TimeOffset::Client(client_type), client_type)
+    })
+    .with_context(|| format!("No stage dropping {} is open now", item.name))
+}
+
+fn select(
+    item: &Item,
+    stages: &[Stage],
+    matrix: &[DropRecord],
+    is_open: impl Fn(&Stage) -> bool,
+) -> Option<BestStage> {
+    let stages: BTreeMap<&str, &Stage> = stages
+        .iter()
+        .map(|stage| (stage.stage_id.as_str(), stage))
+        .collect();
+
+    matrix
+        .iter()
+        .filter(|r| r.item_id == item.item_id && r.end.is_none())
+        .filter(|r| r.times >= MIN_TIMES && r.quantity > 0)
+        .filter_map(|r| {
+            let stage = stages.get(r.stage_id.as_str())?;
+            let ap_cost = stage.ap_cost.filter(|&cost| cost > 0)?;
+            is_open(stage).then(|| (stage, ap_cost as f64 * r.times as f64 / r.quantity as f64))
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(stage, sanity)| BestStage {
+            code: stage.code.clone(),
+            item_id: item.item_id.clone(),
+            item: item.name.clone(),
+            sanity,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item() -> Item {
+        serde_json::from_value(serde_json::json!({
+            "itemId": "30012",
+            "name": "固源岩",
+            "name_i18n": { "en": "Orirock Cube", "zh": "固源岩" },
+            "alias": { "zh": ["固源岩", "guyuanyan"] },
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn match_item() {
+        let item = item();
+        assert!(item.matches("30012"));
+        assert!(item.matches("固源岩"));
+        assert!(item.matches("orirock cube"));
+        assert!(item.matches("guyuanyan"));
+        assert!(!item.matches("Orirock"));
+    }
+
+    #[test]
+    fn select_stage() {
+        let stages: Vec<Stage> = serde_json::from_value(serde_json::json!([
+            { "stageId": "main_01-07", "code": "1-7", "apCost": 6,
+              "existence": { "CN": { "exist": true } } },
+            { "stageId": "sub_02-05", "code": "S2-5", "apCost": 9,
+              "existence": { "CN": { "exist": true } } },
+            { "stageId": "act_01", "code": "XX-1", "apCost": 10,
+              "existence": { "CN": { "exist": true, "openTime": 0, "closeTime": 1000 } } },
+        ]))
+        .unwrap();
+        let matrix: Vec<DropRecord> = serde_json::from_value(serde_json::json!([
+            // 6 sanity per item
+            { "stageId": "main_01-07", "itemId": "30012", "times": 1000, "quantity": 1000 },
+            // 4.5 sanity per item
+            { "stageId": "sub_02-05", "itemId": "30012", "times": 1000, "quantity": 2000 },
+            // 1 sanity per item, but closed
+            { "stageId": "act_01", "itemId": "30012", "times": 1000, "quantity": 10000 },
+            // Too few samples
+            { "stageId": "main_01-07", "itemId": "30012", "times": 10, "quantity": 100 },
+            // Another item
+            { "stageId": "main_01-07", "itemId": "30011", "times": 1000, "quantity": 9000 },
+        ]))
+        .unwrap();
+
+        let item = item();
+        let is_open = |stage: &Stage| stage.existence["CN"].is_open_at(2000);
+        let best = select(&item, &stages, &matrix, is_open).unwrap();
+        assert_eq!(best.code, "S2-5");
+        assert_eq!(best.item, "固源岩");
+        assert_eq!(best.sanity, 4.5);
+
+        let is_open = |stage: &Stage| stage.existence["CN"].is_open_at(500);
+        assert_eq!(
+            select(&item, &stages, &matrix, is_open).unwrap().code,
+            "XX-1"
+        );
+
+        assert!(select(&item, &stages, &matrix, |_| false).is_none());
+    }
+}
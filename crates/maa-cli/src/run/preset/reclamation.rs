@@ -6,12 +6,14 @@ use super::MAAValue;
 #[cfg_attr(test, derive(Debug, PartialEq))]
 #[derive(Clone, Copy)]
 enum Theme {
+    Fire,
     Tales,
 }
 
 impl Theme {
     const fn to_str(self) -> &'static str {
         match self {
+            Theme::Fire => "Fire",
             Theme::Tales => "Tales",
         }
     }
@@ -19,7 +21,7 @@ impl Theme {
 
 impl clap::ValueEnum for Theme {
     fn value_variants<'a>() -> &'a [Self] {
-        &[Theme::Tales]
+        &[Theme::Fire, Theme::Tales]
     }
 
     fn to_possible_value(&self) -> Option<clap::builder::PossibleValue> {
@@ -31,7 +33,9 @@ impl clap::ValueEnum for Theme {
 pub struct ReclamationParams {
     /// Theme of the reclamation algorithm
     ///
+    /// - Fire: Fire Within the Sand
     /// - Tales: Tales Within the Sand
+    #[arg(verbatim_doc_comment)]
     theme: Theme,
     #[arg(short = 'm', long, default_value = "1",
         help = "Reclamation Algorithm task mode, 0 or 1",
@@ -45,7 +49,7 @@ pub struct ReclamationParams {
             <bold>This task mode should only be used when you already have a save and can craft certain tools.</bold>
             <bold>It is recommend to start task from a new calculation day.</bold>
             <red><bold>Using it may result in losing your progress after last calculation day.</bold></red>
-    "#))]
+    "#), value_parser = clap::value_parser!(i32).range(0..=1))]
     mode: i32,
     /// Name of tool to craft in mode 1
     #[arg(short = 'C', long, default_value = "荧光棒")]
@@ -54,10 +58,15 @@ pub struct ReclamationParams {
     ///
     /// 0: increase the number by clicking the button.
     /// 1: increase the number by holding the button.
-    #[arg(long, default_value = "0", verbatim_doc_comment)]
+    #[arg(
+        long,
+        default_value = "0",
+        value_parser = clap::value_parser!(i32).range(0..=1),
+        verbatim_doc_comment
+    )]
     increase_mode: i32,
     /// Number of batches in each game run, with each batch containing 99 items
-    #[arg(long, default_value = "16")]
+    #[arg(long, default_value = "16", value_parser = clap::value_parser!(i32).range(1..))]
     num_craft_batches: i32,
 }
 
@@ -84,7 +93,10 @@ impl From<ReclamationParams> for MAAValue {
 
 #[cfg(test)]
 mod tests {
+    use clap::Parser;
+
     use super::*;
+    use crate::command::Cli;
 
     mod theme {
         use clap::ValueEnum;
@@ -93,12 +105,13 @@ mod tests {
 
         #[test]
         fn to_str() {
+            assert_eq!(Theme::Fire.to_str(), "Fire");
             assert_eq!(Theme::Tales.to_str(), "Tales");
         }
 
         #[test]
         fn value_variants() {
-            assert_eq!(Theme::value_variants(), &[Theme::Tales]);
+            assert_eq!(Theme::value_variants(), &[Theme::Fire, Theme::Tales]);
         }
 
         #[test]
@@ -140,6 +153,10 @@ mod tests {
                 "num_craft_batches" => 16,
             )),
         );
+        assert_eq!(
+            parse(["maa", "reclamation", "Fire", "-m0"]),
+            object!("theme" => "Fire", "mode" => 0),
+        );
         assert_eq!(
             parse(["maa", "reclamation", "Tales", "-m0"]),
             base_params.join(object!("mode" => 0)),
@@ -162,5 +179,13 @@ mod tests {
                 "num_craft_batches" => 32,
             )),
         );
+
+        for args in [
+            ["maa", "reclamation", "Tales", "-m2"],
+            ["maa", "reclamation", "Tales", "--increase-mode=2"],
+            ["maa", "reclamation", "Tales", "--num-craft-batches=0"],
+        ] {
+            assert!(Cli::try_parse_from(args).is_err());
+        }
     }
 }
@@ -0,0 +1,216 @@
+use super::MAAValue;
+use crate::{config::task::duration, object};
+
+#[derive(clap::Args)]
+pub struct RecruitParams {
+    /// Only recognize tags and show possible operators, without confirming any recruitment
+    ///
+    /// This is useful if you want advice on tags but recruit manually.
+    /// The tags and possible operators of each combination will be printed.
+    #[arg(long, verbatim_doc_comment)]
+    recognize: bool,
+    /// Number of recruitment, default to 4
+    #[arg(long)]
+    times: Option<i32>,
+    /// Levels of tags to select, default to 4
+    #[arg(long, value_delimiter = ',')]
+    select: Vec<i32>,
+    /// Levels of tags to confirm, default to 3 and 4
+    #[arg(long, value_delimiter = ',')]
+    confirm: Vec<i32>,
+    /// Refresh tags if the tags are all 3-star
+    #[arg(long)]
+    refresh: bool,
+    /// Use expedited plans
+    #[arg(long)]
+    expedite: bool,
+    /// Maximum number of expedited plans to use, implies `--expedite`
+    #[arg(long, value_parser = clap::value_parser!(i32).range(1..))]
+    expedite_times: Option<i32>,
+    /// Tags preferred when only 3-star tags are available, e.g. `--first-tags 治疗,输出`
+    ///
+    /// Tags are given in the language of the client.
+    #[arg(long, value_delimiter = ',', verbatim_doc_comment)]
+    first_tags: Vec<String>,
+    /// How to select more tags than the ones of the best combination
+    ///
+    /// 0: don't select more tags (default);
+    /// 1: select up to 3 tags, even if they conflict with the best combination;
+    /// 2: select as many high-star combinations as possible, even if they conflict.
+    #[arg(long, value_parser = clap::value_parser!(i32).range(0..=2), verbatim_doc_comment)]
+    extra_tags_mode: Option<i32>,
+    /// Recruit 1-star robots instead of skipping the slot with the Robot tag
+    ///
+    /// By default, slots with the Robot tag are skipped, so that they can be recruited
+    /// manually. Add 1 to `--select` and `--confirm` to recruit robots with this option.
+    #[arg(long, verbatim_doc_comment)]
+    recruit_robot: bool,
+    /// Recruitment time of 3-star tags, e.g. `7h40m`, default to 9 hours
+    ///
+    /// The time must be between 1 and 9 hours, in steps of 10 minutes.
+    #[arg(long, value_parser = parse_recruitment_time, verbatim_doc_comment)]
+    three_star_time: Option<i32>,
+}
+
+/// Parse a recruitment time to minutes
+fn parse_recruitment_time(s: &str) -> Result<i32, String> {
+    let minutes = duration::parse_arg(s)?.as_secs() / 60;
+    if !(60..=540).contains(&minutes) || minutes % 10 != 0 {
+        return Err(format!(
+            "invalid recruitment time `{s}`, expected 1 to 9 hours in steps of 10 minutes"
+        ));
+    }
+    Ok(minutes as i32)
+}
+
+impl super::ToTaskType for RecruitParams {
+    fn to_task_type(&self) -> super::TaskType {
+        super::TaskType::Recruit
+    }
+}
+
+impl From<RecruitParams> for MAAValue {
+    fn from(params: RecruitParams) -> Self {
+        let mut value = MAAValue::new();
+
+        let select = if params.select.is_empty() {
+            vec![4]
+        } else {
+            params.select
+        };
+        value.insert("select", select);
+
+        // Recruitment is only calculated if confirm is empty or times is 0
+        if params.recognize {
+            value.insert("confirm", Vec::<i32>::new());
+            value.insert("times", 0);
+            return value;
+        }
+
+        let confirm = if params.confirm.is_empty() {
+            vec![3, 4]
+        } else {
+            params.confirm
+        };
+        value.insert("confirm", confirm);
+        value.insert("times", params.times.unwrap_or(4));
+        value.insert("refresh", params.refresh);
+        value.insert(
+            "expedite",
+            params.expedite || params.expedite_times.is_some(),
+        );
+        value.maybe_insert("expedite_times", params.expedite_times);
+        if !params.first_tags.is_empty() {
+            value.insert("first_tags", params.first_tags);
+        }
+        value.maybe_insert("extra_tags_mode", params.extra_tags_mode);
+        if params.recruit_robot {
+            value.insert("skip_robot", false);
+        }
+        if let Some(minutes) = params.three_star_time {
+            value.insert("recruitment_time", object!("3" => minutes));
+        }
+
+        value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::command::Cli;
+
+    #[test]
+    fn parse_recruit_params() {
+        fn parse<I, T>(args: I) -> MAAValue
+        where
+            I: IntoIterator<Item = T>,
+            T: Into<std::ffi::OsString> + Clone,
+        {
+            let command = crate::command::parse_from(args).command;
+            match command {
+                crate::Command::Recruit { params, .. } => {
+                    use super::super::{TaskType, ToTaskType};
+                    assert_eq!(params.to_task_type(), TaskType::Recruit);
+                    params.into()
+                }
+                _ => panic!("Not a Recruit command"),
+            }
+        }
+
+        assert_eq!(
+            parse(["maa", "recruit"]),
+            object!(
+                "select" => [4],
+                "confirm" => [3, 4],
+                "times" => 4,
+                "refresh" => false,
+                "expedite" => false,
+            )
+        );
+
+        assert_eq!(
+            parse([
+                "maa",
+                "recruit",
+                "--times",
+                "2",
+                "--select",
+                "4,5",
+                "--confirm=4",
+                "--refresh",
+            ]),
+            object!(
+                "select" => [4, 5],
+                "confirm" => [4],
+                "times" => 2,
+                "refresh" => true,
+                "expedite" => false,
+            )
+        );
+
+        assert_eq!(
+            parse([
+                "maa",
+                "recruit",
+                "--select=1,4",
+                "--confirm=1,3,4",
+                "--expedite-times",
+                "2",
+                "--first-tags",
+                "治疗,输出",
+                "--extra-tags-mode",
+                "1",
+                "--recruit-robot",
+                "--three-star-time",
+                "7h40m",
+            ]),
+            object!(
+                "select" => [1, 4],
+                "confirm" => [1, 3, 4],
+                "times" => 4,
+                "refresh" => false,
+                "expedite" => true,
+                "expedite_times" => 2,
+                "first_tags" => ["治疗", "输出"],
+                "extra_tags_mode" => 1,
+                "skip_robot" => false,
+                "recruitment_time" => object!("3" => 460),
+            )
+        );
+        for time in ["30m", "9h10m", "7h45m"] {
+            assert!(Cli::try_parse_from(["maa", "recruit", "--three-star-time", time]).is_err());
+        }
+
+        assert_eq!(
+            parse(["maa", "recruit", "--recognize"]),
+            object!(
+                "select" => [4],
+                "confirm" => MAAValue::Array(vec![]),
+                "times" => 0,
+            )
+        );
+    }
+}
@@ -34,19 +34,41 @@ impl ValueEnum for Theme {
     }
 }
 
+/// Parse mode of roguelike from its number or name
+fn parse_mode(s: &str) -> Result<i32, String> {
+    match s.to_lowercase().as_str() {
+        "score" => Ok(0),
+        "invest" | "ingots" => Ok(1),
+        "pass" => Ok(3),
+        "collectible" => Ok(4),
+        "collapse" => Ok(5),
+        s => s.parse().map_err(|_| {
+            format!(
+                "invalid mode `{s}`, expected a number or one of \
+                 score, invest, pass, collectible, collapse"
+            )
+        }),
+    }
+}
+
 #[derive(clap::Args)]
 pub struct RoguelikeParams {
     /// Theme of the roguelike
-    theme: Theme,
-    /// Mode of the roguelike
+    #[arg(required_unless_present = "theme_flag")]
+    theme: Option<Theme>,
+    /// Theme of the roguelike, same as the positional argument
+    #[arg(long = "theme", value_name = "THEME", conflicts_with = "theme")]
+    theme_flag: Option<Theme>,
+    /// Mode of the roguelike, either the number or the name
     ///
-    /// 0: mode for score;
-    /// 1: mode for ingots;
+    /// 0 or score: mode for score;
+    /// 1 or invest: mode for ingots;
     /// 2: combination of 0 and 1, deprecated;
-    /// 3: mode for pass, not implemented yet;
-    /// 4: mode that exist after 3rd floor;
-    /// 5: mode for collapsal paradigms, only for Sami, use with `expected_collapsal_paradigms`
-    #[arg(long, default_value = "0")]
+    /// 3 or pass: mode for pass, not implemented yet;
+    /// 4 or collectible: mode that exist after 3rd floor;
+    /// 5 or collapse: mode for collapsal paradigms, only for Sami,
+    /// use with `expected_collapsal_paradigms`
+    #[arg(long, default_value = "0", value_parser = parse_mode, verbatim_doc_comment)]
     mode: i32,
 
     // TODO: input localized names, maybe during initialization of tasks
@@ -64,7 +86,7 @@ pub struct RoguelikeParams {
     roles: Option<String>,
 
     /// Stop after given count, if not given, never stop
-    #[arg(long)]
+    #[arg(long, alias = "starts")]
     start_count: Option<i32>,
 
     /// Difficulty, not valid for Phantom theme (no numerical difficulty)
@@ -84,8 +106,8 @@ pub struct RoguelikeParams {
     /// If this option is enabled, try to gain exp score in investment mode.
     #[arg(long)]
     investment_with_more_score: bool,
-    /// Stop exploration investment reaches given count
-    #[arg(long)]
+    /// Stop exploration after given number of ingots invested
+    #[arg(long, alias = "stop-after-investments")]
     investments_count: Option<i32>,
     /// Do not stop exploration when investment is full
     #[arg(long)]
@@ -110,6 +132,19 @@ pub struct RoguelikeParams {
     /// Stop exploration before final boss
     #[arg(long)]
     stop_at_final_boss: bool,
+    /// Stop exploration when the level of the roguelike reaches the maximum
+    #[arg(long)]
+    stop_at_max_level: bool,
+
+    // Collectible mode specific parameters
+    /// Squad to start with in collectible mode, in Chinese, e.g. "指挥分队"
+    ///
+    /// If not given, the squad given by `--squad` is used.
+    #[arg(long)]
+    collectible_mode_squad: Option<String>,
+    /// Buy items in the trader in collectible mode
+    #[arg(long)]
+    collectible_mode_shopping: bool,
 
     // Mizuki specific parameters
     /// Whether to refresh trader with dice (only available in Mizuki theme)
@@ -146,7 +181,8 @@ impl TryFrom<RoguelikeParams> for MAAValue {
     fn try_from(params: RoguelikeParams) -> Result<Self, Self::Error> {
         let mut value = MAAValue::new();
 
-        let theme = params.theme;
+        // One of them is required by clap
+        let theme = params.theme.or(params.theme_flag).unwrap();
         let mode = params.mode;
 
         match mode {
@@ -157,7 +193,7 @@ impl TryFrom<RoguelikeParams> for MAAValue {
             _ => bail!("Mode must be in range between 0 and 5"),
         }
 
-        value.insert("theme", params.theme.to_str());
+        value.insert("theme", theme.to_str());
         value.insert("mode", params.mode);
 
         value.maybe_insert("squad", params.squad);
@@ -203,6 +239,19 @@ impl TryFrom<RoguelikeParams> for MAAValue {
         }
 
         value.insert("stop_at_final_boss", params.stop_at_final_boss);
+        if params.stop_at_max_level {
+            value.insert("stop_at_max_level", true);
+        }
+
+        if mode == 4 {
+            value.maybe_insert("collectible_mode_squad", params.collectible_mode_squad);
+            value.insert(
+                "collectible_mode_shopping",
+                params.collectible_mode_shopping,
+            );
+        } else if params.collectible_mode_squad.is_some() || params.collectible_mode_shopping {
+            log::warn!("Collectible mode options are only valid in mode 4, ignored");
+        }
 
         // Theme specific parameters
         match theme {
@@ -303,6 +352,17 @@ mod tests {
         }
     }
 
+    #[test]
+    fn mode() {
+        assert_eq!(parse_mode("0"), Ok(0));
+        assert_eq!(parse_mode("Score"), Ok(0));
+        assert_eq!(parse_mode("invest"), Ok(1));
+        assert_eq!(parse_mode("ingots"), Ok(1));
+        assert_eq!(parse_mode("collectible"), Ok(4));
+        assert_eq!(parse_mode("collapse"), Ok(5));
+        assert!(parse_mode("unknown").is_err());
+    }
+
     #[test]
     fn parse_roguellike_params() {
         fn parse<I, T>(args: I) -> Result<MAAValue, anyhow::Error>
@@ -336,6 +396,28 @@ mod tests {
         assert!(parse(["maa", "roguelike", "Phantom", "--mode", "5"]).is_err());
         assert!(parse(["maa", "roguelike", "Phantom", "--mode", "7"]).is_err());
 
+        // Theme by flag, mode by name and starts as alias of start count
+        assert_eq!(
+            parse([
+                "maa",
+                "roguelike",
+                "--theme",
+                "Sami",
+                "--mode",
+                "invest",
+                "--starts",
+                "999"
+            ])
+            .unwrap(),
+            default_params.join(object!(
+                "theme" => "Sami",
+                "mode" => 1,
+                "start_count" => 999,
+                "use_foldartal" => false,
+            )),
+        );
+        assert!(parse(["maa", "roguelike", "Phantom", "--mode", "collapse"]).is_err());
+
         // Difficulty is ignored for Phantom theme
         assert_eq!(
             parse(["maa", "roguelike", "Phantom", "--difficulty", "15"]).unwrap(),
@@ -405,6 +487,7 @@ mod tests {
                 "--start-with-elite-two",
                 "--only-start-with-elite-two",
                 "--stop-at-final-boss",
+                "--stop-at-max-level",
             ])
             .unwrap(),
             default_params.join(object!(
@@ -414,8 +497,35 @@ mod tests {
                 "start_with_elite_two" => true,
                 "only_start_with_elite_two" => true,
                 "stop_at_final_boss" => true,
+                "stop_at_max_level" => true,
+            )),
+        );
+
+        assert_eq!(
+            parse([
+                "maa",
+                "roguelike",
+                "Sarkaz",
+                "--mode=collectible",
+                "--collectible-mode-squad",
+                "蓝图测绘分队",
+                "--collectible-mode-shopping",
+                "--stop-after-investments=50",
+            ])
+            .unwrap(),
+            default_params.join(object!(
+                "theme" => "Sarkaz",
+                "mode" => 4,
+                "investments_count" => 50,
+                "collectible_mode_squad" => "蓝图测绘分队",
+                "collectible_mode_shopping" => true,
             )),
         );
+        // Collectible mode options are ignored in other modes
+        assert_eq!(
+            parse(["maa", "roguelike", "Phantom", "--collectible-mode-shopping"]).unwrap(),
+            default_params.join(object!("theme" => "Phantom")),
+        );
 
         assert_eq!(
             parse(["maa", "roguelike", "Mizuki"]).unwrap(),
@@ -0,0 +1,264 @@
+//! A status line of the running task, updated in place on interactive terminals
+//!
+//! The line shows the running task, its current step and the elapsed time of the run,
+//! e.g. `[1m23s] Fight (2/6): 1-7, times 3/5`, so that the progress can be followed without
+//! verbose logs. Logs printed to the console clear the line first, and it's drawn again later.
+
+use std::{
+    io::{IsTerminal, Write},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use maa_sys::TaskType;
+
+use crate::{
+    config::task::{duration, InitializedTask},
+    i18n::tr,
+    value::MAAValue,
+};
+
+/// Width of the line if the width of terminal is unknown
+const DEFAULT_WIDTH: usize = 80;
+
+struct Status {
+    task: String,
+    /// Stage of the running fight task
+    stage: Option<String>,
+    /// Times to fight of the running fight task
+    times: Option<i32>,
+    step: Option<String>,
+}
+
+impl Status {
+    fn new(index: usize, total: usize, task: &InitializedTask) -> Self {
+        let mut status = Self {
+            task: format!("{} ({}/{total})", task.name_or_default(), index + 1),
+            stage: None,
+            times: None,
+            step: None,
+        };
+        if task.task_type == TaskType::Fight {
+            status.stage = task
+                .params
+                .get("stage")
+                .and_then(MAAValue::as_str)
+                .filter(|stage| !stage.is_empty())
+                .map(str::to_owned);
+            status.times = task
+                .params
+                .get("times")
+                .and_then(MAAValue::as_int)
+                .filter(|&times| times < i32::MAX);
+            status.step = status
+                .stage
+                .as_ref()
+                .map(|stage| tr!("progress-navigating", stage = stage.as_str()));
+        }
+        status
+    }
+
+    fn set_times(&mut self, times: i64) {
+        let times = match self.times {
+            Some(total) => tr!("progress-times-total", times = times, total = total),
+            None => tr!("progress-times", times = times),
+        };
+        self.step = Some(match &self.stage {
+            Some(stage) => format!("{stage}, {times}"),
+            None => times,
+        });
+    }
+
+    fn line(&self, elapsed: Duration, width: usize) -> String {
+        let line = match &self.step {
+            Some(step) => format!("[{}] {}: {step}", duration::format(elapsed), self.task),
+            None => format!("[{}] {}", duration::format(elapsed), self.task),
+        };
+        truncate(&line, width)
+    }
+}
+
+/// Truncate a line to fit given width, where wide characters like CJK take two columns
+///
+/// A line longer than the terminal is wrapped, which can't be cleared by returning the cursor.
+fn truncate(line: &str, width: usize) -> String {
+    let mut columns = 0;
+    let mut truncated = String::with_capacity(line.len());
+    for c in line.chars() {
+        columns += if c >= '\u{1100}' { 2 } else { 1 };
+        if columns >= width {
+            break;
+        }
+        truncated.push(c);
+    }
+    truncated
+}
+
+struct Progress {
+    start: Instant,
+    status: Option<Status>,
+    drawn: bool,
+}
+
+impl Progress {
+    fn draw(&mut self) {
+        let Some(status) = &self.status else {
+            return;
+        };
+        let width = std::env::var("COLUMNS")
+            .ok()
+            .and_then(|columns| columns.parse().ok())
+            .unwrap_or(DEFAULT_WIDTH);
+        let line = status.line(self.start.elapsed(), width);
+        // Errors of writing to console can't be reported anywhere
+        let _ = write!(std::io::stderr(), "\r\x1b[2K{line}");
+        self.drawn = true;
+    }
+
+    fn clear(&mut self) {
+        if self.drawn {
+            let _ = write!(std::io::stderr(), "\r\x1b[2K");
+            self.drawn = false;
+        }
+    }
+}
+
+static PROGRESS: Mutex<Option<Progress>> = Mutex::new(None);
+
+/// Show the status line until the guard is dropped, if stderr is an interactive terminal
+///
+/// The line is not shown if logs are in JSON, or the console is quieted by `-q`.
+pub fn enable() -> Guard {
+    if std::io::stderr().is_terminal()
+        && !crate::log::is_json()
+        && crate::log::verbosity() >= log::LevelFilter::Warn
+    {
+        *PROGRESS.lock().unwrap() = Some(Progress {
+            start: Instant::now(),
+            status: None,
+            drawn: false,
+        });
+    }
+    Guard
+}
+
+/// Clear the status line when dropped, so that later outputs are not mixed with it
+pub struct Guard;
+
+impl Drop for Guard {
+    fn drop(&mut self) {
+        if let Some(mut progress) = PROGRESS.lock().unwrap().take() {
+            progress.clear();
+        }
+    }
+}
+
+fn with_progress(f: impl FnOnce(&mut Progress)) {
+    if let Some(progress) = PROGRESS.lock().unwrap().as_mut() {
+        f(progress);
+    }
+}
+
+/// Start showing the status of a task, which is the `index`-th of `total` tasks
+pub fn start_task(index: usize, total: usize, task: &InitializedTask) {
+    with_progress(|progress| {
+        progress.status = Some(Status::new(index, total, task));
+        progress.draw();
+    });
+}
+
+/// Update the current step of the running task, e.g. `entering Mfg #1`
+pub fn step(step: impl Into<String>) {
+    with_progress(|progress| {
+        if let Some(status) = progress.status.as_mut() {
+            status.step = Some(step.into());
+            progress.draw();
+        }
+    });
+}
+
+/// Update the stage of the running fight task, which may differ from the given one
+pub fn stage(stage: &str) {
+    with_progress(|progress| {
+        if let Some(status) = progress.status.as_mut() {
+            status.stage = Some(stage.to_owned());
+        }
+    });
+}
+
+/// Update the times of battles fought by the running fight task
+pub fn times(times: i64) {
+    with_progress(|progress| {
+        if let Some(status) = progress.status.as_mut() {
+            status.set_times(times);
+            progress.draw();
+        }
+    });
+}
+
+/// Draw the status line again to update the elapsed time
+pub fn tick() {
+    with_progress(Progress::draw);
+}
+
+/// Clear the status line before printing logs to the console, it's drawn again by [`tick`]
+pub fn clear() {
+    with_progress(Progress::clear);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::task::{Task, TaskConfig},
+        object,
+    };
+
+    fn init_task(task_type: TaskType, params: MAAValue) -> InitializedTask {
+        let mut config = TaskConfig::new();
+        config.push(Task::new(task_type, params));
+        config.init().unwrap().tasks.remove(0)
+    }
+
+    #[test]
+    fn status() {
+        let task = init_task(TaskType::Fight, object!("stage" => "1-7", "times" => 5));
+        let mut status = Status::new(1, 6, &task);
+        assert_eq!(
+            status.line(Duration::from_secs(65), DEFAULT_WIDTH),
+            "[1m5s] Fight (2/6): navigating to 1-7"
+        );
+        status.set_times(3);
+        assert_eq!(
+            status.line(Duration::from_secs(83), DEFAULT_WIDTH),
+            "[1m23s] Fight (2/6): 1-7, times 3/5"
+        );
+
+        let task = init_task(TaskType::Fight, object!());
+        let mut status = Status::new(0, 1, &task);
+        assert_eq!(
+            status.line(Duration::ZERO, DEFAULT_WIDTH),
+            "[0s] Fight (1/1)"
+        );
+        status.set_times(2);
+        assert_eq!(
+            status.line(Duration::ZERO, DEFAULT_WIDTH),
+            "[0s] Fight (1/1): times 2"
+        );
+
+        let task = init_task(TaskType::Infrast, object!());
+        let mut status = Status::new(0, 1, &task);
+        status.step = Some("entering Mfg #1".to_owned());
+        assert_eq!(
+            status.line(Duration::ZERO, DEFAULT_WIDTH),
+            "[0s] Infrast (1/1): entering Mfg #1"
+        );
+    }
+
+    #[test]
+    fn truncate_line() {
+        assert_eq!(truncate("short", 80), "short");
+        assert_eq!(truncate("abcdef", 5), "abcd");
+        assert_eq!(truncate("固源岩 × 2", 6), "固源");
+    }
+}
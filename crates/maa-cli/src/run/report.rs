@@ -0,0 +1,328 @@
+//! Report drops of fight tasks to Penguin Statistics at the end of a run
+//!
+//! Drops of each battle are recorded from the `StageDrops` messages of MaaCore, and reported
+//! stage by stage after all tasks are finished, so that reporting never slows down tasks.
+//! Only battles cleared with 3 stars are reported, as others are rejected by Penguin Statistics.
+//! Each battle is still a report of its own, as Penguin Statistics counts reports as battles.
+//!
+//! Fight tasks setting `report_to_penguin` themselves are left to MaaCore, which reports them
+//! right after each battle if enabled, so no battle is reported twice.
+//!
+//! Reports are sent as the Penguin ID given in config. If it's not given, the ID assigned by
+//! Penguin Statistics for the first report is saved in state directory and used later,
+//! so that all reports are counted as contributions of one user like in the GUI.
+
+use std::{fs, path::PathBuf, sync::Mutex, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use log::{debug, info, warn};
+use maa_types::primitive::AsstTaskId;
+use serde_json::{json, Value};
+
+use crate::{
+    config::{
+        asst::{Penguin, PenguinServer},
+        task::ClientType,
+    },
+    value::MAAValue,
+};
+
+const PENGUIN_API: &str = "https://penguin-stats.io/PenguinStats/api/v2";
+
+/// Header of the response with the Penguin ID assigned to a new user
+const SET_PENGUIN_ID: &str = "X-Penguin-Set-PenguinID";
+
+/// Types of drops accepted by Penguin Statistics
+const DROP_TYPES: [&str; 4] = ["NORMAL_DROP", "SPECIAL_DROP", "EXTRA_DROP", "FURNITURE"];
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+struct Battle {
+    stage_id: String,
+    stage_code: String,
+    /// Drops of the battle as `(drop type, item id, quantity)`
+    drops: Vec<(String, String, i64)>,
+}
+
+impl Battle {
+    /// Parse the details of a `StageDrops` message, `None` if it can't be reported
+    fn from_details(details: &Value) -> Option<Self> {
+        if details.get("stars")?.as_i64()? != 3 {
+            return None;
+        }
+        let stage = details.get("stage")?;
+        let drops = details
+            .get("drops")?
+            .as_array()?
+            .iter()
+            .filter_map(|drop| {
+                let drop_type = drop.get("dropType")?.as_str()?;
+                let item_id = drop.get("itemId")?.as_str()?;
+                if !DROP_TYPES.contains(&drop_type) || item_id.is_empty() {
+                    return None;
+                }
+                Some((
+                    drop_type.to_owned(),
+                    item_id.to_owned(),
+                    drop.get("quantity")?.as_i64()?,
+                ))
+            })
+            .collect();
+        Some(Self {
+            stage_id: stage.get("stageId")?.as_str()?.to_owned(),
+            stage_code: stage.get("stageCode")?.as_str()?.to_owned(),
+            drops,
+        })
+    }
+
+    fn payload(&self, server: PenguinServer) -> Value {
+        json!({
+            "server": server.to_str(),
+            "stageId": self.stage_id,
+            "drops": self
+                .drops
+                .iter()
+                .map(|(drop_type, item_id, quantity)| json!({
+                    "dropType": drop_type,
+                    "itemId": item_id,
+                    "quantity": quantity,
+                }))
+                .collect::<Vec<_>>(),
+            "source": "maa-cli",
+            "version": env!("CARGO_PKG_VERSION"),
+        })
+    }
+}
+
+struct Reporter {
+    config: Penguin,
+    server: PenguinServer,
+    /// Fight tasks whose drops are reported
+    tasks: Vec<AsstTaskId>,
+    battles: Vec<Battle>,
+}
+
+static REPORTER: Mutex<Option<Reporter>> = Mutex::new(None);
+
+/// Start recording drops to report, if reporting is enabled in config
+pub fn enable(config: &Penguin, client_type: ClientType) {
+    if !config.report {
+        return;
+    }
+    let server = config
+        .server
+        .or_else(|| PenguinServer::from_client_type(client_type));
+    let Some(server) = server else {
+        warn!("Drops of {client_type} are not accepted by Penguin Statistics, skip reporting");
+        return;
+    };
+    *REPORTER.lock().unwrap() = Some(Reporter {
+        config: config.clone(),
+        server,
+        tasks: Vec::new(),
+        battles: Vec::new(),
+    });
+}
+
+/// Report drops of an appended fight task, unless it's reported by MaaCore
+pub fn track(id: AsstTaskId, params: &MAAValue) {
+    if let Some(reporter) = REPORTER.lock().unwrap().as_mut() {
+        if params.get("report_to_penguin").is_none() {
+            reporter.tasks.push(id);
+        }
+    }
+}
+
+/// Record drops of a battle from the details of a `StageDrops` message of a task
+pub fn record(id: AsstTaskId, details: &Value) {
+    if let Some(reporter) = REPORTER.lock().unwrap().as_mut() {
+        if !reporter.tasks.contains(&id) {
+            return;
+        }
+        match Battle::from_details(details) {
+            Some(battle) => reporter.battles.push(battle),
+            None => debug!("Drops not reported to Penguin Statistics: {details}"),
+        }
+    }
+}
+
+fn id_file() -> PathBuf {
+    crate::dirs::state().join("penguin_id")
+}
+
+fn save_id(id: &str) -> std::io::Result<()> {
+    use crate::dirs::Ensure;
+    crate::dirs::state().ensure()?;
+    fs::write(id_file(), id)
+}
+
+/// Report recorded drops stage by stage, and print the hashes of reports
+pub fn send() {
+    let Some(reporter) = REPORTER.lock().unwrap().take() else {
+        return;
+    };
+    if reporter.battles.is_empty() {
+        return;
+    }
+
+    let client = match reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+    {
+        Ok(client) => client,
+        Err(err) => {
+            warn!("Failed to report drops to Penguin Statistics: {err}");
+            return;
+        }
+    };
+    let mut penguin_id = reporter
+        .config
+        .id
+        .clone()
+        .or_else(|| fs::read_to_string(id_file()).ok())
+        .map(|id| id.trim().to_owned())
+        .filter(|id| !id.is_empty());
+
+    let mut stages: Vec<&str> = Vec::new();
+    for battle in &reporter.battles {
+        if !stages.contains(&battle.stage_code.as_str()) {
+            stages.push(&battle.stage_code);
+        }
+    }
+
+    for stage in stages {
+        let battles = reporter
+            .battles
+            .iter()
+            .filter(|battle| battle.stage_code == stage);
+        let mut hashes = Vec::new();
+        let mut failed = 0;
+        for battle in battles {
+            match report(&client, &battle.payload(reporter.server), &mut penguin_id) {
+                Ok(hash) => hashes.push(hash),
+                Err(err) => {
+                    warn!("Failed to report drops of {stage} to Penguin Statistics: {err:#}");
+                    failed += 1;
+                }
+            }
+        }
+        if !hashes.is_empty() {
+            println!(
+                "Reported {} battles of {stage} to Penguin Statistics: {}",
+                hashes.len(),
+                hashes.join(", ")
+            );
+        }
+        if failed > 0 {
+            println!("Failed to report {failed} battles of {stage} to Penguin Statistics");
+        }
+    }
+}
+
+/// Send a report, returning its hash and saving the Penguin ID if a new one is assigned
+fn report(
+    client: &reqwest::blocking::Client,
+    payload: &Value,
+    penguin_id: &mut Option<String>,
+) -> Result<String> {
+    let mut request = client.post(format!("{PENGUIN_API}/report")).json(payload);
+    if let Some(id) = penguin_id {
+        request = request.header(reqwest::header::AUTHORIZATION, format!("PenguinID {id}"));
+    }
+    let response = request.send()?;
+    let status = response.status();
+    if let Some(id) = response
+        .headers()
+        .get(SET_PENGUIN_ID)
+        .and_then(|id| id.to_str().ok())
+    {
+        if penguin_id.as_deref() != Some(id) {
+            info!("Penguin ID assigned by Penguin Statistics: {id}");
+            if let Err(err) = save_id(id) {
+                warn!("Failed to save Penguin ID: {err}");
+            }
+            *penguin_id = Some(id.to_owned());
+        }
+    }
+    let text = response.text()?;
+    if !status.is_success() {
+        bail!("Server responded with {status}: {text}");
+    }
+    let body: Value = serde_json::from_str(&text).context("Invalid response")?;
+    body.get("reportHash")
+        .and_then(Value::as_str)
+        .map(str::to_owned)
+        .context("No report hash in response")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn details(stars: i64) -> Value {
+        json!({
+            "stars": stars,
+            "stage": { "stageCode": "1-7", "stageId": "main_01-07" },
+            "drops": [
+                { "dropType": "NORMAL_DROP", "itemId": "30012", "itemName": "固源岩", "quantity": 2 },
+                { "dropType": "EXTRA_DROP", "itemId": "30011", "itemName": "源岩", "quantity": 1 },
+                { "dropType": "EXP_LMB_DROP", "itemId": "4001", "itemName": "龙门币", "quantity": 12 },
+            ],
+        })
+    }
+
+    #[test]
+    fn battle() {
+        let battle = Battle::from_details(&details(3)).unwrap();
+        assert_eq!(battle, Battle {
+            stage_id: "main_01-07".to_owned(),
+            stage_code: "1-7".to_owned(),
+            drops: vec![
+                ("NORMAL_DROP".to_owned(), "30012".to_owned(), 2),
+                ("EXTRA_DROP".to_owned(), "30011".to_owned(), 1),
+            ],
+        });
+        assert!(Battle::from_details(&details(2)).is_none());
+        assert!(Battle::from_details(&json!({ "stars": 3 })).is_none());
+
+        assert_eq!(
+            battle.payload(PenguinServer::CN),
+            json!({
+                "server": "CN",
+                "stageId": "main_01-07",
+                "drops": [
+                    { "dropType": "NORMAL_DROP", "itemId": "30012", "quantity": 2 },
+                    { "dropType": "EXTRA_DROP", "itemId": "30011", "quantity": 1 },
+                ],
+                "source": "maa-cli",
+                "version": env!("CARGO_PKG_VERSION"),
+            })
+        );
+    }
+
+    #[test]
+    fn track() {
+        enable(
+            &Penguin {
+                report: true,
+                id: None,
+                server: None,
+            },
+            ClientType::Official,
+        );
+
+        // Tasks setting `report_to_penguin` are reported by MaaCore
+        super::track(1, &MAAValue::new());
+        let mut params = MAAValue::new();
+        params.insert("report_to_penguin", true);
+        super::track(2, &params);
+
+        record(1, &details(3));
+        record(2, &details(3));
+        record(3, &details(3));
+
+        let reporter = REPORTER.lock().unwrap().take().unwrap();
+        assert_eq!(reporter.server, PenguinServer::CN);
+        assert_eq!(reporter.tasks, vec![1]);
+        assert_eq!(reporter.battles.len(), 1);
+    }
+}
@@ -0,0 +1,216 @@
+//! Check the resolution of the device before running tasks
+//!
+//! MaaCore only supports 16:9 resolutions of at least 1280x720, and tasks fail in strange ways
+//! on other resolutions, so the resolution is checked by `adb shell wm size` before connecting.
+//! The resolution can be changed to a supported one while running tasks, and restored after.
+
+use std::process::{Command, Stdio};
+
+use anyhow::{bail, Context, Result};
+use log::{debug, info, warn};
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone, Copy)]
+struct Size {
+    width: u32,
+    height: u32,
+}
+
+impl std::fmt::Display for Size {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}x{}", self.width, self.height)
+    }
+}
+
+impl Size {
+    fn parse(s: &str) -> Option<Self> {
+        let (width, height) = s.trim().split_once('x')?;
+        Some(Self {
+            width: width.parse().ok()?,
+            height: height.parse().ok()?,
+        })
+    }
+
+    fn is_supported(self) -> bool {
+        let long = self.width.max(self.height);
+        let short = self.width.min(self.height);
+        short >= 720 && long * 9 == short * 16
+    }
+
+    /// The supported size closest to this one, in the same orientation
+    fn supported(self) -> Self {
+        let short = if self.width.min(self.height) >= 1080 {
+            1080
+        } else {
+            720
+        };
+        let long = short * 16 / 9;
+        if self.width >= self.height {
+            Self {
+                width: long,
+                height: short,
+            }
+        } else {
+            Self {
+                width: short,
+                height: long,
+            }
+        }
+    }
+}
+
+/// Parse the physical size and the override size if any from the output of `wm size`
+fn parse_wm_size(output: &str) -> Option<(Size, Option<Size>)> {
+    let mut physical = None;
+    let mut overridden = None;
+    for line in output.lines() {
+        if let Some(size) = line.strip_prefix("Physical size:") {
+            physical = Size::parse(size);
+        } else if let Some(size) = line.strip_prefix("Override size:") {
+            overridden = Size::parse(size);
+        }
+    }
+    Some((physical?, overridden))
+}
+
+struct Device {
+    adb_path: String,
+    address: String,
+}
+
+impl Device {
+    fn adb(&self, args: &[&str]) -> Result<String> {
+        let output = Command::new(&self.adb_path)
+            .args(["-s", &self.address])
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .with_context(|| format!("Failed to run {}", self.adb_path))?;
+        if !output.status.success() {
+            bail!(
+                "{} failed with {}: {}",
+                self.adb_path,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            );
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+}
+
+/// Resolution of a device, which is restored when dropped
+///
+/// The resolution is restored even if the run returns early by an error.
+pub struct Restore {
+    device: Device,
+    /// The override size before changed, or `None` to reset to the physical size
+    size: Option<Size>,
+}
+
+impl Drop for Restore {
+    fn drop(&mut self) {
+        let size = self
+            .size
+            .map_or_else(|| "reset".to_owned(), |s| s.to_string());
+        info!("Restoring the resolution of device {}", self.device.address);
+        if let Err(err) = self.device.adb(&["shell", "wm", "size", &size]) {
+            warn!("Failed to restore the resolution: {err:#}");
+        }
+    }
+}
+
+/// Check the resolution of given device, and change it to a supported one if `fix` is true
+///
+/// Returns the resolution to restore if it's changed. The check is skipped with a warning
+/// if the resolution can not be got, e.g. the device is not reachable by adb.
+pub fn check(adb_path: &str, address: &str, fix: bool) -> Result<Option<Restore>> {
+    let device = Device {
+        adb_path: adb_path.to_owned(),
+        address: address.to_owned(),
+    };
+
+    // Network devices must be connected before, which is done by MaaCore later as well
+    if address.contains(':') {
+        let _ = Command::new(adb_path)
+            .args(["connect", address])
+            .stdin(Stdio::null())
+            .output();
+    }
+
+    let (physical, overridden) = match device.adb(&["shell", "wm", "size"]) {
+        Ok(output) => match parse_wm_size(&output) {
+            Some(sizes) => sizes,
+            None => {
+                warn!("Unknown resolution of device {address}: {}", output.trim());
+                return Ok(None);
+            }
+        },
+        Err(err) => {
+            warn!("Failed to get the resolution of device {address}, skip checking it: {err:#}");
+            return Ok(None);
+        }
+    };
+
+    let size = overridden.unwrap_or(physical);
+    if size.is_supported() {
+        debug!("Resolution of device {address}: {size}");
+        return Ok(None);
+    }
+
+    let supported = size.supported();
+    if !fix {
+        bail!(
+            "The resolution {size} of device {address} is not supported, \
+             MaaCore only supports 16:9 resolutions of at least 1280x720. \
+             Run with `--fix-resolution` to change it to {supported} while running tasks, \
+             or change it by `{adb_path} -s {address} shell wm size {supported}`"
+        );
+    }
+
+    info!("Changing the resolution of device {address} from {size} to {supported}");
+    device
+        .adb(&["shell", "wm", "size", &supported.to_string()])
+        .context("Failed to change the resolution")?;
+
+    Ok(Some(Restore {
+        device,
+        size: overridden,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const fn size(width: u32, height: u32) -> Size {
+        Size { width, height }
+    }
+
+    #[test]
+    fn parse_output() {
+        assert_eq!(
+            parse_wm_size("Physical size: 1080x2400\n"),
+            Some((size(1080, 2400), None))
+        );
+        assert_eq!(
+            parse_wm_size("Physical size: 1080x2400\nOverride size: 720x1280\n"),
+            Some((size(1080, 2400), Some(size(720, 1280))))
+        );
+        assert_eq!(parse_wm_size("error: device offline"), None);
+    }
+
+    #[test]
+    fn supported_size() {
+        assert!(size(1280, 720).is_supported());
+        assert!(size(1080, 1920).is_supported());
+        assert!(size(2560, 1440).is_supported());
+        assert!(!size(960, 540).is_supported());
+        assert!(!size(1920, 1200).is_supported());
+        assert!(!size(1080, 2400).is_supported());
+
+        assert_eq!(size(960, 540).supported(), size(1280, 720));
+        assert_eq!(size(1920, 1200).supported(), size(1920, 1080));
+        assert_eq!(size(1080, 2400).supported(), size(1080, 1920));
+        assert_eq!(size(720, 1600).supported(), size(720, 1280));
+    }
+}
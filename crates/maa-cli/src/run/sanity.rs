@@ -0,0 +1,57 @@
+//! Sanity reported by fight tasks, used to wait for sanity to regenerate between loops
+//!
+//! MaaCore reports the current sanity before each battle, and a fight task ends when the
+//! sanity is not enough for the next battle, so the last reported sanity is the sanity left
+//! after the task. One point of sanity regenerates every 6 minutes until the maximum is reached.
+
+use std::{sync::Mutex, time::Duration};
+
+/// Time to regenerate one point of sanity
+const REGENERATION: Duration = Duration::from_secs(6 * 60);
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Clone, Copy)]
+pub struct Sanity {
+    pub current: i64,
+    pub max: i64,
+}
+
+impl Sanity {
+    /// Time to wait until the sanity regenerates to the target, capped by the maximum sanity
+    ///
+    /// The target is the maximum sanity if not given.
+    pub fn time_to(self, target: Option<i64>) -> Duration {
+        let target = target.map_or(self.max, |target| target.min(self.max));
+        let points = (target - self.current).max(0);
+        REGENERATION * points as u32
+    }
+}
+
+static LAST: Mutex<Option<Sanity>> = Mutex::new(None);
+
+/// Record the sanity reported before a battle
+pub fn record(current: i64, max: i64) {
+    *LAST.lock().unwrap() = Some(Sanity { current, max });
+}
+
+/// Take the last reported sanity, if any battle is fought since last taken
+pub fn take() -> Option<Sanity> {
+    LAST.lock().unwrap().take()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regeneration_time() {
+        let sanity = Sanity {
+            current: 5,
+            max: 135,
+        };
+        assert_eq!(sanity.time_to(None), Duration::from_secs(130 * 6 * 60));
+        assert_eq!(sanity.time_to(Some(35)), Duration::from_secs(30 * 6 * 60));
+        assert_eq!(sanity.time_to(Some(200)), sanity.time_to(None));
+        assert_eq!(sanity.time_to(Some(3)), Duration::ZERO);
+    }
+}
@@ -0,0 +1,136 @@
+//! Screenshots of the game, taken by MaaCore through the connection to the device
+//!
+//! When a task fails or times out, a screenshot is saved in `$(maa dir log)/screenshots`
+//! while the game is still in the failed state, so that failures of unattended runs can be
+//! investigated later. Only the newest ones are kept, as set by `log.screenshots` in `cli.toml`.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{Context, Result};
+use log::{debug, warn};
+use maa_sys::Assistant;
+
+use crate::{config::cli::CLI_CONFIG, dirs::Ensure};
+
+/// Take a screenshot of the game in PNG
+pub fn take(asst: &Assistant) -> Result<Vec<u8>> {
+    // Large enough for a PNG of a 4K screen
+    const BUFFER_SIZE: usize = 3840 * 2160 * 4;
+
+    asst.async_screncap(true)?;
+    let mut buffer = vec![0; BUFFER_SIZE];
+    let size = asst.get_image(&mut buffer, BUFFER_SIZE as _)?;
+    buffer.truncate(size as usize);
+    Ok(buffer)
+}
+
+fn dir() -> PathBuf {
+    crate::dirs::log().join("screenshots")
+}
+
+/// Name of the screenshot of a failed task, e.g. `2024-01-01T04-00-00_Fight.png`
+///
+/// Names are sorted by time, and characters not allowed in paths are replaced.
+fn file_name(time: chrono::DateTime<chrono::Local>, task: &str) -> String {
+    let task: String = task
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("{}_{task}.png", time.format("%Y-%m-%dT%H-%M-%S"))
+}
+
+/// Remove old screenshots in given directory except the newest `keep` ones
+fn clean(dir: &Path, keep: usize) -> std::io::Result<()> {
+    let mut screenshots: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "png"))
+        .collect();
+    screenshots.sort();
+    let old = screenshots.len().saturating_sub(keep);
+    for path in &screenshots[..old] {
+        debug!("Removing old screenshot {}", path.display());
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn save(asst: &Assistant, task: &str, keep: usize) -> Result<PathBuf> {
+    let png = take(asst)?;
+    let dir = dir();
+    dir.ensure()?;
+    let path = dir.join(file_name(chrono::Local::now(), task));
+    fs::write(&path, png).with_context(|| format!("Failed to write {}", path.display()))?;
+    if let Err(err) = clean(&dir, keep) {
+        warn!("Failed to remove old screenshots: {err}");
+    }
+    Ok(path)
+}
+
+/// Save a screenshot of a failed task, and print where it's saved
+pub fn save_failure(asst: &Assistant, task: &str) {
+    let keep = CLI_CONFIG.log_config().screenshots();
+    if keep == 0 {
+        return;
+    }
+    match save(asst, task, keep) {
+        Ok(path) => warn!(
+            "Screenshot of the failed task {task} is saved to {}",
+            path.display()
+        ),
+        Err(err) => warn!("Failed to save a screenshot of the failed task {task}: {err:#}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    #[test]
+    fn name() {
+        let time = chrono::Local.with_ymd_and_hms(2024, 1, 1, 4, 0, 0).unwrap();
+        assert_eq!(file_name(time, "Fight"), "2024-01-01T04-00-00_Fight.png");
+        assert_eq!(
+            file_name(time, "daily/剿灭 1"),
+            "2024-01-01T04-00-00_daily_剿灭_1.png"
+        );
+    }
+
+    #[test]
+    fn clean_old() {
+        let dir = std::env::temp_dir().join("maa-test-screenshots");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let names = [
+            "2024-01-01T04-00-00_Fight.png",
+            "2024-01-02T04-00-00_Fight.png",
+            "2024-01-03T04-00-00_Fight.png",
+        ];
+        for name in names {
+            fs::write(dir.join(name), "png").unwrap();
+        }
+        fs::write(dir.join("notes.txt"), "").unwrap();
+
+        clean(&dir, 2).unwrap();
+        assert!(!dir.join(names[0]).exists());
+        assert!(dir.join(names[1]).exists());
+        assert!(dir.join(names[2]).exists());
+        assert!(dir.join("notes.txt").exists());
+
+        clean(&dir, 5).unwrap();
+        assert!(dir.join(names[1]).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
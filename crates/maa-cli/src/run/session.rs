@@ -0,0 +1,175 @@
+//! Progress of a run of a task file, used to resume the run after interruption
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use log::{debug, info};
+use maa_dirs::Ensure;
+use maa_sys::TaskType;
+use serde::{Deserialize, Serialize};
+
+use crate::{config::task::InitializedTask, dirs};
+
+/// A task finished in a session, identified by its index, name and parameters
+#[cfg_attr(test, derive(Debug))]
+#[derive(Serialize, Deserialize, PartialEq)]
+struct FinishedTask {
+    index: usize,
+    name: String,
+    params: serde_json::Value,
+}
+
+impl FinishedTask {
+    fn new(index: usize, task: &InitializedTask) -> Result<Self> {
+        Ok(Self {
+            index,
+            name: task.name_or_default().to_owned(),
+            params: serde_json::to_value(&task.params)?,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct Session {
+    #[serde(skip)]
+    path: PathBuf,
+    finished: Vec<FinishedTask>,
+}
+
+impl Session {
+    /// Open the session of given task file
+    ///
+    /// If `resume` is true, the progress of the last run is loaded if any,
+    /// otherwise a new session is started and the progress of the last run is discarded.
+    pub fn open(task: &str, resume: bool) -> Result<Self> {
+        let name: String = task
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+                _ => '_',
+            })
+            .collect();
+        let path = join!(dirs::state(), "sessions", format!("{name}.json"));
+        Self::open_at(path, resume)
+    }
+
+    fn open_at(path: PathBuf, resume: bool) -> Result<Self> {
+        if resume && path.exists() {
+            let file = std::fs::File::open(&path)
+                .with_context(|| format!("Failed to open session {}", path.display()))?;
+            let mut session: Session = serde_json::from_reader(file)
+                .with_context(|| format!("Failed to parse session {}", path.display()))?;
+            info!(
+                "Resuming session with {} finished tasks",
+                session.finished.len()
+            );
+            session.path = path;
+            Ok(session)
+        } else {
+            if resume {
+                info!("No session to resume, start a new one");
+            }
+            remove_file(&path)?;
+            Ok(Self {
+                path,
+                finished: Vec::new(),
+            })
+        }
+    }
+
+    /// Whether the task has been finished in this session and can be skipped
+    ///
+    /// StartUp and CloseDown tasks are never skipped,
+    /// because the game may need to be started again after interruption.
+    pub fn is_finished(&self, index: usize, task: &InitializedTask) -> bool {
+        if matches!(task.task_type, TaskType::StartUp | TaskType::CloseDown) {
+            return false;
+        }
+        FinishedTask::new(index, task).is_ok_and(|task| self.finished.contains(&task))
+    }
+
+    /// Mark the task as finished and save the progress
+    pub fn finish(&mut self, index: usize, task: &InitializedTask) -> Result<()> {
+        self.finished.push(FinishedTask::new(index, task)?);
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(dir) = self.path.parent() {
+            dir.ensure()?;
+        }
+        let content = serde_json::to_string_pretty(self)?;
+        std::fs::write(&self.path, content)
+            .with_context(|| format!("Failed to save session {}", self.path.display()))
+    }
+
+    /// Discard the session, because all tasks are finished
+    pub fn complete(self) -> Result<()> {
+        remove_file(&self.path)
+    }
+}
+
+fn remove_file(path: &Path) -> Result<()> {
+    if path.exists() {
+        debug!("Removing session {}", path.display());
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove session {}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        config::task::{Task, TaskConfig},
+        object,
+        value::MAAValue,
+    };
+
+    #[test]
+    fn resume() {
+        let dir = std::env::temp_dir().join("maa-test-session");
+        let path = dir.join("daily.json");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let mut task_config = TaskConfig::new();
+        task_config.set_startup(true);
+        task_config.push(Task::new(TaskType::Fight, object!("stage" => "1-7")));
+        task_config.push(Task::new(TaskType::Mall, MAAValue::new()));
+        let tasks = task_config.init().unwrap().tasks;
+
+        let mut session = Session::open_at(path.clone(), true).unwrap();
+        assert!(!session.is_finished(1, &tasks[1]));
+        session.finish(0, &tasks[0]).unwrap();
+        session.finish(1, &tasks[1]).unwrap();
+        assert!(path.exists());
+
+        let session = Session::open_at(path.clone(), true).unwrap();
+        // StartUp task is never skipped
+        assert!(!session.is_finished(0, &tasks[0]));
+        assert!(session.is_finished(1, &tasks[1]));
+        assert!(!session.is_finished(2, &tasks[2]));
+        // Index or parameters changed
+        assert!(!session.is_finished(2, &tasks[1]));
+        let mut changed = TaskConfig::new();
+        changed.set_startup(true);
+        changed.push(Task::new(TaskType::Fight, object!("stage" => "CE-6")));
+        assert!(!session.is_finished(1, &changed.init().unwrap().tasks[1]));
+
+        // Progress is discarded when all tasks are finished
+        session.complete().unwrap();
+        assert!(!path.exists());
+        let session = Session::open_at(path.clone(), true).unwrap();
+        assert!(!session.is_finished(1, &tasks[1]));
+
+        // Progress is discarded if not resuming
+        let mut session = Session::open_at(path.clone(), true).unwrap();
+        session.finish(1, &tasks[1]).unwrap();
+        let session = Session::open_at(path.clone(), false).unwrap();
+        assert!(!session.is_finished(1, &tasks[1]));
+        assert!(!path.exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,273 @@
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use chrono::{
+    DateTime, Datelike, LocalResult, NaiveDate, NaiveDateTime, TimeDelta, TimeZone, Timelike,
+};
+use serde::Deserialize;
+
+const MONTHS: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAYS: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A cron expression with five fields: minute, hour, day of month, month and day of week
+///
+/// Each field can be `*`, a number, a range like `1-5`, a step like `*/15` or `1-30/2`,
+/// or a comma separated list of them. Months and days of week can also be given by their
+/// three-letter English names. Shortcuts like `@daily` and `@hourly` are supported as well.
+///
+/// Like cron, if both day of month and day of week are restricted,
+/// a day matching either of them is matched.
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Clone)]
+#[serde(try_from = "String")]
+pub struct Cron {
+    expr: String,
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    /// The next time matching this expression strictly after given time
+    ///
+    /// Return `None` if there is no such time in five years, e.g. `0 0 30 2 *`.
+    /// Local times skipped by daylight saving time are never matched,
+    /// and the earlier one is used for repeated local times.
+    pub fn next_after<Tz: TimeZone>(&self, time: &DateTime<Tz>) -> Option<DateTime<Tz>> {
+        let tz = time.timezone();
+        let mut t = time.naive_local().with_second(0)?.with_nanosecond(0)? + TimeDelta::minutes(1);
+        let limit = t + TimeDelta::days(5 * 366);
+
+        while t < limit {
+            if !contains(self.months, t.month()) {
+                let (year, month) = if t.month() == 12 {
+                    (t.year() + 1, 1)
+                } else {
+                    (t.year(), t.month() + 1)
+                };
+                t = NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)?;
+            } else if !self.day_matches(&t) {
+                t = t.date().succ_opt()?.and_hms_opt(0, 0, 0)?;
+            } else if !contains(self.hours, t.hour()) {
+                t = t.date().and_hms_opt(t.hour(), 0, 0)? + TimeDelta::hours(1);
+            } else if !contains(self.minutes, t.minute()) {
+                t += TimeDelta::minutes(1);
+            } else {
+                match tz.from_local_datetime(&t) {
+                    LocalResult::Single(time) | LocalResult::Ambiguous(time, _) => {
+                        return Some(time)
+                    }
+                    LocalResult::None => t += TimeDelta::minutes(1),
+                }
+            }
+        }
+
+        None
+    }
+
+    fn day_matches(&self, t: &NaiveDateTime) -> bool {
+        let day = contains(self.days, t.day());
+        let weekday = contains(self.weekdays, t.weekday().num_days_from_sunday());
+        match (self.any_day, self.any_weekday) {
+            (true, true) => true,
+            (true, false) => weekday,
+            (false, true) => day,
+            (false, false) => day || weekday,
+        }
+    }
+}
+
+impl std::fmt::Display for Cron {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.expr)
+    }
+}
+
+impl FromStr for Cron {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let expanded = match s.trim() {
+            "@hourly" => "0 * * * *",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@weekly" => "0 0 * * 0",
+            "@monthly" => "0 0 1 * *",
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            expr if expr.starts_with('@') => bail!("Unknown cron shortcut `{expr}`"),
+            expr => expr,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        let [minute, hour, day, month, weekday] = fields[..] else {
+            bail!(
+                "Cron expression `{s}` should have 5 fields, but got {}",
+                fields.len()
+            );
+        };
+
+        let parse = |field: &str, name: &str, min, max, names: &[&str]| {
+            parse_field(field, min, max, names)
+                .with_context(|| format!("Invalid {name} field `{field}` in cron expression `{s}`"))
+        };
+
+        let mut weekdays = parse(weekday, "day of week", 0, 7, &WEEKDAYS)?;
+        // Both 0 and 7 are Sunday
+        if contains(weekdays, 7) {
+            weekdays = (weekdays | 1) & !(1 << 7);
+        }
+
+        Ok(Self {
+            expr: s.trim().to_owned(),
+            minutes: parse(minute, "minute", 0, 59, &[])?,
+            hours: parse(hour, "hour", 0, 23, &[])?,
+            days: parse(day, "day of month", 1, 31, &[])?,
+            months: parse(month, "month", 1, 12, &MONTHS)?,
+            weekdays,
+            any_day: day == "*",
+            any_weekday: weekday == "*",
+        })
+    }
+}
+
+impl TryFrom<String> for Cron {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+fn contains(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+/// Parse a field of cron expression into a bit set
+///
+/// Names are matched case-insensitively, and the first name is the minimum value.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Result<u64> {
+    let value = |s: &str| -> Result<u32> {
+        let value = match names.iter().position(|name| name.eq_ignore_ascii_case(s)) {
+            Some(i) => i as u32 + min,
+            None => s
+                .parse()
+                .with_context(|| format!("`{s}` is not a number"))?,
+        };
+        if value < min || value > max {
+            bail!("{value} is out of range {min}-{max}");
+        }
+        Ok(value)
+    };
+
+    let mut set = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => (range, step.parse().ok().filter(|&s| s > 0)),
+            None => (part, Some(1)),
+        };
+        let Some(step) = step else {
+            bail!("Invalid step in `{part}`");
+        };
+        let (start, end) = match range.split_once('-') {
+            _ if range == "*" => (min, max),
+            Some((start, end)) => (value(start)?, value(end)?),
+            // A single value with step means from the value to the maximum, like cron
+            None if part.contains('/') => (value(range)?, max),
+            None => {
+                let value = value(range)?;
+                (value, value)
+            }
+        };
+        if start > end {
+            bail!("Invalid range `{range}`");
+        }
+        for value in (start..=end).step_by(step) {
+            set |= 1 << value;
+        }
+    }
+
+    Ok(set)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn cron(s: &str) -> Cron {
+        s.parse().unwrap()
+    }
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    fn next(expr: &str, time: &str) -> Option<DateTime<Utc>> {
+        cron(expr).next_after(&utc(time))
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(parse_field("*", 0, 5, &[]).unwrap(), 0b111111);
+        assert_eq!(parse_field("1,3", 0, 5, &[]).unwrap(), 0b1010);
+        assert_eq!(parse_field("1-3", 0, 5, &[]).unwrap(), 0b1110);
+        assert_eq!(parse_field("*/2", 0, 5, &[]).unwrap(), 0b10101);
+        assert_eq!(parse_field("1/2", 0, 5, &[]).unwrap(), 0b101010);
+        assert_eq!(parse_field("mon-wed", 0, 7, &WEEKDAYS).unwrap(), 0b1110);
+        assert_eq!(parse_field("Feb", 1, 12, &MONTHS).unwrap(), 0b100);
+
+        assert!(parse_field("6", 0, 5, &[]).is_err());
+        assert!(parse_field("3-1", 0, 5, &[]).is_err());
+        assert!(parse_field("*/0", 0, 5, &[]).is_err());
+        assert!(parse_field("a", 0, 5, &[]).is_err());
+
+        assert_eq!(cron("0 4 * * 7").weekdays, 1);
+        assert_eq!(cron("@daily"), Cron {
+            expr: "@daily".to_owned(),
+            ..cron("0 0 * * *")
+        });
+        assert_eq!(cron(" 0 4 * * * ").to_string(), "0 4 * * *");
+
+        assert!("0 4 * *".parse::<Cron>().is_err());
+        assert!("60 4 * * *".parse::<Cron>().is_err());
+        assert!("@reboot".parse::<Cron>().is_err());
+    }
+
+    #[test]
+    fn next_after() {
+        assert_eq!(
+            next("0 4 * * *", "2024-01-01T03:59:30Z"),
+            Some(utc("2024-01-01T04:00:00Z"))
+        );
+        // Strictly after the given time
+        assert_eq!(
+            next("0 4 * * *", "2024-01-01T04:00:00Z"),
+            Some(utc("2024-01-02T04:00:00Z"))
+        );
+        assert_eq!(
+            next("*/15 * * * *", "2024-01-01T23:50:00Z"),
+            Some(utc("2024-01-02T00:00:00Z"))
+        );
+        // 2024-01-01 is Monday
+        assert_eq!(
+            next("30 12 * * sat,sun", "2024-01-01T00:00:00Z"),
+            Some(utc("2024-01-06T12:30:00Z"))
+        );
+        assert_eq!(
+            next("0 0 29 2 *", "2024-03-01T00:00:00Z"),
+            Some(utc("2028-02-29T00:00:00Z"))
+        );
+        // Either day of month or day of week matches
+        assert_eq!(
+            next("0 0 15 * fri", "2024-01-01T00:00:00Z"),
+            Some(utc("2024-01-05T00:00:00Z"))
+        );
+        assert_eq!(next("0 0 30 2 *", "2024-01-01T00:00:00Z"), None);
+    }
+}
@@ -0,0 +1,405 @@
+//! Run custom tasks by cron-like schedules in a long-lived process
+//!
+//! Schedules are defined in `schedule.toml` in the config directory. Each scheduled task is run
+//! by a `maa run` child process in batch mode, so that a failed or crashed run never stops the
+//! scheduler. The time of the last run of each schedule is recorded in the state directory,
+//! which is used to catch up runs missed when the scheduler is not running.
+//!
+//! Changes of `schedule.toml` are applied between runs without restarting the scheduler, while
+//! task files and profiles are loaded by each run, so their changes are always applied.
+
+mod cron;
+
+use std::{collections::BTreeMap, path::PathBuf, process::ExitStatus, time::Duration};
+
+use anyhow::{bail, Context, Result};
+use chrono::{DateTime, Local, TimeDelta, TimeZone};
+use cron::Cron;
+use log::{info, warn};
+use maa_dirs::Ensure;
+use serde::Deserialize;
+
+use crate::{
+    config::{watch::Watched, FindFile, FromFile},
+    dirs,
+    run::PostAction,
+};
+
+/// How long a run can be delayed before it's considered missed
+const GRACE: TimeDelta = TimeDelta::minutes(1);
+
+/// Interval to check the clock, so that a changed clock or a suspended system is noticed in time
+const TICK: Duration = Duration::from_secs(60);
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize, Default)]
+#[serde(deny_unknown_fields)]
+pub struct ScheduleConfig {
+    #[serde(default)]
+    schedule: Vec<Entry>,
+}
+
+impl ScheduleConfig {
+    fn entries(&self) -> Result<&[Entry]> {
+        if self.schedule.is_empty() {
+            bail!("No schedule defined");
+        }
+        for (i, entry) in self.schedule.iter().enumerate() {
+            if self.schedule[..i].iter().any(|e| e.name() == entry.name()) {
+                bail!(
+                    "Duplicate schedule name `{}`, set different `name` for them",
+                    entry.name()
+                );
+            }
+        }
+        Ok(&self.schedule)
+    }
+}
+
+/// What to do with runs missed when the scheduler is not running, the system is suspended
+/// or another scheduled task is still running
+#[cfg_attr(test, derive(Debug))]
+#[derive(Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MissedPolicy {
+    /// Skip missed runs and wait for the next scheduled time
+    #[default]
+    Skip,
+    /// Run once as soon as possible no matter how many runs are missed
+    CatchUp,
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Entry {
+    /// Name of this schedule, default to the name of task
+    name: Option<String>,
+    /// Name of the custom task to run
+    task: String,
+    /// When to run the task
+    cron: Cron,
+    /// Profile of MaaCore used to run the task
+    profile: Option<String>,
+    /// Address of the device to connect
+    addr: Option<String>,
+    #[serde(default)]
+    missed: MissedPolicy,
+    /// Action after the task finished, like shutting down the computer
+    post_action: Option<PostAction>,
+    /// Extra arguments passed to `maa run`
+    #[serde(default)]
+    args: Vec<String>,
+    /// Submit the task to the daemon instead of running it by `maa run`
+    ///
+    /// The profile, address, post action and arguments are ignored, because they are set by
+    /// the daemon.
+    #[serde(default)]
+    daemon: bool,
+}
+
+impl Entry {
+    fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.task)
+    }
+
+    fn run(&self) -> Result<ExitStatus> {
+        if self.daemon {
+            crate::run::daemon::submit(self.task.clone(), None)?;
+            return Ok(ExitStatus::default());
+        }
+
+        let mut command = std::process::Command::new(std::env::current_exe()?);
+        command.arg("run").arg(&self.task).arg("--batch");
+        if let Some(profile) = &self.profile {
+            command.arg("--profile").arg(profile);
+        }
+        if let Some(addr) = &self.addr {
+            command.arg("--addr").arg(addr);
+        }
+        if let Some(post_action) = self.post_action {
+            command.arg("--post-action").arg(post_action.as_arg());
+        }
+        command.args(&self.args);
+        command
+            .status()
+            .with_context(|| format!("Failed to run scheduled task {}", self.name()))
+    }
+}
+
+#[cfg_attr(test, derive(Debug, PartialEq))]
+enum Plan<Tz: TimeZone> {
+    /// Wait until the given time
+    Wait(DateTime<Tz>),
+    /// Run now
+    Run,
+    /// Skip the run missed at the given time
+    Skip(DateTime<Tz>),
+    /// The schedule will never run again
+    Never,
+}
+
+fn plan<Tz: TimeZone>(
+    cron: &Cron,
+    missed: MissedPolicy,
+    last: &DateTime<Tz>,
+    now: &DateTime<Tz>,
+) -> Plan<Tz> {
+    match cron.next_after(last) {
+        None => Plan::Never,
+        Some(due) if due > *now => Plan::Wait(due),
+        Some(due) if missed == MissedPolicy::Skip && now.clone() - due.clone() > GRACE => {
+            Plan::Skip(due)
+        }
+        Some(_) => Plan::Run,
+    }
+}
+
+/// Time of the last run of each schedule
+#[derive(Deserialize, serde::Serialize, Default)]
+struct State {
+    #[serde(skip)]
+    path: PathBuf,
+    last: BTreeMap<String, DateTime<Local>>,
+}
+
+impl State {
+    fn load() -> Result<Self> {
+        let path = dirs::state().join("schedule.json");
+        let mut state = if path.exists() {
+            Self::from_file(&path)
+                .with_context(|| format!("Failed to load schedule state {}", path.display()))?
+        } else {
+            Self::default()
+        };
+        state.path = path;
+        Ok(state)
+    }
+
+    fn update(&mut self, name: &str, time: DateTime<Local>) -> Result<()> {
+        self.last.insert(name.to_owned(), time);
+        if let Some(dir) = self.path.parent() {
+            dir.ensure()?;
+        }
+        std::fs::write(&self.path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to save schedule state {}", self.path.display()))
+    }
+}
+
+fn config_path() -> PathBuf {
+    dirs::config().join("schedule")
+}
+
+fn load_config() -> Result<ScheduleConfig> {
+    let path = config_path();
+    let config = ScheduleConfig::find_file(&path)
+        .with_context(|| format!("Failed to load schedules from {}", path.display()))?;
+    config.entries()?;
+    Ok(config)
+}
+
+/// Print schedules and their next run time
+pub fn list() -> Result<()> {
+    let config = load_config()?;
+    let state = State::load()?;
+    let now = Local::now();
+
+    for entry in config.entries()? {
+        let last = state.last.get(entry.name()).unwrap_or(&now);
+        let next = match plan(&entry.cron, entry.missed, last, &now) {
+            Plan::Wait(time) => time.format("%Y-%m-%d %H:%M").to_string(),
+            Plan::Run => "now".to_owned(),
+            Plan::Skip(_) => match entry.cron.next_after(&now) {
+                Some(time) => time.format("%Y-%m-%d %H:%M").to_string(),
+                None => "never".to_owned(),
+            },
+            Plan::Never => "never".to_owned(),
+        };
+        println!("{}\t{}\t{}\t{next}", entry.name(), entry.task, entry.cron);
+    }
+
+    Ok(())
+}
+
+/// Run scheduled tasks until the process is terminated
+pub fn run() -> Result<()> {
+    let mut config = Watched::new(config_path(), load_config)?;
+    let mut state = State::load()?;
+    // Schedules never run before start from now, instead of catching up from the beginning
+    let mut start = Local::now();
+
+    info!(
+        "Scheduler started with {} schedules",
+        config.get().entries()?.len()
+    );
+
+    loop {
+        if config.reload(load_config).is_some() {
+            // Schedules added by the change are started from now as well
+            start = Local::now();
+        }
+        let entries = config.get().entries()?;
+        let now = Local::now();
+        let mut wake: Option<DateTime<Local>> = None;
+        let mut ran = false;
+
+        for entry in entries {
+            let name = entry.name();
+            let last = state.last.get(name).unwrap_or(&start);
+            match plan(&entry.cron, entry.missed, last, &now) {
+                Plan::Wait(time) => wake = Some(wake.map_or(time, |wake| wake.min(time))),
+                Plan::Run => {
+                    let started = Local::now();
+                    info!("Running scheduled task {name}");
+                    match entry.run() {
+                        Ok(_) if entry.daemon => info!("Scheduled task {name} submitted to daemon"),
+                        Ok(status) if status.success() => info!("Scheduled task {name} finished"),
+                        Ok(status) => warn!("Scheduled task {name} failed with {status}"),
+                        Err(err) => warn!("{err:#}"),
+                    }
+                    // Runs missed during a catch-up run are caught up again after it
+                    let last = match entry.missed {
+                        MissedPolicy::Skip => Local::now(),
+                        MissedPolicy::CatchUp => started,
+                    };
+                    state.update(name, last)?;
+                    ran = true;
+                    // Time has passed, so other schedules must be planned again
+                    break;
+                }
+                Plan::Skip(due) => {
+                    warn!(
+                        "Skip scheduled task {name} missed at {}",
+                        due.format("%Y-%m-%d %H:%M")
+                    );
+                    state.update(name, now)?;
+                    if let Some(time) = entry.cron.next_after(&now) {
+                        wake = Some(wake.map_or(time, |wake| wake.min(time)));
+                    }
+                }
+                Plan::Never => {}
+            }
+        }
+
+        if ran {
+            continue;
+        }
+
+        let Some(wake) = wake else {
+            bail!("No schedule will run anymore");
+        };
+        let duration = (wake - Local::now()).to_std().unwrap_or_default();
+        std::thread::sleep(duration.min(TICK));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Utc;
+
+    use super::*;
+
+    fn utc(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().to_utc()
+    }
+
+    #[test]
+    fn deserialize() {
+        let config: ScheduleConfig = toml::from_str(
+            r#"
+            [[schedule]]
+            task = "daily"
+            cron = "0 4 * * *"
+
+            [[schedule]]
+            name = "daily-bilibili"
+            task = "daily"
+            cron = "0 5 * * *"
+            profile = "bilibili"
+            addr = "emulator-5556"
+            missed = "catch-up"
+            post_action = "shutdown"
+            args = ["--resume"]
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.schedule, vec![
+            Entry {
+                name: None,
+                task: "daily".to_owned(),
+                cron: "0 4 * * *".parse().unwrap(),
+                profile: None,
+                addr: None,
+                missed: MissedPolicy::Skip,
+                post_action: None,
+                args: Vec::new(),
+                daemon: false,
+            },
+            Entry {
+                name: Some("daily-bilibili".to_owned()),
+                task: "daily".to_owned(),
+                cron: "0 5 * * *".parse().unwrap(),
+                profile: Some("bilibili".to_owned()),
+                addr: Some("emulator-5556".to_owned()),
+                missed: MissedPolicy::CatchUp,
+                post_action: Some(PostAction::Shutdown),
+                args: vec!["--resume".to_owned()],
+                daemon: false,
+            },
+        ]);
+        assert!(config.entries().is_ok());
+
+        let duplicated: ScheduleConfig = toml::from_str(
+            r#"
+            [[schedule]]
+            task = "daily"
+            cron = "0 4 * * *"
+
+            [[schedule]]
+            task = "daily"
+            cron = "0 5 * * *"
+            "#,
+        )
+        .unwrap();
+        assert!(duplicated.entries().is_err());
+        assert!(ScheduleConfig::default().entries().is_err());
+
+        assert!(toml::from_str::<ScheduleConfig>(
+            r#"
+            [[schedule]]
+            task = "daily"
+            cron = "0 4 * *"
+            "#,
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn plan_run() {
+        use MissedPolicy::*;
+
+        let cron: Cron = "0 4 * * *".parse().unwrap();
+        let last = utc("2024-01-01T12:00:00Z");
+
+        assert_eq!(
+            plan(&cron, Skip, &last, &utc("2024-01-02T03:00:00Z")),
+            Plan::Wait(utc("2024-01-02T04:00:00Z"))
+        );
+        assert_eq!(
+            plan(&cron, Skip, &last, &utc("2024-01-02T04:00:30Z")),
+            Plan::Run
+        );
+        assert_eq!(
+            plan(&cron, Skip, &last, &utc("2024-01-03T08:00:00Z")),
+            Plan::Skip(utc("2024-01-02T04:00:00Z"))
+        );
+        assert_eq!(
+            plan(&cron, CatchUp, &last, &utc("2024-01-03T08:00:00Z")),
+            Plan::Run
+        );
+
+        let never: Cron = "0 0 30 2 *".parse().unwrap();
+        assert_eq!(plan(&never, Skip, &last, &last), Plan::Never);
+    }
+}
@@ -35,6 +35,20 @@ impl MAAInput {
             SelectString(v) => Ok(String(v.value()?)),
         }
     }
+
+    /// Convert an answer to the type of this input, `None` if the type doesn't match
+    pub(super) fn accept(&self, answer: &MAAPrimate) -> Option<MAAPrimate> {
+        use MAAInput::*;
+        use MAAPrimate::*;
+        match (self, answer) {
+            (InputBool(_), Bool(_))
+            | (InputInt(_) | SelectInt(_), Int(_))
+            | (InputFloat(_) | SelectFloat(_), Float(_))
+            | (InputString(_) | SelectString(_), String(_)) => Some(answer.clone()),
+            (InputFloat(_) | SelectFloat(_), Int(v)) => Some(Float(*v as f32)),
+            _ => None,
+        }
+    }
 }
 
 impl From<BoolInput> for MAAInput {
@@ -209,6 +209,25 @@ impl MAAValue {
         }
     }
 
+    /// Answer the input value with given answer instead of querying it from user
+    ///
+    /// The inner value of an optional value is answered, so it's still dropped if the
+    /// dependencies are not satisfied. Other values are not changed.
+    /// Return `false` if the answer doesn't match the type of the input.
+    pub fn answer(&mut self, answer: &MAAPrimate) -> bool {
+        match self {
+            Self::Input(input) => match input.accept(answer) {
+                Some(answer) => {
+                    *self = answer.into();
+                    true
+                }
+                None => false,
+            },
+            Self::Optional { value, .. } => value.0.answer(answer),
+            _ => true,
+        }
+    }
+
     /// Get inner value if the value is an object
     pub fn as_object(&self) -> Option<&Map<String, MAAValue>> {
         match self {
@@ -638,6 +657,34 @@ mod tests {
         );
     }
 
+    #[test]
+    fn answer() {
+        let mut value = MAAValue::from(Input::<f32>::new(Some(1.0), None));
+        assert!(value.answer(&MAAPrimate::Int(2)));
+        assert_eq!(value, MAAValue::from(2.0));
+
+        let mut value = MAAValue::from(BoolInput::new(None, None));
+        assert!(!value.answer(&MAAPrimate::Int(2)));
+        assert_eq!(value, MAAValue::from(BoolInput::new(None, None)));
+
+        let mut value = object!(
+            "enable" => true,
+            "stage" if "enable" == true => Input::<String>::new(None, None),
+        );
+        value
+            .get_mut("stage")
+            .unwrap()
+            .answer(&MAAPrimate::String("1-7".to_owned()));
+        assert_eq!(
+            value.init().unwrap(),
+            object!("enable" => true, "stage" => "1-7")
+        );
+
+        let mut value = MAAValue::from(1);
+        assert!(value.answer(&MAAPrimate::String("1-7".to_owned())));
+        assert_eq!(value, MAAValue::from(1));
+    }
+
     #[test]
     fn init() {
         let input = BoolInput::new(Some(true), None);
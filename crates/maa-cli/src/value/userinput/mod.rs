@@ -15,6 +15,20 @@ fn is_batch_mode() -> bool {
     BATCH_MODE.load(Ordering::Relaxed)
 }
 
+// In strict batch mode, all inputs should be answered in advance,
+// and default values are not used for inputs without answer.
+static STRICT_BATCH_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Enable batch mode, in which inputs without answer are errors
+pub fn enable_strict_batch_mode() {
+    enable_batch_mode();
+    STRICT_BATCH_MODE.store(true, Ordering::Relaxed);
+}
+
+fn is_strict_batch_mode() -> bool {
+    STRICT_BATCH_MODE.load(Ordering::Relaxed)
+}
+
 pub trait UserInput: Sized {
     type Value: Sized;
 
@@ -27,15 +41,20 @@ pub trait UserInput: Sized {
     /// Errors:
     ///
     /// - If in batch mode and `batch_default` returns `None`, return an io::Error with kind other.
+    /// - If in strict batch mode, return an io::Error with kind other, because the input should be
+    ///   answered in advance.
     /// - If not in batch mode and `ask` returns an io::Error, return the error.
     fn value(self) -> io::Result<Self::Value> {
-        if is_batch_mode() {
-            self.batch_default().map_err(|_| {
-                io::Error::new(
-                    io::ErrorKind::Other,
-                    "can not get default value in batch mode",
-                )
-            })
+        if is_strict_batch_mode() {
+            let mut prompt = Vec::new();
+            self.prompt(&mut prompt)?;
+            Err(io::Error::other(format!(
+                "no answer for `{}` in batch mode",
+                String::from_utf8_lossy(&prompt)
+            )))
+        } else if is_batch_mode() {
+            self.batch_default()
+                .map_err(|_| io::Error::other("can not get default value in batch mode"))
         } else {
             self.ask(&mut std::io::stdout(), &mut std::io::stdin().lock())
         }
@@ -249,6 +249,10 @@ impl TaskType {
     }
 
     fn from_str_opt(s: &str) -> Option<Self> {
+        // Reclamation Algorithm is also called `ReclamationAlgorithm` in MaaCore
+        if s.eq_ignore_ascii_case("ReclamationAlgorithm") {
+            return Some(Self::Reclamation);
+        }
         Self::VARIANTS
             .iter()
             .find(|v| v.to_str().eq_ignore_ascii_case(s))
@@ -458,6 +462,7 @@ mod tests {
             assert_eq!("Depot".parse(), Ok(Depot));
             assert_eq!("OperBox".parse(), Ok(OperBox));
             assert_eq!("Reclamation".parse(), Ok(Reclamation));
+            assert_eq!("ReclamationAlgorithm".parse(), Ok(Reclamation));
             assert_eq!("Custom".parse(), Ok(Custom));
             assert_eq!("SingleStep".parse(), Ok(SingleStep));
             assert_eq!("VideoRecognition".parse(), Ok(VideoRecognition));